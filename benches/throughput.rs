@@ -0,0 +1,118 @@
+//! Messages per second through one actor, against a plain mpsc actor as the baseline.
+//!
+//! Run with `cargo bench --bench throughput`. To compare two revisions of the mailbox, run
+//! it with `-- --save-baseline before` on one and `-- --baseline before` on the other.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use priact::prelude::*;
+use priact::{spawn_actor_with, ActorConfig};
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+
+const MESSAGES: u64 = 10_000;
+
+define_actor! {
+    Sum {
+        total: u64,
+    }
+
+    impl SumMsg {
+        @priority(Low)
+        fn Add(&mut self, n: u64) {
+            self.total += n;
+        }
+
+        @priority(High)
+        fn AddUrgent(&mut self, n: u64) {
+            self.total += n;
+        }
+    }
+}
+
+/// Sends `MESSAGES` messages split across `senders` tasks, every fourth one `High`, and
+/// waits until the actor has handled them all.
+async fn priact(config: ActorConfig, senders: u64) {
+    let handle = spawn_actor_with(Sum { total: 0 }, config);
+    let tasks: Vec<_> = (0..senders)
+        .map(|_| {
+            let handle = handle.clone();
+            tokio::spawn(async move {
+                for n in 0..MESSAGES / senders {
+                    let msg = if n % 4 == 0 {
+                        SumMsg::AddUrgent(n)
+                    } else {
+                        SumMsg::Add(n)
+                    };
+                    handle.send(msg).await.unwrap();
+                }
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+    handle.join().await;
+}
+
+/// The same load through a bare mpsc channel drained by one task, ignoring priorities.
+async fn mpsc(capacity: usize, senders: u64) {
+    let (tx, mut rx) = mpsc::channel::<u64>(capacity);
+    let actor = tokio::spawn(async move {
+        let mut total = 0u64;
+        while let Some(n) = rx.recv().await {
+            total += n;
+        }
+        total
+    });
+    let tasks: Vec<_> = (0..senders)
+        .map(|_| {
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                for n in 0..MESSAGES / senders {
+                    tx.send(n).await.unwrap();
+                }
+            })
+        })
+        .collect();
+    drop(tx);
+    for task in tasks {
+        task.await.unwrap();
+    }
+    actor.await.unwrap();
+}
+
+fn throughput(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("throughput");
+    group.throughput(Throughput::Elements(MESSAGES));
+    for senders in [1, 4, 16] {
+        group.bench_with_input(
+            BenchmarkId::new("mpsc", senders),
+            &senders,
+            |b, &senders| {
+                b.to_async(&runtime)
+                    .iter(|| mpsc(priact::DEFAULT_CAPACITY, senders))
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("bounded", senders),
+            &senders,
+            |b, &senders| {
+                b.to_async(&runtime)
+                    .iter(|| priact(ActorConfig::new(), senders))
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("unbounded", senders),
+            &senders,
+            |b, &senders| {
+                b.to_async(&runtime)
+                    .iter(|| priact(ActorConfig::new().unbounded(), senders))
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, throughput);
+criterion_main!(benches);
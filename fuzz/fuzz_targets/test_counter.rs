@@ -0,0 +1,60 @@
+//! Run with `cargo +nightly fuzz run test_counter` from the repository root.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use priact::prelude::*;
+use priact::testing::{fuzz_actor, Invariants};
+use tokio::sync::oneshot;
+
+define_actor! {
+    TestCounter {
+        count: i32,
+        increments: u32,
+        decrements: u32,
+    }
+
+    impl TestCounterMsg {
+        @arbitrary
+
+        @priority(High)
+        fn GetValue(&mut self, tx: oneshot::Sender<i32>) {
+            let _ = tx.send(self.count);
+        }
+
+        @priority(Low)
+        fn Increment(&mut self, ack: oneshot::Sender<()>) {
+            self.count += 1;
+            self.increments += 1;
+            let _ = ack.send(());
+        }
+
+        @priority(Medium)
+        async fn DecrementAsync(&mut self) {
+            tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+            self.count -= 1;
+            self.decrements += 1;
+        }
+    }
+}
+
+impl Invariants for TestCounter {
+    fn check_invariants(&self) -> Result<(), String> {
+        let expected = self.increments as i64 - self.decrements as i64;
+        if self.count as i64 == expected {
+            Ok(())
+        } else {
+            Err(format!("count {} != {}", self.count, expected))
+        }
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    fuzz_actor(
+        || TestCounter {
+            count: 0,
+            increments: 0,
+            decrements: 0,
+        },
+        data,
+    );
+});
@@ -7,138 +7,766 @@
 #![doc = include_str!("../README.md")]
 
 use proc_macro::TokenStream;
-use quote::quote;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote, ToTokens};
 use syn::parse::{Parse, ParseStream, Result};
-use syn::{braced, parenthesized, punctuated::Punctuated, token, Ident, ItemFn, Token, Type};
+use syn::{
+    braced, parenthesized, punctuated::Punctuated, Attribute, Data, DeriveInput, Expr, Fields,
+    Generics, Ident, ItemFn, LitInt, Token, Type, Visibility,
+};
 
-// Represents one field: `name: Type`
+// Represents one field: `name: Type` or `name: Type = default`, possibly with attributes
+// and a visibility
 struct FieldDef {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
     name: Ident,
     #[allow(dead_code)]
     colon_token: Token![:],
     ty: Type,
+    default: Option<Expr>,
 }
 
 impl Parse for FieldDef {
     fn parse(input: ParseStream) -> Result<Self> {
         Ok(FieldDef {
+            attrs: input.call(Attribute::parse_outer)?,
+            vis: input.parse()?,
             name: input.parse()?,
             colon_token: input.parse()?,
             ty: input.parse()?,
+            default: if input.peek(Token![=]) {
+                let _eq: Token![=] = input.parse()?;
+                Some(input.parse()?)
+            } else {
+                None
+            },
         })
     }
 }
 
-// Represents one method: `@priority(P) fn foo(&mut self, ...) -> Ret { .. }` or `async fn`
+// Represents one method: `@priority(P) fn foo(&mut self, ...) -> Ret { .. }` or `async fn`.
+// The `@priority(P)` prefix is optional; `@priority_from(expr)` computes the level from the
+// message's arguments instead. `@from` makes the message convertible from its only argument.
+// `@blocking` runs a synchronous handler through `block_in_place`. `@coalesce` has a newer
+// message replace one of the same variant still waiting in the queue.
 struct MethodDef {
-    _at: Token![@],
-    _prio_kw: Ident,
-    _paren: token::Paren,
-    priority: Ident,
+    priority: Option<PriorityLevel>,
+    priority_from: Option<Expr>,
+    from: Option<Ident>,
+    blocking: Option<Ident>,
+    coalesce: Option<Ident>,
     func: ItemFn,
 }
 
+// The argument of `@priority(..)`: a named level such as `High`, or a numeric one written
+// `42` or `Custom(42)`.
+enum PriorityLevel {
+    Named(Ident),
+    Custom(LitInt),
+}
+
+impl Parse for PriorityLevel {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let level: LitInt = if input.peek(LitInt) {
+            input.parse()?
+        } else {
+            let name: Ident = input.parse()?;
+            if ["Low", "Medium", "High", "Shutdown"]
+                .iter()
+                .any(|n| name == n)
+            {
+                return Ok(PriorityLevel::Named(name));
+            }
+            if name != "Custom" {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!(
+                        "unknown priority `{}`; expected `Low`, `Medium`, `High`, `Shutdown`, or a level from 0 to 255",
+                        name
+                    ),
+                ));
+            }
+            let content;
+            parenthesized!(content in input);
+            content.parse()?
+        };
+        level.base10_parse::<u8>().map_err(|_| {
+            syn::Error::new(
+                level.span(),
+                "custom priority levels must be between 0 and 255",
+            )
+        })?;
+        Ok(PriorityLevel::Custom(level))
+    }
+}
+
+impl ToTokens for PriorityLevel {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        match self {
+            PriorityLevel::Named(name) => tokens.extend(quote! { Priority::#name }),
+            PriorityLevel::Custom(level) => tokens.extend(quote! { Priority::Custom(#level) }),
+        }
+    }
+}
+
 impl Parse for MethodDef {
     fn parse(input: ParseStream) -> Result<Self> {
-        let _at: Token![@] = input.parse()?;
-        let _prio_kw: Ident = input.parse()?;
-        if _prio_kw != "priority" {
-            return Err(syn::Error::new(_prio_kw.span(), "expected `priority`"));
+        // Attributes may come before `@priority(..)` as well as after it.
+        let attrs = input.call(Attribute::parse_outer)?;
+        let (mut priority, mut priority_from, mut from, mut blocking) = (None, None, None, None);
+        let mut coalesce = None;
+        while input.peek(Token![@]) {
+            let _at: Token![@] = input.parse()?;
+            let kw: Ident = input.parse()?;
+            let content;
+            if kw == "from" && from.is_none() {
+                from = Some(kw);
+            } else if kw == "from" {
+                return Err(syn::Error::new(kw.span(), "`@from` may only be given once"));
+            } else if kw == "blocking" && blocking.is_none() {
+                blocking = Some(kw);
+            } else if kw == "blocking" {
+                return Err(syn::Error::new(
+                    kw.span(),
+                    "`@blocking` may only be given once",
+                ));
+            } else if kw == "coalesce" && coalesce.is_none() {
+                coalesce = Some(kw);
+            } else if kw == "coalesce" {
+                return Err(syn::Error::new(
+                    kw.span(),
+                    "`@coalesce` may only be given once",
+                ));
+            } else if (kw == "priority" || kw == "priority_from")
+                && (priority.is_some() || priority_from.is_some())
+            {
+                return Err(syn::Error::new(
+                    kw.span(),
+                    "a handler takes a single `@priority(..)` or `@priority_from(..)`",
+                ));
+            } else if kw == "priority" {
+                parenthesized!(content in input);
+                priority = Some(content.parse()?);
+            } else if kw == "priority_from" {
+                parenthesized!(content in input);
+                priority_from = Some(content.parse()?);
+            } else {
+                return Err(syn::Error::new(
+                    kw.span(),
+                    "expected `priority`, `priority_from`, `from`, `blocking`, or `coalesce`",
+                ));
+            }
+        }
+
+        let mut func: ItemFn = input.parse()?;
+        func.attrs.splice(0..0, attrs);
+        match func.sig.inputs.first() {
+            Some(syn::FnArg::Receiver(r)) if r.reference.is_some() => {}
+            Some(syn::FnArg::Receiver(r)) => return Err(syn::Error::new_spanned(
+                r,
+                "handlers take `&self` or `&mut self`; the actor cannot be moved into a handler",
+            )),
+            _ => {
+                return Err(syn::Error::new(
+                    func.sig.ident.span(),
+                    "handlers take `&self` or `&mut self` as their first argument",
+                ))
+            }
+        }
+        for arg in message_inputs(&func.sig) {
+            if let syn::FnArg::Typed(pat_ty) = arg {
+                check_arg_pattern(&pat_ty.pat)?;
+            }
+        }
+
+        if let Some(kw) = &from {
+            if message_inputs(&func.sig).count() != 1 || reply_type(&func.sig).is_some() {
+                return Err(syn::Error::new(
+                    kw.span(),
+                    "`@from` handlers take exactly one argument besides `self` and the context, and return nothing",
+                ));
+            }
+        }
+
+        if let Some(kw) = &blocking {
+            if func.sig.asyncness.is_some() {
+                return Err(syn::Error::new(
+                    kw.span(),
+                    "`@blocking` handlers must not be `async`; an `async` handler should await instead of blocking",
+                ));
+            }
         }
-        let content;
-        let _paren = parenthesized!(content in input);
-        let priority: Ident = content.parse()?;
 
-        let func: ItemFn = input.parse()?;
+        if let Some(kw) = &coalesce {
+            if reply_type(&func.sig).is_some() {
+                return Err(syn::Error::new(
+                    kw.span(),
+                    "`@coalesce` handlers must not return a reply; a replaced message's caller would never get it",
+                ));
+            }
+        }
 
         Ok(MethodDef {
-            _at,
-            _prio_kw,
-            _paren,
             priority,
+            priority_from,
+            from,
+            blocking,
+            coalesce,
             func,
         })
     }
 }
 
+// A lifecycle hook: `@on_start fn name(&mut self) { .. }`, `@on_stop` or `@on_shutdown`,
+// optionally `async`. An `@on_shutdown` hook may take one more argument, which the
+// `Shutdown` message then carries.
+struct HookDef {
+    kind: Ident,
+    func: ItemFn,
+}
+
+impl HookDef {
+    fn is_hook(name: &Ident) -> bool {
+        name == "on_start" || name == "on_stop" || name == "on_shutdown"
+    }
+}
+
+impl Parse for HookDef {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let _at: Token![@] = input.parse()?;
+        let kind: Ident = input.parse()?;
+        let mut func: ItemFn = input.parse()?;
+        func.attrs.splice(0..0, attrs);
+        let sig = &func.sig;
+        let takes_mut_self = matches!(
+            sig.inputs.first(),
+            Some(syn::FnArg::Receiver(r)) if r.reference.is_some() && r.mutability.is_some()
+        );
+        let returns_nothing = matches!(sig.output, syn::ReturnType::Default);
+        if kind == "on_shutdown" {
+            let takes_payload = matches!(sig.inputs.iter().nth(1), Some(syn::FnArg::Typed(_)));
+            if !takes_mut_self
+                || !returns_nothing
+                || sig.inputs.len() > 2
+                || (sig.inputs.len() == 2 && !takes_payload)
+            {
+                return Err(syn::Error::new(
+                    sig.ident.span(),
+                    "`@on_shutdown` hooks take `&mut self` and at most one argument, carried by the `Shutdown` message, and return nothing",
+                ));
+            }
+            if let Some(syn::FnArg::Typed(pat_ty)) = sig.inputs.iter().nth(1) {
+                check_arg_pattern(&pat_ty.pat)?;
+            }
+        } else if !takes_mut_self || sig.inputs.len() != 1 || !returns_nothing {
+            return Err(syn::Error::new(
+                sig.ident.span(),
+                format!("`@{}` hooks take only `&mut self` and return nothing", kind),
+            ));
+        }
+        Ok(HookDef { kind, func })
+    }
+}
+
+// A hand-written variant with its own handler:
+// `@priority(P) extern Name(T1, T2) => |self, a, b| body`. The body runs inside `handle()`
+// with the variant's fields bound to the patterns after `self`; `Self` in the field types
+// names the actor.
+struct ExternDef {
+    attrs: Vec<Attribute>,
+    priority: Option<PriorityLevel>,
+    name: Ident,
+    types: Vec<Type>,
+    pats: Vec<syn::Pat>,
+    body: Expr,
+}
+
+impl ExternDef {
+    // Whether the next item, past its attributes and `@` options, is an `extern` variant.
+    fn peek(input: ParseStream) -> bool {
+        let fork = input.fork();
+        if fork.call(Attribute::parse_outer).is_err() {
+            return false;
+        }
+        while fork.peek(Token![@]) {
+            let _at: Token![@] = fork.parse().unwrap();
+            if fork.parse::<Ident>().is_err() {
+                return false;
+            }
+            if fork.peek(syn::token::Paren) {
+                let _group: proc_macro2::Group = fork.parse().unwrap();
+            }
+        }
+        fork.peek(Token![extern])
+    }
+}
+
+impl Parse for ExternDef {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let mut priority = None;
+        while input.peek(Token![@]) {
+            let _at: Token![@] = input.parse()?;
+            let kw: Ident = input.parse()?;
+            if kw != "priority" {
+                return Err(syn::Error::new(
+                    kw.span(),
+                    "`extern` variants take only `@priority(..)`",
+                ));
+            }
+            if priority.is_some() {
+                return Err(syn::Error::new(
+                    kw.span(),
+                    "`@priority` may only be given once",
+                ));
+            }
+            let content;
+            parenthesized!(content in input);
+            priority = Some(content.parse()?);
+        }
+        let _extern: Token![extern] = input.parse()?;
+        let name: Ident = input.parse()?;
+        let content;
+        parenthesized!(content in input);
+        let types: Vec<Type> = content
+            .parse_terminated::<Type, Token![,]>(Type::parse)?
+            .into_iter()
+            .collect();
+        let _arrow: Token![=>] = input.parse()?;
+        let _open: Token![|] = input.parse()?;
+        let _self: Token![self] = input.parse()?;
+        let mut pats = Vec::new();
+        while input.peek(Token![,]) {
+            let _comma: Token![,] = input.parse()?;
+            if input.peek(Token![|]) {
+                break;
+            }
+            let pat: syn::Pat = input.parse()?;
+            check_arg_pattern(&pat)?;
+            pats.push(pat);
+        }
+        let _close: Token![|] = input.parse()?;
+        let body: Expr = input.parse()?;
+        if input.peek(Token![,]) {
+            let _comma: Token![,] = input.parse()?;
+        } else if input.peek(Token![;]) {
+            let _semi: Token![;] = input.parse()?;
+        }
+        if pats.len() != types.len() {
+            return Err(syn::Error::new(
+                name.span(),
+                format!(
+                    "`{}` has {} field(s) but its handler binds {}; list one pattern per field after `self`",
+                    name,
+                    types.len(),
+                    pats.len()
+                ),
+            ));
+        }
+        Ok(ExternDef {
+            attrs,
+            priority,
+            name,
+            types,
+            pats,
+            body,
+        })
+    }
+}
+
+// Options that may open the impl block, e.g. `@arbitrary` or `@default_priority(Low)`
+#[derive(Default)]
+struct MsgOptions {
+    arbitrary: bool,
+    debug_drop: bool,
+    default_priority: Option<PriorityLevel>,
+}
+
+impl MsgOptions {
+    // Consumes leading `@option` markers; stops at the first `@priority(..)` method or hook.
+    fn parse_leading(input: ParseStream) -> Result<Self> {
+        let mut options = MsgOptions::default();
+        while input.peek(Token![@]) {
+            let fork = input.fork();
+            let _at: Token![@] = fork.parse()?;
+            let name: Ident = fork.parse()?;
+            if name == "priority"
+                || name == "priority_from"
+                || name == "from"
+                || name == "blocking"
+                || name == "coalesce"
+                || HookDef::is_hook(&name)
+            {
+                break;
+            }
+            let _at: Token![@] = input.parse()?;
+            let _name: Ident = input.parse()?;
+            if name == "arbitrary" {
+                options.arbitrary = true;
+            } else if name == "debug_drop" {
+                options.debug_drop = true;
+            } else if name == "default_priority" && options.default_priority.is_none() {
+                let content;
+                parenthesized!(content in input);
+                options.default_priority = Some(content.parse()?);
+            } else if name == "default_priority" {
+                return Err(syn::Error::new(
+                    name.span(),
+                    "`@default_priority` may only be given once",
+                ));
+            } else {
+                return Err(syn::Error::new(
+                    name.span(),
+                    "expected `priority`, `priority_from`, `from`, `blocking`, `coalesce`, `default_priority`, `on_start`, `on_stop`, `on_shutdown`, `arbitrary`, or `debug_drop`",
+                ));
+            }
+        }
+        Ok(options)
+    }
+}
+
 // Top-level parse for define_actor!
 struct ActorDef {
+    actor_attrs: Vec<Attribute>,
+    vis: Visibility,
     actor_name: Ident,
+    actor_generics: Generics,
     fields: Punctuated<FieldDef, Token![,]>,
+    msg_attrs: Vec<Attribute>,
     _impl_kw: Token![impl],
     msg_name: Ident,
+    msg_generics: Generics,
+    options: MsgOptions,
     methods: Vec<MethodDef>,
+    externs: Vec<ExternDef>,
+    on_start: Option<ItemFn>,
+    on_stop: Option<ItemFn>,
+    on_shutdown: Option<ItemFn>,
 }
 
 impl Parse for ActorDef {
     fn parse(input: ParseStream) -> Result<Self> {
+        let actor_attrs = input.call(Attribute::parse_outer)?;
+        // Without a qualifier everything is `pub`, as it always has been.
+        let vis = match input.parse()? {
+            Visibility::Inherited => syn::parse_quote! { pub },
+            vis => vis,
+        };
         let actor_name: Ident = input.parse()?;
+        let actor_generics = parse_generics(input)?;
         let content;
         braced!(content in input);
         let fields = content.parse_terminated(FieldDef::parse)?;
 
+        let msg_attrs = input.call(Attribute::parse_outer)?;
         let _impl_kw: Token![impl] = input.parse()?;
         let msg_name: Ident = input.parse()?;
+        let msg_generics = parse_generics(input)?;
         let methods_content;
         braced!(methods_content in input);
 
+        let options = MsgOptions::parse_leading(&methods_content)?;
+        if options.arbitrary && !msg_generics.params.is_empty() {
+            return Err(syn::Error::new(
+                msg_name.span(),
+                "`@arbitrary` does not support generic message enums",
+            ));
+        }
         let mut methods = Vec::new();
+        let mut externs = Vec::new();
+        let (mut on_start, mut on_stop, mut on_shutdown) = (None, None, None);
         while !methods_content.is_empty() {
-            methods.push(methods_content.parse::<MethodDef>()?);
+            let fork = methods_content.fork();
+            let is_hook = fork.call(Attribute::parse_outer).is_ok()
+                && fork.parse::<Token![@]>().is_ok()
+                && fork
+                    .parse::<Ident>()
+                    .is_ok_and(|name| HookDef::is_hook(&name));
+            if !is_hook && ExternDef::peek(&methods_content) {
+                externs.push(methods_content.parse::<ExternDef>()?);
+                continue;
+            }
+            if !is_hook {
+                methods.push(methods_content.parse::<MethodDef>()?);
+                continue;
+            }
+            let hook: HookDef = methods_content.parse()?;
+            let slot = if hook.kind == "on_start" {
+                &mut on_start
+            } else if hook.kind == "on_stop" {
+                &mut on_stop
+            } else {
+                &mut on_shutdown
+            };
+            if slot.is_some() {
+                return Err(syn::Error::new(
+                    hook.kind.span(),
+                    format!("an actor takes a single `@{}` hook", hook.kind),
+                ));
+            }
+            *slot = Some(hook.func);
         }
+        check_handler_names(&methods, &externs)?;
+        check_handle_methods(&methods)?;
+        check_from_types(&methods)?;
         Ok(ActorDef {
+            actor_attrs,
+            vis,
             actor_name,
+            actor_generics,
             fields,
+            msg_attrs,
             _impl_kw,
             msg_name,
+            msg_generics,
+            options,
             methods,
+            externs,
+            on_start,
+            on_stop,
+            on_shutdown,
         })
     }
 }
 
+// Every handler and `extern` variant becomes a variant of the message enum, next to the
+// generated `Shutdown`, so their names must be unique and cannot be `Shutdown`.
+fn check_handler_names(methods: &[MethodDef], externs: &[ExternDef]) -> Result<()> {
+    let names: Vec<&Ident> = methods
+        .iter()
+        .map(|m| &m.func.sig.ident)
+        .chain(externs.iter().map(|e| &e.name))
+        .collect();
+    for (i, name) in names.iter().enumerate() {
+        if *name == "Shutdown" {
+            return Err(syn::Error::new_spanned(
+                name,
+                "`Shutdown` is generated for every actor; pick another name for this handler",
+            ));
+        }
+        if names[..i].contains(name) {
+            return Err(syn::Error::new_spanned(
+                name,
+                format!(
+                    "a handler named `{}` is already defined for this actor",
+                    name
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// Every handler becomes a method of the generated typed handle, named in snake case, so
+// no two of them may convert to the same name, nor to one the handle defines itself.
+fn check_handle_methods(methods: &[MethodDef]) -> Result<()> {
+    let names: Vec<(&Ident, String)> = methods
+        .iter()
+        .map(|m| (&m.func.sig.ident, snake_case(&m.func.sig.ident.to_string())))
+        .collect();
+    for (i, (name, method)) in names.iter().enumerate() {
+        if ["shutdown", "inner", "into_inner"].contains(&method.as_str()) {
+            return Err(syn::Error::new_spanned(
+                name,
+                format!(
+                    "the typed handle already defines `{}`; pick another name for this handler",
+                    method
+                ),
+            ));
+        }
+        if let Some((prev, _)) = names[..i].iter().find(|(_, prev)| prev == method) {
+            return Err(syn::Error::new_spanned(
+                name,
+                format!(
+                    "handlers `{}` and `{}` both become the typed handle method `{}`; rename one of them",
+                    prev, name, method
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// Each `@from` handler implements `From<its argument>` for the message enum, so no two of
+// them may take the same type.
+fn check_from_types(methods: &[MethodDef]) -> Result<()> {
+    let from_types: Vec<_> = methods
+        .iter()
+        .filter(|m| m.from.is_some())
+        .map(|m| (&m.func.sig.ident, handler_args(&m.func.sig)[0].1))
+        .collect();
+    for (i, (name, ty)) in from_types.iter().enumerate() {
+        let key = ty.to_token_stream().to_string();
+        if let Some((prev, _)) = from_types[..i]
+            .iter()
+            .find(|(_, prev_ty)| prev_ty.to_token_stream().to_string() == key)
+        {
+            return Err(syn::Error::new_spanned(
+                ty,
+                format!(
+                    "`@from` handlers `{}` and `{}` both take this type; the message can only be converted from it once",
+                    prev, name
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// Optional `<..>` parameters followed by an optional `where` clause.
+fn parse_generics(input: ParseStream) -> Result<Generics> {
+    let mut generics: Generics = input.parse()?;
+    generics.where_clause = input.parse()?;
+    Ok(generics)
+}
+
 /// The procedural macro entry point
 #[proc_macro]
 pub fn define_actor(input: TokenStream) -> TokenStream {
     let ActorDef {
+        actor_attrs,
+        vis,
         actor_name,
+        actor_generics,
         fields,
+        msg_attrs,
         _impl_kw: _,
         msg_name,
+        msg_generics,
+        options,
         methods,
+        externs,
+        on_start,
+        on_stop,
+        on_shutdown,
     } = syn::parse_macro_input!(input as ActorDef);
 
-    // Struct fields
+    // Struct fields; a field without its own visibility gets the actor's
     let struct_fields = fields.iter().map(|f| {
+        let attrs = &f.attrs;
+        let field_vis = match &f.vis {
+            Visibility::Inherited => &vis,
+            own => own,
+        };
         let name = &f.name;
         let ty = &f.ty;
-        quote! { pub #name: #ty, }
+        quote! { #(#attrs)* #field_vis #name: #ty, }
     });
 
+    // Constructor, generated once any field has a default: fields without one become
+    // parameters of `new()`, and every field gets a `with_<field>` setter.
+    let constructor = if fields.iter().any(|f| f.default.is_some()) {
+        let params = fields.iter().filter(|f| f.default.is_none()).map(|f| {
+            let name = &f.name;
+            let ty = &f.ty;
+            quote! { #name: #ty }
+        });
+        let inits = fields.iter().map(|f| {
+            let name = &f.name;
+            match &f.default {
+                Some(default) => quote! { #name: #default },
+                None => quote! { #name },
+            }
+        });
+        let setters = fields.iter().map(|f| {
+            let name = &f.name;
+            let ty = &f.ty;
+            let setter = format_ident!("with_{}", name);
+            quote! {
+                #vis fn #setter(mut self, #name: #ty) -> Self {
+                    self.#name = #name;
+                    self
+                }
+            }
+        });
+        quote! {
+            #vis fn new(#(#params),*) -> Self {
+                Self { #(#inits),* }
+            }
+
+            #(#setters)*
+        }
+    } else {
+        quote! {}
+    };
+
     // Enum variants: always tuple variants (even zero-arg)
     let variants = methods.iter().map(|m| {
         let name = &m.func.sig.ident;
-        let args: Vec<_> = m
+        let args = handler_args(&m.func.sig).into_iter().map(|(_, ty)| ty);
+        let reply =
+            reply_type(&m.func.sig).map(|ret| quote! { ::priact::internal::oneshot::Sender<#ret> });
+        // Docs and deprecations describe the message as much as the method.
+        let attrs = m
             .func
-            .sig
-            .inputs
+            .attrs
             .iter()
-            .skip(1)
-            .filter_map(|arg| {
-                if let syn::FnArg::Typed(pat_ty) = arg {
-                    Some(&pat_ty.ty)
-                } else {
-                    None
-                }
-            })
-            .collect();
-        quote! { #name( #(#args),* ), }
+            .filter(|a| a.path.is_ident("doc") || a.path.is_ident("deprecated"));
+        quote! { #(#attrs)* #name( #(#args,)* #reply ), }
     });
 
-    // Priority match arms
+    // Priority match arms; unannotated methods get the block's default, or `Medium`
+    let default_priority = match &options.default_priority {
+        Some(level) => level.to_token_stream(),
+        None => quote! { Priority::Medium },
+    };
     let priorities = methods.iter().map(|m| {
         let name = &m.func.sig.ident;
-        let prio = &m.priority;
-        quote! { #msg_name::#name(..) => Priority::#prio, }
+        // A computed priority sees the message's arguments by reference, with the named
+        // levels in scope.
+        if let Some(expr) = &m.priority_from {
+            let arg_idents = handler_args(&m.func.sig)
+                .into_iter()
+                .map(|(ident, _)| ident);
+            return quote! {
+                #[allow(unused_variables)]
+                #msg_name::#name( #(#arg_idents,)* .. ) => {
+                    #[allow(dead_code, non_upper_case_globals)]
+                    const Low: Priority = Priority::Low;
+                    #[allow(dead_code, non_upper_case_globals)]
+                    const Medium: Priority = Priority::Medium;
+                    #[allow(dead_code, non_upper_case_globals)]
+                    const High: Priority = Priority::High;
+                    #[allow(dead_code, non_snake_case)]
+                    const fn Custom(level: u8) -> Priority {
+                        Priority::Custom(level)
+                    }
+                    #expr
+                }
+            };
+        }
+        let prio = match &m.priority {
+            Some(level) => level.to_token_stream(),
+            None => default_priority.clone(),
+        };
+        quote! { #msg_name::#name(..) => #prio, }
+    });
+
+    let names = methods.iter().map(|m| {
+        let name = &m.func.sig.ident;
+        quote! { #msg_name::#name(..) => stringify!(#name), }
+    });
+
+    // Coalescing handlers are keyed by their position, so each variant has its own key.
+    let coalesce_arms: Vec<_> = (0u64..)
+        .zip(&methods)
+        .filter(|(_, m)| m.coalesce.is_some())
+        .map(|(key, m)| {
+            let name = &m.func.sig.ident;
+            quote! { #msg_name::#name(..) => ::std::option::Option::Some(#key), }
+        })
+        .collect();
+    let coalesce_key = (!coalesce_arms.is_empty()).then(|| {
+        quote! {
+            fn coalesce_key(&self) -> ::std::option::Option<u64> {
+                match self {
+                    #(#coalesce_arms)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+        }
     });
 
     // handle() match arms: always tuple patterns
@@ -146,72 +774,676 @@ pub fn define_actor(input: TokenStream) -> TokenStream {
         let sig = &m.func.sig;
         let name = &sig.ident;
         let is_async = sig.asyncness.is_some();
-        let arg_idents: Vec<_> = sig.inputs.iter().skip(1).filter_map(|arg| {
-            if let syn::FnArg::Typed(pat_ty) = arg {
-                if let syn::Pat::Ident(pi) = &*pat_ty.pat {
-                    Some(&pi.ident)
-                } else { None }
-            } else { None }
-        }).collect();
-
-        if is_async {
-            quote! { #msg_name::#name( #(#arg_idents),* ) => { self.#name( #(#arg_idents),* ).await; true }, }
+        let arg_idents: Vec<_> = handler_args(sig).into_iter().map(|(ident, _)| ident).collect();
+        let ctx = takes_context(sig).then(|| quote! { __priact_ctx, });
+
+        let call = if is_async {
+            quote! { self.#name( #ctx #(#arg_idents),* ).await }
+        } else if m.blocking.is_some() {
+            quote! {
+                ::priact::internal::block_in_place(__priact_ctx.name(), || {
+                    self.#name( #ctx #(#arg_idents),* )
+                })
+            }
         } else {
-            quote! { #msg_name::#name( #(#arg_idents),* ) => { self.#name( #(#arg_idents),* ); true }, }
+            quote! { self.#name( #ctx #(#arg_idents),* ) }
+        };
+        // A handler with a return type replies through the channel appended to its variant;
+        // nobody waiting for the reply is not an error.
+        if reply_type(sig).is_some() {
+            let reply = format_ident!("__priact_reply");
+            quote! { #msg_name::#name( #(#arg_idents,)* #reply ) => { let _ = #reply.send(#call); ::std::ops::ControlFlow::Continue(()) }, }
+        } else {
+            quote! { #msg_name::#name( #(#arg_idents),* ) => { #call; ::std::ops::ControlFlow::Continue(()) }, }
         }
     });
 
-    // Method implementations: directly use the parsed signature and body
+    // `CounterMsg::get_value(..)` for every replying handler, pairing the message with the
+    // receiver of its reply
+    let reply_helpers = methods.iter().filter_map(|m| {
+        let ret = reply_type(&m.func.sig)?;
+        let name = &m.func.sig.ident;
+        let helper = method_ident(name);
+        let (idents, types): (Vec<_>, Vec<_>) = handler_args(&m.func.sig).into_iter().unzip();
+        let doc = format!(
+            "Builds a [`{}`](Self::{}) message together with the receiver of its reply.",
+            name, name
+        );
+        Some(quote! {
+            #[doc = #doc]
+            #vis fn #helper( #(#idents: #types),* ) -> (Self, ::priact::internal::oneshot::Receiver<#ret>) {
+                let (tx, rx) = ::priact::internal::oneshot::channel();
+                (Self::#name( #(#idents,)* tx ), rx)
+            }
+        })
+    });
+
+    // Method implementations: directly use the parsed signature and body, with the
+    // method's own visibility or else the actor's
     let method_defs = methods.iter().map(|m| {
+        let method_vis = match &m.func.vis {
+            Visibility::Inherited => &vis,
+            own => own,
+        };
+        let attrs = &m.func.attrs;
         let sig = &m.func.sig;
         let body = &m.func.block;
         quote! {
-            // Note: ItemFn's visibility is not used; we force `pub` here.
-            pub #sig #body
+            #(#attrs)*
+            #method_vis #sig #body
         }
     });
 
-    let expanded = quote! {
-        pub struct #actor_name {
-            #(#struct_fields)*
+    // Lifecycle hooks stay inherent methods; the `Actor` impl forwards to them.
+    let hooks = [
+        (quote! { on_start }, &on_start),
+        (quote! { on_stop }, &on_stop),
+    ];
+    let hook_defs = hooks
+        .iter()
+        .map(|(_, hook)| *hook)
+        .chain([&on_shutdown])
+        .filter_map(|hook| {
+            let ItemFn {
+                attrs, sig, block, ..
+            } = hook.as_ref()?;
+            Some(quote! { #(#attrs)* #sig #block })
+        });
+    let hook_impls = hooks.iter().filter_map(|(trait_fn, hook)| {
+        let sig = &hook.as_ref()?.sig;
+        let name = &sig.ident;
+        let call = if sig.asyncness.is_some() {
+            quote! { Self::#name(self).await; }
+        } else {
+            quote! { Self::#name(self); }
+        };
+        Some(quote! {
+            async fn #trait_fn(&mut self) {
+                #call
+            }
+        })
+    });
+
+    // `@on_shutdown` runs in the `Shutdown` arm only, before the actor stops; if it takes
+    // an argument, `Shutdown` carries it.
+    let shutdown_payload =
+        on_shutdown
+            .as_ref()
+            .and_then(|hook| match hook.sig.inputs.iter().nth(1) {
+                Some(syn::FnArg::Typed(pat_ty)) => Some(&*pat_ty.ty),
+                _ => None,
+            });
+    let (shutdown_variant, shutdown_pattern) = match shutdown_payload {
+        Some(ty) => (quote! { Shutdown(#ty) }, quote! { Shutdown(..) }),
+        None => (quote! { Shutdown }, quote! { Shutdown }),
+    };
+    let (shutdown_binding, shutdown_call) = match &on_shutdown {
+        Some(hook) => {
+            let name = &hook.sig.ident;
+            let (binding, arg) = match shutdown_payload {
+                Some(_) => (
+                    quote! { Shutdown(__priact_reason) },
+                    quote! { , __priact_reason },
+                ),
+                None => (quote! { Shutdown }, quote! {}),
+            };
+            let call = if hook.sig.asyncness.is_some() {
+                quote! { Self::#name(self #arg).await; }
+            } else {
+                quote! { Self::#name(self #arg); }
+            };
+            (binding, call)
+        }
+        None => (quote! { Shutdown }, quote! {}),
+    };
+
+    let arbitrary_impl = if options.arbitrary {
+        arbitrary_impl(&msg_name, &methods)
+    } else {
+        quote! {}
+    };
+
+    let (impl_generics, ty_generics, where_clause) = actor_generics.split_for_impl();
+    let (msg_impl_generics, msg_ty_generics, msg_where_clause) = msg_generics.split_for_impl();
+    // `Actor` needs the actor and its messages to be `Send + 'static`.
+    let mut actor_impl_generics = actor_generics.clone();
+    for param in actor_generics.type_params() {
+        let ident = &param.ident;
+        actor_impl_generics
+            .make_where_clause()
+            .predicates
+            .push(syn::parse_quote! { #ident: Send + 'static });
+    }
+    let (_, _, actor_where_clause) = actor_impl_generics.split_for_impl();
+
+    // `@from` handlers let their argument convert straight into the message.
+    let from_impls = methods.iter().filter(|m| m.from.is_some()).map(|m| {
+        let name = &m.func.sig.ident;
+        let ty = handler_args(&m.func.sig)[0].1;
+        quote! {
+            #[allow(deprecated)]
+            impl #msg_impl_generics ::core::convert::From<#ty> for #msg_name #msg_ty_generics #msg_where_clause {
+                fn from(value: #ty) -> Self {
+                    #msg_name::#name(value)
+                }
+            }
         }
+    });
 
-        impl Drop for #actor_name {
-            fn drop(&mut self) {
-                println!("[{}] Actor instance being dropped.", stringify!(#actor_name));
+    // `extern` variants are written out as given, with `Self` in their fields naming the
+    // actor rather than the enum; their handlers are spliced into `handle()`.
+    let actor_ty = quote! { #actor_name #ty_generics };
+    let extern_variants = externs.iter().map(|e| {
+        let attrs = &e.attrs;
+        let name = &e.name;
+        let types = e
+            .types
+            .iter()
+            .map(|ty| replace_self(ty.to_token_stream(), &actor_ty));
+        quote! { #(#attrs)* #name( #(#types),* ), }
+    });
+    let extern_priorities = externs.iter().map(|e| {
+        let name = &e.name;
+        let prio = match &e.priority {
+            Some(level) => level.to_token_stream(),
+            None => default_priority.clone(),
+        };
+        quote! { #msg_name::#name(..) => #prio, }
+    });
+    let extern_names = externs.iter().map(|e| {
+        let name = &e.name;
+        quote! { #msg_name::#name(..) => stringify!(#name), }
+    });
+    let extern_arms = externs.iter().map(|e| {
+        let name = &e.name;
+        let pats = &e.pats;
+        let body = &e.body;
+        quote! { #msg_name::#name( #(#pats),* ) => { #body; ::std::ops::ControlFlow::Continue(()) }, }
+    });
+
+    // Traces when the actor value is dropped; opt-in so users can write their own `Drop`.
+    let drop_impl = if options.debug_drop {
+        quote! {
+            impl #impl_generics Drop for #actor_name #ty_generics #where_clause {
+                fn drop(&mut self) {
+                    ::priact::internal::actor_dropped(stringify!(#actor_name));
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // `CounterHandle`: wraps the actor's `ActorHandle` with an async method per handler,
+    // named like its reply helper, that sends the message and waits for any reply.
+    let handle_name = format_ident!("{}Handle", actor_name);
+    let msg_ty = quote! { #msg_name #msg_ty_generics };
+    let handle_methods = methods.iter().map(|m| {
+        let name = &m.func.sig.ident;
+        let method = method_ident(name);
+        let method_vis = match &m.func.vis {
+            Visibility::Inherited => &vis,
+            own => own,
+        };
+        let attrs = m
+            .func
+            .attrs
+            .iter()
+            .filter(|a| a.path.is_ident("deprecated"));
+        let (idents, types): (Vec<_>, Vec<_>) = handler_args(&m.func.sig).into_iter().unzip();
+        match reply_type(&m.func.sig) {
+            Some(ret) => {
+                let doc = format!(
+                    "Sends a [`{}`]({}::{}) message and waits for the reply.",
+                    name, msg_name, name
+                );
+                quote! {
+                    #[doc = #doc]
+                    #(#attrs)*
+                    #method_vis async fn #method(&self, #(#idents: #types),*) -> ::std::result::Result<#ret, ::priact::Error> {
+                        let (tx, rx) = ::priact::internal::oneshot::channel();
+                        ::priact::internal::request(&self.handle, #msg_name::#name( #(#idents,)* tx ), rx).await
+                    }
+                }
+            }
+            None => {
+                let doc = format!("Sends a [`{}`]({}::{}) message.", name, msg_name, name);
+                quote! {
+                    #[doc = #doc]
+                    #(#attrs)*
+                    #method_vis async fn #method(&self, #(#idents: #types),*) -> ::std::result::Result<(), ::priact::Error<#msg_ty>> {
+                        self.handle.send(#msg_name::#name( #(#idents),* )).await
+                    }
+                }
+            }
+        }
+    });
+    let shutdown_method = match shutdown_payload {
+        Some(ty) => quote! {
+            #vis async fn shutdown(&self, reason: #ty) -> ::std::result::Result<(), ::priact::Error<#msg_ty>> {
+                self.handle.send(#msg_name::Shutdown(reason)).await
+            }
+        },
+        None => quote! {
+            #vis async fn shutdown(&self) -> ::std::result::Result<(), ::priact::Error<#msg_ty>> {
+                self.handle.send(#msg_name::Shutdown).await
             }
+        },
+    };
+    let mut handle_generics = msg_generics.clone();
+    handle_generics
+        .make_where_clause()
+        .predicates
+        .push(syn::parse_quote! { #msg_ty: ::priact::Prioritized + Send + 'static });
+    let (_, _, handle_where_clause) = handle_generics.split_for_impl();
+    let handle_doc = format!(
+        "A handle to a [`{}`] actor with a method per handler, so callers need not build [`{}`] messages themselves.",
+        actor_name, msg_name
+    );
+
+    let expanded = quote! {
+        #(#actor_attrs)*
+        #vis struct #actor_name #actor_generics #where_clause {
+            #(#struct_fields)*
         }
 
-        pub enum #msg_name {
+        #drop_impl
+
+        #(#msg_attrs)*
+        #vis enum #msg_name #msg_generics #msg_where_clause {
             #(#variants)*
-            Shutdown,
+            #(#extern_variants)*
+            /// Stops the actor. Handled ahead of every other message.
+            #shutdown_variant,
         }
 
-        impl Prioritized for #msg_name {
+        #(#from_impls)*
+
+        // Matching on a deprecated handler's variant is not a use of it.
+        #[allow(deprecated)]
+        impl #msg_impl_generics Prioritized for #msg_name #msg_ty_generics #msg_where_clause {
             fn priority(&self) -> Priority {
                 match self {
                     #(#priorities)*
-                    #msg_name::Shutdown => Priority::Shutdown,
+                    #(#extern_priorities)*
+                    #msg_name::#shutdown_pattern => Priority::Shutdown,
+                }
+            }
+
+            fn variant_name(&self) -> &'static str {
+                match self {
+                    #(#names)*
+                    #(#extern_names)*
+                    #msg_name::#shutdown_pattern => "Shutdown",
                 }
             }
+
+            #coalesce_key
         }
 
+        #[allow(deprecated)]
         #[async_trait::async_trait]
-        impl Actor for #actor_name {
-            type Msg = #msg_name;
-            async fn handle(&mut self, msg: Self::Msg) -> bool {
+        impl #impl_generics Actor for #actor_name #ty_generics #actor_where_clause {
+            type Msg = #msg_name #msg_ty_generics;
+            async fn handle(
+                &mut self,
+                msg: Self::Msg,
+                __priact_ctx: &::priact::ActorContext<Self::Msg>,
+            ) -> ::std::ops::ControlFlow<::priact::ShutdownReason> {
                 match msg {
                     #(#handle_arms)*
-                    #msg_name::Shutdown => false,
+                    #(#extern_arms)*
+                    #msg_name::#shutdown_binding => {
+                        #shutdown_call
+                        ::std::ops::ControlFlow::Break(::priact::ShutdownReason::Requested)
+                    }
                 }
             }
+
+            #(#hook_impls)*
+        }
+
+        #[allow(deprecated)]
+        impl #msg_impl_generics #msg_name #msg_ty_generics #msg_where_clause {
+            #(#reply_helpers)*
         }
 
         #[allow(non_snake_case)]
-        impl #actor_name {
+        impl #impl_generics #actor_name #ty_generics #where_clause {
+            #constructor
             #(#method_defs)*
+            #(#hook_defs)*
+        }
+
+        // Callers of a private actor may stick to the untyped handle.
+        #[allow(dead_code)]
+        impl #impl_generics #actor_name #ty_generics #actor_where_clause {
+            /// Spawns the actor with [`spawn_actor`](::priact::spawn_actor), returning its
+            /// typed handle.
+            #vis fn spawn(self) -> #handle_name #msg_ty_generics {
+                #handle_name::from(::priact::spawn_actor(self))
+            }
+
+            /// Spawns the actor with [`spawn_actor_with`](::priact::spawn_actor_with),
+            /// returning its typed handle.
+            #vis fn spawn_with(self, config: ::priact::ActorConfig) -> #handle_name #msg_ty_generics {
+                #handle_name::from(::priact::spawn_actor_with(self, config))
+            }
+        }
+
+        #[doc = #handle_doc]
+        #[allow(dead_code)]
+        #vis struct #handle_name #msg_generics #msg_where_clause {
+            handle: ::priact::ActorHandle<#msg_ty>,
+        }
+
+        impl #msg_impl_generics ::core::clone::Clone for #handle_name #msg_ty_generics #msg_where_clause {
+            fn clone(&self) -> Self {
+                #handle_name {
+                    handle: self.handle.clone(),
+                }
+            }
+        }
+
+        impl #msg_impl_generics ::core::convert::From<::priact::ActorHandle<#msg_ty>> for #handle_name #msg_ty_generics #msg_where_clause {
+            fn from(handle: ::priact::ActorHandle<#msg_ty>) -> Self {
+                #handle_name { handle }
+            }
+        }
+
+        #[allow(dead_code, deprecated)]
+        impl #msg_impl_generics #handle_name #msg_ty_generics #handle_where_clause {
+            #(#handle_methods)*
+
+            /// Sends `Shutdown`, which stops the actor ahead of every other message.
+            #shutdown_method
+
+            /// The untyped handle, for everything else an actor handle offers.
+            #vis fn inner(&self) -> &::priact::ActorHandle<#msg_ty> {
+                &self.handle
+            }
+
+            /// Unwraps the untyped handle, e.g. to [`join`](::priact::ActorHandle::join) the actor.
+            #vis fn into_inner(self) -> ::priact::ActorHandle<#msg_ty> {
+                self.handle
+            }
         }
+
+        #arbitrary_impl
     };
 
     TokenStream::from(expanded)
 }
+
+// Replaces every `Self` in `tokens` with `with`.
+fn replace_self(tokens: TokenStream2, with: &TokenStream2) -> TokenStream2 {
+    tokens
+        .into_iter()
+        .map(|tree| match tree {
+            proc_macro2::TokenTree::Ident(ident) if ident == "Self" => with.clone(),
+            proc_macro2::TokenTree::Group(group) => {
+                let mut replaced =
+                    proc_macro2::Group::new(group.delimiter(), replace_self(group.stream(), with));
+                replaced.set_span(group.span());
+                proc_macro2::TokenTree::Group(replaced).into()
+            }
+            tree => tree.into(),
+        })
+        .collect()
+}
+
+// `impl arbitrary::Arbitrary` for the message enum, used by fuzz targets. Every handler
+// variant can be generated; `oneshot::Sender` reply arguments get a throwaway channel whose
+// receiver is already dropped. `Shutdown` and `extern` variants are never generated.
+fn arbitrary_impl(msg_name: &Ident, methods: &[MethodDef]) -> proc_macro2::TokenStream {
+    let count = methods.len();
+    let arms = methods.iter().enumerate().map(|(i, m)| {
+        let name = &m.func.sig.ident;
+        let args = message_inputs(&m.func.sig).filter_map(|arg| {
+            if let syn::FnArg::Typed(pat_ty) = arg {
+                if is_oneshot_sender(&pat_ty.ty) {
+                    Some(quote! { ::priact::internal::oneshot::channel().0 })
+                } else {
+                    Some(quote! { u.arbitrary()? })
+                }
+            } else {
+                None
+            }
+        });
+        let reply =
+            reply_type(&m.func.sig).map(|_| quote! { ::priact::internal::oneshot::channel().0 });
+        quote! { #i => #msg_name::#name( #(#args,)* #reply ), }
+    });
+
+    quote! {
+        #[allow(deprecated)]
+        impl<'a> ::arbitrary::Arbitrary<'a> for #msg_name {
+            fn arbitrary(
+                u: &mut ::arbitrary::Unstructured<'a>,
+            ) -> ::arbitrary::Result<Self> {
+                Ok(match u.choose_index(#count)? {
+                    #(#arms)*
+                    _ => unreachable!(),
+                })
+            }
+        }
+    }
+}
+
+// Whether a handler's first argument after the receiver is an `&ActorContext<..>`, which
+// the generated `handle` passes along instead of taking it from the message.
+fn takes_context(sig: &syn::Signature) -> bool {
+    match sig.inputs.iter().nth(1) {
+        Some(syn::FnArg::Typed(pat_ty)) => match &*pat_ty.ty {
+            Type::Reference(r) if r.mutability.is_none() => match &*r.elem {
+                Type::Path(tp) => tp
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|s| s.ident == "ActorContext"),
+                _ => false,
+            },
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+// The arguments a handler's message carries: everything after the receiver and context.
+fn message_inputs(sig: &syn::Signature) -> impl Iterator<Item = &syn::FnArg> {
+    let skip = if takes_context(sig) { 2 } else { 1 };
+    sig.inputs.iter().skip(skip)
+}
+
+// A handler's message arguments: the name each is bound to in generated code, and its type.
+// Plain identifiers keep their name; any other pattern gets a fresh one and is destructured
+// by the handler method itself.
+fn handler_args(sig: &syn::Signature) -> Vec<(Ident, &Type)> {
+    message_inputs(sig)
+        .enumerate()
+        .filter_map(|(i, arg)| match arg {
+            syn::FnArg::Typed(pat_ty) => {
+                let ident = match &*pat_ty.pat {
+                    syn::Pat::Ident(pi) if pi.subpat.is_none() => pi.ident.clone(),
+                    _ => format_ident!("__arg{}", i),
+                };
+                Some((ident, &*pat_ty.ty))
+            }
+            syn::FnArg::Receiver(_) => None,
+        })
+        .collect()
+}
+
+// Handler arguments must bind whatever value the message carries.
+fn check_arg_pattern(pat: &syn::Pat) -> Result<()> {
+    use syn::Pat;
+    match pat {
+        Pat::Ident(pi) => match &pi.subpat {
+            Some((_, sub)) => check_arg_pattern(sub),
+            None => Ok(()),
+        },
+        Pat::Wild(_) | Pat::Rest(_) | Pat::Path(_) => Ok(()),
+        Pat::Tuple(tuple) => tuple.elems.iter().try_for_each(check_arg_pattern),
+        Pat::TupleStruct(tuple) => tuple.pat.elems.iter().try_for_each(check_arg_pattern),
+        Pat::Struct(fields) => fields
+            .fields
+            .iter()
+            .try_for_each(|field| check_arg_pattern(&field.pat)),
+        Pat::Slice(slice) => slice.elems.iter().try_for_each(check_arg_pattern),
+        other => Err(syn::Error::new_spanned(
+            other,
+            "unsupported handler argument pattern; use a name, `mut name`, `_`, or a tuple, struct or array destructuring",
+        )),
+    }
+}
+
+// The type a handler replies with: its return type, unless that is `()`.
+fn reply_type(sig: &syn::Signature) -> Option<&Type> {
+    match &sig.output {
+        syn::ReturnType::Type(_, ty) => match &**ty {
+            Type::Tuple(unit) if unit.elems.is_empty() => None,
+            ty => Some(ty),
+        },
+        syn::ReturnType::Default => None,
+    }
+}
+
+// The snake case name a handler goes by as a method; a raw identifier if that is a keyword,
+// as `r#type` for `Type`.
+fn method_ident(name: &Ident) -> Ident {
+    let snake = snake_case(&name.to_string());
+    if syn::parse_str::<Ident>(&snake).is_ok() {
+        Ident::new(&snake, name.span())
+    } else {
+        Ident::new_raw(&snake, name.span())
+    }
+}
+
+// `GetValue` -> `get_value`, `HTTPGet` -> `http_get`
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_uppercase() && next_is_lower)
+            {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
+
+fn is_oneshot_sender(ty: &Type) -> bool {
+    match ty {
+        Type::Path(tp) => {
+            let segments = &tp.path.segments;
+            segments.last().is_some_and(|s| s.ident == "Sender")
+                && segments.iter().any(|s| s.ident == "oneshot")
+        }
+        _ => false,
+    }
+}
+
+/// Derives `Prioritized` for a hand-written message enum.
+///
+/// Annotate variants with `#[priority(..)]`, taking the same levels as `@priority(..)` in
+/// `define_actor!`; variants without one are `Medium`. `variant_name` returns the variant's
+/// name. A variant marked `#[coalesce]` replaces one of the same variant still waiting in
+/// the queue, like `@coalesce` handlers.
+#[proc_macro_derive(Prioritized, attributes(priority, coalesce))]
+pub fn derive_prioritized(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    match prioritized_impl(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn prioritized_impl(input: &DeriveInput) -> Result<TokenStream2> {
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "#[derive(Prioritized)] only supports enums",
+        ));
+    };
+    if let Some(attr) = input.attrs.iter().find(|a| a.path.is_ident("priority")) {
+        return Err(syn::Error::new_spanned(
+            attr,
+            "#[priority(..)] belongs on the enum's variants",
+        ));
+    }
+
+    let mut arms = Vec::new();
+    let mut names = Vec::new();
+    let mut coalesce_arms = Vec::new();
+    for (key, variant) in (0u64..).zip(&data.variants) {
+        let mut levels = variant.attrs.iter().filter(|a| a.path.is_ident("priority"));
+        let level = match levels.next() {
+            Some(attr) => attr.parse_args::<PriorityLevel>()?.into_token_stream(),
+            None => quote! { Priority::Medium },
+        };
+        if let Some(extra) = levels.next() {
+            return Err(syn::Error::new_spanned(
+                extra,
+                "a variant takes a single #[priority(..)]",
+            ));
+        }
+        let name = &variant.ident;
+        let pattern = match &variant.fields {
+            Fields::Named(_) => quote! { Self::#name { .. } },
+            Fields::Unnamed(_) => quote! { Self::#name(..) },
+            Fields::Unit => quote! { Self::#name },
+        };
+        arms.push(quote! { #pattern => #level, });
+        names.push(quote! { #pattern => stringify!(#name), });
+        if variant.attrs.iter().any(|a| a.path.is_ident("coalesce")) {
+            coalesce_arms.push(quote! { #pattern => ::std::option::Option::Some(#key), });
+        }
+    }
+    let coalesce_key = (!coalesce_arms.is_empty()).then(|| {
+        quote! {
+            fn coalesce_key(&self) -> ::std::option::Option<u64> {
+                #[allow(unreachable_patterns)]
+                match self {
+                    #(#coalesce_arms)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+        }
+    });
+
+    let enum_name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    // An enum without variants has no values to match on.
+    let (priority_body, name_body) = if arms.is_empty() {
+        (quote! { match *self {} }, quote! { match *self {} })
+    } else {
+        (
+            quote! { match self { #(#arms)* } },
+            quote! { match self { #(#names)* } },
+        )
+    };
+    Ok(quote! {
+        const _: () = {
+            use ::priact::{Prioritized, Priority};
+
+            impl #impl_generics Prioritized for #enum_name #ty_generics #where_clause {
+                fn priority(&self) -> Priority {
+                    #priority_body
+                }
+
+                fn variant_name(&self) -> &'static str {
+                    #name_body
+                }
+
+                #coalesce_key
+            }
+        };
+    })
+}
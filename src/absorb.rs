@@ -0,0 +1,84 @@
+use crate::{ActorHandle, Prioritized};
+use std::sync::Arc;
+
+/// Where an absorbed actor's pending messages go relative to the absorbing actor's own
+/// queued messages of the same priority.
+///
+/// Either way, messages of different priorities are ordered by priority as always, and the
+/// moved messages keep their order among themselves.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AbsorbPlacement {
+    /// After the messages already waiting, as if they had just been sent.
+    #[default]
+    Behind,
+    /// Before the messages already waiting, as if they had been sent first.
+    Ahead,
+}
+
+/// What [`ActorHandle::absorb`] did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AbsorbReport {
+    /// Pending messages moved from the absorbed actor.
+    pub moved: usize,
+    /// Other live handles to the absorbed actor, whose sends now reach the absorbing one.
+    pub redirected_senders: usize,
+}
+
+impl<M: Prioritized + Send + 'static> ActorHandle<M> {
+    /// Takes over `other`'s pending messages and senders, retiring `other`.
+    ///
+    /// Same as [`absorb_with`](Self::absorb_with) with [`AbsorbPlacement::Behind`].
+    pub async fn absorb(&self, other: ActorHandle<M>) -> AbsorbReport {
+        self.absorb_with(other, AbsorbPlacement::Behind).await
+    }
+
+    /// Takes over `other`'s pending messages and senders, retiring `other`; meant for
+    /// consolidating two actors of the same kind into one.
+    ///
+    /// `other`'s mailbox is closed and everything waiting in it moves into this actor's
+    /// queue, placed according to `placement`. The moved messages no longer count against
+    /// either mailbox's capacity. `other` finishes the message it is handling, if any,
+    /// and then stops with [`TerminationReason::Absorbed`](crate::TerminationReason::Absorbed)
+    /// without handling anything else; this does not wait for that.
+    ///
+    /// From then on, sends through any handle to `other` are delivered to this actor
+    /// instead. Only sends are redirected: metrics, taps, schedules and `join` on those
+    /// handles still refer to `other`. If this actor has already stopped, the pending
+    /// messages are dropped.
+    ///
+    /// # Panics
+    ///
+    /// If `other` is this actor.
+    pub async fn absorb_with(
+        &self,
+        other: ActorHandle<M>,
+        placement: AbsorbPlacement,
+    ) -> AbsorbReport {
+        assert!(
+            !Arc::ptr_eq(&self.shared, &other.shared),
+            "an actor cannot absorb itself"
+        );
+        let redirected_senders = other.tx.strong_count() - 1;
+        // Redirect first: sends that still reach `other`'s mailbox are moved below, and any
+        // that arrive after it closes fall back to the redirect.
+        other.shared.redirect.store(Some(Arc::new(self.clone())));
+
+        let pending = other
+            .tx
+            .retire()
+            .into_iter()
+            .map(|mut envelope| {
+                envelope.permit = None;
+                envelope.pending = Some(self.shared.pending.track());
+                envelope
+            })
+            .collect();
+        let moved = self.tx.adopt(pending, placement);
+        AbsorbReport {
+            moved,
+            redirected_senders,
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use crate::prelude::*;
+use crate::{AbsorbPlacement, AbsorbReport};
+use tokio::sync::oneshot;
+
+define_actor! {
+    Shard {
+        seen: Vec<(char, u32)>,
+    }
+
+    impl ShardMsg {
+        @priority(Medium)
+        async fn Hold(&mut self, started: oneshot::Sender<()>, release: oneshot::Receiver<()>) {
+            let _ = started.send(());
+            let _ = release.await;
+        }
+
+        @priority(Low)
+        fn Item(&mut self, source: char, n: u32) {
+            self.seen.push((source, n));
+        }
+
+        @priority(High)
+        fn Urgent(&mut self, source: char, n: u32) {
+            self.seen.push((source.to_ascii_uppercase(), n));
+        }
+
+        @priority(Low)
+        fn Report(&mut self, tx: oneshot::Sender<Vec<(char, u32)>>) {
+            let _ = tx.send(std::mem::take(&mut self.seen));
+        }
+    }
+}
+
+/// A shard whose processor is parked, with `source`'s work queued behind the parked handler.
+async fn stalled_shard(source: char) -> (ActorHandle<ShardMsg>, oneshot::Sender<()>) {
+    let handle = spawn_actor(Shard { seen: Vec::new() });
+    let (started_tx, started_rx) = oneshot::channel();
+    let (release_tx, release_rx) = oneshot::channel();
+    handle
+        .send(ShardMsg::Hold(started_tx, release_rx))
+        .await
+        .unwrap();
+    started_rx.await.unwrap();
+
+    for n in 0..3 {
+        handle.send(ShardMsg::Item(source, n)).await.unwrap();
+    }
+    handle.send(ShardMsg::Urgent(source, 0)).await.unwrap();
+    (handle, release_tx)
+}
+
+async fn report(handle: &ActorHandle<ShardMsg>) -> Vec<(char, u32)> {
+    let (tx, rx) = oneshot::channel();
+    handle.send(ShardMsg::Report(tx)).await.unwrap();
+    rx.await.unwrap()
+}
+
+#[tokio::test]
+async fn test_absorb_moves_pending_work_behind_and_redirects_senders() {
+    let (a, release_a) = stalled_shard('a').await;
+    let (b, release_b) = stalled_shard('b').await;
+    let old_b = b.clone();
+
+    let report_ = a.absorb(b).await;
+    assert_eq!(
+        report_,
+        AbsorbReport {
+            moved: 4,
+            redirected_senders: 1,
+        }
+    );
+
+    // The retired shard finishes its current handler and stops without handling more.
+    drop(release_b);
+    assert!(!old_b.is_closed());
+    old_b.send(ShardMsg::Item('b', 9)).await.unwrap();
+    drop(release_a);
+
+    assert_eq!(
+        report(&a).await,
+        [
+            ('A', 0),
+            ('B', 0),
+            ('a', 0),
+            ('a', 1),
+            ('a', 2),
+            ('b', 0),
+            ('b', 1),
+            ('b', 2),
+            ('b', 9),
+        ]
+    );
+    assert_eq!(old_b.join().await, TerminationReason::Absorbed);
+}
+
+#[tokio::test]
+async fn test_absorb_ahead_puts_moved_work_first() {
+    let (a, release_a) = stalled_shard('a').await;
+    let (b, release_b) = stalled_shard('b').await;
+
+    let report_ = a.absorb_with(b, AbsorbPlacement::Ahead).await;
+    assert_eq!(report_.moved, 4);
+    assert_eq!(report_.redirected_senders, 0);
+    drop(release_b);
+    drop(release_a);
+
+    assert_eq!(
+        report(&a).await,
+        [
+            ('B', 0),
+            ('A', 0),
+            ('b', 0),
+            ('b', 1),
+            ('b', 2),
+            ('a', 0),
+            ('a', 1),
+            ('a', 2),
+        ]
+    );
+}
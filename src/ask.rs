@@ -0,0 +1,81 @@
+use crate::{ActorHandle, Error, Prioritized};
+use async_trait::async_trait;
+use tokio::sync::oneshot;
+use tokio::time::Duration;
+
+/// Request/response on top of [`ActorHandle::send`].
+///
+/// ```
+/// use priact::prelude::*;
+/// use tokio::sync::oneshot;
+///
+/// define_actor! {
+///     Counter { count: u64 }
+///
+///     impl CounterMsg {
+///         @priority(Low)
+///         fn Get(&mut self, tx: oneshot::Sender<u64>) {
+///             let _ = tx.send(self.count);
+///         }
+///     }
+/// }
+///
+/// # #[tokio::main] async fn main() {
+/// let handle = spawn_actor(Counter { count: 3 });
+/// assert_eq!(handle.ask(CounterMsg::Get).await, Ok(3));
+/// # }
+/// ```
+#[async_trait]
+pub trait AskExt: crate::sealed::Sealed {
+    type Msg;
+
+    /// Sends the message built by `make` around a fresh reply channel and waits for the reply.
+    ///
+    /// Fails with [`Error::MailboxClosed`] if the actor has stopped, with
+    /// [`Error::NotReady`] if it rejects messages until it is
+    /// [ready](crate::ActorConfig::readiness) and is not yet, and with
+    /// [`Error::ReplyDropped`] if it drops the reply sender unanswered, e.g. because it
+    /// stopped before handling the request. The request itself is not returned: it holds the
+    /// reply sender, which is of no use to the caller.
+    async fn ask<T, F>(&self, make: F) -> Result<T, Error>
+    where
+        T: Send + 'static,
+        F: FnOnce(oneshot::Sender<T>) -> Self::Msg + Send;
+
+    /// Like [`ask`](Self::ask), but gives up with [`Error::Timeout`] after `timeout`.
+    ///
+    /// The timeout covers waiting for mailbox capacity as well as for the reply. A request
+    /// that times out may still be handled later; its reply is then discarded.
+    async fn ask_timeout<T, F>(&self, timeout: Duration, make: F) -> Result<T, Error>
+    where
+        T: Send + 'static,
+        F: FnOnce(oneshot::Sender<T>) -> Self::Msg + Send;
+}
+
+#[async_trait]
+impl<M: Prioritized + Send + 'static> AskExt for ActorHandle<M> {
+    type Msg = M;
+
+    async fn ask<T, F>(&self, make: F) -> Result<T, Error>
+    where
+        T: Send + 'static,
+        F: FnOnce(oneshot::Sender<T>) -> M + Send,
+    {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.request(make(reply_tx), reply_rx).await
+    }
+
+    async fn ask_timeout<T, F>(&self, timeout: Duration, make: F) -> Result<T, Error>
+    where
+        T: Send + 'static,
+        F: FnOnce(oneshot::Sender<T>) -> M + Send,
+    {
+        tokio::time::timeout(timeout, self.ask(make))
+            .await
+            .unwrap_or_else(|_elapsed| {
+                Err(Error::Timeout {
+                    actor: self.name().to_owned(),
+                })
+            })
+    }
+}
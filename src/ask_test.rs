@@ -0,0 +1,92 @@
+use crate::prelude::*;
+use crate::test_support::{gated_init, stall, Gated, GatedMsg};
+use crate::Readiness;
+use tokio::time::{timeout, Duration};
+
+#[tokio::test]
+async fn test_ask_returns_reply() {
+    let handle = spawn_actor(Gated::new());
+    handle.send(GatedMsg::Urgent()).await.unwrap();
+
+    let handled = handle.ask(GatedMsg::Report).await;
+    assert_eq!(handled, Ok(vec![Priority::High]));
+}
+
+#[tokio::test]
+async fn test_ask_reports_reply_dropped_when_actor_stops_first() {
+    let handle = spawn_actor(Gated::new());
+    let release = stall(&handle).await;
+
+    // The request is queued, then Shutdown overtakes it once the handler is released.
+    let (reply, ()) = tokio::join!(handle.ask(GatedMsg::Report), async {
+        handle.send(GatedMsg::Shutdown).await.unwrap();
+        release.send(()).unwrap();
+    });
+    assert!(matches!(reply, Err(Error::ReplyDropped { .. })));
+}
+
+#[tokio::test]
+async fn test_ask_reports_actor_gone_after_stop() {
+    let handle = spawn_actor(Gated::new());
+    let observer = handle.clone();
+    handle.send(GatedMsg::Shutdown).await.unwrap();
+    handle.join().await;
+
+    timeout(Duration::from_secs(1), async {
+        while !observer.is_closed() {
+            tokio::task::yield_now().await;
+        }
+    })
+    .await
+    .unwrap();
+    assert!(matches!(
+        observer.ask(GatedMsg::Report).await,
+        Err(Error::MailboxClosed { returned: None, .. })
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_ask_timeout_expires_while_actor_is_busy() {
+    let handle = spawn_actor(Gated::new());
+    let release = stall(&handle).await;
+
+    let reply = handle
+        .ask_timeout(Duration::from_millis(100), GatedMsg::Report)
+        .await;
+    assert!(matches!(reply, Err(Error::Timeout { .. })));
+
+    release.send(()).unwrap();
+    let reply = handle
+        .ask_timeout(Duration::from_millis(100), GatedMsg::Report)
+        .await;
+    assert_eq!(reply, Ok(vec![]));
+}
+
+define_actor! {
+    Doubler {}
+
+    impl DoublerMsg {
+        fn Double(&self, value: u32) -> u32 {
+            value * 2
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_ask_pairs_with_generated_replies() {
+    let handle = spawn_actor(Doubler {});
+    let doubled = handle.ask(|tx| DoublerMsg::Double(21, tx)).await.unwrap();
+    assert_eq!(doubled, 42);
+}
+
+#[tokio::test]
+async fn test_ask_before_ready_reports_not_ready() {
+    let (release, gate) = tokio::sync::oneshot::channel();
+    let handle = gated_init(ActorConfig::new().readiness(Readiness::Reject), gate);
+
+    let err = handle.ask(GatedMsg::Report).await.unwrap_err();
+    assert!(err.is_not_ready(), "got {err:?}");
+    release.send(()).unwrap();
+    handle.ready().await.unwrap();
+    assert_eq!(handle.ask(GatedMsg::Report).await, Ok(vec![]));
+}
@@ -0,0 +1,44 @@
+use crate::{Origin, Priority, TerminationReason};
+
+/// What happened to an actor's messages, reported through the audit sink configured with
+/// [`ActorConfig::audit`](crate::ActorConfig::audit).
+///
+/// [`Stopped`](AuditEvent::Stopped) is always the last event an actor reports, and it is
+/// delivered before [`join`](crate::ActorHandle::join) resolves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AuditEvent {
+    /// A message was handed to the actor.
+    Handled { priority: Priority, origin: Origin },
+    /// A message was dropped on arrival because the queue had reached its shed threshold.
+    Shed { priority: Priority, origin: Origin },
+    /// A message was dropped instead of handled because it had outlived its TTL.
+    Expired { priority: Priority, origin: Origin },
+    /// A message was dropped because the queue was at its limit, either on arrival or to
+    /// make room for a more urgent one.
+    Overflowed { priority: Priority, origin: Origin },
+    /// A waiting message was replaced by a newer one with the same
+    /// [coalescing key](crate::Prioritized::coalesce_key).
+    Coalesced { priority: Priority, origin: Origin },
+    /// The actor stopped; `totals` counts everything it reported before.
+    Stopped {
+        reason: TerminationReason,
+        totals: AuditTotals,
+    },
+}
+
+/// Final message counts carried by [`AuditEvent::Stopped`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AuditTotals {
+    /// Messages handed to the actor, including the one that stopped it.
+    pub handled: u64,
+    /// Messages shed on arrival.
+    pub shed: u64,
+    /// Messages dropped after outliving their TTL.
+    pub expired: u64,
+    /// Messages dropped because the queue was at its limit.
+    pub overflowed: u64,
+    /// Messages replaced by a newer one with the same coalescing key.
+    pub coalesced: u64,
+}
@@ -0,0 +1,82 @@
+use crate::prelude::*;
+use crate::sink::Sink;
+use crate::test_support::{stall, Gated, GatedMsg};
+use crate::{AuditEvent, AuditTotals, Origin};
+use tokio::sync::mpsc;
+
+/// Everything already delivered to the audit channel, without waiting for more.
+fn drain(rx: &mut mpsc::UnboundedReceiver<AuditEvent>) -> Vec<AuditEvent> {
+    std::iter::from_fn(|| rx.try_recv().ok()).collect()
+}
+
+#[tokio::test]
+async fn test_audit_ends_with_stopped_before_join_resolves() {
+    let (audit_tx, mut audit_rx) = mpsc::unbounded_channel();
+    let config = ActorConfig::new().audit(Sink::unbounded(audit_tx));
+    let handle = spawn_actor_with(Gated::new(), config);
+
+    for _ in 0..50 {
+        handle.send(GatedMsg::Fill()).await.unwrap();
+    }
+    handle.send(GatedMsg::Shutdown).await.unwrap();
+    assert_eq!(handle.join().await, TerminationReason::ShutdownMessage);
+
+    let events = drain(&mut audit_rx);
+    let handled = events
+        .iter()
+        .filter(|event| matches!(event, AuditEvent::Handled { .. }))
+        .count() as u64;
+    let Some(AuditEvent::Stopped { reason, totals }) = events.last() else {
+        panic!(
+            "audit stream should end with Stopped, got {:?}",
+            events.last()
+        );
+    };
+    assert_eq!(*reason, TerminationReason::ShutdownMessage);
+    assert_eq!(totals.handled, handled);
+    assert_eq!(totals.shed, 0);
+    // Shutdown overtakes whatever Fill messages had not been handled yet.
+    assert!((1..=51).contains(&handled));
+}
+
+#[tokio::test]
+async fn test_audit_totals_include_shed_messages() {
+    let (audit_tx, mut audit_rx) = mpsc::unbounded_channel();
+    let config = ActorConfig::new()
+        .shed_threshold(2)
+        .audit(Sink::unbounded(audit_tx));
+    let handle = spawn_actor_with(Gated::new(), config);
+    let release = stall(&handle).await;
+
+    for _ in 0..5 {
+        handle.send(GatedMsg::Fill()).await.unwrap();
+    }
+    release.send(()).unwrap();
+    assert_eq!(handle.join().await, TerminationReason::SendersDropped);
+
+    let events = drain(&mut audit_rx);
+    let shed = events
+        .iter()
+        .filter(|event| {
+            **event
+                == AuditEvent::Shed {
+                    priority: Priority::Low,
+                    origin: Origin::External,
+                }
+        })
+        .count();
+    assert_eq!(shed, 3);
+    assert_eq!(
+        events.last(),
+        Some(&AuditEvent::Stopped {
+            reason: TerminationReason::SendersDropped,
+            totals: AuditTotals {
+                handled: 3,
+                shed: 3,
+                expired: 0,
+                overflowed: 0,
+                coalesced: 0,
+            },
+        })
+    );
+}
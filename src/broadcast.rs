@@ -0,0 +1,136 @@
+use crate::{ActorHandle, Prioritized};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+type Subscribers<M> = Mutex<BTreeMap<u64, ActorHandle<M>>>;
+
+/// Sends one message to every subscribed actor, e.g. a `ConfigChanged` event several actors
+/// react to.
+///
+/// Cloning a broadcaster is cheap and every clone shares the same subscribers.
+pub struct Broadcaster<M> {
+    inner: Arc<Inner<M>>,
+}
+
+struct Inner<M> {
+    subscribers: Subscribers<M>,
+    next_id: AtomicU64,
+}
+
+impl<M> Clone for Broadcaster<M> {
+    fn clone(&self) -> Self {
+        Broadcaster {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<M> Default for Broadcaster<M> {
+    fn default() -> Self {
+        Broadcaster {
+            inner: Arc::new(Inner {
+                subscribers: Mutex::new(BTreeMap::new()),
+                next_id: AtomicU64::new(0),
+            }),
+        }
+    }
+}
+
+impl<M> fmt::Debug for Broadcaster<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Broadcaster")
+            .field("subscribers", &self.len())
+            .finish()
+    }
+}
+
+impl<M> Broadcaster<M> {
+    /// A broadcaster without subscribers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `handle` to the actors every broadcast reaches, until the returned guard is
+    /// dropped.
+    ///
+    /// The broadcaster holds on to `handle`, so a subscribed actor does not stop for lack of
+    /// senders. Subscribing the same actor twice delivers every broadcast to it twice.
+    pub fn subscribe(&self, handle: ActorHandle<M>) -> Subscription<M> {
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        self.inner.subscribers.lock().unwrap().insert(id, handle);
+        Subscription {
+            id,
+            broadcaster: Arc::downgrade(&self.inner),
+        }
+    }
+
+    /// The number of subscribed actors, including stopped ones not yet pruned by a broadcast.
+    pub fn len(&self) -> usize {
+        self.inner.subscribers.lock().unwrap().len()
+    }
+
+    /// Returns `true` if no actor is subscribed.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<M: Prioritized + Clone + Send + 'static> Broadcaster<M> {
+    /// Sends a clone of `msg` to every subscriber, in the order they subscribed, and returns
+    /// how many took it.
+    ///
+    /// Each send waits for room in that subscriber's mailbox, as [`ActorHandle::send`] does,
+    /// and each subscriber handles its copy by priority like any other message. Subscribers
+    /// that have stopped are unsubscribed.
+    pub async fn broadcast(&self, msg: M) -> usize {
+        let subscribers: Vec<_> = self
+            .inner
+            .subscribers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(&id, handle)| (id, handle.clone()))
+            .collect();
+
+        let mut delivered = 0;
+        for (id, handle) in subscribers {
+            match handle.send(msg.clone()).await {
+                Ok(()) => delivered += 1,
+                Err(_) => {
+                    self.inner.subscribers.lock().unwrap().remove(&id);
+                }
+            }
+        }
+        delivered
+    }
+}
+
+/// Keeps an actor subscribed to a [`Broadcaster`]; dropping it unsubscribes the actor.
+#[must_use = "the actor is unsubscribed as soon as the subscription is dropped"]
+pub struct Subscription<M> {
+    id: u64,
+    broadcaster: Weak<Inner<M>>,
+}
+
+impl<M> Subscription<M> {
+    /// Unsubscribes the actor; same as dropping the subscription.
+    pub fn unsubscribe(self) {}
+}
+
+impl<M> Drop for Subscription<M> {
+    fn drop(&mut self) {
+        if let Some(inner) = self.broadcaster.upgrade() {
+            inner.subscribers.lock().unwrap().remove(&self.id);
+        }
+    }
+}
+
+impl<M> fmt::Debug for Subscription<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscription")
+            .field("id", &self.id)
+            .finish()
+    }
+}
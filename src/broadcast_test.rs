@@ -0,0 +1,105 @@
+use crate::prelude::*;
+use crate::Broadcaster;
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+#[derive(Prioritized, Clone)]
+enum ConfigMsg {
+    /// Parks the listener until `release` is notified.
+    Hold(Arc<Notify>, Arc<Notify>),
+    #[priority(Low)]
+    Changed(u32),
+    #[priority(High)]
+    Reload,
+    #[priority(Shutdown)]
+    Stop,
+}
+
+#[derive(Default)]
+struct Listener {
+    seen: Vec<Option<u32>>,
+}
+
+#[async_trait]
+impl Actor for Listener {
+    type Msg = ConfigMsg;
+
+    async fn handle(
+        &mut self,
+        msg: ConfigMsg,
+        _ctx: &ActorContext<ConfigMsg>,
+    ) -> ControlFlow<ShutdownReason> {
+        match msg {
+            ConfigMsg::Hold(started, release) => {
+                started.notify_one();
+                release.notified().await;
+            }
+            ConfigMsg::Changed(version) => self.seen.push(Some(version)),
+            ConfigMsg::Reload => self.seen.push(None),
+            ConfigMsg::Stop => return ControlFlow::Break(ShutdownReason::Requested),
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// Parks `handle`'s processor until the returned notify fires.
+async fn hold(handle: &ActorHandle<ConfigMsg>) -> Arc<Notify> {
+    let started = Arc::new(Notify::new());
+    let release = Arc::new(Notify::new());
+    handle
+        .send(ConfigMsg::Hold(Arc::clone(&started), Arc::clone(&release)))
+        .await
+        .unwrap();
+    started.notified().await;
+    release
+}
+
+#[tokio::test]
+async fn test_broadcast_reaches_every_subscriber_and_prunes_stopped_ones() {
+    let broadcaster = Broadcaster::new();
+    let first = spawn_actor_with(Listener::default(), ActorConfig::new().capacity(1));
+    let second = spawn_actor(Listener::default());
+    let third = spawn_actor_owned(Listener::default(), ActorConfig::new());
+    let subscriptions = [
+        broadcaster.subscribe(first.clone()),
+        broadcaster.subscribe(second.clone()),
+        broadcaster.subscribe(third.handle().clone()),
+    ];
+
+    // The broadcast waits on the first subscriber's full mailbox while the second stops.
+    let release = hold(&first).await;
+    first.send(ConfigMsg::Changed(0)).await.unwrap();
+    let broadcast = tokio::spawn({
+        let broadcaster = broadcaster.clone();
+        async move { broadcaster.broadcast(ConfigMsg::Changed(1)).await }
+    });
+    second.send(ConfigMsg::Stop).await.unwrap();
+    assert_eq!(second.join().await, TerminationReason::ShutdownMessage);
+    release.notify_one();
+
+    assert_eq!(broadcast.await.unwrap(), 2);
+    assert_eq!(broadcaster.len(), 2);
+    assert_eq!(broadcaster.broadcast(ConfigMsg::Changed(2)).await, 2);
+
+    drop(subscriptions);
+    assert!(broadcaster.is_empty());
+    let third = third.join().await.unwrap();
+    assert_eq!(third.seen, [Some(1), Some(2)]);
+}
+
+#[tokio::test]
+async fn test_broadcast_copies_are_handled_by_priority() {
+    let broadcaster = Broadcaster::new();
+    let listener = spawn_actor_owned(Listener::default(), ActorConfig::new());
+    let subscription = broadcaster.subscribe(listener.handle().clone());
+
+    let release = hold(&listener).await;
+    broadcaster.broadcast(ConfigMsg::Changed(1)).await;
+    broadcaster.broadcast(ConfigMsg::Reload).await;
+    release.notify_one();
+
+    subscription.unsubscribe();
+    let listener = listener.join().await.unwrap();
+    assert_eq!(listener.seen, [None, Some(1)]);
+}
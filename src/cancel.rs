@@ -0,0 +1,41 @@
+//! Stopping actors when a [`CancellationToken`] fires, e.g. to shut a whole service down
+//! without tracking down every handle.
+
+#[cfg(feature = "cancellation")]
+pub use tokio_util::sync::CancellationToken;
+
+/// How an actor stops once the token given to
+/// [`ActorConfig::cancel_on`](crate::ActorConfig::cancel_on) is cancelled.
+#[cfg(feature = "cancellation")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CancelMode {
+    /// Once the running handler, if any, returns, the mailbox closes, so sends fail from
+    /// then on, and the actor handles what was already queued before it stops.
+    #[default]
+    Graceful,
+    /// The actor stops as soon as the running handler, if any, returns; what is left in the
+    /// queue goes to the dead-letter sink, if there is one.
+    Immediate,
+}
+
+/// The token an actor stops on, if any.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CancelOn {
+    #[cfg(feature = "cancellation")]
+    pub(crate) token: Option<CancellationToken>,
+    #[cfg(feature = "cancellation")]
+    pub(crate) mode: CancelMode,
+}
+
+impl CancelOn {
+    /// Resolves once the token is cancelled, with whether the actor should handle what is
+    /// already queued before it stops. Never resolves without a token.
+    pub(crate) async fn fired(&self) -> bool {
+        #[cfg(feature = "cancellation")]
+        if let Some(token) = &self.token {
+            token.cancelled().await;
+            return self.mode == CancelMode::Graceful;
+        }
+        std::future::pending().await
+    }
+}
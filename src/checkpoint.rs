@@ -0,0 +1,172 @@
+//! Snapshotting an actor's state as it runs, so that a replacement can pick up where it
+//! left off after a crash.
+//!
+//! [`ActorConfig::checkpoint_every`] has the processor take a [`Checkpoint::snapshot`] of
+//! the actor between messages and hand the bytes to a callback, which can write them
+//! wherever they should outlive the process. [`spawn_actor_restored`] boots an actor from
+//! the latest of them.
+
+use crate::sink::Sink;
+use crate::{spawn_actor_with, Actor, ActorConfig, ActorHandle};
+use std::any::{type_name, Any, TypeId};
+use std::fmt;
+use tokio::time::{Duration, Instant};
+
+/// An actor whose state can be saved as bytes and rebuilt from them.
+pub trait Checkpoint: Sized {
+    /// Why a snapshot could not be restored.
+    type Error;
+
+    /// Encodes the actor's state. Called on the processor task between messages, so it
+    /// should return quickly.
+    fn snapshot(&self) -> Vec<u8>;
+
+    /// Rebuilds the actor from bytes that [`snapshot`](Self::snapshot) returned.
+    fn restore(bytes: &[u8]) -> Result<Self, Self::Error>;
+}
+
+/// How often [`ActorConfig::checkpoint_every`] takes a snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckpointEvery {
+    /// After every `n` messages handled.
+    Messages(u64),
+    /// Once per interval, if a message has been handled since the last snapshot.
+    Interval(Duration),
+}
+
+/// A checkpoint cadence and sink, with the actor type erased since the config is not
+/// generic.
+#[derive(Clone)]
+pub(crate) struct Checkpointing {
+    every: CheckpointEvery,
+    actor_type: TypeId,
+    actor_name: &'static str,
+    snapshot: fn(&dyn Any) -> Vec<u8>,
+    sink: Sink<Vec<u8>>,
+}
+
+impl Checkpointing {
+    pub(crate) fn new<A: Checkpoint + 'static>(
+        every: CheckpointEvery,
+        sink: Sink<Vec<u8>>,
+    ) -> Self {
+        assert!(
+            !matches!(
+                every,
+                CheckpointEvery::Messages(0) | CheckpointEvery::Interval(Duration::ZERO)
+            ),
+            "checkpoint cadence must be greater than zero"
+        );
+        Checkpointing {
+            every,
+            actor_type: TypeId::of::<A>(),
+            actor_name: type_name::<A>(),
+            snapshot: |state| {
+                state
+                    .downcast_ref::<A>()
+                    .expect("the actor's type is checked on spawning")
+                    .snapshot()
+            },
+            sink,
+        }
+    }
+}
+
+impl fmt::Debug for Checkpointing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Checkpointing<{}>({:?})", self.actor_name, self.every)
+    }
+}
+
+/// Where the processor keeps track of when the next snapshot is due.
+pub(crate) struct Checkpoints {
+    checkpointing: Option<Checkpointing>,
+    // Messages handled since the last snapshot.
+    handled: u64,
+    due: bool,
+    next_at: Option<Instant>,
+}
+
+impl Checkpoints {
+    pub(crate) fn new(checkpointing: Option<Checkpointing>) -> Self {
+        let next_at = match checkpointing.as_ref().map(|c| c.every) {
+            Some(CheckpointEvery::Interval(interval)) => Some(Instant::now() + interval),
+            _ => None,
+        };
+        Checkpoints {
+            checkpointing,
+            handled: 0,
+            due: false,
+            next_at,
+        }
+    }
+
+    /// Panics unless the snapshots are of the actor in `state`.
+    pub(crate) fn check(&self, state: &dyn Any) {
+        if let Some(checkpointing) = &self.checkpointing {
+            assert!(
+                Any::type_id(state) == checkpointing.actor_type,
+                "checkpoints take `{}`, not the actor",
+                checkpointing.actor_name
+            );
+        }
+    }
+
+    /// Counts `n` more handled messages.
+    pub(crate) fn handled(&mut self, n: usize) {
+        self.handled += n as u64;
+        if let Some(CheckpointEvery::Messages(every)) = self.checkpointing.as_ref().map(|c| c.every)
+        {
+            self.due |= self.handled >= every;
+        }
+    }
+
+    /// Resolves once an interval has passed, marking a snapshot due; never without one.
+    pub(crate) async fn elapsed(&mut self) {
+        match self.next_at {
+            Some(at) => {
+                tokio::time::sleep_until(at).await;
+                self.due = true;
+            }
+            None => std::future::pending().await,
+        }
+    }
+
+    pub(crate) fn is_due(&self) -> bool {
+        self.due
+    }
+
+    /// Snapshots the actor in `state` if one is due and it has handled anything since the
+    /// last, which must be between handlers.
+    pub(crate) fn take(&mut self, state: &dyn Any) {
+        let Some(checkpointing) = &self.checkpointing else {
+            return;
+        };
+        if !self.due {
+            return;
+        }
+        if self.handled > 0 {
+            checkpointing.sink.deliver((checkpointing.snapshot)(state));
+        }
+        self.handled = 0;
+        self.due = false;
+        if let CheckpointEvery::Interval(interval) = checkpointing.every {
+            self.next_at = Some(Instant::now() + interval);
+        }
+    }
+}
+
+/// Spawns an actor like [`spawn_actor_with`] from a snapshot taken with
+/// [`ActorConfig::checkpoint_every`], e.g. the last one saved before a crash.
+///
+/// Messages the original actor had queued or was handling when it stopped are not part of
+/// the snapshot.
+pub fn spawn_actor_restored<A>(
+    snapshot: &[u8],
+    config: ActorConfig,
+) -> Result<ActorHandle<A::Msg>, A::Error>
+where
+    A: Actor + Checkpoint,
+{
+    Ok(spawn_actor_with(A::restore(snapshot)?, config))
+}
@@ -0,0 +1,111 @@
+use crate::prelude::*;
+use crate::test_support::Gated;
+use crate::{spawn_actor_in, spawn_actor_restored, Checkpoint, CheckpointEvery};
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinSet;
+use tokio::time::Duration;
+
+define_actor! {
+    /// Counts the messages it has handled.
+    Tally {
+        count: u64,
+    }
+
+    impl TallyMsg {
+        @priority(Medium)
+        fn Add(&mut self) {
+            self.count += 1;
+        }
+
+        @priority(Low)
+        fn Count(&self) -> u64 {
+            self.count
+        }
+    }
+}
+
+impl Checkpoint for Tally {
+    type Error = usize;
+
+    fn snapshot(&self) -> Vec<u8> {
+        self.count.to_le_bytes().to_vec()
+    }
+
+    fn restore(bytes: &[u8]) -> Result<Self, usize> {
+        let count = bytes.try_into().map_err(|_| bytes.len())?;
+        Ok(Tally {
+            count: u64::from_le_bytes(count),
+        })
+    }
+}
+
+/// A config checkpointing `every` into the returned list.
+fn checkpointed(every: CheckpointEvery) -> (ActorConfig, Arc<Mutex<Vec<Vec<u8>>>>) {
+    let snapshots = Arc::new(Mutex::new(Vec::new()));
+    let saved = Arc::clone(&snapshots);
+    let config = ActorConfig::new().checkpoint_every::<Tally>(every, move |snapshot| {
+        saved.lock().unwrap().push(snapshot);
+    });
+    (config, snapshots)
+}
+
+async fn count(handle: &ActorHandle<TallyMsg>) -> u64 {
+    let (msg, count) = TallyMsg::count();
+    handle.send(msg).await.unwrap();
+    count.await.unwrap()
+}
+
+#[tokio::test]
+async fn test_restored_actor_resumes_from_the_last_snapshot() {
+    let (config, snapshots) = checkpointed(CheckpointEvery::Messages(5));
+    let mut tasks = JoinSet::new();
+    let handle = spawn_actor_in(Tally { count: 0 }, config, &mut tasks);
+    for _ in 0..12 {
+        handle.send(TallyMsg::Add()).await.unwrap();
+    }
+    handle.await_idle().await;
+    tasks.abort_all();
+    assert_eq!(handle.clone().join().await, TerminationReason::Aborted);
+
+    let last = snapshots.lock().unwrap().last().cloned().unwrap();
+    assert_eq!(snapshots.lock().unwrap().len(), 2);
+    let handle = spawn_actor_restored::<Tally>(&last, ActorConfig::new()).unwrap();
+    assert_eq!(count(&handle).await, 10);
+    handle.send(TallyMsg::Add()).await.unwrap();
+    assert_eq!(count(&handle).await, 11);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_interval_snapshots_skip_idle_periods() {
+    let (config, snapshots) = checkpointed(CheckpointEvery::Interval(Duration::from_secs(1)));
+    let handle = spawn_actor_with(Tally { count: 0 }, config);
+    handle.send(TallyMsg::Add()).await.unwrap();
+    handle.send(TallyMsg::Add()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert_eq!(*snapshots.lock().unwrap(), [2u64.to_le_bytes().to_vec()]);
+
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    assert_eq!(snapshots.lock().unwrap().len(), 1);
+    handle.send(TallyMsg::Add()).await.unwrap();
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(snapshots.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_garbled_snapshot_spawns_nothing() {
+    let restored = spawn_actor_restored::<Tally>(&[1, 2, 3], ActorConfig::new());
+    assert!(matches!(restored, Err(3)));
+}
+
+#[test]
+#[should_panic(expected = "checkpoint cadence must be greater than zero")]
+fn test_checkpoint_cadence_must_be_positive() {
+    ActorConfig::new().checkpoint_every::<Tally>(CheckpointEvery::Messages(0), drop);
+}
+
+#[tokio::test]
+#[should_panic(expected = "checkpoints take `")]
+async fn test_checkpoints_of_another_actor_type_are_rejected() {
+    let config = ActorConfig::new().checkpoint_every::<Tally>(CheckpointEvery::Messages(1), drop);
+    spawn_actor_with(Gated::new(), config);
+}
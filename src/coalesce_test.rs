@@ -0,0 +1,125 @@
+use crate::prelude::*;
+use crate::sink::Sink;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, oneshot};
+
+define_actor! {
+    /// Logs the frames it draws and the notes it takes.
+    Canvas {
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl CanvasMsg {
+        @priority(High)
+        async fn Hold(&mut self, started: oneshot::Sender<()>, release: oneshot::Receiver<()>) {
+            let _ = started.send(());
+            let _ = release.await;
+        }
+
+        @priority(Low) @coalesce
+        fn Redraw(&mut self, frame: u64) {
+            self.log.lock().unwrap().push(format!("redraw {}", frame));
+        }
+
+        @priority(Low)
+        fn Note(&mut self, id: u64) {
+            self.log.lock().unwrap().push(format!("note {}", id));
+        }
+    }
+}
+
+/// A canvas parked inside a handler until the returned sender fires, and its log.
+async fn stalled_canvas(
+    config: ActorConfig,
+) -> (
+    ActorHandle<CanvasMsg>,
+    oneshot::Sender<()>,
+    Arc<Mutex<Vec<String>>>,
+) {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let handle = spawn_actor_with(
+        Canvas {
+            log: Arc::clone(&log),
+        },
+        config,
+    );
+    let (started_tx, started_rx) = oneshot::channel();
+    let (release_tx, release_rx) = oneshot::channel();
+    handle
+        .send(CanvasMsg::Hold(started_tx, release_rx))
+        .await
+        .unwrap();
+    started_rx.await.unwrap();
+    (handle, release_tx, log)
+}
+
+#[tokio::test]
+async fn test_burst_of_redraws_is_handled_once_with_the_latest_frame() {
+    let (handle, release, log) = stalled_canvas(ActorConfig::new()).await;
+    for frame in 0..1000 {
+        handle.send(CanvasMsg::Redraw(frame)).await.unwrap();
+    }
+    #[cfg(feature = "metrics")]
+    {
+        use crate::MetricsExt;
+        assert_eq!(handle.metrics().queue_len(), 1);
+    }
+    release.send(()).unwrap();
+    handle.await_idle().await;
+
+    assert_eq!(*log.lock().unwrap(), ["redraw 999"]);
+    #[cfg(feature = "metrics")]
+    {
+        use crate::MetricsExt;
+        assert_eq!(handle.metrics().messages_coalesced(), 999);
+        assert_eq!(handle.metrics().messages_dropped(), 999);
+    }
+}
+
+#[tokio::test]
+async fn test_coalesced_message_keeps_its_place_in_line() {
+    let (handle, release, log) = stalled_canvas(ActorConfig::new()).await;
+    handle.send(CanvasMsg::Redraw(1)).await.unwrap();
+    handle.send(CanvasMsg::Note(2)).await.unwrap();
+    handle.send(CanvasMsg::Redraw(3)).await.unwrap();
+    handle.send(CanvasMsg::Note(4)).await.unwrap();
+    release.send(()).unwrap();
+    handle.await_idle().await;
+
+    assert_eq!(*log.lock().unwrap(), ["redraw 3", "note 2", "note 4"]);
+}
+
+#[tokio::test]
+async fn test_replaced_messages_go_to_dead_letters() {
+    let (tx, mut dead_letters) = mpsc::unbounded_channel();
+    let config = ActorConfig::new().dead_letters(Sink::unbounded(tx));
+    let (handle, release, log) = stalled_canvas(config).await;
+    handle.send(CanvasMsg::Redraw(1)).await.unwrap();
+    handle.send(CanvasMsg::Redraw(2)).await.unwrap();
+    release.send(()).unwrap();
+    handle.await_idle().await;
+
+    assert!(matches!(dead_letters.try_recv(), Ok(CanvasMsg::Redraw(1))));
+    assert!(dead_letters.try_recv().is_err());
+    assert_eq!(*log.lock().unwrap(), ["redraw 2"]);
+}
+
+#[tokio::test]
+async fn test_inspected_queue_shows_coalesced_messages_once() {
+    let (handle, release, _log) = stalled_canvas(ActorConfig::new()).await;
+    handle.send(CanvasMsg::Redraw(1)).await.unwrap();
+    handle.send(CanvasMsg::Note(7)).await.unwrap();
+    handle.send(CanvasMsg::Redraw(2)).await.unwrap();
+
+    let queued = handle.inspect_pending(|msgs| {
+        msgs.iter()
+            .map(|msg| match msg {
+                CanvasMsg::Redraw(frame) => format!("redraw {}", frame),
+                CanvasMsg::Note(id) => format!("note {}", id),
+                other => other.variant_name().to_owned(),
+            })
+            .collect::<Vec<_>>()
+    });
+    assert_eq!(queued, ["redraw 2", "note 7"]);
+    release.send(()).unwrap();
+}
@@ -0,0 +1,262 @@
+use crate::correlation;
+use crate::log::diag;
+use crate::mailbox::Visit;
+use crate::receiver::Received;
+use crate::{
+    channel, default_name, forced, task, unexpired, ActorConfig, ActorContext, ActorHandle,
+    Dispatched, Prioritized, PriorityMailbox, ShutdownReason, Teardown, TerminationReason,
+};
+use async_trait::async_trait;
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use std::ops::ControlFlow;
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tokio::time::{Duration, Instant};
+
+/// An actor whose messages are independent of one another, so that it can handle several
+/// at once, e.g. a proxy waiting on a different upstream request for each message.
+/// Spawned with [`spawn_concurrent_actor`].
+///
+/// Handlers take `&self`, so whatever state they change sits behind the actor's own locks
+/// or atomics. Up to [`ActorConfig::max_concurrent_handlers`] of them run at once, all on
+/// the actor's processor task.
+#[async_trait]
+pub trait ConcurrentActor: Send + Sync + 'static {
+    type Msg: Send + 'static + Prioritized;
+
+    /// Handles one message, alongside whatever other handlers are running. Return
+    /// `ControlFlow::Break` to stop the actor once the handlers still running return.
+    async fn handle(
+        &self,
+        msg: Self::Msg,
+        ctx: &ActorContext<Self::Msg>,
+    ) -> ControlFlow<ShutdownReason>;
+
+    /// See [`Actor::on_start`](crate::Actor::on_start).
+    async fn on_start(&mut self) {}
+
+    /// Runs on the processor task once the actor has stopped and every handler has
+    /// returned, however it stopped, unless a handler panicked.
+    async fn on_stop(&mut self) {}
+}
+
+/// Spawns a [`ConcurrentActor`] that handles up to
+/// [`max_concurrent_handlers`](ActorConfig::max_concurrent_handlers) messages at once.
+///
+/// Messages leave the queue in priority order, as for any actor, and a new one is taken
+/// whenever fewer handlers than the limit are running. **The order in which handlers finish
+/// is not guaranteed**: a slow handler for a `High` message may still be running when the
+/// handler for a `Low` message taken after it returns.
+///
+/// A `Shutdown` message, or a handler that breaks, stops the actor from taking new messages;
+/// it stops once the handlers already running have returned. Handler timeouts cancel only
+/// the handler that ran over. [`ActorContext::priority`] is `None` in these handlers, and
+/// [`ActorConfig::batch_size`] does not apply.
+pub fn spawn_concurrent_actor<A: ConcurrentActor>(
+    actor: A,
+    mut config: ActorConfig,
+) -> ActorHandle<A::Msg> {
+    if config.name.is_none() {
+        config.name = Some(default_name(std::any::type_name::<A>()));
+    }
+    let limit = config.max_concurrent_handlers;
+    let (handle, mailbox) = channel(config);
+    task::spawn_on(
+        handle.name(),
+        run(actor, mailbox, limit),
+        &Handle::current(),
+    );
+    handle
+}
+
+/// A handler that has returned, with what its message is accounted under.
+struct Handled {
+    dispatched: Dispatched,
+    elapsed: Duration,
+    // `None` if the handler timed out.
+    flow: Option<ControlFlow<ShutdownReason>>,
+}
+
+async fn run<A: ConcurrentActor>(
+    mut actor: A,
+    mailbox: PriorityMailbox<A::Msg>,
+    limit: usize,
+) -> A {
+    let PriorityMailbox {
+        metrics,
+        schedules,
+        mut queue,
+        status_tx,
+        report,
+        audit,
+        batch_size: _,
+        dead_letters,
+        bookkeeping,
+        children,
+        force_stop,
+        cancel_on,
+        mut checkpoints,
+        events,
+        ready,
+        context,
+    } = mailbox;
+
+    let actor_name = context.name().to_owned();
+    let teardown = Teardown {
+        metrics,
+        schedules,
+        status_tx,
+        report,
+        audit,
+        dead_letters,
+        children: children.guard(),
+        events,
+    };
+    checkpoints.check(&actor);
+    diag!(debug, actor_name, "message processor task started");
+    actor.on_start().await;
+    ready.send_replace(true);
+    // Handlers hold a reference to the actor, so that it is only lent out mutably, to
+    // visits, once none are running.
+    let mut shared = Arc::new(actor);
+    // The handlers running on the processor task, each polled only once woken.
+    let mut running = FuturesUnordered::new();
+    let mut visits: Vec<Visit> = Vec::new();
+    // Set once the actor takes no more messages; it stops as soon as no handler is running.
+    let mut stopping: Option<TerminationReason> = None;
+    let mut next_dispatch: Option<Instant> = None;
+    let mut cancelled = false;
+    let reason = loop {
+        if running.is_empty() {
+            if !visits.is_empty() {
+                let state = Arc::get_mut(&mut shared)
+                    .expect("no handler holds the actor while none is running");
+                for visit in visits.drain(..) {
+                    visit(state);
+                }
+            }
+            checkpoints.take(&*shared);
+            if let Some(reason) = stopping.take() {
+                break reason;
+            }
+        }
+        let busy = !running.is_empty();
+        // Visits and snapshots wait for the running handlers to return; no message is taken
+        // meanwhile.
+        let room = running.len() < limit
+            && stopping.is_none()
+            && visits.is_empty()
+            && !checkpoints.is_due();
+        let received = tokio::select! {
+            biased;
+            () = forced(force_stop.clone()) => {
+                break TerminationReason::DeadlineExceeded { mid_handler: busy };
+            }
+            graceful = cancel_on.fired(), if !cancelled => {
+                cancelled = true;
+                if graceful {
+                    diag!(info, actor_name, "cancelled; draining the queue");
+                    queue.inbox().drain();
+                } else {
+                    diag!(info, actor_name, "cancelled; processor task terminating");
+                    stopping.get_or_insert(TerminationReason::Cancelled);
+                }
+                continue;
+            }
+            () = checkpoints.elapsed(), if !checkpoints.is_due() => continue,
+            Some(handled) = running.next(), if busy => {
+                let Handled { mut dispatched, elapsed, flow } = handled;
+                let priority = dispatched.priority;
+                bookkeeping.took(dispatched.variant, priority, elapsed);
+                // A handler that timed out was cancelled; it never returned.
+                let returned = flow.is_some();
+                if !returned {
+                    bookkeeping.timed_out(priority);
+                }
+                bookkeeping.handled(&mut dispatched, returned, returned.then_some(elapsed));
+                checkpoints.handled(1);
+                bookkeeping.loop_back(&queue, context.take_loopback());
+                if let Some(ControlFlow::Break(reason)) = flow {
+                    if stopping.is_none() {
+                        diag!(
+                            info,
+                            actor_name,
+                            "actor received shutdown signal; waiting for running handlers"
+                        );
+                        stopping = Some(reason.into());
+                    }
+                }
+                continue;
+            }
+            received = queue.recv_next(next_dispatch), if room => received,
+        };
+        let msg = match received {
+            Some(Received::Message(msg)) => msg,
+            Some(Received::Visits(more)) => {
+                visits.extend(more);
+                continue;
+            }
+            None => {
+                diag!(
+                    info,
+                    actor_name,
+                    "all senders dropped and queue is empty; processor task terminating"
+                );
+                stopping = Some(if cancelled {
+                    TerminationReason::Cancelled
+                } else {
+                    TerminationReason::SendersDropped
+                });
+                continue;
+            }
+        };
+
+        let Some(msg) = unexpired(&queue, msg, Instant::now()) else {
+            continue;
+        };
+        let Some((msg, dispatched)) = bookkeeping.admit(msg) else {
+            continue;
+        };
+        let (priority, correlation) = (dispatched.priority, dispatched.correlation);
+        bookkeeping.dispatched(priority, 1, &mut next_dispatch);
+        let limit = bookkeeping.time_limit(priority);
+        let actor = Arc::clone(&shared);
+        let ctx = &context;
+        let handler = async move { actor.handle(msg, ctx).await };
+        #[cfg(feature = "tracing")]
+        let handler = tracing::Instrument::instrument(
+            handler,
+            tracing::debug_span!(
+                "handle",
+                actor = %actor_name,
+                variant = dispatched.variant,
+                ?priority,
+                origin = ?dispatched.origin,
+                queue_depth = queue.len(),
+                correlation_id = correlation
+            ),
+        );
+        let handler = correlation::scope(correlation, handler);
+        running.push(async move {
+            let started = Instant::now();
+            let flow = match limit {
+                Some(limit) => tokio::time::timeout(limit, handler).await.ok(),
+                None => Some(handler.await),
+            };
+            Handled {
+                dispatched,
+                elapsed: started.elapsed(),
+                flow,
+            }
+        });
+    };
+    // Past a shutdown deadline, the handlers still running are dropped unfinished.
+    drop(running);
+    let mut actor = Arc::try_unwrap(shared)
+        .unwrap_or_else(|_| unreachable!("no handler holds the actor once they are dropped"));
+    if !matches!(reason, TerminationReason::DeadlineExceeded { .. }) {
+        actor.on_stop().await;
+    }
+    teardown.finish(&queue, reason).await;
+    actor
+}
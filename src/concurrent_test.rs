@@ -0,0 +1,136 @@
+use crate::prelude::*;
+use crate::{spawn_concurrent_actor, ConcurrentActor};
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::time::{Duration, Instant};
+
+#[derive(Prioritized)]
+enum Job {
+    #[priority(Medium)]
+    Sleep(u64),
+    #[priority(Shutdown)]
+    Stop,
+}
+
+/// Sleeps for as many milliseconds as each job says, counting the jobs it finishes.
+struct Sleeper {
+    finished: Arc<AtomicU32>,
+}
+
+#[async_trait]
+impl ConcurrentActor for Sleeper {
+    type Msg = Job;
+
+    async fn handle(&self, msg: Job, _ctx: &ActorContext<Job>) -> ControlFlow<ShutdownReason> {
+        match msg {
+            Job::Sleep(millis) => {
+                tokio::time::sleep(Duration::from_millis(millis)).await;
+                self.finished.fetch_add(1, Ordering::SeqCst);
+                ControlFlow::Continue(())
+            }
+            Job::Stop => ControlFlow::Break(ShutdownReason::Requested),
+        }
+    }
+}
+
+fn sleeper(max_concurrent: usize) -> (ActorHandle<Job>, Arc<AtomicU32>) {
+    let finished = Arc::new(AtomicU32::new(0));
+    let actor = Sleeper {
+        finished: Arc::clone(&finished),
+    };
+    let config = ActorConfig::new().max_concurrent_handlers(max_concurrent);
+    (spawn_concurrent_actor(actor, config), finished)
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_sleeping_handlers_run_side_by_side() {
+    let (handle, finished) = sleeper(4);
+    let started = Instant::now();
+    for _ in 0..4 {
+        handle.send(Job::Sleep(100)).await.unwrap();
+    }
+    handle.await_idle().await;
+
+    assert_eq!(finished.load(Ordering::SeqCst), 4);
+    assert_eq!(started.elapsed(), Duration::from_millis(100));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_handlers_beyond_the_limit_wait_their_turn() {
+    let (handle, finished) = sleeper(2);
+    let started = Instant::now();
+    for _ in 0..5 {
+        handle.send(Job::Sleep(100)).await.unwrap();
+    }
+    handle.await_idle().await;
+
+    assert_eq!(finished.load(Ordering::SeqCst), 5);
+    assert_eq!(started.elapsed(), Duration::from_millis(300));
+    #[cfg(feature = "metrics")]
+    assert_eq!(handle.metrics().messages_handled(), 5);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_shutdown_waits_for_running_handlers() {
+    let (handle, finished) = sleeper(3);
+    for millis in [50, 100, 150] {
+        handle.send(Job::Sleep(millis)).await.unwrap();
+    }
+    // Let all three start before the shutdown jumps the queue.
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    handle.send(Job::Stop).await.unwrap();
+    let reason = handle.clone().join().await;
+
+    assert_eq!(reason, TerminationReason::ShutdownMessage);
+    assert_eq!(finished.load(Ordering::SeqCst), 3);
+}
+
+#[derive(Prioritized)]
+enum Traced {
+    #[priority(Medium)]
+    Slow,
+    #[priority(Medium)]
+    Quick,
+    #[priority(Low)]
+    Followup,
+}
+
+/// Sends itself a follow-up from every handler, recording the correlation id each one
+/// arrives with.
+struct Relay {
+    seen: Arc<Mutex<Vec<Option<u64>>>>,
+}
+
+#[async_trait]
+impl ConcurrentActor for Relay {
+    type Msg = Traced;
+
+    async fn handle(&self, msg: Traced, ctx: &ActorContext<Traced>) -> ControlFlow<ShutdownReason> {
+        match msg {
+            Traced::Slow => {
+                ctx.send(Traced::Followup);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            Traced::Quick => ctx.send(Traced::Followup),
+            Traced::Followup => self.seen.lock().unwrap().push(ctx.correlation_id()),
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_self_sends_keep_their_own_handlers_correlation_id() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let actor = Relay {
+        seen: Arc::clone(&seen),
+    };
+    let handle = spawn_concurrent_actor(actor, ActorConfig::new().max_concurrent_handlers(2));
+    handle.send_traced(Traced::Slow, 1).await.unwrap();
+    handle.send_traced(Traced::Quick, 2).await.unwrap();
+    handle.await_idle().await;
+
+    let mut seen = seen.lock().unwrap().clone();
+    seen.sort();
+    assert_eq!(seen, [Some(1), Some(2)]);
+}
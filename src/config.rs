@@ -0,0 +1,575 @@
+use crate::cancel::CancelOn;
+use crate::checkpoint::Checkpointing;
+use crate::middleware::ErasedMiddleware;
+use crate::permits::Reserved;
+use crate::sink::Sink;
+use crate::{
+    AuditEvent, Checkpoint, CheckpointEvery, Middleware, OverflowPolicy, Priority, RateLimitPolicy,
+    Readiness, RuntimeConfig, SchedulingPolicy, SlowHandler,
+};
+use std::any::{type_name, Any};
+use std::fmt;
+use std::sync::Arc;
+use tokio::time::Duration;
+
+/// Mailbox capacity used by [`spawn_actor`](crate::spawn_actor).
+pub const DEFAULT_CAPACITY: usize = 32;
+
+/// How long [`ActorHandle::ping`](crate::ActorHandle::ping) waits for an answer unless
+/// configured otherwise.
+pub const DEFAULT_PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many events an actor's [emitters](crate::EventEmitter) buffer for their slowest
+/// subscriber unless configured otherwise.
+pub const DEFAULT_EVENT_CAPACITY: usize = 64;
+
+/// Options controlling how an actor is spawned by [`spawn_actor_with`](crate::spawn_actor_with).
+#[derive(Debug, Clone)]
+pub struct ActorConfig {
+    // `None` names the actor after its type.
+    pub(crate) name: Option<String>,
+    // `None` means the mailbox is unbounded.
+    pub(crate) capacity: Option<usize>,
+    pub(crate) reservations: Vec<(Priority, Reserved)>,
+    pub(crate) runtime: RuntimeConfig,
+    pub(crate) keep_alive_while_scheduled: bool,
+    pub(crate) audit: Option<Sink<AuditEvent>>,
+    pub(crate) fairness: Option<usize>,
+    pub(crate) aging: Option<Duration>,
+    pub(crate) scheduling: SchedulingPolicy,
+    // Exemplars kept per drop reason and variant, and whether they include the message.
+    pub(crate) drop_exemplars: Option<(usize, bool)>,
+    pub(crate) dead_letters: Option<DeadLetters>,
+    pub(crate) handler_timeout: Option<Duration>,
+    // Overrides of `handler_timeout` for single priorities.
+    pub(crate) handler_timeouts: Vec<(Priority, Duration)>,
+    pub(crate) batch_size: usize,
+    pub(crate) max_concurrent_handlers: usize,
+    pub(crate) middleware: Vec<ErasedMiddleware>,
+    // High and low queue lengths at which the mailbox pressure flips.
+    pub(crate) watermarks: Option<(usize, usize)>,
+    pub(crate) track_queue_latency: bool,
+    pub(crate) on_dequeue: Option<Sink<(Priority, Duration)>>,
+    pub(crate) slow_handler_threshold: Option<Duration>,
+    pub(crate) on_slow_handler: Option<Sink<SlowHandler>>,
+    pub(crate) ping_timeout: Duration,
+    pub(crate) event_capacity: usize,
+    pub(crate) cancel_on: CancelOn,
+    pub(crate) checkpoint: Option<Checkpointing>,
+    // Token buckets for single priorities: burst, tokens per second and policy.
+    pub(crate) rate_limits: Vec<(Priority, u32, u32, RateLimitPolicy)>,
+    pub(crate) readiness: Readiness,
+}
+
+/// A dead-letter [`Sink`] with its message type erased, since the config is not generic.
+#[derive(Clone)]
+pub(crate) struct DeadLetters {
+    sink: Arc<dyn Any + Send + Sync>,
+    msg_type: &'static str,
+}
+
+impl DeadLetters {
+    /// The sink, if it takes `M`.
+    pub(crate) fn downcast<M: 'static>(self) -> Sink<M> {
+        match self.sink.downcast_ref::<Sink<M>>() {
+            Some(sink) => sink.clone(),
+            None => panic!(
+                "dead-letter sink takes `{}`, not the actor's messages (`{}`)",
+                self.msg_type,
+                type_name::<M>()
+            ),
+        }
+    }
+}
+
+impl fmt::Debug for DeadLetters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DeadLetters<{}>", self.msg_type)
+    }
+}
+
+impl Default for ActorConfig {
+    fn default() -> Self {
+        ActorConfig {
+            name: None,
+            capacity: Some(DEFAULT_CAPACITY),
+            reservations: Vec::new(),
+            runtime: RuntimeConfig::default(),
+            keep_alive_while_scheduled: false,
+            audit: None,
+            fairness: None,
+            aging: None,
+            scheduling: SchedulingPolicy::Strict,
+            drop_exemplars: None,
+            dead_letters: None,
+            handler_timeout: None,
+            handler_timeouts: Vec::new(),
+            batch_size: 1,
+            max_concurrent_handlers: 1,
+            middleware: Vec::new(),
+            watermarks: None,
+            track_queue_latency: false,
+            on_dequeue: None,
+            slow_handler_threshold: None,
+            on_slow_handler: None,
+            ping_timeout: DEFAULT_PING_TIMEOUT,
+            event_capacity: DEFAULT_EVENT_CAPACITY,
+            cancel_on: CancelOn::default(),
+            checkpoint: None,
+            rate_limits: Vec::new(),
+            readiness: Readiness::Queue,
+        }
+    }
+}
+
+impl ActorConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Names the actor in its logs, traces and metrics, e.g. to tell apart several actors
+    /// of the same type.
+    ///
+    /// Without a name the actor goes by its type name followed by a number unique to this
+    /// process, such as `my_app::Worker#3`. See [`ActorHandle::name`](crate::ActorHandle::name).
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets how many messages may wait in the mailbox before `send` starts waiting.
+    ///
+    /// A capacity of zero is treated the same as [`unbounded`](Self::unbounded).
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = (capacity > 0).then_some(capacity);
+        self
+    }
+
+    /// Lets the mailbox grow without limit, so `send` never waits for room.
+    pub fn unbounded(mut self) -> Self {
+        self.capacity = None;
+        self
+    }
+
+    /// Reserves part of the mailbox capacity for messages at `priority` or above: a fraction
+    /// such as `0.25`, or a number of slots with [`Reserved::Slots`].
+    ///
+    /// Senders of lower-priority messages block once they would eat into the reserved share,
+    /// while messages at or above `priority` may use the whole capacity. Reserving again for
+    /// the same priority replaces the previous reservation. Reservations require a bounded
+    /// mailbox.
+    pub fn reserve(mut self, priority: Priority, reserved: impl Into<Reserved>) -> Self {
+        let reserved = reserved.into();
+        if let Reserved::Fraction(fraction) = reserved {
+            assert!(
+                (0.0..=1.0).contains(&fraction),
+                "reservation fraction must be within 0.0..=1.0, got {}",
+                fraction
+            );
+        }
+        self.reservations.retain(|(p, _)| *p != priority);
+        self.reservations.push((priority, reserved));
+        self
+    }
+
+    /// Shorthand for [`reserve`](Self::reserve)`(Priority::High, Reserved::Slots(slots))`.
+    pub fn reserved_high_slots(self, slots: usize) -> Self {
+        self.reserve(Priority::High, Reserved::Slots(slots))
+    }
+
+    /// Limits the actor to handling `per_second` messages per second.
+    ///
+    /// See [`RuntimeConfig::rate_limit`]; can be changed later with
+    /// [`ActorHandle::reconfigure`](crate::ActorHandle::reconfigure).
+    pub fn rate_limit(mut self, per_second: u32) -> Self {
+        assert!(per_second > 0, "rate limit must be greater than zero");
+        self.runtime.rate_limit = Some(per_second);
+        self
+    }
+
+    /// Drops incoming messages while `len` messages are already queued.
+    ///
+    /// See [`RuntimeConfig::shed_threshold`]; can be changed later with
+    /// [`ActorHandle::reconfigure`](crate::ActorHandle::reconfigure).
+    pub fn shed_threshold(mut self, len: usize) -> Self {
+        self.runtime.shed_threshold = Some(len);
+        self
+    }
+
+    /// Caps the queue at `len` messages, applying `overflow` to messages beyond that.
+    ///
+    /// See [`RuntimeConfig::max_queued`]; can be changed later with
+    /// [`ActorHandle::reconfigure`](crate::ActorHandle::reconfigure). Unlike the mailbox
+    /// capacity, the limit also covers messages that skip the mailbox, such as self-sends.
+    pub fn max_queued(mut self, len: usize, overflow: OverflowPolicy) -> Self {
+        assert!(len > 0, "queue limit must be greater than zero");
+        self.runtime.max_queued = Some(len);
+        self.runtime.overflow = overflow;
+        self
+    }
+
+    /// Limits messages of exactly `priority` to `per_second` a second, in bursts of up to
+    /// `burst`, e.g. so that a client flooding `High` messages cannot starve every other
+    /// priority. Setting a limit again for the same priority replaces it.
+    ///
+    /// Each priority gets a token bucket that starts full. Under [`RateLimitPolicy::Delay`]
+    /// a message spends a token as it leaves the queue, and once the bucket is empty the
+    /// actor handles messages of other priorities, lower ones included, until a token comes
+    /// in. Under [`RateLimitPolicy::Reject`] a message spends a token as it arrives, and is
+    /// dropped on arrival if there is none. A message raised by [aging](Self::aging) counts
+    /// against the limit of the level it has reached.
+    ///
+    /// # Panics
+    ///
+    /// If `priority` is [`Priority::Shutdown`], which is never rate limited, or `burst` or
+    /// `per_second` is zero.
+    pub fn priority_rate_limit(
+        mut self,
+        priority: Priority,
+        burst: u32,
+        per_second: u32,
+        policy: RateLimitPolicy,
+    ) -> Self {
+        assert!(
+            priority != Priority::Shutdown,
+            "`Shutdown` messages cannot be rate limited"
+        );
+        assert!(
+            burst > 0 && per_second > 0,
+            "rate limit must be greater than zero"
+        );
+        self.rate_limits.retain(|(p, ..)| *p != priority);
+        self.rate_limits.push((priority, burst, per_second, policy));
+        self
+    }
+
+    /// Caps the queue at `len` messages of exactly `priority`, independently of other
+    /// priorities, e.g. so a flood of `Low` telemetry leaves room for `High` control
+    /// messages. Setting a quota again for the same priority replaces it.
+    ///
+    /// Under the default [`OverflowPolicy::Reject`](crate::OverflowPolicy::Reject), sends
+    /// past the quota fail with [`Error::MailboxFull`](crate::Error::MailboxFull). See
+    /// [`RuntimeConfig::quotas`]; can be changed later with
+    /// [`ActorHandle::reconfigure`](crate::ActorHandle::reconfigure). Combine with
+    /// [`unbounded`](Self::unbounded) or a capacity at least the sum of the quotas, so that
+    /// senders are not held up by the mailbox capacity first.
+    pub fn quota(mut self, priority: Priority, len: usize) -> Self {
+        assert!(len > 0, "quota must be greater than zero");
+        self.runtime.quotas.retain(|(p, _)| *p != priority);
+        self.runtime.quotas.push((priority, len));
+        self
+    }
+
+    /// Reports [`MailboxPressure::High`](crate::MailboxPressure::High) through
+    /// [`ActorHandle::pressure`](crate::ActorHandle::pressure) once `high` messages are
+    /// queued, and [`Normal`](crate::MailboxPressure::Normal) again once no more than `low`
+    /// are, so a producer can back off before sends start to wait.
+    ///
+    /// The gap between the two keeps the pressure from flipping with every message. Counts
+    /// every queued message, self-sends included, like
+    /// [`max_queued`](Self::max_queued).
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `low` is below `high`.
+    pub fn watermarks(mut self, high: usize, low: usize) -> Self {
+        assert!(
+            low < high,
+            "low watermark ({}) must be below the high watermark ({})",
+            low,
+            high
+        );
+        self.watermarks = Some((high, low));
+        self
+    }
+
+    /// Measures how long each message waits in the queue before its handler is called,
+    /// rolled up per priority in
+    /// [`ActorMetrics::queue_latency`](crate::ActorMetrics::queue_latency). Off by default.
+    pub fn track_queue_latency(mut self) -> Self {
+        self.track_queue_latency = true;
+        self
+    }
+
+    /// Like [`track_queue_latency`](Self::track_queue_latency), also calling `f` with the
+    /// priority of every message about to be handled and how long it waited in the queue.
+    ///
+    /// `f` runs on the processor task, guarded like a [`Sink::callback`], so it should
+    /// return quickly.
+    pub fn on_dequeue(mut self, mut f: impl FnMut(Priority, Duration) + Send + 'static) -> Self {
+        self.track_queue_latency = true;
+        self.on_dequeue = Some(Sink::callback(move |(priority, wait)| f(priority, wait)));
+        self
+    }
+
+    /// Logs a warning whenever a handler takes longer than `threshold`, naming the
+    /// message's variant and priority, and times every handler per variant in
+    /// [`ActorMetrics::handler_time`](crate::ActorMetrics::handler_time). Off by default.
+    ///
+    /// A batch is timed as one handler. The warning goes through `tracing` or stdout like
+    /// the actor's other diagnostics; see [`on_slow_handler`](Self::on_slow_handler) to act
+    /// on it.
+    pub fn slow_handler_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_handler_threshold = Some(threshold);
+        self
+    }
+
+    /// Calls `f` with every handler that takes longer than the
+    /// [`slow_handler_threshold`](Self::slow_handler_threshold), which must be set for it
+    /// to have any effect.
+    ///
+    /// `f` runs on the processor task, guarded like a [`Sink::callback`], so it should
+    /// return quickly.
+    pub fn on_slow_handler(mut self, f: impl FnMut(SlowHandler) + Send + 'static) -> Self {
+        self.on_slow_handler = Some(Sink::callback(f));
+        self
+    }
+
+    /// Lets scheduled messages keep the actor alive after every handle is dropped.
+    ///
+    /// Off by default, so an actor whose only senders are its schedules shuts down
+    /// implicitly. Turn it on for actors driven purely by timers; they then run until a
+    /// handler stops them.
+    pub fn keep_alive_while_scheduled(mut self, keep_alive: bool) -> Self {
+        self.keep_alive_while_scheduled = keep_alive;
+        self
+    }
+
+    /// Reports what happens to the actor's messages to `sink`, ending with
+    /// [`AuditEvent::Stopped`].
+    pub fn audit(mut self, sink: Sink<AuditEvent>) -> Self {
+        self.audit = Some(sink);
+        self
+    }
+
+    /// Handles at most `max_consecutive` messages in a row from one [`Origin`] while
+    /// messages from another origin are waiting.
+    ///
+    /// Keeps an actor's self-sent or scheduled work from starving external senders, and the
+    /// other way round. When the limit is reached the best waiting message from a different
+    /// origin goes next, even if its priority is lower; `Shutdown` messages are never held
+    /// back. Off by default, in which case priority and arrival order alone decide.
+    ///
+    /// [`Origin`]: crate::Origin
+    pub fn fairness(mut self, max_consecutive: usize) -> Self {
+        assert!(
+            max_consecutive > 0,
+            "fairness limit must be greater than zero"
+        );
+        self.fairness = Some(max_consecutive);
+        self
+    }
+
+    /// Raises the priority of waiting messages by one named level for every `step` they
+    /// wait, up to `High`, so a steady stream of urgent messages cannot starve the rest.
+    ///
+    /// With a one-second step, a `Low` message that has waited a second is ordered like a
+    /// `Medium` one, and after two seconds like a `High` one, going ahead of any `High`
+    /// message that arrived after it. Custom levels move up by the same amount. Messages
+    /// above `High` are unaffected, and the handler still sees the message's own priority.
+    /// Off by default.
+    pub fn aging(mut self, step: Duration) -> Self {
+        assert!(!step.is_zero(), "aging step must be greater than zero");
+        self.aging = Some(step);
+        self
+    }
+
+    /// Sets how the next message is picked among those waiting at different priorities.
+    ///
+    /// [`SchedulingPolicy::Strict`], the default, always takes the highest priority first;
+    /// [`SchedulingPolicy::WeightedFair`] gives lower bands a share of the slots even while
+    /// higher ones stay busy. Combines with [`fairness`](Self::fairness), which then applies
+    /// within the band whose turn it is, and with [`aging`](Self::aging), which can move a
+    /// message up a band.
+    pub fn scheduling(mut self, policy: SchedulingPolicy) -> Self {
+        if let SchedulingPolicy::WeightedFair { weights } = policy {
+            assert!(
+                weights.iter().all(|weight| *weight > 0),
+                "scheduling weights must be greater than zero"
+            );
+        }
+        self.scheduling = policy;
+        self
+    }
+
+    /// Records every message the runtime drops without handling it, for
+    /// [`ActorHandle::drop_exemplars`](crate::ActorHandle::drop_exemplars).
+    ///
+    /// Drops are counted exactly per [`DropReason`](crate::DropReason) and variant, and the
+    /// `per_variant` most recent of each are kept as examples, without the message itself.
+    pub fn drop_exemplars(mut self, per_variant: usize) -> Self {
+        self.drop_exemplars = Some((per_variant, false));
+        self
+    }
+
+    /// Like [`drop_exemplars`](Self::drop_exemplars), also keeping the dropped messages.
+    ///
+    /// A kept message lives until newer drops push it out or it is taken with
+    /// [`ActorHandle::take_drop_exemplars`](crate::ActorHandle::take_drop_exemplars), along
+    /// with anything it owns; a reply channel it carries stays open until then.
+    pub fn drop_exemplars_with_messages(mut self, per_variant: usize) -> Self {
+        self.drop_exemplars = Some((per_variant, true));
+        self
+    }
+
+    /// Hands every message still waiting when the actor stops to `sink` instead of dropping
+    /// it, so that e.g. the reply channels they carry can be answered.
+    ///
+    /// Covers messages queued behind a handler that stopped the actor, as well as messages
+    /// still in the mailbox; once the actor has stopped the mailbox accepts nothing more.
+    /// They are delivered in the order they arrived, before
+    /// [`ActorHandle::join`](crate::ActorHandle::join) resolves.
+    ///
+    /// # Panics
+    ///
+    /// Spawning panics if `M` is not the actor's message type.
+    pub fn dead_letters<M: Send + 'static>(mut self, sink: Sink<M>) -> Self {
+        self.dead_letters = Some(DeadLetters {
+            sink: Arc::new(sink),
+            msg_type: type_name::<M>(),
+        });
+        self
+    }
+
+    /// Cancels any handler still running after `limit`, calling
+    /// [`Actor::on_timeout`](crate::Actor::on_timeout) and moving on to the next message.
+    ///
+    /// Keeps a handler stuck on e.g. a dead connection from wedging the actor. A timed-out
+    /// message counts as handled, and in
+    /// [`ActorMetrics::messages_timed_out`](crate::ActorMetrics::messages_timed_out).
+    /// Messages the handler sent the actor before it was cancelled are still delivered.
+    ///
+    /// The handler is cancelled at whatever `.await` it is waiting on, so any state it
+    /// updates across awaits may be left half-changed; `on_timeout` is the place to repair
+    /// it. Off by default.
+    pub fn handler_timeout(mut self, limit: Duration) -> Self {
+        assert!(
+            !limit.is_zero(),
+            "handler timeout must be greater than zero"
+        );
+        self.handler_timeout = Some(limit);
+        self
+    }
+
+    /// Sets how long [`ActorHandle::ping`](crate::ActorHandle::ping) waits for the actor to
+    /// answer before reporting it unresponsive. Defaults to [`DEFAULT_PING_TIMEOUT`].
+    pub fn ping_timeout(mut self, limit: Duration) -> Self {
+        assert!(!limit.is_zero(), "ping timeout must be greater than zero");
+        self.ping_timeout = limit;
+        self
+    }
+
+    /// Sets how many events each of the actor's
+    /// [event emitters](crate::ActorContext::events) buffers for its slowest subscriber
+    /// before that subscriber starts losing them. Defaults to [`DEFAULT_EVENT_CAPACITY`].
+    pub fn event_capacity(mut self, capacity: usize) -> Self {
+        assert!(capacity > 0, "event capacity must be greater than zero");
+        self.event_capacity = capacity;
+        self
+    }
+
+    /// Stops the actor once `token` is cancelled, even while handles to it remain, the way
+    /// [`cancel_mode`](Self::cancel_mode) says: gracefully unless told otherwise.
+    ///
+    /// The actor then reports [`TerminationReason::Cancelled`](crate::TerminationReason::Cancelled).
+    /// One token can be shared by any number of actors; see also
+    /// [`ActorGroup`](crate::ActorGroup).
+    #[cfg(feature = "cancellation")]
+    pub fn cancel_on(mut self, token: crate::CancellationToken) -> Self {
+        self.cancel_on.token = Some(token);
+        self
+    }
+
+    /// Sets how the actor stops once the token given to [`cancel_on`](Self::cancel_on) is
+    /// cancelled.
+    #[cfg(feature = "cancellation")]
+    pub fn cancel_mode(mut self, mode: crate::CancelMode) -> Self {
+        self.cancel_on.mode = mode;
+        self
+    }
+
+    /// Snapshots the actor with [`Checkpoint::snapshot`] as often as `every` says and
+    /// hands the bytes to `sink`, e.g. to write them to disk for
+    /// [`spawn_actor_restored`](crate::spawn_actor_restored) to boot from after a crash.
+    ///
+    /// Snapshots are only taken between handlers, never while one is running; a concurrent
+    /// actor stops taking new messages until its running handlers have returned. No
+    /// snapshot is taken after a handler panics, nor when the actor stops. `sink` runs on
+    /// the processor task, guarded like a [`Sink::callback`], so it should return quickly.
+    ///
+    /// # Panics
+    ///
+    /// If `every` is zero. Spawning panics if `A` is not the actor's type.
+    pub fn checkpoint_every<A: Checkpoint + 'static>(
+        mut self,
+        every: CheckpointEvery,
+        sink: impl FnMut(Vec<u8>) + Send + 'static,
+    ) -> Self {
+        self.checkpoint = Some(Checkpointing::new::<A>(every, Sink::callback(sink)));
+        self
+    }
+
+    /// Like [`handler_timeout`](Self::handler_timeout), only for messages of exactly
+    /// `priority`, taking precedence over the limit for all messages.
+    pub fn handler_timeout_for(mut self, priority: Priority, limit: Duration) -> Self {
+        assert!(
+            !limit.is_zero(),
+            "handler timeout must be greater than zero"
+        );
+        self.handler_timeouts.retain(|(p, _)| *p != priority);
+        self.handler_timeouts.push((priority, limit));
+        self
+    }
+
+    /// Hands up to `size` waiting messages to [`Actor::handle_batch`](crate::Actor::handle_batch)
+    /// at once, instead of one at a time to `handle`.
+    ///
+    /// A batch is taken whenever more than one message is waiting, highest priority first.
+    /// `Shutdown` messages are never batched: one that arrives while a batch is being
+    /// handled stops the actor once the batch is done. A batch counts as one handler for
+    /// [`handler_timeout`](Self::handler_timeout), with the limit of its first message, and
+    /// as one message per entry under the rate limit. `1`, the default, turns batching off.
+    pub fn batch_size(mut self, size: usize) -> Self {
+        assert!(size > 0, "batch size must be greater than zero");
+        self.batch_size = size;
+        self
+    }
+
+    /// Sets what happens to messages sent before the actor is ready: until the future given
+    /// to [`spawn_actor_init`](crate::spawn_actor_init), if any, and its `on_start` hook
+    /// have completed. Defaults to [`Readiness::Queue`].
+    ///
+    /// [`ActorHandle::ready`](crate::ActorHandle::ready) waits for the actor to be ready
+    /// either way.
+    pub fn readiness(mut self, readiness: Readiness) -> Self {
+        self.readiness = readiness;
+        self
+    }
+
+    /// Lets an actor spawned with [`spawn_concurrent_actor`](crate::spawn_concurrent_actor)
+    /// handle up to `n` messages at once. Other actors handle one at a time regardless.
+    ///
+    /// Messages still leave the queue in priority order, but a handler that starts later may
+    /// finish first. `1`, the default, handles one message at a time.
+    pub fn max_concurrent_handlers(mut self, n: usize) -> Self {
+        assert!(n > 0, "max concurrent handlers must be greater than zero");
+        self.max_concurrent_handlers = n;
+        self
+    }
+
+    /// Wraps `middleware` around the handling of every message, after the middlewares
+    /// installed before it.
+    ///
+    /// Each message goes through every middleware's [`before`](Middleware::before) in
+    /// installation order, stopping at the first that drops it; the handler runs only if
+    /// none does, and every middleware's [`after`](Middleware::after) runs once it returns.
+    /// Actors spawned with clones of this config share the middleware.
+    ///
+    /// # Panics
+    ///
+    /// Spawning panics if `M` is not the actor's message type.
+    pub fn middleware<M: 'static>(mut self, middleware: impl Middleware<M>) -> Self {
+        self.middleware.push(ErasedMiddleware::new(middleware));
+        self
+    }
+}
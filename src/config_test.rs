@@ -0,0 +1,102 @@
+use crate::sink::Sink;
+use crate::test_support::{stall, Gated, GatedMsg};
+use crate::{spawn_actor_with, ActorConfig, Priority, SchedulingPolicy, TerminationReason};
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+
+#[tokio::test]
+async fn test_small_capacity_applies_backpressure() {
+    let handle = spawn_actor_with(Gated::new(), ActorConfig::new().capacity(2));
+    let release = stall(&handle).await;
+
+    handle.send(GatedMsg::Fill()).await.unwrap();
+    handle.send(GatedMsg::Fill()).await.unwrap();
+
+    // The mailbox is full, so a third send has to wait for the handler to make room.
+    let third = timeout(Duration::from_millis(50), handle.send(GatedMsg::Fill())).await;
+    assert!(third.is_err(), "send should block on a full mailbox");
+
+    release.send(()).unwrap();
+    timeout(Duration::from_secs(1), handle.send(GatedMsg::Fill()))
+        .await
+        .expect("send should proceed once the mailbox drains")
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_unbounded_mailbox_never_blocks() {
+    for config in [
+        ActorConfig::new().unbounded(),
+        ActorConfig::new().capacity(0),
+    ] {
+        let handle = spawn_actor_with(Gated::new(), config);
+        let _release = stall(&handle).await;
+
+        timeout(Duration::from_millis(500), async {
+            for _ in 0..10_000 {
+                handle.send(GatedMsg::Fill()).await.unwrap();
+            }
+        })
+        .await
+        .expect("sends to an unbounded mailbox should never wait");
+    }
+}
+
+#[test]
+#[should_panic(expected = "capacity reservations require a bounded mailbox")]
+fn test_reservations_reject_unbounded_mailbox() {
+    let config = ActorConfig::new().unbounded().reserve(Priority::High, 0.25);
+    spawn_actor_with(Gated::new(), config);
+}
+
+#[test]
+#[should_panic(expected = "scheduling weights must be greater than zero")]
+fn test_weighted_fair_scheduling_needs_every_band_weighed() {
+    let policy = SchedulingPolicy::WeightedFair { weights: [0, 1, 1] };
+    ActorConfig::new().scheduling(policy);
+}
+
+#[test]
+#[should_panic(expected = "max concurrent handlers must be greater than zero")]
+fn test_concurrent_handlers_need_at_least_one() {
+    ActorConfig::new().max_concurrent_handlers(0);
+}
+
+#[tokio::test]
+async fn test_messages_left_at_shutdown_go_to_dead_letters() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let config = ActorConfig::new().dead_letters(Sink::unbounded(tx));
+    let handle = spawn_actor_with(Gated::new(), config);
+    let release = stall(&handle).await;
+    for _ in 0..10 {
+        handle.send(GatedMsg::Fill()).await.unwrap();
+    }
+    handle.send(GatedMsg::Shutdown).await.unwrap();
+
+    drop(release);
+    assert_eq!(handle.join().await, TerminationReason::ShutdownMessage);
+    let dead: Vec<_> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+    assert_eq!(dead.len(), 10);
+    assert!(dead.iter().all(|msg| matches!(msg, GatedMsg::Fill())));
+}
+
+#[test]
+#[should_panic(expected = "dead-letter sink takes `u32`")]
+fn test_dead_letters_must_take_the_actors_messages() {
+    let config = ActorConfig::new().dead_letters(Sink::callback(|_: u32| {}));
+    spawn_actor_with(Gated::new(), config);
+}
+
+#[tokio::test]
+async fn test_actors_are_named_after_their_type_unless_given_a_name() {
+    let first = spawn_actor_with(Gated::new(), ActorConfig::new());
+    let second = spawn_actor_with(Gated::new(), ActorConfig::new());
+    let named = spawn_actor_with(Gated::new(), ActorConfig::new().name("gatekeeper"));
+
+    let prefix = format!("{}#", std::any::type_name::<Gated>());
+    assert!(first.name().starts_with(&prefix), "{}", first.name());
+    assert!(second.name().starts_with(&prefix), "{}", second.name());
+    assert_ne!(first.name(), second.name());
+    assert_eq!(named.name(), "gatekeeper");
+    assert_eq!(named.clone().name(), "gatekeeper");
+}
@@ -0,0 +1,76 @@
+//! Confirmations for messages sent with
+//! [`ActorHandle::send_confirmed`](crate::ActorHandle::send_confirmed), so a sender can learn
+//! that a message was handled without threading a reply channel through it.
+
+use crate::DropReason;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::sync::oneshot;
+
+/// Why a message sent with [`ActorHandle::send_confirmed`](crate::ActorHandle::send_confirmed)
+/// was never handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NotHandled {
+    /// The runtime dropped the message on its way to the handler.
+    Dropped(DropReason),
+    /// The message was taken out of the queue, e.g. by
+    /// [`ActorHandle::purge`](crate::ActorHandle::purge) or
+    /// [`ActorHandle::cancel_pending`](crate::ActorHandle::cancel_pending).
+    Removed,
+    /// [Middleware](crate::Middleware) turned the message away.
+    Rejected,
+    /// The handler started but did not return: it panicked or timed out.
+    Failed,
+    /// The actor stopped before handling the message, or while handling it.
+    Stopped,
+}
+
+impl fmt::Display for NotHandled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotHandled::Dropped(reason) => write!(f, "message dropped unhandled ({reason:?})"),
+            NotHandled::Removed => write!(f, "message removed from the queue"),
+            NotHandled::Rejected => write!(f, "message rejected by middleware"),
+            NotHandled::Failed => write!(f, "handler did not return"),
+            NotHandled::Stopped => write!(f, "actor stopped before handling the message"),
+        }
+    }
+}
+
+impl std::error::Error for NotHandled {}
+
+/// Resolves once the actor's handler for a message sent with
+/// [`ActorHandle::send_confirmed`](crate::ActorHandle::send_confirmed) has returned, or with
+/// why it never will.
+#[must_use = "a confirmation does nothing unless awaited"]
+#[derive(Debug)]
+pub struct Confirmation(oneshot::Receiver<Result<(), NotHandled>>);
+
+impl Future for Confirmation {
+    type Output = Result<(), NotHandled>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // A confirmation dropped unanswered went down with the actor's queue.
+        Pin::new(&mut self.0)
+            .poll(cx)
+            .map(|outcome| outcome.unwrap_or(Err(NotHandled::Stopped)))
+    }
+}
+
+/// Travels with a message sent with `send_confirmed` and answers its [`Confirmation`].
+/// Dropped unanswered, it reports [`NotHandled::Stopped`].
+pub(crate) struct Confirm(oneshot::Sender<Result<(), NotHandled>>);
+
+impl Confirm {
+    pub(crate) fn new() -> (Self, Confirmation) {
+        let (tx, rx) = oneshot::channel();
+        (Confirm(tx), Confirmation(rx))
+    }
+
+    pub(crate) fn send(self, outcome: Result<(), NotHandled>) {
+        let _ = self.0.send(outcome);
+    }
+}
@@ -0,0 +1,147 @@
+use crate::events::Events;
+use crate::handle::WeakHandle;
+use crate::mailbox::Origin;
+use crate::{ActorHandle, Error, EventEmitter, Prioritized, Priority};
+use std::sync::{Arc, Mutex};
+
+/// What a handler knows about the actor running it, passed to [`Actor::handle`](crate::Actor::handle).
+///
+/// Lets an actor send messages to itself without holding a handle, which would keep it
+/// alive forever.
+pub struct ActorContext<M> {
+    // `None` when no processor is running the actor, as under an `ActorHarness`.
+    handle: Option<WeakHandle<M>>,
+    name: String,
+    // Messages sent with `send`, queued once the current handler returns, each with the
+    // correlation id of the handler that sent it.
+    loopback: Mutex<Vec<(M, Option<u64>)>>,
+    // The priority of the message being handled, while a handler runs.
+    priority: Mutex<Option<Priority>>,
+    events: Arc<Events>,
+}
+
+impl<M> ActorContext<M> {
+    pub(crate) fn new(handle: WeakHandle<M>, name: String, events: Arc<Events>) -> Self {
+        ActorContext {
+            handle: Some(handle),
+            name,
+            loopback: Mutex::new(Vec::new()),
+            priority: Mutex::new(None),
+            events,
+        }
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn detached(name: &str) -> Self {
+        ActorContext {
+            handle: None,
+            name: name.to_owned(),
+            loopback: Mutex::new(Vec::new()),
+            priority: Mutex::new(None),
+            events: Arc::new(Events::new(crate::DEFAULT_EVENT_CAPACITY)),
+        }
+    }
+
+    /// Sends a message to this actor.
+    ///
+    /// Never waits and never fails: the message skips the mailbox, so a handler cannot
+    /// deadlock on its own full mailbox. It is queued with [`Origin::SelfSend`] once the
+    /// current handler returns, unless that handler stops the actor or panics.
+    pub fn send(&self, msg: M) {
+        let correlation = crate::correlation::current();
+        self.loopback.lock().unwrap().push((msg, correlation));
+    }
+
+    /// Sends a message to this actor through its mailbox, like any other sender, only if
+    /// the mailbox has room right now.
+    ///
+    /// Fails with [`Error::MailboxClosed`] once every handle to the actor is gone. Under an
+    /// [`ActorHarness`](crate::testing::ActorHarness) there is no mailbox and this behaves
+    /// like [`send`](Self::send).
+    pub fn try_send(&self, msg: M) -> Result<(), Error<M>>
+    where
+        M: Prioritized + Send + 'static,
+    {
+        match &self.handle {
+            Some(weak) => match weak.upgrade() {
+                Some(handle) => handle.try_send_from(Origin::SelfSend, msg),
+                None => Err(Error::closed(&self.name, Some(msg))),
+            },
+            None => {
+                self.send(msg);
+                Ok(())
+            }
+        }
+    }
+
+    /// A handle to this actor, e.g. to give out to other actors, or `None` once every
+    /// handle to it is gone.
+    ///
+    /// The returned handle keeps the actor alive like any other; do not store it in the
+    /// actor itself.
+    pub fn handle(&self) -> Option<ActorHandle<M>> {
+        self.handle.as_ref()?.upgrade()
+    }
+
+    /// The actor's name, as in [`ActorHandle::name`].
+    ///
+    /// Under an [`ActorHarness`](crate::testing::ActorHarness) this is the actor's type name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The correlation id of the message being handled: the one it was
+    /// [sent with](ActorHandle::send_traced), or carried on from the handler that sent it.
+    ///
+    /// Messages sent from the handler, including with [`send`](Self::send), carry the id on.
+    /// `None` for messages without one, and outside a handler. A batch goes by the id of its
+    /// first message.
+    pub fn correlation_id(&self) -> Option<u64> {
+        crate::correlation::current()
+    }
+
+    /// The priority the message being handled was queued at, or `None` outside a handler.
+    ///
+    /// A batch goes by the priority of its first message.
+    pub fn priority(&self) -> Option<Priority> {
+        *self.priority.lock().unwrap()
+    }
+
+    /// Sends `msg` to `target` at the priority of the message being handled instead of its
+    /// own, e.g. from a router whose translated messages would otherwise lose their urgency.
+    ///
+    /// Otherwise this is [`target.send(msg)`](ActorHandle::send): it waits for room in
+    /// `target`'s mailbox, and `msg` carries on the correlation id of the message being
+    /// handled. A `Shutdown` message being handled forwards at `High`, as with
+    /// [`send_with_priority`](ActorHandle::send_with_priority). Outside a handler, `msg`
+    /// goes at its own priority.
+    pub async fn forward<T>(&self, target: &ActorHandle<T>, msg: T) -> Result<(), Error<T>>
+    where
+        T: Prioritized + Send + 'static,
+    {
+        match self.priority() {
+            Some(priority) => target.send_with_priority(msg, priority).await,
+            None => target.send(msg).await,
+        }
+    }
+
+    /// The actor's emitter for events of type `E`, which
+    /// [`ActorHandle::subscribe_events`] subscribes to; keep a clone in a field to emit from
+    /// elsewhere in the actor.
+    ///
+    /// Each event type has one emitter per actor, created on first use with the actor's
+    /// [event capacity](crate::ActorConfig::event_capacity).
+    pub fn events<E: Clone + Send + 'static>(&self) -> EventEmitter<E> {
+        self.events.emitter()
+    }
+
+    /// Records the priority of the message about to be handled, or `None` once handled.
+    pub(crate) fn set_priority(&self, priority: Option<Priority>) {
+        *self.priority.lock().unwrap() = priority;
+    }
+
+    /// Takes the messages queued with `send` so far, with the correlation ids they carry on.
+    pub(crate) fn take_loopback(&self) -> Vec<(M, Option<u64>)> {
+        std::mem::take(&mut *self.loopback.lock().unwrap())
+    }
+}
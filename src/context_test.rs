@@ -0,0 +1,166 @@
+use crate::prelude::*;
+use crate::test_support::{stall, Gated, GatedMsg};
+use crate::testing::ActorHarness;
+use tokio::sync::oneshot;
+
+define_actor! {
+    Countdown {
+        steps: u32,
+    }
+
+    impl CountdownMsg {
+        /// Handles `remaining` more steps by sending them to itself one at a time.
+        @priority(Medium)
+        fn Step(&mut self, ctx: &ActorContext<CountdownMsg>, remaining: u32) {
+            self.steps += 1;
+            if remaining > 0 {
+                ctx.send(CountdownMsg::Step(remaining - 1));
+            }
+        }
+
+        /// Sends itself `n` steps at once, all while the mailbox is full.
+        @priority(Medium)
+        fn Burst(&mut self, ctx: &ActorContext<CountdownMsg>, n: u32) {
+            for _ in 0..n {
+                ctx.send(CountdownMsg::Step(0));
+            }
+        }
+
+        @priority(Medium)
+        fn HasHandle(&self, ctx: &ActorContext<CountdownMsg>) -> bool {
+            ctx.handle().is_some()
+        }
+
+        /// Tries to send itself two steps; the first takes the mailbox's only slot.
+        @priority(Medium)
+        fn TrySelf(&mut self, ctx: &ActorContext<CountdownMsg>) -> (bool, bool) {
+            let first = ctx.try_send(CountdownMsg::Step(0)).is_ok();
+            let second = ctx.try_send(CountdownMsg::Step(0));
+            (first, matches!(second, Err(Error::MailboxFull { .. })))
+        }
+
+        @priority(Low)
+        fn Report(&mut self, tx: oneshot::Sender<u32>) {
+            let _ = tx.send(self.steps);
+        }
+    }
+}
+
+define_actor! {
+    /// Relays alerts to a `Gated` actor as its lowly `Fill` messages.
+    Relay {
+        target: ActorHandle<GatedMsg>,
+    }
+
+    impl RelayMsg {
+        @priority(High)
+        async fn Alert(&mut self, ctx: &ActorContext<RelayMsg>) {
+            ctx.forward(&self.target, GatedMsg::Fill()).await.unwrap();
+        }
+
+        @priority(High)
+        async fn Notify(&mut self) {
+            self.target.send(GatedMsg::Fill()).await.unwrap();
+        }
+
+        @priority(Medium)
+        fn CurrentPriority(&self, ctx: &ActorContext<RelayMsg>) -> Option<Priority> {
+            ctx.priority()
+        }
+    }
+}
+
+fn countdown() -> Countdown {
+    Countdown { steps: 0 }
+}
+
+async fn steps(handle: &ActorHandle<CountdownMsg>) -> u32 {
+    let (tx, rx) = oneshot::channel();
+    handle.send(CountdownMsg::Report(tx)).await.unwrap();
+    rx.await.unwrap()
+}
+
+#[tokio::test]
+async fn test_self_sent_follow_ups_are_all_handled() {
+    let handle = spawn_actor(countdown());
+    handle.send(CountdownMsg::Step(9)).await.unwrap();
+    // The follow-ups outrank the report, so they are all handled first.
+    assert_eq!(steps(&handle).await, 10);
+}
+
+#[tokio::test]
+async fn test_self_sends_do_not_wait_for_a_full_mailbox() {
+    let handle = spawn_actor_with(countdown(), ActorConfig::new().capacity(1));
+    handle.send(CountdownMsg::Burst(100)).await.unwrap();
+    assert_eq!(steps(&handle).await, 100);
+}
+
+#[tokio::test]
+async fn test_try_send_respects_mailbox_capacity() {
+    let handle = spawn_actor_with(countdown(), ActorConfig::new().capacity(1));
+    let (msg, reply) = CountdownMsg::try_self();
+    handle.send(msg).await.unwrap();
+    assert_eq!(reply.await.unwrap(), (true, true));
+    assert_eq!(steps(&handle).await, 1);
+}
+
+#[tokio::test]
+async fn test_context_hands_out_the_actors_handle_while_it_has_senders() {
+    let handle = spawn_actor(countdown());
+    let (msg, reply) = CountdownMsg::has_handle();
+    handle.send(msg).await.unwrap();
+    assert!(reply.await.unwrap());
+
+    // Once the last handle is gone the context has none to give out.
+    let (msg, reply) = CountdownMsg::has_handle();
+    handle.send(msg).await.unwrap();
+    drop(handle);
+    assert!(!reply.await.unwrap());
+}
+
+#[tokio::test]
+async fn test_harness_queues_self_sends() {
+    let mut harness = ActorHarness::new(countdown());
+    harness.send(CountdownMsg::Step(3));
+    harness.run_until_idle().await;
+    assert_eq!(harness.actor().steps, 4);
+}
+
+#[tokio::test]
+async fn test_forward_keeps_the_priority_of_the_message_being_handled() {
+    let target = spawn_actor_owned(Gated::new(), ActorConfig::new());
+    let release = stall(&target).await;
+    target.send(GatedMsg::Routine()).await.unwrap();
+    target.send(GatedMsg::Routine()).await.unwrap();
+
+    let relay = spawn_actor(Relay {
+        target: target.handle().clone(),
+    });
+    relay.send(RelayMsg::Notify()).await.unwrap();
+    relay.send(RelayMsg::Alert()).await.unwrap();
+    relay.await_idle().await;
+    drop(relay);
+
+    drop(release);
+    let handled = target.join().await.unwrap().handled.clone();
+    // The forwarded `Fill` goes at `High`, ahead of the queued routine work; the plain one
+    // goes at its own `Low`, behind it.
+    assert_eq!(
+        handled,
+        [
+            Priority::Low,
+            Priority::Medium,
+            Priority::Medium,
+            Priority::Low
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_context_knows_the_priority_of_the_message_being_handled() {
+    let target = spawn_actor(Gated::new());
+    let relay = spawn_actor(Relay { target });
+    let (msg, reply) = RelayMsg::current_priority();
+    relay.send(msg).await.unwrap();
+    assert_eq!(reply.await.unwrap(), Some(Priority::Medium));
+}
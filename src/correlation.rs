@@ -0,0 +1,19 @@
+//! The correlation id of the message being handled, followed from actor to actor.
+
+use std::future::Future;
+
+tokio::task_local! {
+    // Set around each handler call; `None` while handling a message sent without an id.
+    static CURRENT: Option<u64>;
+}
+
+/// The correlation id of the message the current task is handling, if any.
+pub(crate) fn current() -> Option<u64> {
+    CURRENT.try_with(|id| *id).ok().flatten()
+}
+
+/// Runs `handling` with `id` as the current correlation id, so that messages it sends carry
+/// the id on.
+pub(crate) fn scope<F: Future>(id: Option<u64>, handling: F) -> impl Future<Output = F::Output> {
+    CURRENT.scope(id, handling)
+}
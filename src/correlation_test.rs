@@ -0,0 +1,72 @@
+use crate::prelude::*;
+use tokio::sync::oneshot;
+
+define_actor! {
+    /// Records the correlation id of every message it handles.
+    Sink {
+        seen: Vec<Option<u64>>,
+    }
+
+    impl SinkMsg {
+        @priority(Medium)
+        fn Record(&mut self, ctx: &ActorContext<SinkMsg>) {
+            self.seen.push(ctx.correlation_id());
+        }
+
+        @priority(Medium)
+        fn Seen(&mut self, tx: oneshot::Sender<Vec<Option<u64>>>) {
+            let _ = tx.send(self.seen.clone());
+        }
+    }
+}
+
+define_actor! {
+    /// Passes every request on to the sink, once directly and once by way of itself.
+    Relay {
+        sink: ActorHandle<SinkMsg>,
+    }
+
+    impl RelayMsg {
+        @priority(Medium)
+        async fn Request(&mut self, ctx: &ActorContext<RelayMsg>) {
+            self.sink.send(SinkMsg::Record()).await.unwrap();
+            ctx.send(RelayMsg::FollowUp());
+        }
+
+        @priority(Medium)
+        async fn FollowUp(&mut self) {
+            self.sink.send(SinkMsg::Record()).await.unwrap();
+        }
+    }
+}
+
+async fn seen(sink: &ActorHandle<SinkMsg>) -> Vec<Option<u64>> {
+    let (tx, rx) = oneshot::channel();
+    sink.send(SinkMsg::Seen(tx)).await.unwrap();
+    rx.await.unwrap()
+}
+
+#[tokio::test]
+async fn test_correlation_id_follows_the_request_downstream() {
+    let sink = spawn_actor(Sink { seen: Vec::new() });
+    let relay = spawn_actor(Relay { sink: sink.clone() });
+
+    relay.send_traced(RelayMsg::Request(), 42).await.unwrap();
+    relay.await_idle().await;
+
+    assert_eq!(seen(&sink).await, [Some(42), Some(42)]);
+}
+
+#[tokio::test]
+async fn test_messages_sent_without_an_id_carry_none() {
+    let sink = spawn_actor(Sink { seen: Vec::new() });
+    let relay = spawn_actor(Relay { sink: sink.clone() });
+
+    relay.send_traced(RelayMsg::Request(), 7).await.unwrap();
+    relay.await_idle().await;
+    relay.send(RelayMsg::Request()).await.unwrap();
+    relay.await_idle().await;
+    sink.send_traced(SinkMsg::Record(), 9).await.unwrap();
+
+    assert_eq!(seen(&sink).await, [Some(7), Some(7), None, None, Some(9)]);
+}
@@ -0,0 +1,104 @@
+use crate::prelude::*;
+use async_trait::async_trait;
+use tokio::sync::oneshot;
+
+#[derive(Prioritized)]
+enum JobMsg {
+    #[priority(Low)]
+    Cleanup,
+    Work(u32),
+    #[priority(High)]
+    Cancel {
+        id: u32,
+    },
+    #[priority(250)]
+    Page,
+    #[priority(Custom(10))]
+    Report(oneshot::Sender<Vec<String>>),
+    #[priority(Shutdown)]
+    Stop,
+}
+
+struct Jobs {
+    log: Vec<String>,
+}
+
+#[async_trait]
+impl Actor for Jobs {
+    type Msg = JobMsg;
+
+    async fn handle(
+        &mut self,
+        msg: JobMsg,
+        _ctx: &ActorContext<JobMsg>,
+    ) -> ControlFlow<ShutdownReason> {
+        match msg {
+            JobMsg::Cleanup => self.log.push("cleanup".into()),
+            JobMsg::Work(id) => self.log.push(format!("work {}", id)),
+            JobMsg::Cancel { id } => self.log.push(format!("cancel {}", id)),
+            JobMsg::Page => self.log.push("page".into()),
+            JobMsg::Report(tx) => {
+                let _ = tx.send(std::mem::take(&mut self.log));
+            }
+            JobMsg::Stop => return ControlFlow::Break(ShutdownReason::Requested),
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[derive(Prioritized)]
+#[allow(dead_code)]
+enum Wrapped<T> {
+    #[priority(High)]
+    Value(T),
+    Empty,
+}
+
+#[test]
+fn test_derived_priorities() {
+    assert_eq!(JobMsg::Cleanup.priority(), Priority::Low);
+    assert_eq!(JobMsg::Work(1).priority(), Priority::Medium);
+    assert_eq!(JobMsg::Cancel { id: 1 }.priority(), Priority::High);
+    assert_eq!(JobMsg::Page.priority(), Priority::Custom(250));
+    assert_eq!(JobMsg::Stop.priority(), Priority::Shutdown);
+    assert_eq!(Wrapped::Value("x").priority(), Priority::High);
+    assert_eq!(Wrapped::<()>::Empty.priority(), Priority::Medium);
+}
+
+#[tokio::test]
+async fn test_derive_with_manual_actor() {
+    let handle = spawn_actor(Jobs { log: Vec::new() });
+
+    // All of these are queued before the actor handles any of them.
+    handle.send(JobMsg::Cleanup).await.unwrap();
+    handle.send(JobMsg::Work(1)).await.unwrap();
+    handle.send(JobMsg::Cancel { id: 2 }).await.unwrap();
+    handle.send(JobMsg::Page).await.unwrap();
+    let (tx, rx) = oneshot::channel();
+    handle.send(JobMsg::Report(tx)).await.unwrap();
+
+    assert_eq!(rx.await.unwrap(), ["page", "cancel 2", "work 1"]);
+    handle.send(JobMsg::Stop).await.unwrap();
+    assert_eq!(handle.join().await, TerminationReason::ShutdownMessage);
+}
+
+#[derive(Prioritized)]
+#[allow(dead_code)]
+enum Quote {
+    #[coalesce]
+    Bid(u64),
+    #[coalesce]
+    #[priority(Low)]
+    Ask(u64),
+    Trade(u64),
+}
+
+#[test]
+fn test_derived_coalescing_keys() {
+    assert!(Quote::Bid(1).coalesce_key().is_some());
+    assert_eq!(Quote::Bid(1).coalesce_key(), Quote::Bid(2).coalesce_key());
+    assert_ne!(Quote::Bid(1).coalesce_key(), Quote::Ask(1).coalesce_key());
+    assert_eq!(Quote::Ask(1).priority(), Priority::Low);
+    assert_eq!(Quote::Trade(1).coalesce_key(), None);
+    assert_eq!(JobMsg::Work(1).coalesce_key(), None);
+}
@@ -0,0 +1,80 @@
+use crate::{Actor, ActorContext, Prioritized, Priority, ShutdownReason};
+use std::any::Any;
+use std::future::Future;
+use std::ops::ControlFlow;
+use std::pin::Pin;
+
+/// What the processor task needs from the actor it runs, so the same processor serves both
+/// [`Actor`]s and [`LocalActor`](crate::LocalActor)s.
+///
+/// The futures are whatever the actor's trait hands out, so the processor is `Send` exactly
+/// when the actor's futures are.
+pub(crate) trait Drive: 'static {
+    type Msg: Prioritized + Send + 'static;
+    type Step<'a, T: 'a>: Future<Output = T> + Unpin + 'a
+    where
+        Self: 'a;
+
+    fn handle<'a>(
+        &'a mut self,
+        msg: Self::Msg,
+        ctx: &'a ActorContext<Self::Msg>,
+    ) -> Self::Step<'a, ControlFlow<ShutdownReason>>;
+
+    fn handle_batch<'a>(
+        &'a mut self,
+        msgs: Vec<Self::Msg>,
+        ctx: &'a ActorContext<Self::Msg>,
+    ) -> Self::Step<'a, ControlFlow<ShutdownReason>>;
+
+    fn on_start(&mut self) -> Self::Step<'_, ()>;
+
+    fn on_stop(&mut self) -> Self::Step<'_, ()>;
+
+    fn on_timeout(&mut self, priority: Priority) -> Self::Step<'_, ControlFlow<ShutdownReason>>;
+
+    /// The actor's name in diagnostics.
+    fn name() -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// The actor's own state, for [`ActorHandle::with_state`](crate::ActorHandle::with_state).
+    fn state(&mut self) -> &mut dyn Any;
+}
+
+impl<A: Actor> Drive for A {
+    type Msg = A::Msg;
+    type Step<'a, T: 'a> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+    fn handle<'a>(
+        &'a mut self,
+        msg: A::Msg,
+        ctx: &'a ActorContext<A::Msg>,
+    ) -> Self::Step<'a, ControlFlow<ShutdownReason>> {
+        Actor::handle(self, msg, ctx)
+    }
+
+    fn handle_batch<'a>(
+        &'a mut self,
+        msgs: Vec<A::Msg>,
+        ctx: &'a ActorContext<A::Msg>,
+    ) -> Self::Step<'a, ControlFlow<ShutdownReason>> {
+        Actor::handle_batch(self, msgs, ctx)
+    }
+
+    fn on_start(&mut self) -> Self::Step<'_, ()> {
+        Actor::on_start(self)
+    }
+
+    fn on_stop(&mut self) -> Self::Step<'_, ()> {
+        Actor::on_stop(self)
+    }
+
+    fn on_timeout(&mut self, priority: Priority) -> Self::Step<'_, ControlFlow<ShutdownReason>> {
+        Actor::on_timeout(self, priority)
+    }
+
+    fn state(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,82 @@
+use crate::{ActorHandle, Origin, Prioritized, Priority};
+use async_trait::async_trait;
+use std::fmt;
+
+/// Why an [`ErasedHandle::send_bytes`] did not deliver its payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErasedSendError {
+    /// The decoder did not recognise the payload.
+    Undecodable,
+    /// The actor has stopped.
+    Closed,
+}
+
+impl fmt::Display for ErasedSendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErasedSendError::Undecodable => write!(f, "payload could not be decoded"),
+            ErasedSendError::Closed => write!(f, "actor has stopped"),
+        }
+    }
+}
+
+impl std::error::Error for ErasedSendError {}
+
+/// An actor that accepts opaque payloads, with its message type erased.
+///
+/// Lets a host keep actors from separately written plugins side by side as
+/// `Box<dyn ErasedHandle>` without knowing their message enums. Built with [`erase`].
+#[async_trait]
+pub trait ErasedHandle: Send + Sync {
+    /// Decodes `payload` into a message and sends it, waiting for mailbox capacity if
+    /// necessary.
+    ///
+    /// `priority` is passed to the decoder; where the message lands in the queue is decided
+    /// by the decoded message's own priority, as always.
+    async fn send_bytes(&self, priority: Priority, payload: Vec<u8>)
+        -> Result<(), ErasedSendError>;
+
+    /// Returns `true` once the actor's mailbox no longer accepts messages.
+    fn is_closed(&self) -> bool;
+}
+
+/// Wraps `handle` so that it accepts payloads `decode` turns into messages.
+///
+/// `decode` returns `None` for payloads it does not recognise, which `send_bytes` reports as
+/// [`ErasedSendError::Undecodable`].
+pub fn erase<M, F>(handle: ActorHandle<M>, decode: F) -> Box<dyn ErasedHandle>
+where
+    M: Prioritized + Send + 'static,
+    F: Fn(Priority, Vec<u8>) -> Option<M> + Send + Sync + 'static,
+{
+    Box::new(Erased { handle, decode })
+}
+
+struct Erased<M, F> {
+    handle: ActorHandle<M>,
+    decode: F,
+}
+
+#[async_trait]
+impl<M, F> ErasedHandle for Erased<M, F>
+where
+    M: Prioritized + Send + 'static,
+    F: Fn(Priority, Vec<u8>) -> Option<M> + Send + Sync + 'static,
+{
+    async fn send_bytes(
+        &self,
+        priority: Priority,
+        payload: Vec<u8>,
+    ) -> Result<(), ErasedSendError> {
+        let msg = (self.decode)(priority, payload).ok_or(ErasedSendError::Undecodable)?;
+        self.handle
+            .send_from(Origin::External, msg)
+            .await
+            .map_err(|_| ErasedSendError::Closed)
+    }
+
+    fn is_closed(&self) -> bool {
+        self.handle.is_closed()
+    }
+}
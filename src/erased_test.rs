@@ -0,0 +1,97 @@
+use crate::prelude::*;
+use crate::{erase, ErasedHandle, ErasedSendError};
+use std::collections::HashMap;
+use tokio::sync::oneshot;
+
+// Two "plugins" with unrelated message enums.
+
+define_actor! {
+    Logger {
+        lines: Vec<String>,
+    }
+
+    impl LoggerMsg {
+        @priority(Low)
+        fn Line(&mut self, text: String) {
+            self.lines.push(text);
+        }
+
+        @priority(Low)
+        fn Lines(&mut self, tx: oneshot::Sender<Vec<String>>) {
+            let _ = tx.send(self.lines.clone());
+        }
+    }
+}
+
+define_actor! {
+    Adder {
+        total: u64,
+    }
+
+    impl AdderMsg {
+        @priority(Medium)
+        fn Add(&mut self, n: u64) {
+            self.total += n;
+        }
+    }
+}
+
+fn decode_logger(_: Priority, payload: Vec<u8>) -> Option<LoggerMsg> {
+    String::from_utf8(payload).ok().map(LoggerMsg::Line)
+}
+
+fn decode_adder(priority: Priority, payload: Vec<u8>) -> Option<AdderMsg> {
+    if priority == Priority::Shutdown {
+        return Some(AdderMsg::Shutdown);
+    }
+    let bytes: [u8; 8] = payload.try_into().ok()?;
+    Some(AdderMsg::Add(u64::from_le_bytes(bytes)))
+}
+
+#[tokio::test]
+async fn test_routing_between_plugins() {
+    let logger = spawn_actor(Logger { lines: Vec::new() });
+    let adder = spawn_actor(Adder { total: 0 });
+
+    let mut plugins: HashMap<&str, Box<dyn ErasedHandle>> = HashMap::new();
+    plugins.insert("logger", erase(logger.clone(), decode_logger));
+    plugins.insert("adder", erase(adder.clone(), decode_adder));
+
+    let route =
+        |name: &str, priority, payload: Vec<u8>| plugins[name].send_bytes(priority, payload);
+    route("logger", Priority::Low, b"hello".to_vec())
+        .await
+        .unwrap();
+    route("adder", Priority::Medium, 5u64.to_le_bytes().to_vec())
+        .await
+        .unwrap();
+
+    // Decode failures are reported, not dropped.
+    assert_eq!(
+        route("logger", Priority::Low, vec![0xff]).await,
+        Err(ErasedSendError::Undecodable)
+    );
+    assert_eq!(
+        route("adder", Priority::Medium, vec![1, 2, 3]).await,
+        Err(ErasedSendError::Undecodable)
+    );
+
+    // Stopping one plugin leaves the other reachable.
+    route("adder", Priority::Shutdown, Vec::new())
+        .await
+        .unwrap();
+    assert_eq!(adder.join().await, TerminationReason::ShutdownMessage);
+    assert!(plugins["adder"].is_closed());
+    assert_eq!(
+        route("adder", Priority::Medium, 1u64.to_le_bytes().to_vec()).await,
+        Err(ErasedSendError::Closed)
+    );
+
+    route("logger", Priority::Low, b"still here".to_vec())
+        .await
+        .unwrap();
+    assert!(!plugins["logger"].is_closed());
+    let (tx, rx) = oneshot::channel();
+    logger.send(LoggerMsg::Lines(tx)).await.unwrap();
+    assert_eq!(rx.await.unwrap(), ["hello", "still here"]);
+}
@@ -0,0 +1,159 @@
+use std::fmt;
+
+/// Why an [`ActorHandle`](crate::ActorHandle) call failed, naming the actor it was for.
+///
+/// Sends hand an undelivered message back, so they fail with `Error<M>` for the actor's
+/// message type `M`; calls with no message to return, such as
+/// [`ping`](crate::ActorHandle::ping), fail with `Error<()>`.
+#[derive(Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error<M = ()> {
+    /// The actor's mailbox no longer accepts messages: the actor has stopped, or is
+    /// stopping.
+    MailboxClosed {
+        /// The actor's [name](crate::ActorHandle::name).
+        actor: String,
+        /// The message that was not delivered, if the call sent one.
+        returned: Option<M>,
+    },
+    /// The actor's mailbox is at capacity, its reservations hold the remaining room back
+    /// from the message's priority, or that priority's [quota](crate::RuntimeConfig::quotas)
+    /// is full.
+    MailboxFull {
+        /// The actor's [name](crate::ActorHandle::name).
+        actor: String,
+        /// The message that was not delivered.
+        returned: M,
+    },
+    /// The actor rejects messages until it is ready, under
+    /// [`Readiness::Reject`](crate::Readiness::Reject), and is not ready yet.
+    NotReady {
+        /// The actor's [name](crate::ActorHandle::name).
+        actor: String,
+        /// The message that was not delivered.
+        returned: M,
+    },
+    /// The request was delivered but its reply sender was dropped unanswered, e.g.
+    /// because the actor stopped before handling it.
+    ReplyDropped {
+        /// The actor's [name](crate::ActorHandle::name).
+        actor: String,
+    },
+    /// No answer arrived within the call's timeout.
+    Timeout {
+        /// The actor's [name](crate::ActorHandle::name).
+        actor: String,
+    },
+}
+
+impl<M> Error<M> {
+    pub(crate) fn closed(actor: &str, returned: Option<M>) -> Self {
+        Error::MailboxClosed {
+            actor: actor.to_owned(),
+            returned,
+        }
+    }
+
+    pub(crate) fn full(actor: &str, returned: M) -> Self {
+        Error::MailboxFull {
+            actor: actor.to_owned(),
+            returned,
+        }
+    }
+
+    pub(crate) fn not_ready(actor: &str, returned: M) -> Self {
+        Error::NotReady {
+            actor: actor.to_owned(),
+            returned,
+        }
+    }
+
+    /// The name of the actor the call was for.
+    pub fn actor(&self) -> &str {
+        match self {
+            Error::MailboxClosed { actor, .. }
+            | Error::MailboxFull { actor, .. }
+            | Error::NotReady { actor, .. }
+            | Error::ReplyDropped { actor }
+            | Error::Timeout { actor } => actor,
+        }
+    }
+
+    /// Whether the actor's mailbox is closed.
+    pub fn is_closed(&self) -> bool {
+        matches!(self, Error::MailboxClosed { .. })
+    }
+
+    /// Whether the actor's mailbox was full.
+    pub fn is_full(&self) -> bool {
+        matches!(self, Error::MailboxFull { .. })
+    }
+
+    /// Whether the actor was not ready for the message yet.
+    pub fn is_not_ready(&self) -> bool {
+        matches!(self, Error::NotReady { .. })
+    }
+
+    /// Takes back the message that was not delivered, if there is one.
+    pub fn into_returned(self) -> Option<M> {
+        match self {
+            Error::MailboxClosed { returned, .. } => returned,
+            Error::MailboxFull { returned, .. } | Error::NotReady { returned, .. } => {
+                Some(returned)
+            }
+            Error::ReplyDropped { .. } | Error::Timeout { .. } => None,
+        }
+    }
+
+    /// Drops the undelivered message, if any, e.g. to pass the error on without the
+    /// actor's message type.
+    pub fn without_message(self) -> Error {
+        match self {
+            Error::MailboxClosed { actor, returned } => Error::MailboxClosed {
+                actor,
+                returned: returned.map(drop),
+            },
+            Error::MailboxFull { actor, .. } => Error::MailboxFull {
+                actor,
+                returned: (),
+            },
+            Error::NotReady { actor, .. } => Error::NotReady {
+                actor,
+                returned: (),
+            },
+            Error::ReplyDropped { actor } => Error::ReplyDropped { actor },
+            Error::Timeout { actor } => Error::Timeout { actor },
+        }
+    }
+}
+
+impl<M> fmt::Display for Error<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problem = match self {
+            Error::MailboxClosed { .. } => "mailbox is closed",
+            Error::MailboxFull { .. } => "mailbox is full",
+            Error::NotReady { .. } => "actor is not ready yet",
+            Error::ReplyDropped { .. } => "reply sender dropped without a reply",
+            Error::Timeout { .. } => "timed out waiting for an answer",
+        };
+        write!(f, "actor `{}`: {}", self.actor(), problem)
+    }
+}
+
+// Written out so that messages need not be `Debug`, as with tokio's send errors.
+impl<M> fmt::Debug for Error<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let variant = match self {
+            Error::MailboxClosed { .. } => "MailboxClosed",
+            Error::MailboxFull { .. } => "MailboxFull",
+            Error::NotReady { .. } => "NotReady",
+            Error::ReplyDropped { .. } => "ReplyDropped",
+            Error::Timeout { .. } => "Timeout",
+        };
+        f.debug_struct(variant)
+            .field("actor", &self.actor())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<M> std::error::Error for Error<M> {}
@@ -0,0 +1,59 @@
+use crate::prelude::*;
+use crate::test_support::{stall, Gated, GatedMsg};
+use tokio::time::Duration;
+
+#[tokio::test]
+async fn test_closed_mailbox_names_the_actor_and_returns_the_message() {
+    let handle = spawn_actor_with(Gated::new(), ActorConfig::new().name("gatekeeper"));
+    handle.send(GatedMsg::Shutdown).await.unwrap();
+    handle.clone().join().await;
+
+    let err = handle.send(GatedMsg::Urgent()).await.unwrap_err();
+    assert!(err.is_closed());
+    assert_eq!(err.actor(), "gatekeeper");
+    assert_eq!(err.to_string(), "actor `gatekeeper`: mailbox is closed");
+    assert!(matches!(err.into_returned(), Some(GatedMsg::Urgent())));
+
+    let err = handle.try_send(GatedMsg::Fill()).unwrap_err();
+    assert!(matches!(err.into_returned(), Some(GatedMsg::Fill())));
+}
+
+#[tokio::test]
+async fn test_full_mailbox_names_the_actor_and_returns_the_message() {
+    let actor = spawn_actor_owned(
+        Gated::new(),
+        ActorConfig::new().name("gatekeeper").capacity(1),
+    );
+    let release = stall(&actor).await;
+    actor.try_send(GatedMsg::Fill()).unwrap();
+
+    let err = actor.try_send(GatedMsg::Urgent()).unwrap_err();
+    assert!(err.is_full());
+    assert_eq!(err.to_string(), "actor `gatekeeper`: mailbox is full");
+    assert!(matches!(err.into_returned(), Some(GatedMsg::Urgent())));
+    drop(release);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_calls_without_a_message_name_the_actor() {
+    let handle = spawn_actor_with(
+        Gated::new(),
+        ActorConfig::new()
+            .name("gatekeeper")
+            .ping_timeout(Duration::from_millis(100)),
+    );
+    let release = stall(&handle).await;
+
+    let err = handle.ping().await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "actor `gatekeeper`: timed out waiting for an answer"
+    );
+    let err = handle
+        .ask_timeout(Duration::from_millis(100), GatedMsg::Report)
+        .await
+        .unwrap_err();
+    assert_eq!(err.actor(), "gatekeeper");
+    assert!(err.into_returned().is_none());
+    drop(release);
+}
@@ -0,0 +1,138 @@
+use crate::ActorHandle;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+/// Publishes events of type `E` to every subscriber, without the publisher knowing who
+/// they are.
+///
+/// Built on a tokio [`broadcast`] channel: emitting never waits, and each subscriber gets
+/// a clone of every event sent after it subscribed. A subscriber that falls more than the
+/// emitter's capacity behind loses the oldest events it has not received, and its next
+/// `recv` reports how many with [`RecvError::Lagged`](broadcast::error::RecvError::Lagged);
+/// the actor is never held up by it.
+///
+/// An actor gets the emitter its handles subscribe to from
+/// [`ActorContext::events`](crate::ActorContext::events), and can keep a clone in a field.
+/// [`EventEmitter::new`] makes a standalone one.
+///
+/// ```
+/// use priact::prelude::*;
+///
+/// #[derive(Clone, Debug, PartialEq)]
+/// enum JobEvent {
+///     Completed(u32),
+/// }
+///
+/// define_actor! {
+///     Worker {}
+///
+///     impl WorkerMsg {
+///         @priority(Medium)
+///         fn Run(&mut self, ctx: &ActorContext<WorkerMsg>, job: u32) {
+///             ctx.events::<JobEvent>().emit(JobEvent::Completed(job));
+///         }
+///     }
+/// }
+///
+/// # #[tokio::main] async fn main() {
+/// let handle = spawn_actor(Worker {});
+/// let mut events = handle.subscribe_events::<JobEvent>();
+/// handle.send(WorkerMsg::Run(7)).await.unwrap();
+/// assert_eq!(events.recv().await, Ok(JobEvent::Completed(7)));
+/// # }
+/// ```
+pub struct EventEmitter<E> {
+    tx: broadcast::Sender<E>,
+}
+
+impl<E> Clone for EventEmitter<E> {
+    fn clone(&self) -> Self {
+        EventEmitter {
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl<E: Clone> EventEmitter<E> {
+    /// An emitter that buffers up to `capacity` events for its slowest subscriber.
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "event capacity must be greater than zero");
+        let (tx, _) = broadcast::channel(capacity);
+        EventEmitter { tx }
+    }
+
+    /// Sends `event` to every current subscriber, returning how many there are; with none,
+    /// the event is dropped.
+    pub fn emit(&self, event: E) -> usize {
+        self.tx.send(event).unwrap_or(0)
+    }
+
+    /// A receiver for the events emitted from now on.
+    ///
+    /// It reports [`RecvError::Closed`](broadcast::error::RecvError::Closed) once every
+    /// clone of the emitter is gone.
+    pub fn subscribe(&self) -> broadcast::Receiver<E> {
+        self.tx.subscribe()
+    }
+
+    /// The number of live subscribers.
+    pub fn subscriber_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+}
+
+/// An actor's emitters, one per event type, shared by its handles and its context.
+pub(crate) struct Events {
+    capacity: usize,
+    // `None` once the actor has stopped.
+    emitters: Mutex<Option<HashMap<TypeId, Box<dyn Any + Send + Sync>>>>,
+}
+
+impl Events {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Events {
+            capacity,
+            emitters: Mutex::new(Some(HashMap::new())),
+        }
+    }
+
+    /// The emitter for `E`, created on first use. Once the actor has stopped, a fresh one
+    /// that nobody else holds, so its subscribers see the channel closed.
+    pub(crate) fn emitter<E: Clone + Send + 'static>(&self) -> EventEmitter<E> {
+        let mut emitters = self.emitters.lock().unwrap();
+        let Some(emitters) = emitters.as_mut() else {
+            return EventEmitter::new(self.capacity);
+        };
+        emitters
+            .entry(TypeId::of::<EventEmitter<E>>())
+            .or_insert_with(|| Box::new(EventEmitter::<E>::new(self.capacity)))
+            .downcast_ref::<EventEmitter<E>>()
+            .expect("emitters are keyed by their type")
+            .clone()
+    }
+
+    /// Drops the actor's emitters, so that subscribers see the channels closed once the
+    /// actor drops its own clones too.
+    pub(crate) fn close(&self) {
+        let emitters = self.emitters.lock().unwrap().take();
+        drop(emitters);
+    }
+}
+
+impl<M> ActorHandle<M> {
+    /// A receiver for the events of type `E` the actor emits from now on through
+    /// [`ActorContext::events`](crate::ActorContext::events).
+    ///
+    /// A receiver that falls behind loses events rather than holding the actor up; see
+    /// [`EventEmitter`]. Once the actor has stopped and dropped its emitters, the receiver
+    /// reports [`RecvError::Closed`](broadcast::error::RecvError::Closed).
+    pub fn subscribe_events<E: Clone + Send + 'static>(&self) -> broadcast::Receiver<E> {
+        self.shared.events.emitter::<E>().subscribe()
+    }
+}
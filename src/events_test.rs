@@ -0,0 +1,106 @@
+use crate::prelude::*;
+use tokio::sync::broadcast::error::RecvError;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Reading {
+    Taken(u32),
+}
+
+define_actor! {
+    /// Publishes a reading per message, through the emitter it got on its first one.
+    Station {
+        emitter: Option<EventEmitter<Reading>>,
+        published: u32,
+    }
+
+    impl StationMsg {
+        @priority(Medium)
+        fn Take(&mut self, ctx: &ActorContext<StationMsg>, value: u32) {
+            let emitter = self.emitter.get_or_insert_with(|| ctx.events());
+            emitter.emit(Reading::Taken(value));
+            self.published += 1;
+        }
+
+        @priority(Low)
+        fn Published(&self) -> u32 {
+            self.published
+        }
+    }
+}
+
+fn station() -> Station {
+    Station {
+        emitter: None,
+        published: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_every_subscriber_receives_every_event() {
+    let handle = spawn_actor(station());
+    let mut first = handle.subscribe_events::<Reading>();
+    let mut second = handle.subscribe_events::<Reading>();
+
+    for value in [1, 2, 3] {
+        handle.send(StationMsg::Take(value)).await.unwrap();
+    }
+    for value in [1, 2, 3] {
+        assert_eq!(first.recv().await, Ok(Reading::Taken(value)));
+        assert_eq!(second.recv().await, Ok(Reading::Taken(value)));
+    }
+}
+
+#[tokio::test]
+async fn test_dropped_subscriber_does_not_affect_the_actor() {
+    let handle = spawn_actor(station());
+    let dropped = handle.subscribe_events::<Reading>();
+    let mut kept = handle.subscribe_events::<Reading>();
+    handle.send(StationMsg::Take(1)).await.unwrap();
+    drop(dropped);
+    handle.send(StationMsg::Take(2)).await.unwrap();
+
+    let (msg, published) = StationMsg::published();
+    handle.send(msg).await.unwrap();
+    assert_eq!(published.await.unwrap(), 2);
+    assert_eq!(kept.recv().await, Ok(Reading::Taken(1)));
+    assert_eq!(kept.recv().await, Ok(Reading::Taken(2)));
+}
+
+#[tokio::test]
+async fn test_lagging_subscriber_loses_the_oldest_events() {
+    let handle = spawn_actor_with(station(), ActorConfig::new().event_capacity(2));
+    let mut slow = handle.subscribe_events::<Reading>();
+    for value in 1..=5 {
+        handle.send(StationMsg::Take(value)).await.unwrap();
+    }
+    handle.await_idle().await;
+
+    assert_eq!(slow.recv().await, Err(RecvError::Lagged(3)));
+    assert_eq!(slow.recv().await, Ok(Reading::Taken(4)));
+    assert_eq!(slow.recv().await, Ok(Reading::Taken(5)));
+}
+
+#[tokio::test]
+async fn test_subscribers_see_the_channel_close_once_the_actor_stops() {
+    let handle = spawn_actor(station());
+    let mut events = handle.subscribe_events::<Reading>();
+    handle.send(StationMsg::Take(1)).await.unwrap();
+    handle.await_idle().await;
+    handle.send(StationMsg::Shutdown).await.unwrap();
+    handle.clone().join().await;
+
+    assert_eq!(events.recv().await, Ok(Reading::Taken(1)));
+    assert_eq!(events.recv().await, Err(RecvError::Closed));
+    let mut late = handle.subscribe_events::<Reading>();
+    assert_eq!(late.recv().await, Err(RecvError::Closed));
+}
+
+#[tokio::test]
+async fn test_standalone_emitter_without_subscribers_drops_events() {
+    let emitter = EventEmitter::new(4);
+    assert_eq!(emitter.emit(Reading::Taken(1)), 0);
+    let mut events = emitter.subscribe();
+    assert_eq!(emitter.subscriber_count(), 1);
+    assert_eq!(emitter.emit(Reading::Taken(2)), 1);
+    assert_eq!(events.recv().await, Ok(Reading::Taken(2)));
+}
@@ -0,0 +1,141 @@
+use crate::{Origin, Prioritized, Priority};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use tokio::time::Instant;
+
+/// Why the runtime dropped a message without handing it to the actor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum DropReason {
+    /// The queue had reached its shed threshold when the message arrived.
+    Shed,
+    /// The message had outlived its TTL by the time it was due to be handled.
+    Expired,
+    /// The queue was at its limit, and the message was either turned away on arrival or
+    /// evicted to make room.
+    Overflow,
+    /// A newer message with the same [coalescing key](Prioritized::coalesce_key) took its
+    /// place in the queue.
+    Coalesced,
+}
+
+/// One retained example of a dropped message.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct DroppedMessage<M> {
+    pub priority: Priority,
+    pub origin: Origin,
+    /// When the message was dropped.
+    pub at: Instant,
+    /// The message itself, if the actor was spawned with
+    /// [`ActorConfig::drop_exemplars_with_messages`](crate::ActorConfig::drop_exemplars_with_messages).
+    pub message: Option<M>,
+}
+
+/// Everything recorded for one drop reason and message variant, from
+/// [`ActorHandle::drop_exemplars`](crate::ActorHandle::drop_exemplars).
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct DropExemplars<M> {
+    pub reason: DropReason,
+    /// The variant name, from [`Prioritized::variant_name`].
+    pub variant: &'static str,
+    /// Exact number of messages dropped for this reason and variant.
+    pub dropped: u64,
+    /// The most recent drops, oldest first.
+    pub exemplars: Vec<DroppedMessage<M>>,
+}
+
+/// Keeps the last few dropped messages per drop reason and variant, and exact drop counts.
+///
+/// Recording is constant time and the memory used is bounded by the number of variants,
+/// however many messages are dropped.
+pub(crate) struct DropSampler<M> {
+    per_variant: usize,
+    keep_messages: bool,
+    slots: Mutex<HashMap<(DropReason, &'static str), Slot<M>>>,
+}
+
+struct Slot<M> {
+    dropped: u64,
+    recent: VecDeque<DroppedMessage<M>>,
+}
+
+impl<M: Prioritized> DropSampler<M> {
+    pub(crate) fn new(per_variant: usize, keep_messages: bool) -> Self {
+        DropSampler {
+            per_variant,
+            keep_messages,
+            slots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records a drop, keeping `msg` if asked to; there is none once it has gone to the
+    /// dead-letter sink.
+    pub(crate) fn record(
+        &self,
+        reason: DropReason,
+        variant: &'static str,
+        priority: Priority,
+        msg: Option<M>,
+        origin: Origin,
+    ) {
+        let key = (reason, variant);
+        // A message that is not kept is dropped at the end, after the lock is released.
+        let (message, _discarded) = if self.keep_messages {
+            (msg, None)
+        } else {
+            (None, msg)
+        };
+        let exemplar = DroppedMessage {
+            priority,
+            origin,
+            at: Instant::now(),
+            message,
+        };
+
+        let mut slots = self.slots.lock().unwrap();
+        let slot = slots.entry(key).or_insert_with(|| Slot {
+            dropped: 0,
+            recent: VecDeque::with_capacity(self.per_variant),
+        });
+        slot.dropped += 1;
+        if self.per_variant > 0 {
+            if slot.recent.len() == self.per_variant {
+                slot.recent.pop_front();
+            }
+            slot.recent.push_back(exemplar);
+        }
+    }
+
+    /// Everything recorded so far, ordered by reason and then variant name.
+    pub(crate) fn snapshot(&self) -> Vec<DropExemplars<M>>
+    where
+        M: Clone,
+    {
+        self.collect(|recent| recent.iter().cloned().collect())
+    }
+
+    /// Like [`snapshot`](Self::snapshot), moving the exemplars out; the counts are kept.
+    pub(crate) fn take(&self) -> Vec<DropExemplars<M>> {
+        self.collect(|recent| recent.drain(..).collect())
+    }
+
+    fn collect(
+        &self,
+        mut exemplars: impl FnMut(&mut VecDeque<DroppedMessage<M>>) -> Vec<DroppedMessage<M>>,
+    ) -> Vec<DropExemplars<M>> {
+        let mut slots = self.slots.lock().unwrap();
+        let mut all: Vec<_> = slots
+            .iter_mut()
+            .map(|(&(reason, variant), slot)| DropExemplars {
+                reason,
+                variant,
+                dropped: slot.dropped,
+                exemplars: exemplars(&mut slot.recent),
+            })
+            .collect();
+        all.sort_by_key(|entry| (entry.reason, entry.variant));
+        all
+    }
+}
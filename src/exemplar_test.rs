@@ -0,0 +1,107 @@
+use crate::prelude::*;
+use crate::{DropReason, Origin};
+
+#[derive(Prioritized, Clone, Debug, PartialEq)]
+enum ProbeMsg {
+    #[priority(Low)]
+    Small(u32),
+    #[priority(High)]
+    Large(u32),
+    #[priority(Shutdown)]
+    Stop,
+}
+
+struct Probe;
+
+#[async_trait::async_trait]
+impl Actor for Probe {
+    type Msg = ProbeMsg;
+
+    async fn handle(
+        &mut self,
+        msg: ProbeMsg,
+        _ctx: &ActorContext<ProbeMsg>,
+    ) -> ControlFlow<ShutdownReason> {
+        if msg == ProbeMsg::Stop {
+            return ControlFlow::Break(ShutdownReason::Requested);
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+// Sheds everything but the stop message.
+fn shedding(config: ActorConfig) -> ActorHandle<ProbeMsg> {
+    spawn_actor_with(Probe, config.unbounded().shed_threshold(0))
+}
+
+#[tokio::test]
+async fn test_exemplars_are_capped_and_counts_exact() {
+    let handle = shedding(ActorConfig::new().drop_exemplars_with_messages(5));
+
+    for id in 0..100_000 {
+        let msg = if id % 2 == 0 {
+            ProbeMsg::Small(id)
+        } else {
+            ProbeMsg::Large(id)
+        };
+        handle.send(msg).await.unwrap();
+    }
+    handle.send(ProbeMsg::Stop).await.unwrap();
+    let observer = handle.clone();
+    handle.join().await;
+
+    let drops = observer.drop_exemplars();
+    assert_eq!(drops.len(), 2);
+    for (entry, variant, newest) in [(&drops[0], "Large", 99_991), (&drops[1], "Small", 99_990)] {
+        assert_eq!(entry.reason, DropReason::Shed);
+        assert_eq!(entry.variant, variant);
+        assert_eq!(entry.dropped, 50_000);
+        let ids: Vec<u32> = entry
+            .exemplars
+            .iter()
+            .map(|exemplar| match exemplar.message {
+                Some(ProbeMsg::Small(id) | ProbeMsg::Large(id)) => id,
+                ref other => panic!("unexpected exemplar {:?}", other),
+            })
+            .collect();
+        assert_eq!(ids, (0..5).map(|i| newest + 2 * i).collect::<Vec<_>>());
+        assert!(entry
+            .exemplars
+            .iter()
+            .all(|exemplar| exemplar.origin == Origin::External));
+    }
+}
+
+#[tokio::test]
+async fn test_summaries_only_and_take() {
+    let handle = shedding(ActorConfig::new().drop_exemplars(2));
+    for id in 0..10 {
+        handle.send(ProbeMsg::Small(id)).await.unwrap();
+    }
+    handle.send(ProbeMsg::Stop).await.unwrap();
+    let observer = handle.clone();
+    handle.join().await;
+
+    let taken = observer.take_drop_exemplars();
+    assert_eq!(taken[0].dropped, 10);
+    assert_eq!(taken[0].exemplars.len(), 2);
+    assert!(taken[0]
+        .exemplars
+        .iter()
+        .all(|e| e.message.is_none() && e.priority == Priority::Low));
+
+    // The counts survive taking; the exemplars do not.
+    let after = observer.take_drop_exemplars();
+    assert_eq!(after[0].dropped, 10);
+    assert!(after[0].exemplars.is_empty());
+}
+
+#[tokio::test]
+async fn test_no_exemplars_by_default() {
+    let handle = shedding(ActorConfig::new());
+    handle.send(ProbeMsg::Small(0)).await.unwrap();
+    handle.send(ProbeMsg::Stop).await.unwrap();
+    let observer = handle.clone();
+    handle.join().await;
+    assert!(observer.drop_exemplars().is_empty());
+}
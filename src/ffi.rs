@@ -0,0 +1,149 @@
+//! C-callable senders for actors spawned from Rust.
+//!
+//! [`export_handle`] turns an [`ActorHandle`] into an opaque `*mut PriactFfiHandle` that C
+//! code passes to [`priact_send`] or [`priact_try_send`] along with a byte buffer. The
+//! buffer is decoded into a message by the callback given at export time, and the call
+//! returns one of the `PRIACT_*` status codes. [`priact_release`] gives the handle back.
+//!
+//! A `PriactFfiHandle` pointer is a token, never dereferenced: every call looks it up in a
+//! registry of live handles, so a null, stale or already-released pointer is reported as
+//! [`PRIACT_INVALID_HANDLE`] instead of touching freed memory. Tokens are never reused.
+//! Handles may be used from any number of threads at once. Panics, including panics from
+//! the decode callback, are caught at the boundary and reported as [`PRIACT_PANICKED`].
+
+use crate::{ActorHandle, Origin, Prioritized};
+use std::collections::HashMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, PoisonError};
+
+/// The message was queued.
+pub const PRIACT_OK: i32 = 0;
+/// The mailbox had no room; only returned by [`priact_try_send`].
+pub const PRIACT_FULL: i32 = 1;
+/// The actor has stopped.
+pub const PRIACT_CLOSED: i32 = 2;
+/// The decode callback returned `None`.
+pub const PRIACT_DECODE_FAILED: i32 = 3;
+/// The handle is null, was never exported, or has already been released.
+pub const PRIACT_INVALID_HANDLE: i32 = -1;
+/// A panic was caught before it could cross into the caller.
+pub const PRIACT_PANICKED: i32 = -2;
+
+/// Turns the bytes passed to [`priact_send`] into a message, or `None` if they are invalid.
+pub type DecodeFn<M> = fn(*const u8, usize) -> Option<M>;
+
+/// Opaque handle to an exported actor, as seen from C.
+#[repr(C)]
+pub struct PriactFfiHandle {
+    _private: [u8; 0],
+}
+
+/// Exports `handle` for C callers, decoding their byte buffers with `decode`.
+///
+/// The exported handle keeps the actor alive until it is released with [`priact_release`].
+///
+/// # Panics
+///
+/// Panics if called outside a Tokio runtime, which [`priact_send`] uses to wait for room.
+pub fn export_handle<M>(handle: ActorHandle<M>, decode: DecodeFn<M>) -> *mut PriactFfiHandle
+where
+    M: Prioritized + Send + 'static,
+{
+    let exported = Arc::new(Exported {
+        handle,
+        decode,
+        runtime: tokio::runtime::Handle::current(),
+    });
+    let token = NEXT_TOKEN.fetch_add(1, Ordering::Relaxed);
+    registry().insert(token, exported);
+    token as *mut PriactFfiHandle
+}
+
+/// Decodes `len` bytes at `data` and sends the message, waiting for room if the mailbox is
+/// full.
+///
+/// Blocks the calling thread, so it must not be called from inside an async task; doing so
+/// returns [`PRIACT_PANICKED`].
+#[no_mangle]
+pub extern "C" fn priact_send(handle: *mut PriactFfiHandle, data: *const u8, len: usize) -> i32 {
+    guarded(|| match lookup(handle) {
+        Some(exported) => exported.send(data, len, true),
+        None => PRIACT_INVALID_HANDLE,
+    })
+}
+
+/// Like [`priact_send`], but returns [`PRIACT_FULL`] instead of waiting for room.
+#[no_mangle]
+pub extern "C" fn priact_try_send(
+    handle: *mut PriactFfiHandle,
+    data: *const u8,
+    len: usize,
+) -> i32 {
+    guarded(|| match lookup(handle) {
+        Some(exported) => exported.send(data, len, false),
+        None => PRIACT_INVALID_HANDLE,
+    })
+}
+
+/// Releases a handle returned by [`export_handle`]. Sends already in progress on other
+/// threads complete normally.
+#[no_mangle]
+pub extern "C" fn priact_release(handle: *mut PriactFfiHandle) -> i32 {
+    guarded(|| match registry().remove(&(handle as usize)) {
+        Some(_) => PRIACT_OK,
+        None => PRIACT_INVALID_HANDLE,
+    })
+}
+
+// Token 0 is never handed out, so a null pointer is always invalid.
+static NEXT_TOKEN: AtomicUsize = AtomicUsize::new(1);
+
+static HANDLES: LazyLock<Mutex<HashMap<usize, Arc<dyn ErasedSender>>>> =
+    LazyLock::new(Default::default);
+
+fn registry() -> std::sync::MutexGuard<'static, HashMap<usize, Arc<dyn ErasedSender>>> {
+    HANDLES.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn lookup(handle: *mut PriactFfiHandle) -> Option<Arc<dyn ErasedSender>> {
+    registry().get(&(handle as usize)).cloned()
+}
+
+fn guarded(call: impl FnOnce() -> i32) -> i32 {
+    catch_unwind(AssertUnwindSafe(call)).unwrap_or(PRIACT_PANICKED)
+}
+
+/// An exported handle with its message type erased.
+trait ErasedSender: Send + Sync {
+    fn send(&self, data: *const u8, len: usize, wait: bool) -> i32;
+}
+
+struct Exported<M> {
+    handle: ActorHandle<M>,
+    decode: DecodeFn<M>,
+    runtime: tokio::runtime::Handle,
+}
+
+impl<M: Prioritized + Send + 'static> ErasedSender for Exported<M> {
+    fn send(&self, data: *const u8, len: usize, wait: bool) -> i32 {
+        let Some(msg) = (self.decode)(data, len) else {
+            return PRIACT_DECODE_FAILED;
+        };
+        if wait {
+            match self
+                .runtime
+                .block_on(self.handle.send_from(Origin::External, msg))
+            {
+                Ok(()) => PRIACT_OK,
+                Err(_) => PRIACT_CLOSED,
+            }
+        } else {
+            match self.handle.try_send_from(Origin::External, msg) {
+                Ok(()) => PRIACT_OK,
+                Err(err) if err.is_full() => PRIACT_FULL,
+                Err(_) => PRIACT_CLOSED,
+            }
+        }
+    }
+}
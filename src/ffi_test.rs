@@ -0,0 +1,162 @@
+use crate::ffi::*;
+use crate::prelude::*;
+use crate::test_support::{stall, Gated, GatedMsg};
+use tokio::sync::oneshot;
+
+define_actor! {
+    Tally {
+        count: usize,
+    }
+
+    impl TallyMsg {
+        @priority(Low)
+        fn Add(&mut self) {
+            self.count += 1;
+        }
+
+        @priority(Low)
+        fn Report(&mut self, tx: oneshot::Sender<usize>) {
+            let _ = tx.send(self.count);
+        }
+    }
+}
+
+/// `[0]` adds one, `[9]` panics, anything else fails to decode.
+fn decode_tally(data: *const u8, len: usize) -> Option<TallyMsg> {
+    if data.is_null() {
+        return None;
+    }
+    match unsafe { std::slice::from_raw_parts(data, len) } {
+        [0] => Some(TallyMsg::Add()),
+        [9] => panic!("decoder blew up"),
+        _ => None,
+    }
+}
+
+/// `[0]` fills, `[1]` shuts the actor down.
+fn decode_gated(data: *const u8, len: usize) -> Option<GatedMsg> {
+    match unsafe { std::slice::from_raw_parts(data, len) } {
+        [0] => Some(GatedMsg::Fill()),
+        [1] => Some(GatedMsg::Shutdown),
+        _ => None,
+    }
+}
+
+// Raw pointers are not `Send`; C callers pass the token around as an integer anyway.
+fn token(handle: *mut PriactFfiHandle) -> usize {
+    handle as usize
+}
+
+fn send(token: usize, bytes: &[u8]) -> i32 {
+    priact_send(token as *mut PriactFfiHandle, bytes.as_ptr(), bytes.len())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_send_from_foreign_threads() {
+    let handle = spawn_actor(Tally { count: 0 });
+    let exported = token(export_handle(handle.clone(), decode_tally));
+
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            std::thread::spawn(move || (0..25).map(|_| send(exported, &[0])).collect::<Vec<_>>())
+        })
+        .collect();
+    for thread in threads {
+        assert!(thread
+            .join()
+            .unwrap()
+            .iter()
+            .all(|&status| status == PRIACT_OK));
+    }
+
+    let (tx, rx) = oneshot::channel();
+    handle.send(TallyMsg::Report(tx)).await.unwrap();
+    assert_eq!(rx.await.unwrap(), 100);
+    assert_eq!(priact_release(exported as *mut PriactFfiHandle), PRIACT_OK);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_decode_failures_and_panics_are_reported() {
+    let handle = spawn_actor(Tally { count: 0 });
+    let exported = token(export_handle(handle.clone(), decode_tally));
+
+    let statuses = tokio::task::spawn_blocking(move || {
+        [
+            send(exported, &[7]),
+            priact_send(exported as *mut PriactFfiHandle, std::ptr::null(), 0),
+            send(exported, &[9]),
+            send(exported, &[0]),
+        ]
+    })
+    .await
+    .unwrap();
+    assert_eq!(
+        statuses,
+        [
+            PRIACT_DECODE_FAILED,
+            PRIACT_DECODE_FAILED,
+            PRIACT_PANICKED,
+            PRIACT_OK,
+        ]
+    );
+
+    // Only the last send reached the actor, and the panic did not poison the handle.
+    let (tx, rx) = oneshot::channel();
+    handle.send(TallyMsg::Report(tx)).await.unwrap();
+    assert_eq!(rx.await.unwrap(), 1);
+    assert_eq!(priact_release(exported as *mut PriactFfiHandle), PRIACT_OK);
+}
+
+#[tokio::test]
+async fn test_try_send_reports_full_mailbox() {
+    let handle = spawn_actor_with(Gated::new(), ActorConfig::new().capacity(1));
+    let exported = export_handle(handle.clone(), decode_gated);
+    let release = stall(&handle).await;
+
+    assert_eq!(priact_try_send(exported, [0].as_ptr(), 1), PRIACT_OK);
+    assert_eq!(priact_try_send(exported, [0].as_ptr(), 1), PRIACT_FULL);
+
+    drop(release);
+    assert_eq!(priact_release(exported), PRIACT_OK);
+}
+
+#[tokio::test]
+async fn test_invalid_and_double_release() {
+    let handle = spawn_actor(Gated::new());
+    let exported = export_handle(handle, decode_gated);
+
+    assert_eq!(priact_release(std::ptr::null_mut()), PRIACT_INVALID_HANDLE);
+    assert_eq!(
+        priact_try_send(std::ptr::null_mut(), [0].as_ptr(), 1),
+        PRIACT_INVALID_HANDLE
+    );
+    assert_eq!(priact_release(exported), PRIACT_OK);
+    assert_eq!(priact_release(exported), PRIACT_INVALID_HANDLE);
+    assert_eq!(
+        priact_try_send(exported, [0].as_ptr(), 1),
+        PRIACT_INVALID_HANDLE
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_send_and_release_after_actor_stops() {
+    let handle = spawn_actor(Gated::new());
+    let exported = token(export_handle(handle.clone(), decode_gated));
+
+    let status = tokio::task::spawn_blocking(move || send(exported, &[1]))
+        .await
+        .unwrap();
+    assert_eq!(status, PRIACT_OK);
+    assert_eq!(handle.join().await, TerminationReason::ShutdownMessage);
+
+    let statuses = tokio::task::spawn_blocking(move || {
+        [
+            send(exported, &[0]),
+            priact_try_send(exported as *mut PriactFfiHandle, [0].as_ptr(), 1),
+            priact_release(exported as *mut PriactFfiHandle),
+        ]
+    })
+    .await
+    .unwrap();
+    assert_eq!(statuses, [PRIACT_CLOSED, PRIACT_CLOSED, PRIACT_OK]);
+}
@@ -0,0 +1,126 @@
+use crate::handle::Shared;
+use crate::{
+    spawn_actor_with, Actor, ActorConfig, ActorHandle, CancellationToken, TerminationReason,
+};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use tokio::time::{Duration, Instant};
+
+/// Actors that stop together: every actor spawned through the group watches the group's
+/// token, and [`shutdown_all`](Self::shutdown_all) cancels it and waits for them all.
+///
+/// The group does not keep its actors alive; they still stop on their own when their
+/// handles are dropped or they are shut down.
+///
+/// ```
+/// use priact::prelude::*;
+/// use priact::ActorGroup;
+/// use tokio::time::Duration;
+///
+/// define_actor! {
+///     Worker {}
+///
+///     impl WorkerMsg {
+///         fn Work(&mut self) {}
+///     }
+/// }
+///
+/// # #[tokio::main] async fn main() {
+/// let group = ActorGroup::new();
+/// let workers: Vec<_> = (0..3).map(|_| group.spawn(Worker {})).collect();
+/// let reasons = group.shutdown_all(Duration::from_secs(1)).await;
+/// assert_eq!(reasons, [TerminationReason::Cancelled; 3]);
+/// assert!(workers[0].send(WorkerMsg::Work()).await.is_err());
+/// # }
+/// ```
+#[derive(Default)]
+pub struct ActorGroup {
+    token: CancellationToken,
+    members: Mutex<Vec<Arc<dyn Member>>>,
+}
+
+impl ActorGroup {
+    /// An empty group with a token of its own.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An empty group that also stops once `token` is cancelled, e.g. a child of a
+    /// service-wide token.
+    pub fn with_token(token: CancellationToken) -> Self {
+        ActorGroup {
+            token,
+            members: Mutex::default(),
+        }
+    }
+
+    /// The token the group's actors stop on.
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Spawns `actor` like [`spawn_actor`](crate::spawn_actor), as a member of the group.
+    pub fn spawn<A>(&self, actor: A) -> ActorHandle<A::Msg>
+    where
+        A: Actor + Send + 'static,
+    {
+        self.spawn_with(actor, ActorConfig::default())
+    }
+
+    /// Spawns `actor` like [`spawn_actor_with`], as a member of the group.
+    ///
+    /// The group's token replaces any token `config` was given; its
+    /// [`cancel_mode`](ActorConfig::cancel_mode) applies. Spawning into a group that has
+    /// already been shut down gives an actor that starts stopping right away.
+    pub fn spawn_with<A>(&self, actor: A, config: ActorConfig) -> ActorHandle<A::Msg>
+    where
+        A: Actor + Send + 'static,
+    {
+        let handle = spawn_actor_with(actor, config.cancel_on(self.token.clone()));
+        let member: Arc<Shared<A::Msg>> = Arc::clone(&handle.shared);
+        self.members.lock().unwrap().push(member);
+        handle
+    }
+
+    /// Cancels the group's token and waits for every member to stop, returning why each
+    /// stopped, in the order they were spawned.
+    ///
+    /// Members still running once `timeout` has passed are stopped on the spot, as by
+    /// [`ActorHandle::shutdown_with_timeout`], and report
+    /// [`TerminationReason::DeadlineExceeded`]. The group is left empty.
+    pub async fn shutdown_all(&self, timeout: Duration) -> Vec<TerminationReason> {
+        self.token.cancel();
+        let members = std::mem::take(&mut *self.members.lock().unwrap());
+        let deadline = Instant::now() + timeout;
+        let mut reasons = Vec::with_capacity(members.len());
+        for member in members {
+            let reason = match tokio::time::timeout_at(deadline, member.terminated()).await {
+                Ok(reason) => reason,
+                Err(_elapsed) => {
+                    member.force_stop();
+                    member.terminated().await
+                }
+            };
+            reasons.push(reason);
+        }
+        reasons
+    }
+}
+
+/// A group member, whatever its message type.
+trait Member: Send + Sync {
+    fn terminated(&self) -> Pin<Box<dyn Future<Output = TerminationReason> + Send>>;
+
+    fn force_stop(&self);
+}
+
+impl<M: Send + 'static> Member for Shared<M> {
+    fn terminated(&self) -> Pin<Box<dyn Future<Output = TerminationReason> + Send>> {
+        Box::pin(self.termination())
+    }
+
+    fn force_stop(&self) {
+        self.force_stop.send_replace(true);
+    }
+}
@@ -0,0 +1,68 @@
+use crate::prelude::*;
+use crate::sink::Sink;
+use crate::test_support::{stall, Gated, GatedMsg};
+use crate::{ActorGroup, CancelMode, CancellationToken};
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+
+#[tokio::test]
+async fn test_group_shutdown_drains_and_stops_every_member() {
+    let (tx, mut dead_letters) = mpsc::unbounded_channel::<GatedMsg>();
+    let group = ActorGroup::new();
+    let busy = group.spawn_with(
+        Gated::new(),
+        ActorConfig::new().dead_letters(Sink::unbounded(tx)),
+    );
+    let idle = [group.spawn(Gated::new()), group.spawn(Gated::new())];
+    let release = stall(&busy).await;
+    busy.send(GatedMsg::Fill()).await.unwrap();
+
+    // The busy member gets to it once its running handler returns.
+    group.token().cancel();
+    drop(release);
+
+    let reasons = timeout(
+        Duration::from_secs(1),
+        group.shutdown_all(Duration::from_secs(5)),
+    )
+    .await
+    .unwrap();
+    assert_eq!(reasons, [TerminationReason::Cancelled; 3]);
+    for handle in idle.iter().chain([&busy]) {
+        let err = handle.send(GatedMsg::Fill()).await.unwrap_err();
+        assert!(err.is_closed());
+    }
+    // Handled before stopping, not dropped.
+    assert!(dead_letters.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_immediate_cancellation_leaves_the_queue_to_dead_letters() {
+    let (tx, mut dead_letters) = mpsc::unbounded_channel();
+    let token = CancellationToken::new();
+    let config = ActorConfig::new()
+        .cancel_on(token.clone())
+        .cancel_mode(CancelMode::Immediate)
+        .dead_letters(Sink::unbounded(tx));
+    let handle = spawn_actor_with(Gated::new(), config);
+    let release = stall(&handle).await;
+    handle.send(GatedMsg::Fill()).await.unwrap();
+
+    token.cancel();
+    drop(release);
+    assert_eq!(handle.join().await, TerminationReason::Cancelled);
+    assert!(matches!(dead_letters.recv().await, Some(GatedMsg::Fill())));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_group_shutdown_stops_members_that_outlast_the_timeout() {
+    let group = ActorGroup::new();
+    let stuck = group.spawn(Gated::new());
+    let _release = stall(&stuck).await;
+
+    let reasons = group.shutdown_all(Duration::from_millis(100)).await;
+    assert_eq!(
+        reasons,
+        [TerminationReason::DeadlineExceeded { mid_handler: true }]
+    );
+}
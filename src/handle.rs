@@ -0,0 +1,672 @@
+use crate::confirm::Confirm;
+use crate::events::Events;
+use crate::exemplar::DropSampler;
+use crate::idle::Pending;
+use crate::link::Children;
+use crate::mailbox::{Envelope, MailboxSender, Origin, WeakMailboxSender};
+use crate::permits::PermitPool;
+use crate::ready::ReadyGate;
+use crate::schedule::Schedules;
+use crate::sink::Sink;
+use crate::tap::Taps;
+use crate::{
+    Actor, ActorMetrics, Confirmation, DropExemplars, Error, MailboxPressure, NotHandled,
+    OverflowPolicy, Prioritized, Priority, ReconfigureError, RuntimeConfig, ShutdownOutcome,
+    ShutdownReport, TerminationReason,
+};
+use arc_swap::{ArcSwap, ArcSwapOption};
+use std::future::Future;
+use std::ops::Deref;
+use std::sync::{Arc, OnceLock};
+use std::task::{Context, Poll, Wake, Waker};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{oneshot, watch, TryAcquireError};
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, Instant};
+
+/// The sending side of a spawned actor.
+///
+/// Cloning a handle is cheap; the actor shuts down implicitly once every handle is dropped.
+pub struct ActorHandle<M> {
+    pub(crate) tx: MailboxSender<M>,
+    pub(crate) shared: Arc<Shared<M>>,
+    // The send this handle is finishing as a `futures::Sink`; never shared with clones.
+    #[cfg(feature = "futures")]
+    pub(crate) in_flight: crate::pipe::InFlight<M>,
+}
+
+/// Per-actor state reachable from every handle, strong or weak.
+// Some fields are only read by feature-gated extension traits.
+#[cfg_attr(
+    not(all(feature = "metrics", feature = "schedule", feature = "tap")),
+    allow(dead_code)
+)]
+pub(crate) struct Shared<M> {
+    pub(crate) permits: Option<Arc<PermitPool>>,
+    pub(crate) metrics: Arc<ActorMetrics>,
+    pub(crate) schedules: Arc<Schedules>,
+    pub(crate) taps: Arc<Taps<M>>,
+    pub(crate) drops: Option<Arc<DropSampler<M>>>,
+    pub(crate) status: watch::Receiver<Option<TerminationReason>>,
+    // Filled in just before the status, once the actor has stopped.
+    pub(crate) report: Arc<OnceLock<ShutdownReport>>,
+    pub(crate) runtime: Arc<ArcSwap<RuntimeConfig>>,
+    pub(crate) pending: Arc<Pending>,
+    pub(crate) children: Arc<Children>,
+    pub(crate) pressure: watch::Receiver<MailboxPressure>,
+    pub(crate) dead_letters: Option<Sink<M>>,
+    pub(crate) ping_timeout: Duration,
+    // Set once a graceful shutdown runs out of time; the processor stops at once.
+    pub(crate) force_stop: watch::Sender<bool>,
+    // Where sends go once the actor has been absorbed by another.
+    pub(crate) redirect: ArcSwapOption<ActorHandle<M>>,
+    pub(crate) events: Arc<Events>,
+    pub(crate) ready: ReadyGate,
+}
+
+impl<M> Shared<M> {
+    /// Resolves with the reason the actor stopped, once it has; holds no handle meanwhile.
+    pub(crate) fn termination(&self) -> impl Future<Output = TerminationReason> + Send + 'static {
+        let mut status = self.status.clone();
+        async move {
+            match status.wait_for(Option::is_some).await {
+                Ok(reason) => reason.expect("waited for a reason"),
+                Err(_) => TerminationReason::Aborted,
+            }
+        }
+    }
+}
+
+impl<M> Clone for ActorHandle<M> {
+    fn clone(&self) -> Self {
+        ActorHandle {
+            tx: self.tx.clone(),
+            shared: Arc::clone(&self.shared),
+            #[cfg(feature = "futures")]
+            in_flight: Default::default(),
+        }
+    }
+}
+
+impl<M> ActorHandle<M> {
+    /// A handle to the same actor that does not keep it alive, e.g. for a metrics scraper
+    /// or debug console that should only talk to the actor while it is still around.
+    pub fn downgrade(&self) -> WeakHandle<M> {
+        WeakHandle {
+            tx: self.tx.downgrade(),
+            shared: Arc::clone(&self.shared),
+        }
+    }
+
+    /// The name the actor goes by in logs, traces and metrics: the one given with
+    /// [`ActorConfig::name`](crate::ActorConfig::name), or else its type name followed by a
+    /// number unique to this process.
+    pub fn name(&self) -> &str {
+        self.shared.metrics.name()
+    }
+
+    /// Resolves with the reason the actor stopped, once it has, e.g. for supervision logic
+    /// outside the actor.
+    ///
+    /// Unlike [`join`](Self::join) this leaves the handle usable, and the returned future
+    /// holds no handle, so it does not keep the actor alive. Any number of observers can
+    /// wait, each getting the same reason; one that starts after the actor stopped resolves
+    /// at once.
+    pub fn terminated(&self) -> impl Future<Output = TerminationReason> + Send + 'static {
+        self.shared.termination()
+    }
+}
+
+impl<M: Prioritized + Send + 'static> ActorHandle<M> {
+    pub(crate) fn new(tx: MailboxSender<M>, shared: Shared<M>) -> Self {
+        ActorHandle {
+            tx,
+            shared: Arc::new(shared),
+            #[cfg(feature = "futures")]
+            in_flight: Default::default(),
+        }
+    }
+
+    /// Sends a message to the actor, waiting for mailbox capacity if necessary.
+    ///
+    /// Never waits on an unbounded mailbox. When the actor was spawned with a capacity
+    /// reservation, messages below the reserved priority wait here until their share of the
+    /// mailbox frees up.
+    ///
+    /// Fails with [`Error::MailboxFull`] if the message's priority
+    /// [quota](RuntimeConfig::quotas) is full under [`OverflowPolicy::Reject`], and with
+    /// [`Error::MailboxClosed`] once the actor has stopped, handing the message back.
+    pub async fn send(&self, msg: M) -> Result<(), Error<M>> {
+        self.send_from(Origin::External, msg).await
+    }
+
+    /// Sends `msg` and waits for the reply it carries the sender of to arrive on `reply`.
+    ///
+    /// The message is not returned on failure: it holds the reply sender, which is of no use
+    /// to the caller.
+    pub(crate) async fn request<R>(&self, msg: M, reply: oneshot::Receiver<R>) -> Result<R, Error> {
+        self.send(msg).await.map_err(|err| match err {
+            Error::MailboxClosed { .. } => Error::closed(self.name(), None),
+            err => err.without_message(),
+        })?;
+        reply.await.map_err(|_| Error::ReplyDropped {
+            actor: self.name().to_owned(),
+        })
+    }
+
+    /// Like [`send`](Self::send), returning a [`Confirmation`] that resolves once the actor's
+    /// handler for the message has returned, e.g. to know an update has been applied without
+    /// adding a reply channel to the message.
+    ///
+    /// The confirmation fails with [`NotHandled`] if the message is dropped, expires, is
+    /// removed from the queue or turned away by middleware, if its handler panics or times
+    /// out, or if the actor stops first.
+    pub async fn send_confirmed(&self, msg: M) -> Result<Confirmation, Error<M>> {
+        let (confirm, confirmation) = Confirm::new();
+        let mut envelope = Envelope::new(msg, Origin::External);
+        envelope.confirm = Some(confirm);
+        self.deliver(envelope).await?;
+        Ok(confirmation)
+    }
+
+    /// Like [`send`](Self::send), tagging the message with where it came from.
+    ///
+    /// The origin only matters to an actor spawned with
+    /// [`ActorConfig::fairness`](crate::ActorConfig::fairness) and to audit events.
+    pub async fn send_from(&self, origin: Origin, msg: M) -> Result<(), Error<M>> {
+        self.deliver(Envelope::new(msg, origin)).await
+    }
+
+    /// Like [`send`](Self::send), dropping the message unhandled if it is still waiting
+    /// once `ttl` has passed.
+    ///
+    /// Takes the place of the message's own [`Prioritized::ttl`]. The message counts as
+    /// expired if the actor has not started handling it by then; it is then dropped
+    /// without reaching the handler and counted in
+    /// [`ActorMetrics::messages_expired`](crate::ActorMetrics::messages_expired).
+    /// `Shutdown` messages never expire.
+    pub async fn send_with_ttl(&self, msg: M, ttl: Duration) -> Result<(), Error<M>> {
+        let mut envelope = Envelope::new(msg, Origin::External);
+        envelope.deadline = Instant::now().checked_add(ttl);
+        self.deliver(envelope).await
+    }
+
+    /// Like [`send`](Self::send), tagging the message with the correlation id `id`, e.g. to
+    /// follow one request through a pipeline of actors in distributed traces.
+    ///
+    /// The handler reads the id with
+    /// [`ActorContext::correlation_id`](crate::ActorContext::correlation_id), and every
+    /// message it sends, to this actor or any other, carries the id on unless sent with an id
+    /// of its own. With the `tracing` feature the id is recorded on the message's `handle`
+    /// span as `correlation_id`.
+    pub async fn send_traced(&self, msg: M, id: u64) -> Result<(), Error<M>> {
+        let mut envelope = Envelope::new(msg, Origin::External);
+        envelope.correlation = Some(id);
+        self.deliver(envelope).await
+    }
+
+    /// Like [`send`](Self::send), queueing the message at `priority` instead of its own, e.g.
+    /// for an admin-triggered message that is normally `Low`.
+    ///
+    /// The override also decides which share of a reserved mailbox the message may use.
+    /// Only a message whose own priority is `Shutdown` is handled as a shutdown: overriding
+    /// to [`Priority::Shutdown`] queues the message at [`Priority::High`], and a `Shutdown`
+    /// message keeps its priority whatever the override.
+    pub async fn send_with_priority(&self, msg: M, priority: Priority) -> Result<(), Error<M>> {
+        let envelope = Envelope::new(msg, Origin::External).with_priority(priority);
+        self.deliver(envelope).await
+    }
+
+    /// Like [`send`](Self::send), failing with [`Error::MailboxFull`] instead of sending
+    /// while the queue, or the message's priority [quota](RuntimeConfig::quotas), is at its
+    /// limit under [`OverflowPolicy::Reject`].
+    ///
+    /// The check races other senders: a message that gets past it but finds the queue full
+    /// by the time it is queued is dropped. Under any other policy, or without a limit,
+    /// this only fails once the actor has stopped.
+    pub async fn offer(&self, msg: M) -> Result<(), Error<M>> {
+        let runtime = self.shared.runtime.load();
+        let metrics = &self.shared.metrics;
+        let priority = msg.priority();
+        let full = runtime.overflow == OverflowPolicy::Reject
+            && priority != Priority::Shutdown
+            && (runtime
+                .max_queued
+                .is_some_and(|limit| metrics.queue_len() >= limit)
+                || runtime
+                    .quota(priority)
+                    .is_some_and(|quota| metrics.priority_depth(priority) >= quota));
+        if full {
+            return Err(Error::full(self.name(), msg));
+        }
+        self.send(msg).await
+    }
+
+    /// Sends a message from a thread that is not running async code, blocking it until the
+    /// mailbox has room.
+    ///
+    /// Meant for plain threads and [`spawn_blocking`](tokio::task::spawn_blocking) tasks.
+    /// Calling it from async code blocks the runtime thread, and may deadlock it; use
+    /// [`send`](Self::send) there instead.
+    pub fn blocking_send(&self, msg: M) -> Result<(), Error<M>> {
+        block_on(self.send(msg))
+    }
+
+    /// Sends a prepared envelope on behalf of a caller, failing while the actor is not
+    /// ready for it.
+    pub(crate) async fn deliver(&self, envelope: Envelope<M>) -> Result<(), Error<M>> {
+        let envelope = self.check_ready(envelope)?;
+        self.send_envelope(envelope)
+            .await
+            .map_err(|err| self.rejected(err))
+    }
+
+    /// Sends a prepared envelope, handing it back if neither this actor nor one that
+    /// absorbed it takes it.
+    async fn send_envelope(&self, envelope: Envelope<M>) -> Result<(), TrySendError<Envelope<M>>> {
+        let envelope = match self.send_direct(envelope).await {
+            Err(TrySendError::Closed(envelope)) => envelope,
+            result => return result,
+        };
+        match self.shared.redirect.load_full() {
+            Some(absorber) => Box::pin(absorber.send_envelope(envelope)).await,
+            None => Err(TrySendError::Closed(envelope)),
+        }
+    }
+
+    async fn send_direct(
+        &self,
+        mut envelope: Envelope<M>,
+    ) -> Result<(), TrySendError<Envelope<M>>> {
+        if let Some(pool) = &self.shared.permits {
+            match pool.acquire(envelope.priority()).await {
+                Some(permit) => envelope.permit = Some(permit),
+                None => return Err(TrySendError::Closed(envelope)),
+            }
+        }
+        envelope.pending = Some(self.shared.pending.track());
+        self.tx.push(envelope).map_err(untracked)
+    }
+
+    /// Sends a message only if the mailbox has room right now, without waiting; for
+    /// synchronous code such as callbacks.
+    ///
+    /// Fails with [`Error::MailboxFull`] while the mailbox is at capacity, or while the
+    /// message would eat into capacity reserved for higher priorities, and with
+    /// [`Error::MailboxClosed`] once the actor has stopped. Either way the message is
+    /// handed back and never entered the mailbox, so everything that did keeps its order.
+    pub fn try_send(&self, msg: M) -> Result<(), Error<M>> {
+        self.try_send_from(Origin::External, msg)
+    }
+
+    /// Like [`try_send`](Self::try_send), tagging the message with where it came from.
+    pub fn try_send_from(&self, origin: Origin, msg: M) -> Result<(), Error<M>> {
+        let envelope = self.check_ready(Envelope::new(msg, origin))?;
+        self.try_send_envelope(envelope)
+            .map_err(|err| self.rejected(err))
+    }
+
+    /// Hands `envelope` back unless the actor rejects it for not being ready yet.
+    pub(crate) fn check_ready(&self, envelope: Envelope<M>) -> Result<Envelope<M>, Error<M>> {
+        if self.shared.ready.rejects(envelope.priority()) {
+            return Err(Error::not_ready(self.name(), envelope.msg));
+        }
+        Ok(envelope)
+    }
+
+    /// The error for a message the mailbox turned away.
+    fn rejected(&self, err: TrySendError<Envelope<M>>) -> Error<M> {
+        match err {
+            TrySendError::Full(envelope) => Error::full(self.name(), envelope.msg),
+            TrySendError::Closed(envelope) => Error::closed(self.name(), Some(envelope.msg)),
+        }
+    }
+
+    pub(crate) fn try_send_envelope(
+        &self,
+        envelope: Envelope<M>,
+    ) -> Result<(), TrySendError<Envelope<M>>> {
+        let envelope = match self.try_send_direct(envelope) {
+            Err(TrySendError::Closed(envelope)) => envelope,
+            result => return result,
+        };
+        match self.shared.redirect.load_full() {
+            Some(absorber) => absorber.try_send_envelope(envelope),
+            None => Err(TrySendError::Closed(envelope)),
+        }
+    }
+
+    fn try_send_direct(&self, mut envelope: Envelope<M>) -> Result<(), TrySendError<Envelope<M>>> {
+        if let Some(pool) = &self.shared.permits {
+            match pool.try_acquire(envelope.priority()) {
+                Ok(permit) => envelope.permit = Some(permit),
+                Err(TryAcquireError::NoPermits) => return Err(TrySendError::Full(envelope)),
+                Err(TryAcquireError::Closed) => return Err(TrySendError::Closed(envelope)),
+            }
+        }
+        envelope.pending = Some(self.shared.pending.track());
+        self.tx.push(envelope).map_err(untracked)
+    }
+
+    /// Changes the actor's [`RuntimeConfig`] without restarting it.
+    ///
+    /// `update` edits a copy of the current configuration; the result is validated and swapped
+    /// in atomically, so concurrent callers never lose each other's changes. The request
+    /// bypasses the mailbox, so it goes through even while the mailbox is full, and it is in
+    /// effect for every message sent after this returns.
+    pub async fn reconfigure<F>(&self, update: F) -> Result<(), ReconfigureError>
+    where
+        F: FnOnce(&mut RuntimeConfig) + Send + 'static,
+    {
+        self.tx.reconfigure(Box::new(update))
+    }
+
+    /// Removes every waiting message for which `predicate` returns `true`, e.g. the render
+    /// requests for a document that was just closed, returning how many were removed.
+    ///
+    /// Removed messages are dropped without being handled or counted as dropped; their
+    /// mailbox slots free up right away. The message being handled is unaffected, as are
+    /// messages its handler sends the actor itself, which are queued once it returns.
+    /// `Shutdown` messages are never removed, and the messages left keep their order.
+    ///
+    /// `predicate` runs with the mailbox locked, so it must not send to this actor.
+    pub fn cancel_pending(&self, mut predicate: impl FnMut(&M) -> bool) -> usize {
+        self.extract(&mut |envelope| predicate(&envelope.msg)).len()
+    }
+
+    /// Runs `inspect` on the messages waiting in the queue, by reference, and returns what
+    /// it returns, e.g. to list them by
+    /// [`variant_name`](crate::Prioritized::variant_name) on a debug endpoint. Nothing is
+    /// removed.
+    ///
+    /// The messages come highest priority first and in arrival order within a priority,
+    /// the order the default scheduling handles them in. Coalesced messages show up once,
+    /// with their latest contents, and expired ones not at all. The message being handled
+    /// is not among them.
+    ///
+    /// `inspect` runs with the mailbox locked, holding up every sender and the actor
+    /// meanwhile, so it must be cheap, and must not send to this actor or inspect it again.
+    pub fn inspect_pending<R>(&self, inspect: impl FnOnce(&[&M]) -> R) -> R {
+        match self.shared.redirect.load_full() {
+            Some(absorber) => absorber.inspect_pending(inspect),
+            None => self.tx.inspect(inspect),
+        }
+    }
+
+    /// Drops every waiting message with a priority below `below`, e.g. to shed load,
+    /// returning how many were dropped.
+    ///
+    /// Goes by the priority a message was queued at. Purged messages go to the actor's
+    /// [dead-letter sink](crate::ActorConfig::dead_letters), if it has one, in the order
+    /// they arrived; otherwise they are dropped unhandled. The message being handled is
+    /// unaffected, and `Shutdown` messages are never purged.
+    pub fn purge(&self, below: Priority) -> usize {
+        self.purge_where(&mut |envelope| envelope.priority() < below)
+    }
+
+    /// Like [`purge`](Self::purge), for every waiting message but `Shutdown` ones.
+    pub fn purge_all(&self) -> usize {
+        self.purge_where(&mut |_| true)
+    }
+
+    fn purge_where(&self, pick: &mut dyn FnMut(&Envelope<M>) -> bool) -> usize {
+        let purged = self.extract(pick);
+        let count = purged.len();
+        if let Some(dead_letters) = &self.shared.dead_letters {
+            for envelope in purged {
+                dead_letters.deliver(envelope.msg);
+            }
+        }
+        count
+    }
+
+    /// Takes the waiting messages `pick` picks, from this actor and whichever absorbed it.
+    fn extract(&self, pick: &mut dyn FnMut(&Envelope<M>) -> bool) -> Vec<Envelope<M>> {
+        let mut extracted = self.tx.extract(pick);
+        if let Some(absorber) = self.shared.redirect.load_full() {
+            extracted.extend(absorber.extract(pick));
+        }
+        for envelope in &mut extracted {
+            if let Some(confirm) = envelope.confirm.take() {
+                confirm.send(Err(NotHandled::Removed));
+            }
+        }
+        extracted
+    }
+
+    /// What the runtime has dropped without handling, per drop reason and variant.
+    ///
+    /// Empty unless the actor was spawned with
+    /// [`ActorConfig::drop_exemplars`](crate::ActorConfig::drop_exemplars). Kept messages
+    /// are cloned; use [`take_drop_exemplars`](Self::take_drop_exemplars) for messages that
+    /// are not `Clone`.
+    pub fn drop_exemplars(&self) -> Vec<DropExemplars<M>>
+    where
+        M: Clone,
+    {
+        self.shared
+            .drops
+            .as_ref()
+            .map(|drops| drops.snapshot())
+            .unwrap_or_default()
+    }
+
+    /// Like [`drop_exemplars`](Self::drop_exemplars), handing the retained exemplars over
+    /// instead of copying them. Drop counts keep accumulating.
+    pub fn take_drop_exemplars(&self) -> Vec<DropExemplars<M>> {
+        self.shared
+            .drops
+            .as_ref()
+            .map(|drops| drops.take())
+            .unwrap_or_default()
+    }
+
+    /// Watches the pressure on the actor's queue, as set by
+    /// [`ActorConfig::watermarks`](crate::ActorConfig::watermarks).
+    ///
+    /// Without watermarks the pressure stays [`MailboxPressure::Normal`] and
+    /// [`changed`](watch::Receiver::changed) fails right away.
+    pub fn pressure(&self) -> watch::Receiver<MailboxPressure> {
+        self.shared.pressure.clone()
+    }
+
+    /// Returns `true` once the actor's mailbox no longer accepts messages.
+    ///
+    /// A handle to an absorbed actor reports on the actor that absorbed it.
+    pub fn is_closed(&self) -> bool {
+        !self.tx.is_open()
+            && self
+                .shared
+                .redirect
+                .load()
+                .as_ref()
+                .is_none_or(|absorber| absorber.is_closed())
+    }
+
+    /// Waits until the actor is ready: until the future given to
+    /// [`spawn_actor_init`](crate::spawn_actor_init), if any, and its `on_start` hook have
+    /// completed. Resolves right away if it already is.
+    ///
+    /// Fails with [`Error::MailboxClosed`] if the actor stops before it is ready.
+    pub async fn ready(&self) -> Result<(), Error> {
+        if self.shared.ready.wait().await {
+            Ok(())
+        } else {
+            Err(Error::closed(self.name(), None))
+        }
+    }
+
+    /// Waits until the actor is idle: nothing in its mailbox or queue, and no handler
+    /// running.
+    ///
+    /// The actor was idle at some point after the call; messages sent concurrently, by
+    /// anyone including the actor itself, may already keep it busy again by the time this
+    /// returns. Resolves right away once the actor has stopped and dropped what it had left.
+    pub async fn await_idle(&self) {
+        self.shared.pending.wait_idle().await
+    }
+
+    /// Stops the actor gracefully, giving up after `deadline`.
+    ///
+    /// The mailbox closes right away, so sends through any handle fail from then on, and
+    /// the actor handles what was already queued, highest priority first, then stops with
+    /// [`TerminationReason::Drained`]. If it is still at it once `deadline` has passed, it is
+    /// stopped there and then: a running handler is cancelled at whatever `.await` it is
+    /// waiting on, `on_stop` is skipped, and the messages left go to the dead-letter sink,
+    /// if there is one. The actor then reports
+    /// [`TerminationReason::DeadlineExceeded`].
+    pub async fn shutdown_with_timeout(&self, deadline: Duration) -> ShutdownOutcome {
+        self.tx.drain();
+        let reason = match tokio::time::timeout(deadline, self.shared.termination()).await {
+            Ok(reason) => reason,
+            Err(_elapsed) => {
+                self.shared.force_stop.send_replace(true);
+                self.shared.termination().await
+            }
+        };
+        match reason {
+            TerminationReason::DeadlineExceeded { mid_handler: true } => {
+                ShutdownOutcome::TimedOutInHandler
+            }
+            TerminationReason::DeadlineExceeded { mid_handler: false } => {
+                ShutdownOutcome::TimedOutInQueue
+            }
+            _ => ShutdownOutcome::Completed,
+        }
+    }
+
+    /// Waits for the actor to stop and reports why it stopped.
+    ///
+    /// Consumes the handle so it no longer keeps the actor alive: the actor stops on an
+    /// explicit shutdown message, or implicitly once every other handle is gone and the
+    /// queue has drained.
+    pub async fn join(self) -> TerminationReason {
+        let stopped = self.shared.termination();
+        drop(self);
+        stopped.await
+    }
+
+    /// Waits for the actor to stop, like [`join`](Self::join), and reports what it left
+    /// behind: the messages still queued, by priority, and how many it handled.
+    ///
+    /// Send the `Shutdown` message first to learn what it abandoned. The report is empty
+    /// if the processor task ended without tearing down, i.e. on
+    /// [`TerminationReason::Aborted`].
+    pub async fn join_with_report(self) -> (TerminationReason, ShutdownReport) {
+        let shared = Arc::clone(&self.shared);
+        let reason = self.join().await;
+        let report = shared.report.get().cloned().unwrap_or_default();
+        (reason, report)
+    }
+}
+
+/// Stops counting an envelope the mailbox turned away as pending.
+fn untracked<M>(mut err: TrySendError<Envelope<M>>) -> TrySendError<Envelope<M>> {
+    let (TrySendError::Full(envelope) | TrySendError::Closed(envelope)) = &mut err;
+    envelope.pending = None;
+    err
+}
+
+/// Runs `future` to completion on the current thread, parking it while the future waits.
+fn block_on<F: Future>(future: F) -> F::Output {
+    struct Unpark(std::thread::Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(Unpark(std::thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => std::thread::park(),
+        }
+    }
+}
+
+/// A handle that does not keep the actor alive, created by [`ActorHandle::downgrade`].
+///
+/// Weak handles do not count as senders: once only weak handles are left, the actor stops
+/// as if every handle had been dropped.
+pub struct WeakHandle<M> {
+    tx: WeakMailboxSender<M>,
+    shared: Arc<Shared<M>>,
+}
+
+impl<M> Clone for WeakHandle<M> {
+    fn clone(&self) -> Self {
+        WeakHandle {
+            tx: self.tx.clone(),
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<M> WeakHandle<M> {
+    /// Returns a strong handle, or `None` once every strong handle is gone.
+    ///
+    /// The returned handle keeps the actor alive like any other. An actor that stopped
+    /// on its own, e.g. after a `Shutdown` message, may still be reached through a handle
+    /// upgraded while others exist; sends to it fail as usual.
+    pub fn upgrade(&self) -> Option<ActorHandle<M>> {
+        Some(ActorHandle {
+            tx: self.tx.upgrade()?,
+            shared: Arc::clone(&self.shared),
+            #[cfg(feature = "futures")]
+            in_flight: Default::default(),
+        })
+    }
+
+    /// Resolves with the reason the actor stopped, as
+    /// [`ActorHandle::terminated`] does, even once every strong handle is gone.
+    pub fn terminated(&self) -> impl Future<Output = TerminationReason> + Send + 'static {
+        self.shared.termination()
+    }
+}
+
+/// A spawned actor together with ownership of its state, returned by
+/// [`spawn_actor_owned`](crate::spawn_actor_owned).
+///
+/// Dereferences to the actor's [`ActorHandle`], so messages are sent through it directly.
+/// Unlike the handle it cannot be cloned; hand out clones of [`handle`](Self::handle) instead.
+pub struct SpawnedActor<A: Actor> {
+    handle: ActorHandle<A::Msg>,
+    processor: JoinHandle<A>,
+}
+
+impl<A: Actor> SpawnedActor<A> {
+    pub(crate) fn new(handle: ActorHandle<A::Msg>, processor: JoinHandle<A>) -> Self {
+        SpawnedActor { handle, processor }
+    }
+
+    /// The actor's handle.
+    pub fn handle(&self) -> &ActorHandle<A::Msg> {
+        &self.handle
+    }
+
+    /// Gives up ownership of the actor's state, keeping only its handle.
+    pub fn into_handle(self) -> ActorHandle<A::Msg> {
+        self.handle
+    }
+
+    /// Waits for the actor to stop and returns its final state.
+    ///
+    /// Drops this handle first, exactly like [`ActorHandle::join`]. Returns `None` if the
+    /// actor did not stop cleanly, e.g. because a handler panicked.
+    pub async fn join(self) -> Option<A> {
+        drop(self.handle);
+        self.processor.await.ok()
+    }
+}
+
+impl<A: Actor> Deref for SpawnedActor<A> {
+    type Target = ActorHandle<A::Msg>;
+
+    fn deref(&self) -> &ActorHandle<A::Msg> {
+        &self.handle
+    }
+}
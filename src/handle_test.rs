@@ -0,0 +1,690 @@
+use crate::prelude::*;
+use crate::test_support::{stall, Gated, GatedMsg};
+use crate::{DropReason, NotHandled, ShutdownOutcome};
+use tokio::sync::oneshot;
+use tokio::time::{timeout, Duration};
+
+define_actor! {
+    Fragile {}
+
+    impl FragileMsg {
+        @priority(Medium)
+        fn Explode(&mut self) {
+            panic!("handler blew up");
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_join_reports_shutdown_message() {
+    let handle = spawn_actor(Gated::new());
+    handle.send(GatedMsg::Fill()).await.unwrap();
+    handle.send(GatedMsg::Shutdown).await.unwrap();
+
+    let reason = timeout(Duration::from_secs(1), handle.join())
+        .await
+        .unwrap();
+    assert_eq!(reason, TerminationReason::ShutdownMessage);
+}
+
+#[tokio::test]
+async fn test_join_with_report_counts_the_messages_a_shutdown_abandons() {
+    let handle = spawn_actor(Gated::new());
+    let release = stall(&handle).await;
+    for _ in 0..7 {
+        handle.send(GatedMsg::Fill()).await.unwrap();
+    }
+    for _ in 0..3 {
+        handle.send(GatedMsg::Routine()).await.unwrap();
+    }
+    handle.send(GatedMsg::Shutdown).await.unwrap();
+    release.send(()).unwrap();
+
+    let (reason, report) = timeout(Duration::from_secs(1), handle.join_with_report())
+        .await
+        .unwrap();
+    assert_eq!(reason, TerminationReason::ShutdownMessage);
+    assert_eq!(report.total_pending(), 10);
+    assert_eq!(
+        report.pending.into_iter().collect::<Vec<_>>(),
+        [(Priority::Low, 7), (Priority::Medium, 3)]
+    );
+    assert_eq!(report.handled, 2);
+}
+
+#[tokio::test]
+async fn test_join_with_report_after_draining_leaves_nothing_pending() {
+    let handle = spawn_actor(Gated::new());
+    handle.send(GatedMsg::Fill()).await.unwrap();
+    handle.send(GatedMsg::Routine()).await.unwrap();
+
+    let (reason, report) = handle.join_with_report().await;
+    assert_eq!(reason, TerminationReason::SendersDropped);
+    assert_eq!(report.total_pending(), 0);
+    assert_eq!(report.handled, 2);
+}
+
+#[tokio::test]
+async fn test_confirmations_say_why_a_message_was_not_handled() {
+    let config = ActorConfig::new().shed_threshold(3);
+    let handle = spawn_actor_with(Gated::new(), config);
+    let release = stall(&handle).await;
+    let fill = handle.send_confirmed(GatedMsg::Fill()).await.unwrap();
+    let routine = handle.send_confirmed(GatedMsg::Routine()).await.unwrap();
+    let urgent = handle.send_confirmed(GatedMsg::Urgent()).await.unwrap();
+    let shed = handle.send_confirmed(GatedMsg::Urgent()).await.unwrap();
+    assert_eq!(shed.await, Err(NotHandled::Dropped(DropReason::Shed)));
+
+    assert_eq!(handle.purge(Priority::Medium), 1);
+    assert_eq!(fill.await, Err(NotHandled::Removed));
+    handle.send(GatedMsg::Shutdown).await.unwrap();
+    release.send(()).unwrap();
+    assert_eq!(routine.await, Err(NotHandled::Stopped));
+    assert_eq!(urgent.await, Err(NotHandled::Stopped));
+}
+
+#[tokio::test]
+async fn test_confirmation_resolves_once_the_handler_returns() {
+    let handle = spawn_actor(Gated::new());
+    let release = stall(&handle).await;
+    let mut urgent = handle.send_confirmed(GatedMsg::Urgent()).await.unwrap();
+    tokio::task::yield_now().await;
+    assert!(futures::poll!(&mut urgent).is_pending());
+
+    release.send(()).unwrap();
+    assert_eq!(urgent.await, Ok(()));
+}
+
+#[tokio::test]
+async fn test_join_reports_senders_dropped() {
+    let handle = spawn_actor(Gated::new());
+    let other = handle.clone();
+
+    // Another handle still keeps the actor alive.
+    let join = tokio::spawn(handle.join());
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!join.is_finished());
+
+    drop(other);
+    let reason = timeout(Duration::from_secs(1), join)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(reason, TerminationReason::SendersDropped);
+}
+
+#[tokio::test]
+async fn test_sends_fail_after_shutdown() {
+    let handle = spawn_actor(Gated::new());
+    let observer = handle.clone();
+    handle.send(GatedMsg::Shutdown).await.unwrap();
+
+    let joined = tokio::spawn(handle.join());
+    assert_eq!(joined.await.unwrap(), TerminationReason::ShutdownMessage);
+    timeout(Duration::from_secs(1), async {
+        while !observer.is_closed() {
+            tokio::task::yield_now().await;
+        }
+    })
+    .await
+    .expect("mailbox should close once the processor stops");
+    assert!(observer.send(GatedMsg::Fill()).await.is_err());
+}
+
+#[tokio::test]
+async fn test_join_reports_aborted_after_handler_panic() {
+    let handle = spawn_actor(Fragile {});
+    handle.send(FragileMsg::Explode()).await.unwrap();
+
+    let reason = timeout(Duration::from_secs(1), handle.join())
+        .await
+        .unwrap();
+    assert_eq!(reason, TerminationReason::Aborted);
+}
+
+#[tokio::test]
+async fn test_every_observer_learns_of_a_shutdown_message() {
+    let handle = spawn_actor(Gated::new());
+    let first = tokio::spawn(handle.terminated());
+    let second = tokio::spawn(handle.terminated());
+    handle.send(GatedMsg::Shutdown).await.unwrap();
+
+    assert_eq!(first.await.unwrap(), TerminationReason::ShutdownMessage);
+    assert_eq!(second.await.unwrap(), TerminationReason::ShutdownMessage);
+    // The handle is still usable, and a late observer gets the same reason.
+    assert!(handle.is_closed());
+    assert_eq!(
+        handle.terminated().await,
+        TerminationReason::ShutdownMessage
+    );
+}
+
+#[tokio::test]
+async fn test_terminated_does_not_keep_the_actor_alive() {
+    let handle = spawn_actor(Gated::new());
+    let weak = handle.downgrade();
+    let terminated = handle.terminated();
+    drop(handle);
+
+    let reason = timeout(Duration::from_secs(1), terminated).await.unwrap();
+    assert_eq!(reason, TerminationReason::SendersDropped);
+    assert_eq!(weak.terminated().await, TerminationReason::SendersDropped);
+}
+
+#[tokio::test]
+async fn test_terminated_reports_an_unsupervised_panic_as_aborted() {
+    let handle = spawn_actor(Fragile {});
+    let terminated = handle.terminated();
+    handle.send(FragileMsg::Explode()).await.unwrap();
+
+    let reason = timeout(Duration::from_secs(1), terminated).await.unwrap();
+    assert_eq!(reason, TerminationReason::Aborted);
+}
+
+#[tokio::test]
+async fn test_processor_stops_when_sender_dropped_immediately() {
+    let handle = spawn_actor(Gated::new());
+
+    let reason = timeout(Duration::from_secs(1), handle.join())
+        .await
+        .expect("processor should stop without ever receiving a message");
+    assert_eq!(reason, TerminationReason::SendersDropped);
+}
+
+#[tokio::test]
+async fn test_processor_parked_on_empty_queue_stops_when_senders_drop() {
+    let handle = spawn_actor(Gated::new());
+    // Let the processor find the queue empty and park on its notification.
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    let reason = timeout(Duration::from_secs(1), handle.join())
+        .await
+        .expect("parked processor should be woken when the senders drop");
+    assert_eq!(reason, TerminationReason::SendersDropped);
+}
+
+#[tokio::test]
+async fn test_owned_join_waits_for_other_handles() {
+    let actor = spawn_actor_owned(Gated::new(), ActorConfig::new());
+    let other = actor.handle().clone();
+    other.send(GatedMsg::Urgent()).await.unwrap();
+
+    let join = tokio::spawn(actor.join());
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!join.is_finished());
+
+    drop(other);
+    let gated = timeout(Duration::from_secs(1), join)
+        .await
+        .unwrap()
+        .unwrap()
+        .expect("actor stopped cleanly");
+    assert_eq!(gated.handled, vec![Priority::High]);
+}
+
+#[tokio::test]
+async fn test_owned_join_returns_none_after_handler_panic() {
+    let actor = spawn_actor_owned(Fragile {}, ActorConfig::new());
+    actor.send(FragileMsg::Explode()).await.unwrap();
+
+    let state = timeout(Duration::from_secs(1), actor.join()).await.unwrap();
+    assert!(state.is_none());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_messages_past_their_ttl_are_dropped_unhandled() {
+    let handle = spawn_actor_with(Gated::new(), ActorConfig::new().drop_exemplars(0));
+    let release = stall(&handle).await;
+    for _ in 0..2 {
+        handle
+            .send_with_ttl(GatedMsg::Urgent(), Duration::from_millis(100))
+            .await
+            .unwrap();
+    }
+    handle.send(GatedMsg::Fill()).await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    drop(release);
+    handle.await_idle().await;
+    let (tx, rx) = oneshot::channel();
+    handle.send(GatedMsg::Report(tx)).await.unwrap();
+    assert_eq!(rx.await.unwrap(), [Priority::Low]);
+
+    let drops = handle.take_drop_exemplars();
+    assert_eq!(drops.len(), 1);
+    assert_eq!(drops[0].reason, DropReason::Expired);
+    assert_eq!(drops[0].variant, "Urgent");
+    assert_eq!(drops[0].dropped, 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_messages_within_their_ttl_are_handled() {
+    let handle = spawn_actor(Gated::new());
+    let release = stall(&handle).await;
+    handle
+        .send_with_ttl(GatedMsg::Fill(), Duration::from_millis(100))
+        .await
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    drop(release);
+    handle.await_idle().await;
+    let (tx, rx) = oneshot::channel();
+    handle.send(GatedMsg::Report(tx)).await.unwrap();
+    assert_eq!(rx.await.unwrap(), [Priority::Low]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_shutdown_never_expires() {
+    let handle = spawn_actor(Gated::new());
+    let release = stall(&handle).await;
+    handle
+        .send_with_ttl(GatedMsg::Shutdown, Duration::from_millis(1))
+        .await
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    drop(release);
+    assert_eq!(handle.join().await, TerminationReason::ShutdownMessage);
+}
+
+/// Quotes are only worth handling for 100ms.
+#[derive(Debug)]
+enum QuoteMsg {
+    Quote(u32),
+    Latest(oneshot::Sender<Vec<u32>>),
+}
+
+impl Prioritized for QuoteMsg {
+    fn ttl(&self) -> Option<Duration> {
+        match self {
+            QuoteMsg::Quote(_) => Some(Duration::from_millis(100)),
+            QuoteMsg::Latest(_) => None,
+        }
+    }
+}
+
+struct Quotes(Vec<u32>);
+
+#[async_trait::async_trait]
+impl Actor for Quotes {
+    type Msg = QuoteMsg;
+
+    async fn handle(
+        &mut self,
+        msg: QuoteMsg,
+        _ctx: &ActorContext<QuoteMsg>,
+    ) -> ControlFlow<ShutdownReason> {
+        match msg {
+            // Handling a quote takes a while, so the ones queued behind it go stale.
+            QuoteMsg::Quote(price) => {
+                self.0.push(price);
+                tokio::time::sleep(Duration::from_millis(60)).await;
+            }
+            QuoteMsg::Latest(tx) => {
+                let _ = tx.send(self.0.clone());
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_message_ttl_comes_from_prioritized() {
+    let handle = spawn_actor(Quotes(Vec::new()));
+    for price in 1..=4 {
+        handle.send(QuoteMsg::Quote(price)).await.unwrap();
+    }
+    let (tx, rx) = oneshot::channel();
+    handle.send(QuoteMsg::Latest(tx)).await.unwrap();
+    // Quotes 1 and 2 are handled by 60ms; by 120ms the rest have gone stale.
+    assert_eq!(rx.await.unwrap(), [1, 2]);
+}
+
+#[tokio::test]
+async fn test_try_send_fails_while_full_and_succeeds_once_drained() {
+    let actor = spawn_actor_owned(Gated::new(), ActorConfig::new().capacity(2));
+    let handle = actor.handle();
+    let release = stall(handle).await;
+    handle.try_send(GatedMsg::Fill()).unwrap();
+    handle.try_send(GatedMsg::Fill()).unwrap();
+    assert!(matches!(
+        handle.try_send(GatedMsg::Fill()),
+        Err(Error::MailboxFull {
+            returned: GatedMsg::Fill(),
+            ..
+        })
+    ));
+
+    drop(release);
+    let (tx, rx) = oneshot::channel();
+    handle.send(GatedMsg::Report(tx)).await.unwrap();
+    rx.await.unwrap();
+    handle.try_send(GatedMsg::Fill()).unwrap();
+
+    let handled = actor.join().await.unwrap().handled.clone();
+    assert_eq!(handled, [Priority::Low; 3]);
+}
+
+#[tokio::test]
+async fn test_try_send_fails_once_closed() {
+    let handle = spawn_actor(Fragile {});
+    handle.send(FragileMsg::Explode()).await.unwrap();
+    while !handle.is_closed() {
+        tokio::task::yield_now().await;
+    }
+    assert!(matches!(
+        handle.try_send(FragileMsg::Explode()),
+        Err(Error::MailboxClosed {
+            returned: Some(FragileMsg::Explode()),
+            ..
+        })
+    ));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_blocking_send_waits_for_room_from_a_plain_thread() {
+    let handle = spawn_actor_with(Gated::new(), ActorConfig::new().capacity(1));
+    let release = stall(&handle).await;
+    handle.send(GatedMsg::Fill()).await.unwrap();
+
+    let sender = handle.clone();
+    let thread = std::thread::spawn(move || sender.blocking_send(GatedMsg::Fill()));
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(!thread.is_finished());
+
+    drop(release);
+    tokio::task::spawn_blocking(move || thread.join().unwrap())
+        .await
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_await_idle_waits_for_every_message_to_be_handled() {
+    let handle = spawn_actor(Gated::new());
+    for _ in 0..100 {
+        handle.send(GatedMsg::Fill()).await.unwrap();
+    }
+    handle.await_idle().await;
+
+    // `Report` outranks `Fill`, so it would jump any still waiting.
+    let (tx, rx) = oneshot::channel();
+    handle.send(GatedMsg::Report(tx)).await.unwrap();
+    assert_eq!(rx.await.unwrap(), [Priority::Low; 100]);
+}
+
+#[tokio::test]
+async fn test_await_idle_waits_for_a_running_handler() {
+    let handle = spawn_actor(Gated::new());
+    let release = stall(&handle).await;
+    let idle = tokio::spawn({
+        let handle = handle.clone();
+        async move { handle.await_idle().await }
+    });
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(!idle.is_finished());
+
+    drop(release);
+    timeout(Duration::from_secs(1), idle)
+        .await
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_send_with_priority_overrides_the_messages_own() {
+    let handle = spawn_actor(Gated::new());
+    let release = stall(&handle).await;
+    handle.send(GatedMsg::Routine()).await.unwrap();
+    handle.send(GatedMsg::Routine()).await.unwrap();
+    handle
+        .send_with_priority(GatedMsg::Fill(), Priority::High)
+        .await
+        .unwrap();
+
+    // Let the mailbox hand everything to the queue before the handler returns.
+    tokio::time::sleep(Duration::from_millis(1)).await;
+    drop(release);
+    let (tx, rx) = oneshot::channel();
+    handle
+        .send_with_priority(GatedMsg::Report(tx), Priority::Low)
+        .await
+        .unwrap();
+    assert_eq!(
+        rx.await.unwrap(),
+        [Priority::Low, Priority::Medium, Priority::Medium]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_send_with_priority_clamps_shutdown_to_high() {
+    let handle = spawn_actor(Gated::new());
+    let release = stall(&handle).await;
+    handle.send(GatedMsg::Urgent()).await.unwrap();
+    handle
+        .send_with_priority(GatedMsg::Fill(), Priority::Shutdown)
+        .await
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(1)).await;
+    drop(release);
+    handle.await_idle().await;
+    let (tx, rx) = oneshot::channel();
+    handle.send(GatedMsg::Report(tx)).await.unwrap();
+    assert_eq!(rx.await.unwrap(), [Priority::High, Priority::Low]);
+}
+
+#[tokio::test]
+async fn test_weak_handles_do_not_keep_the_actor_alive() {
+    let handle = spawn_actor(Gated::new());
+    let weak = handle.downgrade();
+    let upgraded = weak.upgrade().expect("a strong handle is still around");
+    upgraded.send(GatedMsg::Fill()).await.unwrap();
+    drop(upgraded);
+
+    let reason = timeout(Duration::from_secs(1), handle.join())
+        .await
+        .unwrap();
+    assert_eq!(reason, TerminationReason::SendersDropped);
+    assert!(weak.upgrade().is_none());
+}
+
+define_actor! {
+    Renderer {
+        rendered: Vec<u32>,
+    }
+
+    impl RendererMsg {
+        @priority(Low)
+        fn Render(&mut self, doc: u32, frame: u32) {
+            let _ = doc;
+            self.rendered.push(frame);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_cancelled_messages_are_never_handled() {
+    let (handle, mailbox) = crate::channel(ActorConfig::new().capacity(20));
+    for frame in 0..20 {
+        handle
+            .send(RendererMsg::Render(frame % 2, frame))
+            .await
+            .unwrap();
+    }
+
+    let cancelled =
+        handle.cancel_pending(|msg| matches!(msg, RendererMsg::Render(doc, _) if *doc == 1));
+    assert_eq!(cancelled, 10);
+    // The cancelled messages gave their mailbox slots back.
+    for frame in 20..30 {
+        handle.try_send(RendererMsg::Render(0, frame)).unwrap();
+    }
+    drop(handle);
+
+    let renderer = crate::run_actor(
+        Renderer {
+            rendered: Vec::new(),
+        },
+        mailbox,
+    )
+    .await;
+    let expected: Vec<u32> = (0..20).step_by(2).chain(20..30).collect();
+    assert_eq!(renderer.rendered, expected);
+}
+
+#[tokio::test]
+async fn test_cancel_pending_spares_the_running_handler_and_shutdown() {
+    let handle = spawn_actor(Gated::new());
+    let release = stall(&handle).await;
+    handle.send(GatedMsg::Fill()).await.unwrap();
+    handle.send(GatedMsg::Shutdown).await.unwrap();
+
+    assert_eq!(handle.cancel_pending(|_| true), 1);
+    drop(release);
+    assert_eq!(handle.join().await, TerminationReason::ShutdownMessage);
+}
+
+#[tokio::test]
+async fn test_inspect_pending_lists_the_queue_in_priority_order() {
+    let handle = spawn_actor(Gated::new());
+    let release = stall(&handle).await;
+    for msg in [
+        GatedMsg::Fill(),
+        GatedMsg::Routine(),
+        GatedMsg::Urgent(),
+        GatedMsg::Fill(),
+        GatedMsg::Urgent(),
+    ] {
+        handle.send(msg).await.unwrap();
+    }
+
+    let names = handle.inspect_pending(|msgs| {
+        msgs.iter()
+            .map(|msg| msg.variant_name())
+            .collect::<Vec<_>>()
+    });
+    assert_eq!(names, ["Urgent", "Urgent", "Routine", "Fill", "Fill"]);
+
+    // Inspecting leaves the queue as it was.
+    assert_eq!(handle.inspect_pending(|msgs| msgs.len()), 5);
+    drop(release);
+    handle.await_idle().await;
+    assert_eq!(handle.inspect_pending(|msgs| msgs.len()), 0);
+    handle.send(GatedMsg::Shutdown).await.unwrap();
+    assert_eq!(handle.join().await, TerminationReason::ShutdownMessage);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_inspect_pending_leaves_out_expired_messages() {
+    let handle = spawn_actor(Gated::new());
+    let _release = stall(&handle).await;
+    handle
+        .send_with_ttl(GatedMsg::Urgent(), Duration::from_secs(1))
+        .await
+        .unwrap();
+    handle.send(GatedMsg::Fill()).await.unwrap();
+    assert_eq!(handle.inspect_pending(|msgs| msgs.len()), 2);
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    let names = handle.inspect_pending(|msgs| {
+        msgs.iter()
+            .map(|msg| msg.variant_name())
+            .collect::<Vec<_>>()
+    });
+    assert_eq!(names, ["Fill"]);
+}
+
+#[tokio::test]
+async fn test_purge_drops_lower_priorities_into_dead_letters() {
+    let (dead_tx, mut dead_rx) = tokio::sync::mpsc::unbounded_channel::<GatedMsg>();
+    let config = ActorConfig::new().dead_letters(crate::sink::Sink::unbounded(dead_tx));
+    let handle = spawn_actor_owned(Gated::new(), config);
+    let release = stall(&handle).await;
+    handle.send(GatedMsg::Fill()).await.unwrap();
+    handle.send(GatedMsg::Urgent()).await.unwrap();
+    handle.send(GatedMsg::Routine()).await.unwrap();
+    handle.send(GatedMsg::Fill()).await.unwrap();
+    handle.send(GatedMsg::Urgent()).await.unwrap();
+
+    assert_eq!(handle.purge(Priority::High), 3);
+    let mut purged = Vec::new();
+    while let Ok(msg) = dead_rx.try_recv() {
+        purged.push(msg.variant_name());
+    }
+    assert_eq!(purged, ["Fill", "Routine", "Fill"]);
+
+    drop(release);
+    handle.await_idle().await;
+    handle.send(GatedMsg::Shutdown).await.unwrap();
+    let gated = handle.join().await.expect("stopped cleanly");
+    assert_eq!(gated.handled, [Priority::High, Priority::High]);
+}
+
+#[tokio::test]
+async fn test_purge_all_keeps_shutdown_messages() {
+    let handle = spawn_actor_owned(Gated::new(), ActorConfig::new());
+    let release = stall(&handle).await;
+    handle.send(GatedMsg::Urgent()).await.unwrap();
+    handle.send(GatedMsg::Shutdown).await.unwrap();
+    handle.send(GatedMsg::Fill()).await.unwrap();
+
+    assert_eq!(handle.purge_all(), 2);
+    drop(release);
+    let gated = handle.join().await.expect("stopped cleanly");
+    assert!(gated.handled.is_empty());
+}
+
+#[tokio::test]
+async fn test_shutdown_with_timeout_drains_the_queue() {
+    let handle = spawn_actor_owned(Gated::new(), ActorConfig::new());
+    let release = stall(&handle).await;
+    handle.send(GatedMsg::Fill()).await.unwrap();
+    handle.send(GatedMsg::Urgent()).await.unwrap();
+    drop(release);
+
+    let outcome = handle.shutdown_with_timeout(Duration::from_secs(1)).await;
+    assert_eq!(outcome, ShutdownOutcome::Completed);
+    assert!(handle.send(GatedMsg::Fill()).await.is_err());
+    assert_eq!(handle.clone().join().await, TerminationReason::Drained);
+    let gated = handle.join().await.expect("stopped cleanly");
+    assert_eq!(gated.handled, [Priority::High, Priority::Low]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_shutdown_with_timeout_cancels_a_stalled_handler() {
+    let (dead_tx, mut dead_rx) = tokio::sync::mpsc::unbounded_channel::<GatedMsg>();
+    let config = ActorConfig::new().dead_letters(crate::sink::Sink::unbounded(dead_tx));
+    let stalled = spawn_actor_with(Gated::new(), config);
+    let _release = stall(&stalled).await;
+    stalled.send(GatedMsg::Fill()).await.unwrap();
+    stalled.send(GatedMsg::Routine()).await.unwrap();
+
+    let outcome = stalled
+        .shutdown_with_timeout(Duration::from_millis(100))
+        .await;
+    assert_eq!(outcome, ShutdownOutcome::TimedOutInHandler);
+    assert_eq!(
+        stalled.join().await,
+        TerminationReason::DeadlineExceeded { mid_handler: true }
+    );
+    let mut dead = Vec::new();
+    while let Ok(msg) = dead_rx.try_recv() {
+        dead.push(msg.variant_name());
+    }
+    assert_eq!(dead, ["Fill", "Routine"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_shutdown_with_timeout_between_rate_limited_messages() {
+    let handle = spawn_actor_with(Gated::new(), ActorConfig::new().rate_limit(1));
+    for _ in 0..3 {
+        handle.send(GatedMsg::Fill()).await.unwrap();
+    }
+
+    let outcome = handle
+        .shutdown_with_timeout(Duration::from_millis(1500))
+        .await;
+    assert_eq!(outcome, ShutdownOutcome::TimedOutInQueue);
+}
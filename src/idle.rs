@@ -0,0 +1,44 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Counts an actor's messages from the moment they enter its mailbox until their handler
+/// returns, so callers can wait for it to go idle.
+#[derive(Default)]
+pub(crate) struct Pending {
+    count: AtomicUsize,
+    idle: Notify,
+}
+
+impl Pending {
+    /// Counts one more message until the returned guard drops.
+    pub(crate) fn track(self: &Arc<Self>) -> PendingGuard {
+        self.count.fetch_add(1, Ordering::AcqRel);
+        PendingGuard(Arc::clone(self))
+    }
+
+    /// Waits until no message is counted.
+    pub(crate) async fn wait_idle(&self) {
+        loop {
+            let notified = self.idle.notified();
+            tokio::pin!(notified);
+            // Register before checking, so a drop to zero in between is not missed.
+            notified.as_mut().enable();
+            if self.count.load(Ordering::Acquire) == 0 {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Keeps a message counted by [`Pending`].
+pub(crate) struct PendingGuard(Arc<Pending>);
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        if self.0.count.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Implementation details that have to be public for technical reasons.
+//!
+//! Nothing in this module is covered by semver: items may change or disappear in any
+//! release. Prefer the types re-exported from [`prelude`](crate::prelude).
+
+use crate::{ActorHandle, Error, Prioritized};
+use std::cmp::Ordering;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+
+/// The reply channels `define_actor!` generates for handlers with a return type.
+pub use tokio::sync::oneshot;
+
+/// A queued item ordered by priority, then by arrival.
+///
+/// The second field is the sequence number assigned when the item entered the queue; among
+/// items of equal priority the lower (earlier) number compares greater, so a max-heap hands
+/// them out first-in, first-out.
+pub struct PrioritizedWrapper<T>(pub T, pub u64);
+
+impl<T: Prioritized> PartialEq for PrioritizedWrapper<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: Prioritized> Eq for PrioritizedWrapper<T> {}
+
+impl<T: Prioritized> PartialOrd for PrioritizedWrapper<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Prioritized> Ord for PrioritizedWrapper<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .priority()
+            .cmp(&other.0.priority())
+            .then_with(|| other.1.cmp(&self.1))
+    }
+}
+
+/// Called by the `Drop` impl that `define_actor!` generates for `@debug_drop` actors.
+pub fn actor_dropped(actor: &str) {
+    crate::log::diag!(trace, actor, "actor instance being dropped");
+}
+
+/// Runs a `@blocking` handler through [`tokio::task::block_in_place`], so the worker
+/// thread's other tasks move to another thread while it runs.
+///
+/// A current-thread runtime has no other thread to move them to, and `block_in_place`
+/// would panic there: the handler then runs inline, holding up everything else on the
+/// runtime, and a warning is logged the first time.
+pub fn block_in_place<R>(actor: &str, handler: impl FnOnce() -> R) -> R {
+    let flavor = tokio::runtime::Handle::try_current().map(|runtime| runtime.runtime_flavor());
+    if let Ok(tokio::runtime::RuntimeFlavor::MultiThread) = flavor {
+        return tokio::task::block_in_place(handler);
+    }
+    static WARNED: AtomicBool = AtomicBool::new(false);
+    if !WARNED.swap(true, AtomicOrdering::Relaxed) {
+        crate::log::diag!(
+            warn,
+            actor,
+            "`@blocking` handler runs inline: `block_in_place` needs a multi-threaded runtime"
+        );
+    }
+    handler()
+}
+
+/// Sends a request from a handle `define_actor!` generates and waits for the reply.
+pub async fn request<M, R>(
+    handle: &ActorHandle<M>,
+    msg: M,
+    reply: oneshot::Receiver<R>,
+) -> Result<R, Error>
+where
+    M: Prioritized + Send + 'static,
+{
+    handle.request(msg, reply).await
+}
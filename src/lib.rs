@@ -6,24 +6,319 @@
 //!
 #![doc = include_str!("../README.md")]
 
+use arc_swap::{ArcSwap, ArcSwapOption};
 use async_trait::async_trait;
-use std::cmp::Ordering;
-use std::collections::BinaryHeap;
-use std::sync::Arc;
-use tokio::sync::{mpsc, Mutex, Notify};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use tokio::runtime::Handle;
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+use tokio::time::{Duration, Instant};
 
-pub use priact_actor_macro::define_actor;
+pub use priact_actor_macro::{define_actor, Prioritized};
 
+// Lets `define_actor!` refer to `::priact` paths from inside this crate as well.
+extern crate self as priact;
+
+mod absorb;
+#[cfg(feature = "ask")]
+mod ask;
+mod audit;
+mod broadcast;
+mod cancel;
+mod checkpoint;
+mod concurrent;
+mod config;
+mod confirm;
+mod context;
+mod correlation;
+mod drive;
+mod erased;
+mod error;
+mod events;
+mod exemplar;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "cancellation")]
+mod group;
+mod handle;
+mod idle;
+pub mod internal;
+mod link;
+mod local;
+mod log;
+mod mailbox;
+mod metrics;
+mod middleware;
+mod permits;
+#[cfg(feature = "persistence")]
+mod persist;
+mod ping;
+#[cfg(feature = "futures")]
+mod pipe;
+pub mod prelude;
+mod pressure;
+mod queue;
+#[cfg(feature = "metrics")]
+mod rate;
+mod ready;
+mod receiver;
+mod registry;
+#[cfg(feature = "remote")]
+pub mod remote;
+mod runtime;
+mod schedule;
+pub mod sink;
+mod slow;
+mod state;
+mod supervise;
+mod tap;
+mod task;
+mod termination;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+mod throttle;
+
+pub use absorb::{AbsorbPlacement, AbsorbReport};
+#[cfg(feature = "ask")]
+pub use ask::AskExt;
+pub use audit::{AuditEvent, AuditTotals};
+pub use broadcast::{Broadcaster, Subscription};
+#[cfg(feature = "cancellation")]
+pub use cancel::{CancelMode, CancellationToken};
+pub use checkpoint::{spawn_actor_restored, Checkpoint, CheckpointEvery};
+pub use concurrent::{spawn_concurrent_actor, ConcurrentActor};
+pub use config::{ActorConfig, DEFAULT_CAPACITY, DEFAULT_EVENT_CAPACITY, DEFAULT_PING_TIMEOUT};
+pub use confirm::{Confirmation, NotHandled};
+pub use context::ActorContext;
+pub use erased::{erase, ErasedHandle, ErasedSendError};
+pub use error::Error;
+pub use events::EventEmitter;
+pub use exemplar::{DropExemplars, DropReason, DroppedMessage};
+#[cfg(feature = "cancellation")]
+pub use group::ActorGroup;
+pub use handle::{ActorHandle, SpawnedActor, WeakHandle};
+pub use local::{spawn_actor_local, spawn_actor_local_with, LocalActor};
+pub use mailbox::Origin;
+#[cfg(feature = "metrics")]
+pub use metrics::MetricsExt;
+pub use metrics::{ActorMetrics, HandlerTime, QueueLatency};
+pub use middleware::{InterceptDecision, Middleware};
+pub use permits::Reserved;
+#[cfg(feature = "persistence")]
+pub use persist::{spawn_actor_with_backlog, BacklogError, PersistentMessage};
+#[cfg(feature = "futures")]
+pub use pipe::spawn_stream_forwarder;
+pub use pressure::MailboxPressure;
+pub use queue::SchedulingPolicy;
+#[cfg(feature = "metrics")]
+pub use rate::{MessageRates, MAX_RATE_WINDOW};
+pub use ready::Readiness;
+pub use receiver::{priority_channel, PriorityReceiver, PrioritySender};
+pub use registry::{Registry, RegistryError};
+pub use runtime::{OverflowPolicy, ReconfigureError, RuntimeConfig};
+#[cfg(feature = "schedule")]
+pub use schedule::{MissedTicks, ScheduleExt, ScheduleGuard};
+pub use slow::SlowHandler;
+pub use supervise::RestartStrategy;
+#[cfg(feature = "tap")]
+pub use tap::{MsgSummary, TapExt, TapGuard};
+pub use termination::{ShutdownOutcome, ShutdownReason, ShutdownReport, TerminationReason};
+pub use throttle::RateLimitPolicy;
+pub use tokio::sync::mpsc::error::{SendError, TryRecvError, TrySendError};
+
+use cancel::CancelOn;
+use checkpoint::Checkpoints;
+use confirm::Confirm;
+use drive::Drive;
+use events::Events;
+use exemplar::DropSampler;
+use handle::Shared;
+use idle::{Pending, PendingGuard};
+use link::{Children, ChildrenGuard};
+use log::diag;
+use mailbox::{Admission, Envelope, Inbox, MailboxSender};
+use middleware::Middlewares;
+use permits::PermitPool;
+use pressure::Watermarks;
+use queue::MessageQueue;
+use ready::ReadyGate;
+use receiver::Received;
+use schedule::Schedules;
+use sink::Sink;
+use slow::SlowHandlers;
+use supervise::{CatchUnwind, Supervisor};
+use tap::Taps;
+use throttle::Throttle;
+
+/// Former home of [`internal::PrioritizedWrapper`].
+#[deprecated(
+    note = "moved to `priact::internal::PrioritizedWrapper`, which carries no stability guarantee"
+)]
+pub type PrioritizedWrapper<T> = internal::PrioritizedWrapper<T>;
+
+// Unused when every extension feature is disabled.
+#[allow(dead_code)]
+mod sealed {
+    /// Keeps the extension traits implementable only inside this crate, so adding methods
+    /// to them is not a breaking change.
+    pub trait Sealed {}
+
+    impl<M> Sealed for crate::ActorHandle<M> {}
+}
+
+#[cfg(test)]
+mod absorb_test;
+#[cfg(all(test, feature = "ask"))]
+mod ask_test;
+#[cfg(test)]
+mod audit_test;
+#[cfg(test)]
+mod broadcast_test;
+#[cfg(test)]
+mod checkpoint_test;
+#[cfg(test)]
+mod coalesce_test;
+#[cfg(test)]
+mod concurrent_test;
+#[cfg(test)]
+mod config_test;
+#[cfg(test)]
+mod context_test;
+#[cfg(test)]
+mod correlation_test;
+#[cfg(test)]
+mod derive_test;
+#[cfg(test)]
+mod erased_test;
+#[cfg(all(test, feature = "ask"))]
+mod error_test;
+#[cfg(test)]
+mod events_test;
+#[cfg(test)]
+mod exemplar_test;
+#[cfg(all(test, feature = "ffi"))]
+mod ffi_test;
+#[cfg(all(test, feature = "cancellation"))]
+mod group_test;
+#[cfg(test)]
+mod handle_test;
 #[cfg(test)]
 mod lib_test;
+#[cfg(test)]
+mod link_test;
+#[cfg(test)]
+mod local_test;
+#[cfg(all(test, feature = "metrics"))]
+mod metrics_test;
+#[cfg(test)]
+mod middleware_test;
+#[cfg(all(test, feature = "metrics"))]
+mod permits_test;
+#[cfg(all(test, feature = "persistence"))]
+mod persist_test;
+#[cfg(test)]
+mod ping_test;
+#[cfg(all(test, feature = "futures"))]
+mod pipe_test;
+#[cfg(all(test, feature = "metrics"))]
+mod prelude_test;
+#[cfg(test)]
+mod pressure_test;
+#[cfg(all(test, feature = "metrics"))]
+mod queue_test;
+#[cfg(all(test, feature = "metrics"))]
+mod rate_test;
+#[cfg(test)]
+mod ready_test;
+#[cfg(test)]
+mod receiver_test;
+#[cfg(test)]
+mod registry_test;
+#[cfg(all(test, feature = "remote", feature = "metrics"))]
+mod remote_test;
+#[cfg(all(test, feature = "metrics"))]
+mod runtime_test;
+#[cfg(all(test, feature = "schedule"))]
+mod schedule_test;
+#[cfg(test)]
+mod sink_test;
+#[cfg(test)]
+mod slow_test;
+#[cfg(test)]
+mod state_test;
+#[cfg(test)]
+mod supervise_test;
+#[cfg(all(test, feature = "tap"))]
+mod tap_test;
+#[cfg(test)]
+mod test_support;
+#[cfg(test)]
+mod testing_test;
+#[cfg(test)]
+mod throttle_test;
+#[cfg(all(test, feature = "tracing"))]
+mod tracing_test;
+
+/// How urgently a message should be handled; higher priorities are handled first.
+///
+/// Besides the named levels, [`Priority::Custom`] gives 256 numeric levels. The named ones
+/// sit on the same scale: `Low` is `Custom(0)`, `Medium` is `Custom(100)` and `High` is
+/// `Custom(200)`. [`Priority::Shutdown`] is above every numeric level.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Clone, Copy, Hash)]
+pub struct Priority(u16);
+
+#[allow(non_upper_case_globals)]
+impl Priority {
+    pub const Low: Priority = Priority(0);
+    pub const Medium: Priority = Priority(100);
+    pub const High: Priority = Priority(200);
+    /// Highest priority, reserved for shutdown messages.
+    pub const Shutdown: Priority = Priority(u8::MAX as u16 + 1);
+
+    /// A numeric priority level; larger levels are handled first.
+    #[allow(non_snake_case)]
+    pub const fn Custom(level: u8) -> Priority {
+        Priority(level as u16)
+    }
+
+    /// The numeric level, or `None` for [`Priority::Shutdown`].
+    pub fn level(self) -> Option<u8> {
+        u8::try_from(self.0).ok()
+    }
+
+    /// Number of distinct priority levels.
+    pub(crate) const LEVELS: usize = Priority::Shutdown.0 as usize + 1;
+
+    pub(crate) fn index(self) -> usize {
+        self.0 as usize
+    }
 
-#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Copy)]
-pub enum Priority {
-    Low,
-    Medium,
-    High,
-    // Highest priority for shutdown messages
-    Shutdown,
+    /// One named level higher per raise, e.g. `Low` to `Medium`, but never past `High`.
+    /// Priorities at or above `High` stay where they are.
+    pub(crate) fn aged(self, raises: u32) -> Priority {
+        if self >= Priority::High {
+            return self;
+        }
+        let step = u32::from(Priority::Medium.0 - Priority::Low.0);
+        let raised = u32::from(self.0).saturating_add(step.saturating_mul(raises));
+        Priority(raised.min(u32::from(Priority::High.0)) as u16)
+    }
+}
+
+impl std::fmt::Debug for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Priority::Low => f.write_str("Low"),
+            Priority::Medium => f.write_str("Medium"),
+            Priority::High => f.write_str("High"),
+            Priority::Shutdown => f.write_str("Shutdown"),
+            Priority(level) => write!(f, "Custom({})", level),
+        }
+    }
 }
 
 impl Prioritized for Priority {
@@ -36,100 +331,1007 @@ pub trait Prioritized {
     fn priority(&self) -> Priority {
         Priority::Medium
     }
+
+    /// The name of the message's variant, e.g. `"Increment"`.
+    ///
+    /// `define_actor!` and `#[derive(Prioritized)]` fill this in; otherwise it is the type's
+    /// name.
+    fn variant_name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// How long the message stays worth handling after it is sent; `None`, the default,
+    /// means forever.
+    ///
+    /// A message still waiting once its TTL has passed is dropped without reaching the
+    /// handler and counted in [`ActorMetrics::messages_expired`]. `Shutdown` messages never
+    /// expire. [`ActorHandle::send_with_ttl`] sets a TTL for a single send instead.
+    fn ttl(&self) -> Option<Duration> {
+        None
+    }
+
+    /// The key under which the message replaces an older one still waiting in the queue;
+    /// `None`, the default, means it never does.
+    ///
+    /// A message sent while another with the same key is queued takes that message's place,
+    /// so at most one message per key is ever pending, e.g. the latest of a stream of redraw
+    /// requests. The replaced message is dropped unhandled, counted in
+    /// [`ActorMetrics::messages_coalesced`], and goes to the
+    /// [dead-letter sink](ActorConfig::dead_letters), if there is one. `Shutdown` messages
+    /// never coalesce. `define_actor!` handlers marked `@coalesce` and variants marked
+    /// `#[coalesce]` in `#[derive(Prioritized)]` coalesce by variant.
+    fn coalesce_key(&self) -> Option<u64> {
+        None
+    }
 }
 
 #[async_trait]
 pub trait Actor: Send + 'static {
     type Msg: Send + 'static + Prioritized;
 
-    async fn handle(&mut self, msg: Self::Msg) -> bool;
+    /// Handles one message. Return `ControlFlow::Break` to stop the actor.
+    ///
+    /// `ctx` lets the handler send messages to the actor itself.
+    async fn handle(
+        &mut self,
+        msg: Self::Msg,
+        ctx: &ActorContext<Self::Msg>,
+    ) -> ControlFlow<ShutdownReason>;
+
+    /// Handles several waiting messages at once, highest priority first, e.g. to write a
+    /// batch of log entries with one syscall.
+    ///
+    /// Only called for actors spawned with [`ActorConfig::batch_size`], whenever more than
+    /// one message is waiting. The default hands the messages to [`handle`](Self::handle)
+    /// one by one; once one breaks, the rest are dropped unhandled and the actor stops.
+    async fn handle_batch(
+        &mut self,
+        msgs: Vec<Self::Msg>,
+        ctx: &ActorContext<Self::Msg>,
+    ) -> ControlFlow<ShutdownReason> {
+        for msg in msgs {
+            self.handle(msg, ctx).await?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Runs on the processor task before the first message is handled.
+    ///
+    /// A supervised actor replaced after a panic runs it again on the replacement.
+    async fn on_start(&mut self) {}
+
+    /// Runs on the processor task once the actor has stopped handling messages, however it
+    /// stopped, unless a handler panicked.
+    async fn on_stop(&mut self) {}
+
+    /// Called when a handler for a message of `priority` ran past its
+    /// [`ActorConfig::handler_timeout`] and was cancelled, after the timeout has been logged.
+    /// By default the actor carries on with its next message.
+    async fn on_timeout(&mut self, priority: Priority) -> ControlFlow<ShutdownReason> {
+        let _ = priority;
+        ControlFlow::Continue(())
+    }
+}
+
+/// An actor whose handler can fail.
+///
+/// Every `TryActor` is an [`Actor`]: errors returned by [`handle`](TryActor::handle) go to
+/// [`on_error`](TryActor::on_error), which decides whether the actor keeps running. An
+/// actor stopped from `on_error` should break with [`ShutdownReason::Failed`], which its
+/// handles report as [`TerminationReason::HandlerFailed`].
+#[async_trait]
+pub trait TryActor: Send + 'static {
+    type Msg: Send + 'static + Prioritized;
+    type Error: std::fmt::Debug + Send + 'static;
+
+    async fn handle(
+        &mut self,
+        msg: Self::Msg,
+        ctx: &ActorContext<Self::Msg>,
+    ) -> Result<ControlFlow<ShutdownReason>, Self::Error>;
+
+    /// Called with every error `handle` returns. By default the error is logged and the
+    /// actor carries on with its next message.
+    async fn on_error(
+        &mut self,
+        err: Self::Error,
+        ctx: &ActorContext<Self::Msg>,
+    ) -> ControlFlow<ShutdownReason> {
+        diag!(warn, ctx.name(), "handler failed: {:?}", err);
+        let _ = (err, ctx);
+        ControlFlow::Continue(())
+    }
+
+    /// See [`Actor::on_start`].
+    async fn on_start(&mut self) {}
+
+    /// See [`Actor::on_stop`].
+    async fn on_stop(&mut self) {}
+
+    /// See [`Actor::on_timeout`].
+    async fn on_timeout(&mut self, priority: Priority) -> ControlFlow<ShutdownReason> {
+        let _ = priority;
+        ControlFlow::Continue(())
+    }
 }
 
-pub fn spawn_actor<A>(mut actor: A) -> mpsc::Sender<A::Msg>
+#[async_trait]
+impl<T: TryActor> Actor for T {
+    type Msg = T::Msg;
+
+    async fn handle(
+        &mut self,
+        msg: Self::Msg,
+        ctx: &ActorContext<Self::Msg>,
+    ) -> ControlFlow<ShutdownReason> {
+        match TryActor::handle(self, msg, ctx).await {
+            Ok(flow) => flow,
+            Err(err) => self.on_error(err, ctx).await,
+        }
+    }
+
+    async fn on_start(&mut self) {
+        TryActor::on_start(self).await
+    }
+
+    async fn on_stop(&mut self) {
+        TryActor::on_stop(self).await
+    }
+
+    async fn on_timeout(&mut self, priority: Priority) -> ControlFlow<ShutdownReason> {
+        TryActor::on_timeout(self, priority).await
+    }
+}
+
+pub fn spawn_actor<A>(actor: A) -> ActorHandle<A::Msg>
 where
     A: Actor + Send + 'static,
 {
-    // The channel capacity.
-    let (tx, mut rx) = mpsc::channel::<A::Msg>(32);
-
-    // Queue for messages, protected by a Mutex, ordered by Priority
-    let queue = Arc::new(Mutex::new(BinaryHeap::<PrioritizedWrapper<A::Msg>>::new()));
-    // Notify to signal new messages in the queue
-    let notify = Arc::new(Notify::new());
-
-    // Receiver task
-    let queue_rx = Arc::clone(&queue);
-    let notify_rx = Arc::clone(&notify);
-    let actor_name_rx = std::any::type_name::<A>().to_string();
-    tokio::spawn(async move {
-        println!("[{}] Message receiver task started.", actor_name_rx);
-        while let Some(msg) = rx.recv().await {
-            let mut q = queue_rx.lock().await;
-            q.push(PrioritizedWrapper(msg));
-            notify_rx.notify_one();
-        }
-        println!(
-            "[{}] All senders dropped. Message receiver task terminating.",
-            actor_name_rx
-        );
+    spawn_actor_with(actor, ActorConfig::default())
+}
+
+pub fn spawn_actor_with<A>(actor: A, config: ActorConfig) -> ActorHandle<A::Msg>
+where
+    A: Actor + Send + 'static,
+{
+    spawn_actor_owned(actor, config).into_handle()
+}
+
+/// Spawns an actor like [`spawn_actor_with`], keeping ownership of it so that
+/// [`SpawnedActor::join`] can hand the actor's final state back once it stops.
+pub fn spawn_actor_owned<A>(actor: A, config: ActorConfig) -> SpawnedActor<A>
+where
+    A: Actor + Send + 'static,
+{
+    spawn(actor, config, None)
+}
+
+/// Spawns an actor like [`spawn_actor_with`], applying `strategy` when a handler panics.
+///
+/// Without supervision a panicking handler takes the actor down and its handles report
+/// [`TerminationReason::Aborted`]. Here the panic is caught instead, and the actor either
+/// stops with [`TerminationReason::Panicked`] or is replaced by a fresh one that carries on
+/// with the same mailbox.
+pub fn spawn_actor_supervised<A>(
+    actor: A,
+    config: ActorConfig,
+    strategy: RestartStrategy<A>,
+) -> ActorHandle<A::Msg>
+where
+    A: Actor + Send + 'static,
+{
+    spawn(actor, config, Some(Supervisor::new(strategy))).into_handle()
+}
+
+/// Spawns an actor like [`spawn_actor_owned`], onto `runtime` instead of the runtime the
+/// caller is on.
+///
+/// Works from any thread, including ones outside a runtime, so an application running
+/// several runtimes can pick the one each actor lives on.
+pub fn spawn_actor_on<A>(actor: A, config: ActorConfig, runtime: &Handle) -> SpawnedActor<A>
+where
+    A: Actor + Send + 'static,
+{
+    spawn_on(actor, config, None, Some(runtime))
+}
+
+/// Spawns an actor like [`spawn_actor_with`], with its tasks in `tasks` so the caller owns
+/// them.
+///
+/// [`JoinSet::abort_all`] or dropping the set stops the actor, which its handles then
+/// report as [`TerminationReason::Aborted`].
+pub fn spawn_actor_in<A>(
+    actor: A,
+    config: ActorConfig,
+    tasks: &mut JoinSet<()>,
+) -> ActorHandle<A::Msg>
+where
+    A: Actor + Send + 'static,
+{
+    let (handle, processor) = launch(actor, config, None);
+    task::spawn_in(
+        handle.name(),
+        async move {
+            processor.await;
+        },
+        tasks,
+    );
+    handle
+}
+
+/// Spawns an actor whose state `init` builds on the processor task, e.g. after opening a
+/// connection, rather than before spawning.
+///
+/// Messages sent while `init` runs queue up as usual and are handled once it returns the
+/// actor, unless the config [rejects](ActorConfig::readiness) them; wait for
+/// [`ActorHandle::ready`] to know it has. If it fails instead, the error is logged and the
+/// actor stops with [`TerminationReason::InitFailed`] without handling anything: sends fail
+/// from then on, and whatever had queued up goes to the dead-letter sink, if there is one.
+pub fn spawn_actor_init<A, E, F, Fut>(init: F) -> ActorHandle<A::Msg>
+where
+    A: Actor + Send + 'static,
+    E: std::fmt::Debug + Send + 'static,
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<A, E>> + Send + 'static,
+{
+    spawn_actor_init_with(init, ActorConfig::default())
+}
+
+/// Like [`spawn_actor_init`], with `config` applied to the actor's mailbox.
+pub fn spawn_actor_init_with<A, E, F, Fut>(init: F, mut config: ActorConfig) -> ActorHandle<A::Msg>
+where
+    A: Actor + Send + 'static,
+    E: std::fmt::Debug + Send + 'static,
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<A, E>> + Send + 'static,
+{
+    if config.name.is_none() {
+        config.name = Some(default_name(<A as Drive>::name()));
+    }
+    let (handle, mailbox) = channel(config);
+    let processor = async move {
+        match init().await {
+            Ok(actor) => {
+                run(actor, mailbox, None).await;
+            }
+            Err(err) => {
+                diag!(
+                    error,
+                    mailbox.context.name(),
+                    "actor failed to initialize: {:?}",
+                    err
+                );
+                mailbox.abandon(TerminationReason::InitFailed).await;
+            }
+        }
+    };
+    task::spawn_on(handle.name(), processor, &Handle::current());
+    handle
+}
+
+fn spawn<A>(actor: A, config: ActorConfig, supervisor: Option<Supervisor<A>>) -> SpawnedActor<A>
+where
+    A: Actor + Send + 'static,
+{
+    spawn_on(actor, config, supervisor, None)
+}
+
+fn spawn_on<A>(
+    actor: A,
+    config: ActorConfig,
+    supervisor: Option<Supervisor<A>>,
+    runtime: Option<&Handle>,
+) -> SpawnedActor<A>
+where
+    A: Actor + Send + 'static,
+{
+    let (handle, processor) = launch(actor, config, supervisor);
+    // Checked only now, so a bad config is reported as such even outside a runtime.
+    let runtime = runtime.cloned().unwrap_or_else(Handle::current);
+    let processor = task::spawn_on(handle.name(), processor, &runtime);
+    SpawnedActor::new(handle, processor)
+}
+
+/// Sets up an actor's mailbox, returning its handle along with its processor task for the
+/// caller to spawn. Aborting the processor stops the actor.
+fn launch<D: Drive>(
+    actor: D,
+    mut config: ActorConfig,
+    supervisor: Option<Supervisor<D>>,
+) -> (ActorHandle<D::Msg>, impl Future<Output = D>) {
+    if config.name.is_none() {
+        config.name = Some(default_name(D::name()));
+    }
+    let (handle, mailbox) = channel(config);
+    (handle, run(actor, mailbox, supervisor))
+}
+
+/// The name of an actor spawned without [`ActorConfig::name`]: `type_name`, numbered so
+/// that instances of the same type can be told apart.
+fn default_name(type_name: &str) -> String {
+    static INSTANCES: AtomicU64 = AtomicU64::new(0);
+    let instance = INSTANCES.fetch_add(1, Ordering::Relaxed) + 1;
+    format!("{}#{}", type_name, instance)
+}
+
+/// Creates an actor's mailbox without spawning anything: messages sent through the handle
+/// wait in the mailbox until [`run_actor`] runs an actor on it.
+///
+/// `config` applies as it would to [`spawn_actor_with`], except that an actor not given a
+/// [name](ActorConfig::name) is named after its message type rather than its own type.
+pub fn channel<M>(config: ActorConfig) -> (ActorHandle<M>, PriorityMailbox<M>)
+where
+    M: Prioritized + Send + 'static,
+{
+    assert!(
+        config.capacity.is_some() || config.reservations.is_empty(),
+        "capacity reservations require a bounded mailbox"
+    );
+    // Read by senders when enqueueing and by the processor task when popping.
+    let runtime = Arc::new(ArcSwap::from_pointee(config.runtime));
+
+    let name = config
+        .name
+        .unwrap_or_else(|| default_name(std::any::type_name::<M>()));
+    let metrics = Arc::new(ActorMetrics::named(name.clone()));
+    let permits = config.capacity.map(|capacity| {
+        Arc::new(PermitPool::new(
+            capacity,
+            &config.reservations,
+            Arc::clone(&metrics),
+        ))
+    });
+
+    let schedules = Arc::new(Schedules::new(
+        config.keep_alive_while_scheduled,
+        Arc::clone(&metrics),
+    ));
+
+    let taps = Arc::new(Taps::new());
+    let drops = config
+        .drop_exemplars
+        .map(|(per_variant, keep)| Arc::new(DropSampler::new(per_variant, keep)));
+    let audit = config.audit;
+    let dead_letters = config.dead_letters.map(|sink| sink.downcast::<M>());
+    let queue_latency = config
+        .track_queue_latency
+        .then_some(QueueWaits(config.on_dequeue));
+    let slow_handlers = config.slow_handler_threshold.map(|threshold| SlowHandlers {
+        threshold,
+        on_slow: config.on_slow_handler,
     });
 
-    // Processor task
-    let actor_name_proc = std::any::type_name::<A>().to_string();
-    let tx_clone = tx.clone();
-    tokio::spawn(async move {
-        println!("[{}] Message processor task started.", actor_name_proc);
-        loop {
-            let msg = loop {
-                let mut q = queue.lock().await;
-                if let Some(msg) = q.pop() {
-                    break msg;
+    let (pressure_tx, pressure) = watch::channel(MailboxPressure::Normal);
+    let watermarks = config
+        .watermarks
+        .map(|(high, low)| Watermarks::new(high, low, pressure_tx));
+
+    // Senders queue messages straight into the inbox, ordered by priority
+    let inbox = Arc::new(Inbox::new(
+        MessageQueue::<M>::new(config.fairness, config.aging, Arc::clone(&metrics))
+            .with_scheduling(config.scheduling)
+            .with_throttle(Throttle::new(&config.rate_limits))
+            .with_watermarks(watermarks),
+        Admission::new(
+            Arc::clone(&runtime),
+            Arc::clone(&metrics),
+            audit.clone(),
+            drops.clone(),
+        )
+        .with_dead_letters(dead_letters.clone()),
+        permits.clone(),
+    ));
+    // Filled in by the processor task when it stops
+    let (status_tx, status_rx) = watch::channel(None);
+    let report = Arc::new(OnceLock::new());
+    let (handler_timeout, handler_timeouts) = (config.handler_timeout, config.handler_timeouts);
+    let batch_size = config.batch_size;
+    let middleware = Middlewares::new(config.middleware);
+
+    let pending = Arc::new(Pending::default());
+    let children = Arc::new(Children::new());
+    let (force_stop, force_stop_rx) = watch::channel(false);
+    let (ready_gate, ready) = ReadyGate::new(config.readiness);
+    let events = Arc::new(Events::new(config.event_capacity));
+    let shared = Shared {
+        permits,
+        metrics: Arc::clone(&metrics),
+        schedules: Arc::clone(&schedules),
+        taps: Arc::clone(&taps),
+        drops,
+        status: status_rx,
+        report: Arc::clone(&report),
+        runtime: Arc::clone(&runtime),
+        pending: Arc::clone(&pending),
+        children: Arc::clone(&children),
+        pressure,
+        dead_letters: dead_letters.clone(),
+        ping_timeout: config.ping_timeout,
+        force_stop,
+        redirect: ArcSwapOption::empty(),
+        events: Arc::clone(&events),
+        ready: ready_gate,
+    };
+    let handle = ActorHandle::new(MailboxSender::new(Arc::clone(&inbox)), shared);
+
+    let bookkeeping = Bookkeeping {
+        name: name.clone(),
+        runtime,
+        metrics: Arc::clone(&metrics),
+        taps,
+        audit: audit.clone(),
+        handler_timeout,
+        handler_timeouts,
+        middleware,
+        queue_latency,
+        slow_handlers,
+        pending,
+    };
+    let context = ActorContext::new(handle.downgrade(), name, Arc::clone(&events));
+    let mailbox = PriorityMailbox {
+        metrics,
+        schedules,
+        queue: PriorityReceiver::new(inbox),
+        status_tx,
+        report,
+        audit,
+        batch_size,
+        dead_letters,
+        bookkeeping,
+        children,
+        force_stop: force_stop_rx,
+        cancel_on: config.cancel_on,
+        checkpoints: Checkpoints::new(config.checkpoint),
+        events,
+        ready,
+        context,
+    };
+    (handle, mailbox)
+}
+
+/// The receiving end of an actor's mailbox, created by [`channel`] and consumed by
+/// [`run_actor`].
+///
+/// Dropping it without running an actor closes the mailbox, failing all sends.
+pub struct PriorityMailbox<M: Prioritized> {
+    metrics: Arc<ActorMetrics>,
+    schedules: Arc<Schedules>,
+    queue: PriorityReceiver<M>,
+    status_tx: watch::Sender<Option<TerminationReason>>,
+    report: Arc<OnceLock<ShutdownReport>>,
+    audit: Option<Sink<AuditEvent>>,
+    batch_size: usize,
+    dead_letters: Option<Sink<M>>,
+    bookkeeping: Bookkeeping<M>,
+    children: Arc<Children>,
+    force_stop: watch::Receiver<bool>,
+    cancel_on: CancelOn,
+    checkpoints: Checkpoints,
+    events: Arc<Events>,
+    // Flipped once the actor is ready, just before it handles its first message.
+    ready: watch::Sender<bool>,
+    context: ActorContext<M>,
+}
+
+impl<M: Prioritized + Send + 'static> PriorityMailbox<M> {
+    /// Stops the actor without ever running it, as though it had stopped for `reason`.
+    async fn abandon(self, reason: TerminationReason) {
+        let PriorityMailbox {
+            metrics,
+            schedules,
+            queue,
+            status_tx,
+            report,
+            audit,
+            dead_letters,
+            children,
+            events,
+            ..
+        } = self;
+        let teardown = Teardown {
+            metrics,
+            schedules,
+            status_tx,
+            report,
+            audit,
+            dead_letters,
+            children: children.guard(),
+            events,
+        };
+        teardown.finish(&queue, reason).await;
+    }
+}
+
+/// Runs `actor` on `mailbox` in the current task until it stops, then returns it.
+///
+/// The same loop [`spawn_actor_with`] spawns, for callers that would rather own it, e.g. to
+/// race it against other futures in `tokio::select!`. The actor stops as a spawned one
+/// does: on a shutdown message, or once every handle is gone and the queue has drained.
+/// A panicking handler panics here.
+pub async fn run_actor<A: Actor>(actor: A, mailbox: PriorityMailbox<A::Msg>) -> A {
+    run(actor, mailbox, None).await
+}
+
+/// Builds the processor task running `actor` on `mailbox`.
+fn run<D: Drive>(
+    mut actor: D,
+    mailbox: PriorityMailbox<D::Msg>,
+    mut supervisor: Option<Supervisor<D>>,
+) -> impl Future<Output = D> {
+    let PriorityMailbox {
+        metrics,
+        schedules,
+        mut queue,
+        status_tx,
+        report,
+        audit,
+        batch_size,
+        dead_letters,
+        bookkeeping,
+        children,
+        force_stop,
+        cancel_on,
+        mut checkpoints,
+        events,
+        ready,
+        context,
+    } = mailbox;
+
+    checkpoints.check(actor.state());
+    let actor_name = context.name().to_owned();
+    let teardown = Teardown {
+        metrics,
+        schedules,
+        status_tx,
+        report,
+        audit,
+        dead_letters,
+        // Linked children stop with this actor, even if it never gets to tear down.
+        children: children.guard(),
+        events,
+    };
+    async move {
+        diag!(debug, actor_name, "message processor task started");
+        // Earliest time the next message may be dispatched under the rate limit.
+        let mut next_dispatch: Option<Instant> = None;
+        // Set once the cancellation token has fired and the queue is draining.
+        let mut cancelled = false;
+        actor.on_start().await;
+        ready.send_replace(true);
+        let reason = 'processing: loop {
+            let received = tokio::select! {
+                biased;
+                () = forced(force_stop.clone()) => {
+                    break 'processing TerminationReason::DeadlineExceeded { mid_handler: false };
+                }
+                graceful = cancel_on.fired(), if !cancelled => {
+                    if !graceful {
+                        diag!(info, actor_name, "cancelled; processor task terminating");
+                        break 'processing TerminationReason::Cancelled;
+                    }
+                    diag!(info, actor_name, "cancelled; draining the queue");
+                    cancelled = true;
+                    queue.inbox().drain();
+                    continue;
                 }
-                // Queue is empty.
-                if tx_clone.is_closed() {
-                    println!(
-                        "[{}] All senders dropped and queue is empty. Processor task terminating.",
-                        actor_name_proc
+                () = checkpoints.elapsed() => {
+                    checkpoints.take(actor.state());
+                    continue;
+                }
+                received = queue.recv_next(next_dispatch) => received,
+            };
+            let msg = match received {
+                Some(Received::Message(msg)) => msg,
+                Some(Received::Visits(visits)) => {
+                    for visit in visits {
+                        visit(actor.state());
+                    }
+                    continue;
+                }
+                None if cancelled => {
+                    diag!(
+                        info,
+                        actor_name,
+                        "queue drained; processor task terminating"
+                    );
+                    break 'processing TerminationReason::Cancelled;
+                }
+                None => {
+                    diag!(
+                        info,
+                        actor_name,
+                        "all senders dropped and queue is empty; processor task terminating"
                     );
-                    return;
+                    break 'processing TerminationReason::SendersDropped;
                 }
-                // Release lock and wait for notification
-                drop(q);
-                notify.notified().await;
             };
 
-            // We have a message.
-            if !actor.handle(msg.0).await {
-                println!(
-                    "[{}] Actor received shutdown signal. Processor task terminating.",
-                    actor_name_proc
-                );
-                break;
+            let Some(msg) = unexpired(&queue, msg, Instant::now()) else {
+                continue;
+            };
+            // With batching on, whatever else is waiting goes along with it.
+            let priority = msg.priority();
+            let mut batch = Vec::new();
+            if batch_size > 1 && priority != Priority::Shutdown {
+                let now = Instant::now();
+                for envelope in queue.recv_ready(batch_size - 1) {
+                    batch.extend(unexpired(&queue, envelope, now));
+                }
+            }
+
+            // We have messages; each one's mailbox slot is free as soon as it leaves the queue,
+            // but it stays pending until its handler has returned and its follow-ups are
+            // queued. Middleware may still drop it unhandled.
+            let mut msgs = Vec::new();
+            let mut admitted = Vec::new();
+            for envelope in std::iter::once(msg).chain(batch) {
+                if let Some((msg, dispatched)) = bookkeeping.admit(envelope) {
+                    msgs.push(msg);
+                    admitted.push(dispatched);
+                }
+            }
+            let (priority, correlation) = match admitted.first() {
+                Some(first) => (first.priority, first.correlation),
+                None => continue,
+            };
+            bookkeeping.dispatched(priority, admitted.len() as u32, &mut next_dispatch);
+            context.set_priority(Some(priority));
+            let handling = if msgs.len() == 1 {
+                actor.handle(msgs.remove(0), &context)
+            } else {
+                actor.handle_batch(msgs, &context)
+            };
+            #[cfg(feature = "tracing")]
+            let handling = tracing::Instrument::instrument(
+                handling,
+                tracing::debug_span!(
+                    "handle",
+                    actor = %actor_name,
+                    variant = admitted[0].variant,
+                    ?priority,
+                    origin = ?admitted[0].origin,
+                    batch = admitted.len(),
+                    queue_depth = queue.len(),
+                    correlation_id = correlation
+                ),
+            );
+            let supervised = supervisor.is_some();
+            let handling = async move {
+                if supervised {
+                    CatchUnwind(handling).await
+                } else {
+                    Ok(handling.await)
+                }
+            };
+            let handling = correlation::scope(correlation, handling);
+            let force = forced(force_stop.clone());
+            let handling = async move {
+                tokio::select! {
+                    biased;
+                    () = force => None,
+                    outcome = handling => Some(outcome),
+                }
+            };
+            let started = Instant::now();
+            let outcome = match bookkeeping.time_limit(priority) {
+                Some(limit) => tokio::time::timeout(limit, handling).await,
+                None => Ok(handling.await),
+            };
+            let returned = matches!(outcome, Ok(Some(Ok(_))));
+            let outcome = match outcome {
+                Ok(Some(outcome)) => outcome,
+                Ok(None) => {
+                    break 'processing TerminationReason::DeadlineExceeded { mid_handler: true };
+                }
+                Err(_elapsed) => {
+                    bookkeeping.timed_out(priority);
+                    Ok(actor.on_timeout(priority).await)
+                }
+            };
+            let elapsed = started.elapsed();
+            bookkeeping.took(admitted[0].variant, priority, elapsed);
+            for dispatched in &mut admitted {
+                bookkeeping.handled(dispatched, returned, outcome.is_ok().then_some(elapsed));
+            }
+            context.set_priority(None);
+            if outcome.is_ok() {
+                checkpoints.handled(admitted.len());
+                checkpoints.take(actor.state());
             }
+            let looped = context.take_loopback();
+            match outcome {
+                Ok(ControlFlow::Break(reason)) => {
+                    diag!(
+                        info,
+                        actor_name,
+                        "actor received shutdown signal; processor task terminating"
+                    );
+                    break reason.into();
+                }
+                Ok(ControlFlow::Continue(())) => bookkeeping.loop_back(&queue, looped),
+                Err(_panic) => {
+                    let supervisor = supervisor
+                        .as_mut()
+                        .expect("only supervised handlers are caught");
+                    if let ControlFlow::Break(reason) =
+                        supervisor.recover(&mut actor, &actor_name).await
+                    {
+                        break reason;
+                    }
+                    actor.on_start().await;
+                }
+            }
+        };
+        // A panicking handler may have left the actor half-updated, and an actor stopped
+        // past its shutdown deadline has no time left for `on_stop`.
+        if !matches!(
+            reason,
+            TerminationReason::Panicked | TerminationReason::DeadlineExceeded { .. }
+        ) {
+            actor.on_stop().await;
         }
-    });
+        teardown.finish(&queue, reason).await;
+        actor
+    }
+}
 
-    tx
+/// Resolves once a graceful shutdown has run out of time, and never if every handle is
+/// gone without one doing so.
+async fn forced(mut force_stop: watch::Receiver<bool>) {
+    if force_stop.wait_for(|forced| *forced).await.is_err() {
+        std::future::pending().await
+    }
 }
 
-pub struct PrioritizedWrapper<T>(pub T);
+/// What is left to do once an actor has stopped, whether or not it ever ran.
+struct Teardown<M> {
+    metrics: Arc<ActorMetrics>,
+    schedules: Arc<Schedules>,
+    status_tx: watch::Sender<Option<TerminationReason>>,
+    report: Arc<OnceLock<ShutdownReport>>,
+    audit: Option<Sink<AuditEvent>>,
+    dead_letters: Option<Sink<M>>,
+    children: ChildrenGuard,
+    events: Arc<Events>,
+}
 
-impl<T: Prioritized> PartialEq for PrioritizedWrapper<T> {
-    fn eq(&self, other: &Self) -> bool {
-        self.0.priority() == other.0.priority()
+impl<M: Prioritized + Send + 'static> Teardown<M> {
+    /// Tears down in order, so that everything the actor reports lands before `Stopped` and
+    /// `Stopped` lands before anyone can observe the stop.
+    async fn finish(self, queue: &PriorityReceiver<M>, mut reason: TerminationReason) {
+        self.schedules.shutdown().await;
+        // Later sends fail from here on; whatever is left will never be handled.
+        let (closed_by, undelivered) = queue.stop();
+        if let Some(closed_by) = closed_by {
+            if reason == TerminationReason::SendersDropped {
+                reason = closed_by;
+            }
+        }
+        let mut pending = BTreeMap::new();
+        for envelope in &undelivered {
+            *pending.entry(envelope.priority()).or_insert(0) += 1;
+        }
+        if let Some(dead_letters) = &self.dead_letters {
+            for envelope in undelivered {
+                dead_letters.deliver(envelope.msg);
+            }
+        } else {
+            drop(undelivered);
+        }
+        if let Some(audit) = &self.audit {
+            let totals = AuditTotals {
+                handled: self.metrics.messages_handled(),
+                shed: self.metrics.messages_shed(),
+                expired: self.metrics.messages_expired(),
+                overflowed: self.metrics.messages_overflowed(),
+                coalesced: self.metrics.messages_coalesced(),
+            };
+            audit.deliver(AuditEvent::Stopped { reason, totals });
+        }
+        self.children.stop_all();
+        self.events.close();
+        let _ = self.report.set(ShutdownReport {
+            pending,
+            handled: self.metrics.messages_handled(),
+        });
+        let _ = self.status_tx.send(Some(reason));
     }
 }
 
-impl<T: Prioritized> Eq for PrioritizedWrapper<T> {}
+/// Where an actor that tracks queue latency reports how long each message waited.
+struct QueueWaits(Option<Sink<(Priority, Duration)>>);
+
+impl QueueWaits {
+    fn record(&self, metrics: &ActorMetrics, priority: Priority, wait: Duration) {
+        metrics.message_waited(priority, wait);
+        if let Some(on_dequeue) = &self.0 {
+            on_dequeue.deliver((priority, wait));
+        }
+    }
+}
+
+/// What both processor loops keep track of for each message, from when it leaves the queue
+/// until its handler has returned and its follow-ups are queued.
+struct Bookkeeping<M> {
+    name: String,
+    runtime: Arc<ArcSwap<RuntimeConfig>>,
+    metrics: Arc<ActorMetrics>,
+    taps: Arc<Taps<M>>,
+    audit: Option<Sink<AuditEvent>>,
+    handler_timeout: Option<Duration>,
+    handler_timeouts: Vec<(Priority, Duration)>,
+    middleware: Middlewares<M>,
+    queue_latency: Option<QueueWaits>,
+    slow_handlers: Option<SlowHandlers>,
+    pending: Arc<Pending>,
+}
+
+/// A message on its way to its handler, with what it is accounted under.
+struct Dispatched {
+    variant: &'static str,
+    priority: Priority,
+    origin: Origin,
+    correlation: Option<u64>,
+    // Released once the handler's follow-ups are queued.
+    handling: Handling,
+}
+
+impl<M: Prioritized + Send + 'static> Bookkeeping<M> {
+    /// Takes a message out of its envelope and runs it past middleware, recording how long
+    /// it waited; `None` if middleware turned it away.
+    fn admit(&self, envelope: Envelope<M>) -> Option<(M, Dispatched)> {
+        let (priority, correlation) = (envelope.priority(), envelope.correlation);
+        let enqueued_at = envelope.enqueued_at;
+        let (msg, origin, mut handling) = unpack(envelope, &self.taps);
+        if !self.middleware.admit(&msg) {
+            handling.confirm(Err(NotHandled::Rejected));
+            return None;
+        }
+        if let (Some(waits), Some(at)) = (&self.queue_latency, enqueued_at) {
+            waits.record(&self.metrics, priority, at.elapsed());
+        }
+        let dispatched = Dispatched {
+            variant: msg.variant_name(),
+            priority,
+            origin,
+            correlation,
+            handling,
+        };
+        Some((msg, dispatched))
+    }
+
+    /// Holds the next message back under the rate limit once `count` messages have been
+    /// dispatched at `priority`; `Shutdown` messages do not count.
+    fn dispatched(&self, priority: Priority, count: u32, next_dispatch: &mut Option<Instant>) {
+        if priority != Priority::Shutdown {
+            *next_dispatch = self
+                .runtime
+                .load()
+                .rate_limit
+                .map(|per_second| Instant::now() + Duration::from_secs(1) * count / per_second);
+        }
+    }
+
+    /// How long a handler for a message at `priority` may run.
+    fn time_limit(&self, priority: Priority) -> Option<Duration> {
+        self.handler_timeouts
+            .iter()
+            .find(|(level, _)| *level == priority)
+            .map(|(_, limit)| *limit)
+            .or(self.handler_timeout)
+    }
+
+    /// Reports a handler for a message at `priority` that ran past its time limit.
+    fn timed_out(&self, priority: Priority) {
+        diag!(
+            warn,
+            self.name,
+            "handler for a {:?} message timed out",
+            priority
+        );
+        self.metrics.message_timed_out();
+    }
+
+    /// Times the handler for a `variant` message, reporting it if it was slow.
+    fn took(&self, variant: &'static str, priority: Priority, elapsed: Duration) {
+        if let Some(slow) = &self.slow_handlers {
+            slow.record(&self.metrics, &self.name, variant, priority, elapsed);
+        }
+    }
+
+    /// Accounts for a message whose handler is done, telling a sender waiting on it whether
+    /// the handler `returned`. Middleware hears how long it took unless `elapsed` is `None`.
+    fn handled(&self, dispatched: &mut Dispatched, returned: bool, elapsed: Option<Duration>) {
+        let (priority, origin) = (dispatched.priority, dispatched.origin);
+        dispatched.handling.confirm(if returned {
+            Ok(())
+        } else {
+            Err(NotHandled::Failed)
+        });
+        self.metrics.message_handled();
+        if let Some(audit) = &self.audit {
+            audit.deliver(AuditEvent::Handled { priority, origin });
+        }
+        if let Some(elapsed) = elapsed {
+            self.middleware
+                .handled(dispatched.variant, priority, elapsed);
+        }
+    }
 
-impl<T: Prioritized> PartialOrd for PrioritizedWrapper<T> {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+    /// Queues the messages a handler sent itself, each pending until it has been handled.
+    fn loop_back(&self, queue: &PriorityReceiver<M>, looped: Vec<(M, Option<u64>)>) {
+        if looped.is_empty() {
+            return;
+        }
+        let looped = looped
+            .into_iter()
+            .map(|(msg, correlation)| {
+                let mut envelope = Envelope::new(msg, Origin::SelfSend);
+                envelope.correlation = correlation;
+                envelope.pending = Some(self.pending.track());
+                envelope
+            })
+            .collect();
+        queue.inbox().push_looped(looped);
     }
 }
 
-impl<T: Prioritized> Ord for PrioritizedWrapper<T> {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.0.priority().cmp(&other.0.priority())
+/// Drops a message past its TTL as of `now` unhandled, freeing its slot all the same.
+fn unexpired<M: Prioritized + Send + 'static>(
+    queue: &PriorityReceiver<M>,
+    envelope: Envelope<M>,
+    now: Instant,
+) -> Option<Envelope<M>> {
+    if envelope.is_expired(now) {
+        queue
+            .inbox()
+            .admission()
+            .discard(DropReason::Expired, envelope);
+        return None;
+    }
+    Some(envelope)
+}
+
+/// Takes a message out of its envelope as it leaves the queue: its mailbox slot is freed and
+/// its taps see it, while the returned [`Handling`] keeps it pending until it has been
+/// handled.
+fn unpack<M>(envelope: Envelope<M>, taps: &Taps<M>) -> (M, Origin, Handling) {
+    let Envelope {
+        msg,
+        permit,
+        origin,
+        ticket,
+        deadline: _,
+        pending,
+        priority: _,
+        correlation: _,
+        enqueued_at: _,
+        confirm,
+    } = envelope;
+    drop((permit, ticket));
+    taps.mirror(&msg, origin);
+    (
+        msg,
+        origin,
+        Handling {
+            _pending: pending,
+            confirm,
+        },
+    )
+}
+
+/// What stays with a message from when it leaves the queue until its handler has returned.
+struct Handling {
+    // Released once the handler's follow-ups are queued.
+    _pending: Option<PendingGuard>,
+    confirm: Option<Confirm>,
+}
+
+impl Handling {
+    /// Tells a sender waiting on the message how it fared.
+    fn confirm(&mut self, outcome: Result<(), NotHandled>) {
+        if let Some(confirm) = self.confirm.take() {
+            confirm.send(outcome);
+        }
     }
 }
@@ -1,4 +1,12 @@
-use crate::{define_actor, spawn_actor, Actor, Prioritized, Priority};
+use crate::sink::Sink;
+use crate::testing::{ActorHarness, StepResult};
+use crate::{
+    channel, define_actor, run_actor, spawn_actor, spawn_actor_in, spawn_actor_init,
+    spawn_actor_init_with, spawn_actor_on, spawn_actor_owned, spawn_actor_with, Actor, ActorConfig,
+    ActorContext, ActorHandle, Prioritized, Priority, ShutdownReason, TerminationReason, TryActor,
+};
+use std::ops::ControlFlow;
+use std::time::Duration;
 use tokio::sync::oneshot;
 
 define_actor! {
@@ -13,9 +21,8 @@ define_actor! {
         }
 
         @priority(Low)
-        fn Increment(&mut self, ack: oneshot::Sender<()>) {
+        fn Increment(&mut self) {
             self.count += 1;
-            let _ = ack.send(());
         }
 
         @priority(Medium)
@@ -28,132 +35,1246 @@ define_actor! {
 
 #[tokio::test]
 async fn test_actor_explicit_shutdown() {
-    let counter_actor_state = TestCounter { count: 0 };
-    let tx = spawn_actor(counter_actor_state);
+    let counter = TestCounter { count: 0 }.spawn();
 
     println!("\n--- Test: Explicit Shutdown ---");
     for _ in 0..5 {
-        // Create a new channel for each acknowledgment
-        let (ack_tx, _) = oneshot::channel();
-        tx.send(TestCounterMsg::Increment(ack_tx)).await.unwrap();
+        counter.increment().await.unwrap();
     }
 
     // Send a shutdown message
     println!("Sending Shutdown message...");
-    tx.send(TestCounterMsg::Shutdown).await.unwrap();
+    counter.shutdown().await.unwrap();
 
     // Try to send more messages.
-    let (ack_tx, _) = oneshot::channel();
-    let send_res = tx.send(TestCounterMsg::Increment(ack_tx)).await;
-    if send_res.is_err() {
+    let send_res = counter.increment().await;
+    if let Err(err) = send_res {
         println!(
             "Attempted to send message after shutdown, got error: {:?}",
-            send_res.unwrap_err()
+            err
         );
     }
 
-    // Give tasks some time to process remaining messages and shut down.
-    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+    assert_eq!(
+        counter.into_inner().join().await,
+        TerminationReason::ShutdownMessage
+    );
 
     println!("--- Test: Explicit Shutdown complete ---");
 }
 
 #[tokio::test]
 async fn test_actor_priority() {
-    // --- Setup ---
-    let counter_actor_state = TestCounter { count: 0 };
-    let tx = spawn_actor(counter_actor_state);
-    println!("\n--- Test: Actor with Priority and Synchronization ---");
+    let mut harness = ActorHarness::new(TestCounter { count: 0 });
 
-    // --- Phase 1: Send 10 low-priority messages and wait for them to be processed ---
-    println!("Sending 10 Increment messages...");
-    for i in 0..10 {
-        // Create a new channel for each acknowledgment
-        let (ack_tx, ack_rx) = oneshot::channel();
+    let confirmations: Vec<_> = (0..10)
+        .map(|_| harness.send_confirmed(TestCounterMsg::Increment()))
+        .collect();
+    assert_eq!(harness.run_until_idle().await, StepResult::Idle);
+    assert_eq!(harness.actor().count, 10);
+    for confirmation in confirmations {
+        assert_eq!(confirmation.await, Ok(()));
+    }
 
-        // Send the message with the ack sender
-        tx.send(TestCounterMsg::Increment(ack_tx)).await.unwrap();
+    // GetValue is sent last but, being High priority, is handled before both Increments.
+    let mut first = harness.send_confirmed(TestCounterMsg::Increment());
+    let second = harness.send_confirmed(TestCounterMsg::Increment());
+    let (resp_tx, mut resp_rx) = oneshot::channel();
+    harness.send(TestCounterMsg::GetValue(resp_tx));
+    assert_eq!(harness.step().await, StepResult::Handled);
+    assert_eq!(resp_rx.try_recv(), Ok(10));
+    assert_eq!(harness.pending(), 2);
+    assert!(futures::poll!(&mut first).is_pending());
 
-        // Wait for the actor to signal that it has processed the message
-        ack_rx.await.unwrap();
-        println!("  - Increment #{} acknowledged.", i + 1);
+    assert_eq!(harness.run_until_idle().await, StepResult::Idle);
+    assert_eq!(first.await, Ok(()));
+    assert_eq!(second.await, Ok(()));
+    assert_eq!(
+        harness.actor().count,
+        12,
+        "The final count should reflect all 12 increments"
+    );
+}
+
+#[tokio::test]
+async fn test_actor_implicit_shutdown_completes() {
+    let counter_actor_state = TestCounter { count: 0 };
+    let tx = spawn_actor(counter_actor_state);
+
+    println!("\n--- Test: Implicit Shutdown Completes ---");
+    for _ in 0..5 {
+        tx.send(TestCounterMsg::Increment()).await.unwrap();
     }
-    println!("All 10 Increment messages have been processed by the actor.");
 
-    // --- Phase 2: Send a high-priority message and check the state ---
-    println!("Sending high-priority GetValue message...");
     let (resp_tx, resp_rx) = oneshot::channel();
     tx.send(TestCounterMsg::GetValue(resp_tx)).await.unwrap();
+    let count_before_drop = resp_rx.await.unwrap();
+    println!("Count before dropping sender: {}", count_before_drop);
+
+    // Joining drops the last sender, which should signal the actor to shut down.
+    println!("Dropping sender. Waiting for actor tasks to terminate...");
+    let shutdown_timeout =
+        tokio::time::timeout(tokio::time::Duration::from_millis(1000), tx.join()).await;
 
-    // --- Phase 3: Send more low-priority messages concurrently ---
-    // These should be processed *after* GetValue because of its high priority.
-    println!("Sending 2 more low-priority Increment messages...");
-    let (ack_tx_11, ack_rx_11) = oneshot::channel();
-    let (ack_tx_12, ack_rx_12) = oneshot::channel();
-    tx.send(TestCounterMsg::Increment(ack_tx_11)).await.unwrap();
-    tx.send(TestCounterMsg::Increment(ack_tx_12)).await.unwrap();
-
-    // --- Assertions ---
-    // Await the response from GetValue. It should be processed before the last two Increments.
-    let count = resp_rx.await.unwrap();
-    println!("Value received from GetValue: {}", count);
     assert_eq!(
-        count, 10,
-        "GetValue should see the count after the first 10 increments"
+        shutdown_timeout.expect("Actor did not shut down within the timeout."),
+        TerminationReason::SendersDropped
     );
+    println!("--- Test: Implicit Shutdown Completes (Assertion successful) ---");
+}
 
-    // Clean up and verify final state ---
-    // Wait for the final two increments to finish
-    ack_rx_11.await.unwrap();
-    ack_rx_12.await.unwrap();
+define_actor! {
+    Sequence {
+        seen: Vec<usize>,
+    }
+
+    impl SequenceMsg {
+        @priority(Low)
+        fn Record(&mut self, index: usize) {
+            self.seen.push(index);
+        }
+
+        @priority(Low)
+        fn Seen(&mut self, tx: oneshot::Sender<Vec<usize>>) {
+            let _ = tx.send(std::mem::take(&mut self.seen));
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_equal_priority_messages_are_fifo() {
+    let tx = spawn_actor(Sequence { seen: Vec::new() });
+
+    for index in 0..100 {
+        tx.send(SequenceMsg::Record(index)).await.unwrap();
+    }
+    // Same priority as Record, so it is handled only after all of them.
+    let (seen_tx, seen_rx) = oneshot::channel();
+    tx.send(SequenceMsg::Seen(seen_tx)).await.unwrap();
+
+    let seen = seen_rx.await.unwrap();
+    assert_eq!(seen, (0..100).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn test_join_returns_final_actor_state() {
+    let actor = spawn_actor_owned(TestCounter { count: 0 }, ActorConfig::new());
+
+    for _ in 0..10 {
+        let handled = actor.send_confirmed(TestCounterMsg::Increment()).await;
+        handled.unwrap().await.unwrap();
+    }
+    actor.send(TestCounterMsg::Shutdown).await.unwrap();
+
+    let counter = actor.join().await.expect("actor stopped cleanly");
+    assert_eq!(counter.count, 10);
+}
+
+define_actor! {
+    Levels {
+        seen: Vec<Priority>,
+    }
+
+    impl LevelsMsg {
+        @priority(Medium)
+        async fn Hold(&mut self, started: oneshot::Sender<()>, release: oneshot::Receiver<()>) {
+            let _ = started.send(());
+            let _ = release.await;
+        }
 
-    // Check the final state of the actor
-    let (final_resp_tx, final_resp_rx) = oneshot::channel();
-    tx.send(TestCounterMsg::GetValue(final_resp_tx))
+        @priority(Low)
+        fn Bottom(&mut self) {
+            self.seen.push(Priority::Low);
+        }
+
+        @priority(42)
+        fn Between(&mut self) {
+            self.seen.push(Priority::Custom(42));
+        }
+
+        @priority(Medium)
+        fn Middle(&mut self) {
+            self.seen.push(Priority::Medium);
+        }
+
+        @priority(Custom(150))
+        fn Raised(&mut self) {
+            self.seen.push(Priority::Custom(150));
+        }
+
+        @priority(High)
+        fn Top(&mut self) {
+            self.seen.push(Priority::High);
+        }
+
+        @priority(255)
+        fn Highest(&mut self) {
+            self.seen.push(Priority::Custom(255));
+        }
+
+        @priority(0)
+        fn Seen(&mut self, tx: oneshot::Sender<Vec<Priority>>) {
+            let _ = tx.send(std::mem::take(&mut self.seen));
+        }
+    }
+}
+
+#[test]
+fn test_named_priorities_sit_on_the_numeric_scale() {
+    assert_eq!(Priority::Custom(0), Priority::Low);
+    assert_eq!(Priority::Custom(100), Priority::Medium);
+    assert_eq!(Priority::Custom(200), Priority::High);
+    assert!(Priority::Custom(255) < Priority::Shutdown);
+    assert_eq!(Priority::Shutdown.level(), None);
+    assert_eq!(format!("{:?}", Priority::Custom(42)), "Custom(42)");
+    assert_eq!(format!("{:?}", Priority::Custom(200)), "High");
+}
+
+#[tokio::test]
+async fn test_custom_priorities_interleave_with_named_ones() {
+    let tx = spawn_actor(Levels { seen: Vec::new() });
+
+    let (started_tx, started_rx) = oneshot::channel();
+    let (release_tx, release_rx) = oneshot::channel();
+    tx.send(LevelsMsg::Hold(started_tx, release_rx))
         .await
         .unwrap();
-    let final_count = final_resp_rx.await.unwrap();
-    println!("Final actor count: {}", final_count);
+    started_rx.await.unwrap();
+
+    tx.send(LevelsMsg::Bottom()).await.unwrap();
+    tx.send(LevelsMsg::Highest()).await.unwrap();
+    tx.send(LevelsMsg::Middle()).await.unwrap();
+    tx.send(LevelsMsg::Between()).await.unwrap();
+    tx.send(LevelsMsg::Top()).await.unwrap();
+    tx.send(LevelsMsg::Raised()).await.unwrap();
+    // Same level as Bottom, so it is handled last.
+    let (seen_tx, seen_rx) = oneshot::channel();
+    tx.send(LevelsMsg::Seen(seen_tx)).await.unwrap();
+    release_tx.send(()).unwrap();
+
     assert_eq!(
-        final_count, 12,
-        "The final count should reflect all 12 increments"
+        seen_rx.await.unwrap(),
+        [
+            Priority::Custom(255),
+            Priority::High,
+            Priority::Custom(150),
+            Priority::Medium,
+            Priority::Custom(42),
+            Priority::Low,
+        ]
     );
+}
+
+define_actor! {
+    Jobs {
+        done: Vec<&'static str>,
+        urgent: usize,
+    }
 
-    // Drop the sender to allow the actor tasks to gracefully shut down
-    drop(tx);
-    // Give a moment for shutdown messages to print
-    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    impl JobsMsg {
+        @priority_from(if *urgent { High } else { Low })
+        fn Submit(&mut self, urgent: bool, job: &'static str) {
+            self.done.push(job);
+            self.urgent += usize::from(urgent);
+        }
+
+        @priority_from(Custom(*level))
+        fn Leveled(&mut self, level: u8, (job, _): (&'static str, u32)) {
+            self.done.push(job);
+            self.urgent += usize::from(level >= 200);
+        }
+    }
 }
 
 #[tokio::test]
-async fn test_actor_implicit_shutdown_completes() {
-    let counter_actor_state = TestCounter { count: 0 };
-    let tx = spawn_actor(counter_actor_state);
+async fn test_computed_priorities_order_dispatch() {
+    let mut harness = ActorHarness::new(Jobs {
+        done: Vec::new(),
+        urgent: 0,
+    });
+    harness.send(JobsMsg::Submit(false, "report"));
+    harness.send(JobsMsg::Submit(true, "page"));
+    harness.send(JobsMsg::Leveled(150, ("reindex", 0)));
+    harness.send(JobsMsg::Submit(false, "cleanup"));
+    harness.send(JobsMsg::Submit(true, "failover"));
 
-    println!("\n--- Test: Implicit Shutdown Completes ---");
-    for _ in 0..5 {
-        // Create a new channel for each acknowledgment
-        let (ack_tx, _) = oneshot::channel();
-        tx.send(TestCounterMsg::Increment(ack_tx)).await.unwrap();
+    assert_eq!(JobsMsg::Submit(true, "").priority(), Priority::High);
+    assert_eq!(JobsMsg::Leveled(7, ("", 0)).priority(), Priority::Custom(7));
+    assert_eq!(harness.run_until_idle().await, StepResult::Idle);
+    assert_eq!(
+        harness.actor().done,
+        ["page", "failover", "reindex", "report", "cleanup"]
+    );
+    assert_eq!(harness.actor().urgent, 2);
+}
+
+/// Stands in for an event type defined by another crate.
+#[derive(Debug, PartialEq)]
+pub struct FsEvent(&'static str);
+
+define_actor! {
+    Watcher {
+        events: Vec<FsEvent>,
+        ticks: u32,
     }
 
-    let (resp_tx, resp_rx) = oneshot::channel();
-    tx.send(TestCounterMsg::GetValue(resp_tx)).await.unwrap();
-    let count_before_drop = resp_rx.await.unwrap();
-    println!("Count before dropping sender: {}", count_before_drop);
+    impl WatcherMsg {
+        @priority(High) @from
+        fn OnFsEvent(&mut self, event: FsEvent) {
+            self.events.push(event);
+        }
+
+        @from
+        fn Tick(&mut self, ctx: &ActorContext<WatcherMsg>, ticks: u32) {
+            let _ = ctx;
+            self.ticks += ticks;
+        }
+    }
+}
 
-    // Drop the sender. This should signal the actor to shut down.
-    drop(tx);
-    println!("Sender dropped. Waiting for actor tasks to terminate...");
+#[tokio::test]
+async fn test_from_handlers_accept_their_argument_type() {
+    let (handle, mailbox) = channel(ActorConfig::new());
+    handle.send(FsEvent("created").into()).await.unwrap();
+    handle.send(WatcherMsg::from(3)).await.unwrap();
+    handle.send(FsEvent("removed").into()).await.unwrap();
+    drop(handle);
 
-    let shutdown_timeout = tokio::time::timeout(tokio::time::Duration::from_millis(1000), async {
-        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-    })
+    assert_eq!(WatcherMsg::from(FsEvent("")).priority(), Priority::High);
+    let watcher = run_actor(
+        Watcher {
+            events: Vec::new(),
+            ticks: 0,
+        },
+        mailbox,
+    )
     .await;
+    assert_eq!(watcher.events, [FsEvent("created"), FsEvent("removed")]);
+    assert_eq!(watcher.ticks, 3);
+}
+
+/// Fails on every third message; escalates its errors when `strict`.
+struct Flaky {
+    seen: u32,
+    errors: Vec<u32>,
+    strict: bool,
+}
+
+enum FlakyMsg {
+    Work,
+    Report(oneshot::Sender<(u32, Vec<u32>)>),
+}
+
+impl Prioritized for FlakyMsg {}
+
+#[async_trait::async_trait]
+impl TryActor for Flaky {
+    type Msg = FlakyMsg;
+    type Error = u32;
+
+    async fn handle(
+        &mut self,
+        msg: FlakyMsg,
+        _ctx: &ActorContext<FlakyMsg>,
+    ) -> Result<ControlFlow<ShutdownReason>, u32> {
+        match msg {
+            FlakyMsg::Work => {
+                self.seen += 1;
+                if self.seen.is_multiple_of(3) {
+                    return Err(self.seen);
+                }
+            }
+            FlakyMsg::Report(tx) => {
+                let _ = tx.send((self.seen, self.errors.clone()));
+            }
+        }
+        Ok(ControlFlow::Continue(()))
+    }
+
+    async fn on_error(
+        &mut self,
+        err: u32,
+        _ctx: &ActorContext<FlakyMsg>,
+    ) -> ControlFlow<ShutdownReason> {
+        self.errors.push(err);
+        if self.strict {
+            ControlFlow::Break(ShutdownReason::Failed)
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_try_actor_recovers_from_errors() {
+    let handle = spawn_actor(Flaky {
+        seen: 0,
+        errors: Vec::new(),
+        strict: false,
+    });
+    for _ in 0..10 {
+        handle.send(FlakyMsg::Work).await.unwrap();
+    }
+    let (tx, rx) = oneshot::channel();
+    handle.send(FlakyMsg::Report(tx)).await.unwrap();
+
+    assert_eq!(rx.await.unwrap(), (10, vec![3, 6, 9]));
+    assert!(!handle.is_closed());
+}
+
+#[tokio::test]
+async fn test_try_actor_escalation_stops_the_actor() {
+    let handle = spawn_actor(Flaky {
+        seen: 0,
+        errors: Vec::new(),
+        strict: true,
+    });
+    for _ in 0..5 {
+        let _ = handle.send(FlakyMsg::Work).await;
+    }
+
+    assert_eq!(handle.join().await, TerminationReason::HandlerFailed);
+}
+
+/// Uses the default `on_error`, which logs and carries on.
+struct Lenient;
+
+#[async_trait::async_trait]
+impl TryActor for Lenient {
+    type Msg = FlakyMsg;
+    type Error = &'static str;
+
+    async fn handle(
+        &mut self,
+        msg: FlakyMsg,
+        _ctx: &ActorContext<FlakyMsg>,
+    ) -> Result<ControlFlow<ShutdownReason>, &'static str> {
+        match msg {
+            FlakyMsg::Work => Err("always fails"),
+            FlakyMsg::Report(tx) => {
+                let _ = tx.send((0, Vec::new()));
+                Ok(ControlFlow::Continue(()))
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_default_on_error_continues() {
+    let handle = spawn_actor(Lenient);
+    handle.send(FlakyMsg::Work).await.unwrap();
+    let (tx, rx) = oneshot::channel();
+    handle.send(FlakyMsg::Report(tx)).await.unwrap();
+    assert!(rx.await.is_ok());
+}
+
+define_actor! {
+    Lifecycle {
+        events: Vec<&'static str>,
+    }
+
+    impl LifecycleMsg {
+        @on_start
+        async fn open(&mut self) {
+            tokio::task::yield_now().await;
+            self.events.push("start");
+        }
+
+        @on_stop
+        fn flush(&mut self) {
+            self.events.push("stop");
+        }
+
+        @priority(Medium)
+        fn Work(&mut self, ack: oneshot::Sender<()>) {
+            self.events.push("work");
+            let _ = ack.send(());
+        }
+    }
+}
+
+async fn work(handle: &ActorHandle<LifecycleMsg>) {
+    let (ack_tx, ack_rx) = oneshot::channel();
+    handle.send(LifecycleMsg::Work(ack_tx)).await.unwrap();
+    ack_rx.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_hooks_bracket_handlers_on_explicit_shutdown() {
+    let actor = spawn_actor_owned(Lifecycle { events: Vec::new() }, ActorConfig::new());
+    work(&actor).await;
+    work(&actor).await;
+    actor.send(LifecycleMsg::Shutdown).await.unwrap();
+
+    let lifecycle = actor.join().await.expect("actor stopped cleanly");
+    assert_eq!(lifecycle.events, ["start", "work", "work", "stop"]);
+}
+
+#[tokio::test]
+async fn test_hooks_bracket_handlers_when_senders_drop() {
+    let actor = spawn_actor_owned(Lifecycle { events: Vec::new() }, ActorConfig::new());
+    work(&actor).await;
+
+    let lifecycle = actor.join().await.expect("actor stopped cleanly");
+    assert_eq!(lifecycle.events, ["start", "work", "stop"]);
+}
+
+define_actor! {
+    Uploader {
+        pending: u32,
+        flushed: Option<oneshot::Sender<u32>>,
+        events: Vec<&'static str>,
+    }
+
+    impl UploaderMsg {
+        @on_shutdown
+        async fn flush(&mut self) {
+            tokio::task::yield_now().await;
+            self.events.push("shutdown");
+            if let Some(tx) = self.flushed.take() {
+                let _ = tx.send(self.pending);
+            }
+        }
+
+        @on_stop
+        fn close(&mut self) {
+            self.events.push("stop");
+        }
+
+        fn Queue(&mut self) {
+            self.pending += 1;
+        }
+    }
+}
+
+fn uploader(flushed: oneshot::Sender<u32>) -> Uploader {
+    Uploader {
+        pending: 0,
+        flushed: Some(flushed),
+        events: Vec::new(),
+    }
+}
+
+#[tokio::test]
+async fn test_on_shutdown_runs_before_on_stop_on_explicit_shutdown() {
+    let (tx, rx) = oneshot::channel();
+    let actor = spawn_actor_owned(uploader(tx), ActorConfig::new());
+    actor.send(UploaderMsg::Queue()).await.unwrap();
+    actor.send(UploaderMsg::Queue()).await.unwrap();
+    actor.await_idle().await;
+    actor.send(UploaderMsg::Shutdown).await.unwrap();
+
+    assert_eq!(rx.await, Ok(2));
+    let uploader = actor.join().await.expect("actor stopped cleanly");
+    assert_eq!(uploader.events, ["shutdown", "stop"]);
+}
+
+#[tokio::test]
+async fn test_on_shutdown_is_skipped_when_senders_drop() {
+    let (tx, rx) = oneshot::channel();
+    let actor = spawn_actor_owned(uploader(tx), ActorConfig::new());
+    actor.send(UploaderMsg::Queue()).await.unwrap();
+
+    let uploader = actor.join().await.expect("actor stopped cleanly");
+    assert_eq!(uploader.events, ["stop"]);
+    drop(uploader);
+    assert!(rx.await.is_err());
+}
+
+define_actor! {
+    Journal {
+        balance: i64,
+        log: Vec<&'static str>,
+    }
+
+    impl JournalMsg {
+        fn Deposit(&mut self, amount: i64) {
+            self.balance += amount;
+            self.log.push("deposit");
+        }
+
+        /// Runs arbitrary code against the ledger.
+        @priority(High)
+        extern Apply(Box<dyn FnOnce(&mut Self) + Send>) => |self, f| f(self),
+
+        extern Audit(&'static str, oneshot::Sender<i64>) => |self, note, tx| {
+            self.log.push(note);
+            let _ = tx.send(self.balance);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_extern_variants_run_their_handler() {
+    let actor = spawn_actor_owned(
+        Journal {
+            balance: 0,
+            log: Vec::new(),
+        },
+        ActorConfig::new(),
+    );
+    let apply = JournalMsg::Apply(Box::new(|journal: &mut Journal| {
+        journal.balance *= 10;
+        journal.log.push("apply");
+    }));
+    assert_eq!(apply.priority(), Priority::High);
+    assert_eq!(apply.variant_name(), "Apply");
+    actor.send(JournalMsg::Deposit(5)).await.unwrap();
+    actor.await_idle().await;
+    actor.send(apply).await.unwrap();
+    let (tx, rx) = oneshot::channel();
+    let audit = JournalMsg::Audit("audit", tx);
+    assert_eq!(audit.priority(), Priority::Medium);
+    actor.send(audit).await.unwrap();
+
+    assert_eq!(rx.await, Ok(50));
+    let journal = actor.join().await.expect("actor stopped cleanly");
+    assert_eq!(journal.log, ["deposit", "apply", "audit"]);
+}
+
+define_actor! {
+    Session {
+        farewell: Option<oneshot::Sender<String>>,
+    }
+
+    impl SessionMsg {
+        @on_shutdown
+        fn say_goodbye(&mut self, reason: String) {
+            if let Some(tx) = self.farewell.take() {
+                let _ = tx.send(reason);
+            }
+        }
+
+        fn Ping(&mut self) {}
+    }
+}
+
+#[tokio::test]
+async fn test_shutdown_carries_the_on_shutdown_argument() {
+    let (tx, rx) = oneshot::channel();
+    let actor = spawn_actor(Session { farewell: Some(tx) });
+    let shutdown = SessionMsg::Shutdown("maintenance".to_owned());
+    assert_eq!(shutdown.priority(), Priority::Shutdown);
+    assert_eq!(shutdown.variant_name(), "Shutdown");
+    actor.send(SessionMsg::Ping()).await.unwrap();
+    actor.send(shutdown).await.unwrap();
+
+    assert_eq!(rx.await.unwrap(), "maintenance");
+    assert_eq!(actor.join().await, TerminationReason::ShutdownMessage);
+}
+
+define_actor! {
+    Defaulted {
+        seen: Vec<&'static str>,
+    }
+
+    impl DefaultedMsg {
+        async fn Hold(&mut self, started: oneshot::Sender<()>, release: oneshot::Receiver<()>) {
+            let _ = started.send(());
+            let _ = release.await;
+        }
+
+        @priority(Low)
+        fn Background(&mut self) {
+            self.seen.push("low");
+        }
+
+        fn Normal(&mut self) {
+            self.seen.push("medium");
+        }
+
+        @priority(High)
+        fn Urgent(&mut self) {
+            self.seen.push("high");
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_unannotated_handlers_dispatch_at_medium() {
+    let actor = spawn_actor_owned(Defaulted { seen: Vec::new() }, ActorConfig::new());
+    let (started_tx, started_rx) = oneshot::channel();
+    let (release_tx, release_rx) = oneshot::channel();
+    actor
+        .send(DefaultedMsg::Hold(started_tx, release_rx))
+        .await
+        .unwrap();
+    started_rx.await.unwrap();
+
+    actor.send(DefaultedMsg::Background()).await.unwrap();
+    actor.send(DefaultedMsg::Normal()).await.unwrap();
+    actor.send(DefaultedMsg::Urgent()).await.unwrap();
+    drop(release_tx);
+
+    let defaulted = actor.join().await.expect("actor stopped cleanly");
+    assert_eq!(defaulted.seen, ["high", "medium", "low"]);
+}
+
+define_actor! {
+    Cache<K, V> where K: std::hash::Hash + Eq, V: Clone {
+        entries: std::collections::HashMap<K, V>,
+    }
+
+    impl CacheMsg<K, V> {
+        fn Put(&mut self, key: K, value: V) {
+            self.entries.insert(key, value);
+        }
+
+        fn Get(&mut self, key: K, tx: oneshot::Sender<Option<V>>) {
+            let _ = tx.send(self.entries.get(&key).cloned());
+        }
+    }
+}
+
+async fn cache_round_trip<K, V>(key: K, value: V) -> Option<V>
+where
+    K: std::hash::Hash + Eq + Clone + Send + 'static,
+    V: Clone + Send + 'static,
+{
+    let cache = spawn_actor(Cache {
+        entries: std::collections::HashMap::new(),
+    });
+    cache.send(CacheMsg::Put(key.clone(), value)).await.unwrap();
+    let (tx, rx) = oneshot::channel();
+    cache.send(CacheMsg::Get(key, tx)).await.unwrap();
+    rx.await.unwrap()
+}
+
+#[tokio::test]
+async fn test_generic_actor_with_two_parameterizations() {
+    assert_eq!(
+        cache_round_trip("answer".to_string(), 42u32).await,
+        Some(42)
+    );
+    assert_eq!(
+        cache_round_trip(7u64, vec!["seven"]).await,
+        Some(vec!["seven"])
+    );
+}
+
+define_actor! {
+    Configured {
+        limit: usize,
+        count: i32 = 5,
+        name: String = String::from("worker"),
+    }
+
+    impl ConfiguredMsg {
+        fn Describe(&mut self, tx: oneshot::Sender<(usize, i32, String)>) {
+            let _ = tx.send((self.limit, self.count, self.name.clone()));
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_generated_constructor_applies_defaults() {
+    let configured = Configured::new(10);
+    assert_eq!(
+        (configured.limit, configured.count, configured.name.as_str()),
+        (10, 5, "worker")
+    );
+
+    let built = Configured::new(10).with_count(-1).with_limit(3);
+    assert_eq!(
+        (built.limit, built.count, built.name.as_str()),
+        (3, -1, "worker")
+    );
+
+    let handle = spawn_actor(built.with_name("renamed".to_string()));
+    let (tx, rx) = oneshot::channel();
+    handle.send(ConfiguredMsg::Describe(tx)).await.unwrap();
+    assert_eq!(rx.await.unwrap(), (3, -1, "renamed".to_string()));
+}
+
+define_actor! {
+    Ledger {
+        balance: i64,
+    }
+
+    impl LedgerMsg {
+        fn Deposit(&mut self, amount: i64) {
+            self.balance += amount;
+        }
+
+        fn Balance(&self, tx: oneshot::Sender<i64>) {
+            let _ = tx.send(self.balance);
+        }
+
+        async fn BalanceLater(&self, tx: oneshot::Sender<i64>) {
+            tokio::task::yield_now().await;
+            let _ = tx.send(self.balance);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_shared_and_exclusive_handlers_mix() {
+    let ledger = spawn_actor(Ledger { balance: 0 });
+    ledger.send(LedgerMsg::Deposit(5)).await.unwrap();
+    let (tx, rx) = oneshot::channel();
+    ledger.send(LedgerMsg::Balance(tx)).await.unwrap();
+    assert_eq!(rx.await.unwrap(), 5);
+
+    ledger.send(LedgerMsg::Deposit(-2)).await.unwrap();
+    let (tx, rx) = oneshot::channel();
+    ledger.send(LedgerMsg::BalanceLater(tx)).await.unwrap();
+    assert_eq!(rx.await.unwrap(), 3);
+
+    // The generated methods keep their receivers.
+    let readonly = Ledger { balance: 9 };
+    let (tx, rx) = oneshot::channel();
+    readonly.Balance(tx);
+    assert_eq!(rx.await.unwrap(), 9);
+}
+
+define_actor! {
+    Replying {
+        count: i32,
+    }
+
+    impl ReplyingMsg {
+        fn Add(&mut self, by: i32) {
+            self.count += by;
+        }
+
+        @priority(High)
+        fn GetValue(&self) -> i32 {
+            self.count
+        }
+
+        async fn ScaledBy(&self, factor: i32) -> i64 {
+            tokio::task::yield_now().await;
+            i64::from(self.count * factor)
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_handlers_with_return_types_reply() {
+    let handle = spawn_actor(Replying { count: 0 });
+    handle.send(ReplyingMsg::Add(4)).await.unwrap();
+
+    let (msg, reply) = ReplyingMsg::scaled_by(3);
+    handle.send(msg).await.unwrap();
+    assert_eq!(reply.await.unwrap(), 12);
+
+    let (msg, reply) = ReplyingMsg::get_value();
+    handle.send(msg).await.unwrap();
+    assert_eq!(reply.await.unwrap(), 4);
+}
+
+#[tokio::test]
+async fn test_dropped_reply_receiver_does_not_stop_the_actor() {
+    let actor = spawn_actor_owned(Replying { count: 1 }, ActorConfig::new());
+    let (msg, reply) = ReplyingMsg::get_value();
+    drop(reply);
+    actor.send(msg).await.unwrap();
+    actor.send(ReplyingMsg::Add(1)).await.unwrap();
+
+    let replying = actor.join().await.expect("actor stopped cleanly");
+    assert_eq!(replying.count, 2);
+}
+
+#[tokio::test]
+async fn test_typed_handle_sends_and_waits_for_replies() {
+    let handle = Replying { count: 0 }.spawn();
+    handle.add(4).await.unwrap();
+    assert_eq!(handle.scaled_by(3).await, Ok(12));
+    assert_eq!(handle.get_value().await, Ok(4));
+
+    handle.shutdown().await.unwrap();
+    handle.inner().clone().join().await;
+    assert!(handle.get_value().await.unwrap_err().is_closed());
+    assert!(matches!(
+        handle.add(1).await.unwrap_err().into_returned(),
+        Some(ReplyingMsg::Add(1))
+    ));
+}
+
+/// Naps for as long as each message asks and records the priorities of timed-out handlers.
+struct Sleepy {
+    naps: u32,
+    timed_out: Vec<Priority>,
+}
 
+#[derive(crate::Prioritized)]
+enum SleepyMsg {
+    #[priority(Low)]
+    Nap(u64),
+    #[priority(High)]
+    UrgentNap(u64),
+    #[priority(High)]
+    Report(oneshot::Sender<(u32, Vec<Priority>)>),
+}
+
+#[async_trait::async_trait]
+impl Actor for Sleepy {
+    type Msg = SleepyMsg;
+
+    async fn handle(
+        &mut self,
+        msg: SleepyMsg,
+        _ctx: &ActorContext<SleepyMsg>,
+    ) -> ControlFlow<ShutdownReason> {
+        match msg {
+            SleepyMsg::Nap(ms) | SleepyMsg::UrgentNap(ms) => {
+                tokio::time::sleep(Duration::from_millis(ms)).await;
+                self.naps += 1;
+            }
+            SleepyMsg::Report(tx) => {
+                let _ = tx.send((self.naps, self.timed_out.clone()));
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    async fn on_timeout(&mut self, priority: Priority) -> ControlFlow<ShutdownReason> {
+        self.timed_out.push(priority);
+        ControlFlow::Continue(())
+    }
+}
+
+fn sleepy() -> Sleepy {
+    Sleepy {
+        naps: 0,
+        timed_out: Vec::new(),
+    }
+}
+
+async fn naps(handle: &ActorHandle<SleepyMsg>) -> (u32, Vec<Priority>) {
+    let (tx, rx) = oneshot::channel();
+    handle.send(SleepyMsg::Report(tx)).await.unwrap();
+    rx.await.unwrap()
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_handler_timeout_cancels_slow_handlers() {
+    let config = ActorConfig::new().handler_timeout(Duration::from_millis(100));
+    let handle = spawn_actor_with(sleepy(), config);
+    handle.send(SleepyMsg::Nap(10)).await.unwrap();
+    handle.send(SleepyMsg::Nap(60_000)).await.unwrap();
+    handle.send(SleepyMsg::Nap(10)).await.unwrap();
+    handle.await_idle().await;
+
+    assert_eq!(naps(&handle).await, (2, vec![Priority::Low]));
+    #[cfg(feature = "metrics")]
+    {
+        use crate::MetricsExt;
+        assert_eq!(handle.metrics().messages_timed_out(), 1);
+        assert_eq!(handle.metrics().messages_handled(), 4);
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_handler_timeout_for_a_priority_overrides_the_default() {
+    let config = ActorConfig::new()
+        .handler_timeout(Duration::from_secs(10))
+        .handler_timeout_for(Priority::High, Duration::from_millis(100));
+    let handle = spawn_actor_with(sleepy(), config);
+    handle.send(SleepyMsg::UrgentNap(1_000)).await.unwrap();
+    handle.send(SleepyMsg::Nap(1_000)).await.unwrap();
+    handle.send(SleepyMsg::Nap(60_000)).await.unwrap();
+    handle.await_idle().await;
+
+    assert_eq!(
+        naps(&handle).await,
+        (1, vec![Priority::High, Priority::Low])
+    );
+}
+
+#[test]
+fn test_spawn_actor_on_a_runtime_from_a_plain_thread() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+        .unwrap();
+    let actor = spawn_actor_on(
+        TestCounter { count: 0 },
+        ActorConfig::new(),
+        runtime.handle(),
+    );
+    for _ in 0..3 {
+        let handled = runtime.block_on(actor.send_confirmed(TestCounterMsg::Increment()));
+        runtime.block_on(handled.unwrap()).unwrap();
+    }
+
+    let counter = runtime
+        .block_on(actor.join())
+        .expect("actor stopped cleanly");
+    assert_eq!(counter.count, 3);
+}
+
+#[tokio::test]
+async fn test_aborting_the_join_set_stops_the_actor() {
+    let mut tasks = tokio::task::JoinSet::new();
+    let handle = spawn_actor_in(TestCounter { count: 0 }, ActorConfig::new(), &mut tasks);
+    let handled = handle.send_confirmed(TestCounterMsg::Increment()).await;
+    handled.unwrap().await.unwrap();
+    assert_eq!(tasks.len(), 1);
+
+    tasks.abort_all();
+    while let Some(result) = tasks.join_next().await {
+        assert!(result.unwrap_err().is_cancelled());
+    }
+    assert!(handle.is_closed());
+    assert_eq!(handle.join().await, TerminationReason::Aborted);
+}
+
+#[tokio::test]
+async fn test_run_actor_returns_the_actor_after_shutdown() {
+    let (handle, mailbox) = channel(ActorConfig::new());
+    let client = tokio::spawn(async move {
+        for _ in 0..3 {
+            let handled = handle.send_confirmed(TestCounterMsg::Increment()).await;
+            handled.unwrap().await.unwrap();
+        }
+        handle.send(TestCounterMsg::Shutdown).await.unwrap();
+    });
+
+    let counter = tokio::time::timeout(
+        Duration::from_secs(1),
+        run_actor(TestCounter { count: 0 }, mailbox),
+    )
+    .await
+    .expect("actor stopped on shutdown");
+    assert_eq!(counter.count, 3);
+    client.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_run_actor_can_be_cancelled_from_select() {
+    let (handle, mailbox) = channel(ActorConfig::new());
+    let (cancel_tx, cancel_rx) = oneshot::channel::<()>();
+    let handled = handle
+        .send_confirmed(TestCounterMsg::Increment())
+        .await
+        .unwrap();
+
+    let ran = tokio::spawn(async move {
+        tokio::select! {
+            _ = run_actor(TestCounter { count: 0 }, mailbox) => false,
+            _ = cancel_rx => true,
+        }
+    });
+    handled.await.unwrap();
+    cancel_tx.send(()).unwrap();
+    assert!(ran.await.unwrap());
+    assert_eq!(handle.join().await, TerminationReason::Aborted);
+}
+
+/// Records the ids of every batch it is handed; single messages count as batches of one.
+#[derive(Default)]
+struct Batcher {
+    batches: Vec<Vec<u32>>,
+}
+
+#[derive(crate::Prioritized)]
+enum BatchMsg {
+    #[priority(Low)]
+    Low(u32),
+    #[priority(High)]
+    High(u32),
+    // Blocks the actor until released.
+    #[priority(Medium)]
+    Hold(u32, oneshot::Sender<()>, oneshot::Receiver<()>),
+    #[priority(Shutdown)]
+    Shutdown,
+}
+
+impl Batcher {
+    async fn take(&mut self, msg: BatchMsg) -> Option<u32> {
+        match msg {
+            BatchMsg::Low(id) | BatchMsg::High(id) => Some(id),
+            BatchMsg::Hold(id, started, release) => {
+                let _ = started.send(());
+                let _ = release.await;
+                Some(id)
+            }
+            BatchMsg::Shutdown => None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for Batcher {
+    type Msg = BatchMsg;
+
+    async fn handle(
+        &mut self,
+        msg: BatchMsg,
+        _ctx: &ActorContext<BatchMsg>,
+    ) -> ControlFlow<ShutdownReason> {
+        match self.take(msg).await {
+            Some(id) => self.batches.push(vec![id]),
+            None => return ControlFlow::Break(ShutdownReason::Requested),
+        }
+        ControlFlow::Continue(())
+    }
+
+    async fn handle_batch(
+        &mut self,
+        msgs: Vec<BatchMsg>,
+        _ctx: &ActorContext<BatchMsg>,
+    ) -> ControlFlow<ShutdownReason> {
+        let mut batch = Vec::new();
+        for msg in msgs {
+            batch.extend(self.take(msg).await);
+        }
+        self.batches.push(batch);
+        ControlFlow::Continue(())
+    }
+}
+
+/// Sends a `Hold` and waits until the actor is blocked on it.
+async fn hold(handle: &ActorHandle<BatchMsg>, id: u32) -> oneshot::Sender<()> {
+    let (started_tx, started_rx) = oneshot::channel();
+    let (release_tx, release_rx) = oneshot::channel();
+    handle
+        .send(BatchMsg::Hold(id, started_tx, release_rx))
+        .await
+        .unwrap();
+    let _ = started_rx.await;
+    release_tx
+}
+
+#[tokio::test]
+async fn test_waiting_messages_are_batched_highest_priority_first() {
+    let actor = spawn_actor_owned(Batcher::default(), ActorConfig::new().batch_size(3));
+    let release = hold(&actor, 0).await;
+    for msg in [
+        BatchMsg::Low(1),
+        BatchMsg::High(2),
+        BatchMsg::Low(3),
+        BatchMsg::High(4),
+        BatchMsg::Low(5),
+    ] {
+        actor.send(msg).await.unwrap();
+    }
+    drop(release);
+
+    let batcher = actor.join().await.unwrap();
+    assert_eq!(batcher.batches, [vec![0], vec![2, 4, 1], vec![3, 5]]);
+}
+
+#[tokio::test]
+async fn test_shutdown_during_a_batch_stops_the_actor_after_it() {
+    let actor = spawn_actor_owned(Batcher::default(), ActorConfig::new().batch_size(8));
+    let handle = actor.handle().clone();
+    let release = hold(&actor, 0).await;
+    let (started_tx, started_rx) = oneshot::channel();
+    let (release_tx, release_rx) = oneshot::channel();
+    actor
+        .send(BatchMsg::Hold(1, started_tx, release_rx))
+        .await
+        .unwrap();
+    actor.send(BatchMsg::Low(2)).await.unwrap();
+    drop(release);
+
+    // The batch is blocked on its `Hold`.
+    started_rx.await.unwrap();
+    actor.send(BatchMsg::Shutdown).await.unwrap();
+    actor.send(BatchMsg::Low(3)).await.unwrap();
+    drop(release_tx);
+
+    let batcher = actor.join().await.unwrap();
+    assert_eq!(batcher.batches, [vec![0], vec![1, 2]]);
+    assert_eq!(handle.join().await, TerminationReason::ShutdownMessage);
+}
+
+#[tokio::test]
+async fn test_default_handle_batch_hands_each_message_to_handle() {
+    let config = ActorConfig::new().batch_size(4);
+    let counter = spawn_actor_owned(TestCounter { count: 0 }, config);
+    let mut confirmations = Vec::new();
+    for _ in 0..10 {
+        let handled = counter.send_confirmed(TestCounterMsg::Increment()).await;
+        confirmations.push(handled.unwrap());
+    }
+    for confirmation in confirmations {
+        assert_eq!(confirmation.await, Ok(()));
+    }
+    assert_eq!(counter.join().await.unwrap().count, 10);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_messages_sent_during_init_are_handled_once_it_finishes() {
+    let handle = spawn_actor_init(|| async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        Ok::<_, String>(Sequence { seen: Vec::new() })
+    });
+    for index in 0..5 {
+        handle.send(SequenceMsg::Record(index)).await.unwrap();
+    }
+
+    let (seen_tx, seen_rx) = oneshot::channel();
+    handle.send(SequenceMsg::Seen(seen_tx)).await.unwrap();
+    assert_eq!(seen_rx.await.unwrap(), [0, 1, 2, 3, 4]);
+}
+
+#[tokio::test]
+async fn test_failed_init_stops_the_actor() {
+    let (dead_tx, mut dead_rx) = tokio::sync::mpsc::unbounded_channel();
+    let (opened_tx, opened_rx) = oneshot::channel::<()>();
+    let handle = spawn_actor_init_with(
+        || async move {
+            let _ = opened_rx.await;
+            Err::<Sequence, _>("connection refused")
+        },
+        ActorConfig::new().dead_letters(Sink::unbounded(dead_tx)),
+    );
+    handle.send(SequenceMsg::Record(7)).await.unwrap();
+    drop(opened_tx);
+
+    assert_eq!(handle.clone().join().await, TerminationReason::InitFailed);
+    assert!(handle.send(SequenceMsg::Record(8)).await.is_err());
+    assert!(matches!(dead_rx.recv().await, Some(SequenceMsg::Record(7))));
+}
+
+define_actor! {
+    /// Stands in for CPU-heavy work by blocking its thread.
+    Encoder {
+        encoded: u32,
+    }
+
+    impl EncoderMsg {
+        @blocking
+        @priority(Medium)
+        fn Encode(&mut self, ctx: &ActorContext<EncoderMsg>, millis: u64) -> (u32, bool) {
+            std::thread::sleep(Duration::from_millis(millis));
+            self.encoded += 1;
+            (self.encoded, ctx.priority() == Some(Priority::Medium))
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_blocking_handler_leaves_the_worker_to_other_actors() {
+    let encoder = spawn_actor(Encoder { encoded: 0 });
+    let other = spawn_actor(Sequence { seen: Vec::new() });
+    let (msg, encoded) = EncoderMsg::encode(400);
+    encoder.send(msg).await.unwrap();
+    // Let the encoder take the only worker thread. The timers wait for a worker too, so
+    // this sleeps the test's own thread instead.
+    std::thread::sleep(Duration::from_millis(50));
+
+    let started = std::time::Instant::now();
+    other.send(SequenceMsg::Record(1)).await.unwrap();
+    let (seen_tx, seen_rx) = oneshot::channel();
+    other.send(SequenceMsg::Seen(seen_tx)).await.unwrap();
+    assert_eq!(seen_rx.await.unwrap(), [1]);
     assert!(
-        shutdown_timeout.is_ok(),
-        "Actor did not shut down within the timeout."
+        started.elapsed() < Duration::from_millis(200),
+        "the other actor waited {:?}",
+        started.elapsed()
     );
-    println!("--- Test: Implicit Shutdown Completes (Assertion successful) ---");
+    assert_eq!(encoded.await.unwrap(), (1, true));
+}
+
+#[tokio::test]
+async fn test_blocking_handler_runs_inline_on_a_current_thread_runtime() {
+    let encoder = spawn_actor(Encoder { encoded: 0 });
+    let (msg, encoded) = EncoderMsg::encode(1);
+    encoder.send(msg).await.unwrap();
+    assert_eq!(encoded.await.unwrap(), (1, true));
 }
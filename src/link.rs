@@ -0,0 +1,125 @@
+use crate::mailbox::WeakMailboxSender;
+use crate::{spawn_actor, Actor, ActorHandle, Prioritized, TerminationReason};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// The actors linked to a parent, stopped when the parent stops.
+pub(crate) struct Children {
+    // `None` once the parent has stopped; anything linked after that stops right away.
+    linked: Mutex<Option<Vec<Box<dyn Child>>>>,
+}
+
+/// A linked actor, whatever its message type.
+trait Child: Send + Sync {
+    fn halt(&self);
+
+    fn is_stopped(&self) -> bool;
+}
+
+impl<M: Send> Child for WeakMailboxSender<M> {
+    fn halt(&self) {
+        WeakMailboxSender::halt(self)
+    }
+
+    fn is_stopped(&self) -> bool {
+        !self.is_open()
+    }
+}
+
+impl Children {
+    pub(crate) fn new() -> Self {
+        Children {
+            linked: Mutex::new(Some(Vec::new())),
+        }
+    }
+
+    fn add(&self, child: Box<dyn Child>) {
+        let mut linked = self.linked.lock().unwrap_or_else(PoisonError::into_inner);
+        match linked.as_mut() {
+            Some(children) => {
+                // Forget children that are gone, so a long-lived parent does not pile them up.
+                children.retain(|child| !child.is_stopped());
+                children.push(child);
+            }
+            None => {
+                drop(linked);
+                child.halt();
+            }
+        }
+    }
+
+    /// Stops every linked child. Called once the parent's processor is done; later links
+    /// stop their child right away.
+    pub(crate) fn stop_all(&self) {
+        let linked = self
+            .linked
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        for child in linked.into_iter().flatten() {
+            child.halt();
+        }
+    }
+
+    /// Stops every linked child when dropped, so they stop even if the parent's processor
+    /// task is aborted or panics.
+    pub(crate) fn guard(self: &Arc<Self>) -> ChildrenGuard {
+        ChildrenGuard(Arc::clone(self))
+    }
+}
+
+pub(crate) struct ChildrenGuard(Arc<Children>);
+
+impl ChildrenGuard {
+    pub(crate) fn stop_all(&self) {
+        self.0.stop_all();
+    }
+}
+
+impl Drop for ChildrenGuard {
+    fn drop(&mut self) {
+        self.0.stop_all();
+    }
+}
+
+impl<M: Prioritized + Send + 'static> ActorHandle<M> {
+    /// Spawns `actor` like [`spawn_actor`], [linked](Self::link) to this actor so that it
+    /// stops when this actor does.
+    pub fn spawn_child<A: Actor>(&self, actor: A) -> ActorHandle<A::Msg> {
+        let child = spawn_actor(actor);
+        self.link(&child);
+        child
+    }
+
+    /// Links `child` to this actor: once this actor stops, for whatever reason, `child`
+    /// stops too.
+    ///
+    /// `child` finishes the message it is handling, if any, and then stops with
+    /// [`TerminationReason::ParentStopped`] without handling anything else; its remaining
+    /// messages go to its dead-letter sink, if it has one. Sends to it fail from then on,
+    /// however many handles to it are left. A child linked after this actor has stopped
+    /// is stopped right away. The link does not keep `child` alive.
+    pub fn link<C: Send + 'static>(&self, child: &ActorHandle<C>) {
+        self.shared.children.add(Box::new(child.tx.downgrade()));
+    }
+
+    /// Like [`link`](Self::link), also sending this actor the message `notice` builds once
+    /// `child` stops, for whatever reason.
+    ///
+    /// The notice is sent from a task on the current runtime and is not sent if this actor
+    /// has stopped by then; waiting for it does not keep this actor alive.
+    pub fn link_with_notice<C, F>(&self, child: &ActorHandle<C>, notice: F)
+    where
+        C: Send + 'static,
+        F: FnOnce(TerminationReason) -> M + Send + 'static,
+    {
+        self.link(child);
+        let stopped = child.shared.termination();
+        let parent = self.downgrade();
+        tokio::spawn(async move {
+            let reason = stopped.await;
+            if let Some(parent) = parent.upgrade() {
+                let _ = parent.send(notice(reason)).await;
+            }
+        });
+    }
+}
@@ -0,0 +1,106 @@
+use crate::prelude::*;
+use crate::test_support::{stall, Gated, GatedMsg};
+use crate::{spawn_actor_in, ActorConfig, TerminationReason};
+use tokio::sync::oneshot;
+use tokio::task::JoinSet;
+
+define_actor! {
+    Manager {
+        stopped: Vec<TerminationReason>,
+    }
+
+    impl ManagerMsg {
+        @priority(Medium)
+        fn ChildStopped(&mut self, reason: TerminationReason) {
+            self.stopped.push(reason);
+        }
+
+        @priority(Medium)
+        fn Stopped(&mut self, tx: oneshot::Sender<Vec<TerminationReason>>) {
+            let _ = tx.send(self.stopped.clone());
+        }
+    }
+}
+
+fn manager() -> Manager {
+    Manager {
+        stopped: Vec::new(),
+    }
+}
+
+#[tokio::test]
+async fn test_children_stop_with_their_parent() {
+    let parent = spawn_actor(manager());
+    let children: Vec<_> = (0..3).map(|_| parent.spawn_child(Gated::new())).collect();
+    for child in &children {
+        child.send(GatedMsg::Fill()).await.unwrap();
+    }
+
+    parent.send(ManagerMsg::Shutdown).await.unwrap();
+    assert_eq!(parent.join().await, TerminationReason::ShutdownMessage);
+    // The test still holds a handle to every child; the link stops them anyway.
+    for child in children {
+        assert!(child.is_closed());
+        assert_eq!(child.join().await, TerminationReason::ParentStopped);
+    }
+}
+
+#[tokio::test]
+async fn test_child_finishes_its_current_message_and_drops_the_rest() {
+    let parent = spawn_actor(manager());
+    let child = spawn_actor_owned(Gated::new(), ActorConfig::new());
+    parent.link(child.handle());
+    let release = stall(&child).await;
+    child.send(GatedMsg::Fill()).await.unwrap();
+
+    parent.send(ManagerMsg::Shutdown).await.unwrap();
+    assert_eq!(parent.join().await, TerminationReason::ShutdownMessage);
+    assert!(child.send(GatedMsg::Fill()).await.is_err());
+    drop(release);
+
+    let gated = child.join().await.expect("child stopped cleanly");
+    assert!(gated.handled.is_empty());
+}
+
+#[tokio::test]
+async fn test_aborting_the_parent_stops_its_children() {
+    let mut tasks = JoinSet::new();
+    let parent = spawn_actor_in(manager(), ActorConfig::new(), &mut tasks);
+    let child = parent.spawn_child(Gated::new());
+
+    tasks.abort_all();
+    assert_eq!(parent.join().await, TerminationReason::Aborted);
+    assert_eq!(child.join().await, TerminationReason::ParentStopped);
+}
+
+#[tokio::test]
+async fn test_linking_to_a_stopped_parent_stops_the_child() {
+    let parent = spawn_actor(manager());
+    parent.send(ManagerMsg::Shutdown).await.unwrap();
+    parent.await_idle().await;
+    let child = parent.spawn_child(Gated::new());
+    drop(parent);
+
+    assert_eq!(child.join().await, TerminationReason::ParentStopped);
+}
+
+#[tokio::test]
+async fn test_parent_is_notified_when_a_child_stops() {
+    let parent = spawn_actor(manager());
+    let child = spawn_actor(Gated::new());
+    parent.link_with_notice(&child, ManagerMsg::ChildStopped);
+
+    child.send(GatedMsg::Shutdown).await.unwrap();
+    assert_eq!(child.join().await, TerminationReason::ShutdownMessage);
+    // The notice is sent from another task; wait for it to reach the parent.
+    loop {
+        let (tx, rx) = oneshot::channel();
+        parent.send(ManagerMsg::Stopped(tx)).await.unwrap();
+        let stopped = rx.await.unwrap();
+        if !stopped.is_empty() {
+            assert_eq!(stopped, [TerminationReason::ShutdownMessage]);
+            break;
+        }
+        tokio::task::yield_now().await;
+    }
+}
@@ -0,0 +1,114 @@
+use crate::drive::Drive;
+use crate::{ActorConfig, ActorContext, ActorHandle, Prioritized, Priority, ShutdownReason};
+use async_trait::async_trait;
+use std::any::Any;
+use std::future::Future;
+use std::ops::ControlFlow;
+use std::pin::Pin;
+
+/// An actor that cannot leave the thread it was spawned on, e.g. one wrapping an `Rc` or a
+/// GUI resource. Spawned with [`spawn_actor_local`].
+///
+/// The same as [`Actor`](crate::Actor) except that neither the actor nor its handler
+/// futures need to be `Send`. Its messages still do, so any thread can send them.
+#[async_trait(?Send)]
+pub trait LocalActor: 'static {
+    type Msg: Send + 'static + Prioritized;
+
+    /// See [`Actor::handle`](crate::Actor::handle).
+    async fn handle(
+        &mut self,
+        msg: Self::Msg,
+        ctx: &ActorContext<Self::Msg>,
+    ) -> ControlFlow<ShutdownReason>;
+
+    /// See [`Actor::handle_batch`](crate::Actor::handle_batch).
+    async fn handle_batch(
+        &mut self,
+        msgs: Vec<Self::Msg>,
+        ctx: &ActorContext<Self::Msg>,
+    ) -> ControlFlow<ShutdownReason> {
+        for msg in msgs {
+            self.handle(msg, ctx).await?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// See [`Actor::on_start`](crate::Actor::on_start).
+    async fn on_start(&mut self) {}
+
+    /// See [`Actor::on_stop`](crate::Actor::on_stop).
+    async fn on_stop(&mut self) {}
+
+    /// See [`Actor::on_timeout`](crate::Actor::on_timeout).
+    async fn on_timeout(&mut self, priority: Priority) -> ControlFlow<ShutdownReason> {
+        let _ = priority;
+        ControlFlow::Continue(())
+    }
+}
+
+/// Runs a [`LocalActor`] on the processor; keeps it apart from the `Actor` blanket impl.
+pub(crate) struct Local<A>(A);
+
+impl<A: LocalActor> Drive for Local<A> {
+    type Msg = A::Msg;
+    type Step<'a, T: 'a> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+    fn handle<'a>(
+        &'a mut self,
+        msg: A::Msg,
+        ctx: &'a ActorContext<A::Msg>,
+    ) -> Self::Step<'a, ControlFlow<ShutdownReason>> {
+        self.0.handle(msg, ctx)
+    }
+
+    fn handle_batch<'a>(
+        &'a mut self,
+        msgs: Vec<A::Msg>,
+        ctx: &'a ActorContext<A::Msg>,
+    ) -> Self::Step<'a, ControlFlow<ShutdownReason>> {
+        self.0.handle_batch(msgs, ctx)
+    }
+
+    fn on_start(&mut self) -> Self::Step<'_, ()> {
+        self.0.on_start()
+    }
+
+    fn on_stop(&mut self) -> Self::Step<'_, ()> {
+        self.0.on_stop()
+    }
+
+    fn on_timeout(&mut self, priority: Priority) -> Self::Step<'_, ControlFlow<ShutdownReason>> {
+        self.0.on_timeout(priority)
+    }
+
+    fn name() -> &'static str {
+        std::any::type_name::<A>()
+    }
+
+    fn state(&mut self) -> &mut dyn Any {
+        &mut self.0
+    }
+}
+
+/// Spawns a [`LocalActor`] onto the current [`LocalSet`](tokio::task::LocalSet) with the
+/// default configuration.
+///
+/// # Panics
+///
+/// Panics if called outside a `LocalSet`, e.g. straight from `#[tokio::main]`; run it
+/// inside [`LocalSet::run_until`](tokio::task::LocalSet::run_until) or a task spawned with
+/// [`spawn_local`](tokio::task::spawn_local).
+pub fn spawn_actor_local<A: LocalActor>(actor: A) -> ActorHandle<A::Msg> {
+    spawn_actor_local_with(actor, ActorConfig::default())
+}
+
+/// Like [`spawn_actor_local`], with the given configuration.
+///
+/// The actor's task runs on the `LocalSet`, so the actor only makes progress while the
+/// `LocalSet` is being driven. Its handle is `Send` as usual.
+pub fn spawn_actor_local_with<A: LocalActor>(actor: A, config: ActorConfig) -> ActorHandle<A::Msg> {
+    let (handle, processor) = crate::launch(Local(actor), config, None);
+    crate::task::spawn_local(handle.name(), processor);
+    handle
+}
@@ -0,0 +1,110 @@
+use crate::prelude::*;
+use crate::{spawn_actor_local, spawn_actor_local_with, LocalActor};
+use std::cell::RefCell;
+use std::rc::Rc;
+use tokio::sync::oneshot;
+use tokio::task::LocalSet;
+use tokio::time::Duration;
+
+/// Records the handled priorities in an `Rc`, which keeps it off other threads.
+struct Recorder {
+    log: Rc<RefCell<Vec<Priority>>>,
+}
+
+#[derive(Prioritized)]
+enum RecorderMsg {
+    #[priority(Medium)]
+    Hold(oneshot::Sender<()>, oneshot::Receiver<()>),
+    #[priority(Low)]
+    Low,
+    #[priority(High)]
+    High,
+    #[priority(Low)]
+    Report(oneshot::Sender<Vec<Priority>>),
+}
+
+#[async_trait::async_trait(?Send)]
+impl LocalActor for Recorder {
+    type Msg = RecorderMsg;
+
+    async fn handle(
+        &mut self,
+        msg: RecorderMsg,
+        _ctx: &ActorContext<RecorderMsg>,
+    ) -> ControlFlow<ShutdownReason> {
+        match msg {
+            RecorderMsg::Hold(started, release) => {
+                let _ = started.send(());
+                let _ = release.await;
+            }
+            RecorderMsg::Low => self.log.borrow_mut().push(Priority::Low),
+            RecorderMsg::High => self.log.borrow_mut().push(Priority::High),
+            RecorderMsg::Report(tx) => {
+                let _ = tx.send(self.log.borrow().clone());
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+fn recorder() -> Recorder {
+    Recorder {
+        log: Rc::new(RefCell::new(Vec::new())),
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_local_actor_handles_messages_by_priority() {
+    LocalSet::new()
+        .run_until(async {
+            let handle = spawn_actor_local(recorder());
+            let (started_tx, started_rx) = oneshot::channel();
+            let (release_tx, release_rx) = oneshot::channel();
+            handle
+                .send(RecorderMsg::Hold(started_tx, release_rx))
+                .await
+                .unwrap();
+            started_rx.await.unwrap();
+            for msg in [RecorderMsg::Low, RecorderMsg::High, RecorderMsg::Low] {
+                handle.send(msg).await.unwrap();
+            }
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            drop(release_tx);
+
+            let (tx, rx) = oneshot::channel();
+            handle.send(RecorderMsg::Report(tx)).await.unwrap();
+            assert_eq!(
+                rx.await.unwrap(),
+                [Priority::High, Priority::Low, Priority::Low]
+            );
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn test_local_actor_takes_messages_from_other_threads() {
+    LocalSet::new()
+        .run_until(async {
+            let handle = spawn_actor_local_with(recorder(), ActorConfig::new().capacity(1));
+            let sender = handle.clone();
+            tokio::task::spawn_blocking(move || {
+                for _ in 0..10 {
+                    sender.blocking_send(RecorderMsg::High).unwrap();
+                }
+            })
+            .await
+            .unwrap();
+
+            let (tx, rx) = oneshot::channel();
+            handle.send(RecorderMsg::Report(tx)).await.unwrap();
+            assert_eq!(rx.await.unwrap(), [Priority::High; 10]);
+            assert_eq!(handle.join().await, TerminationReason::SendersDropped);
+        })
+        .await;
+}
+
+#[tokio::test]
+#[should_panic(expected = "`spawn_local` called from outside of a `task::LocalSet`")]
+async fn test_spawn_actor_local_outside_a_local_set_panics() {
+    spawn_actor_local(recorder());
+}
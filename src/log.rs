@@ -0,0 +1,20 @@
+//! Diagnostics for the runtime's own lifecycle events.
+//!
+//! With the `tracing` feature these are `tracing` events carrying the actor's
+//! [name](crate::ActorHandle::name) in an `actor` field. Without it they go to stdout only if
+//! the `log-stdout` feature is on, and are compiled out otherwise.
+
+/// Emits a lifecycle event for `actor` at the given `tracing` level.
+macro_rules! diag {
+    ($level:ident, $actor:expr, $($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        tracing::$level!(actor = %$actor, $($arg)+);
+        #[cfg(all(not(feature = "tracing"), feature = "log-stdout"))]
+        println!("[{}] {}", $actor, format_args!($($arg)+));
+        // Still borrows the arguments, so callers need not silence unused warnings.
+        #[cfg(not(any(feature = "tracing", feature = "log-stdout")))]
+        let _ = (&$actor, format_args!($($arg)+));
+    }};
+}
+
+pub(crate) use diag;
@@ -0,0 +1,695 @@
+use crate::confirm::Confirm;
+use crate::exemplar::DropSampler;
+use crate::idle::PendingGuard;
+use crate::permits::{MailboxPermit, PermitPool};
+use crate::queue::{Inlets, MessageQueue};
+use crate::runtime::ConfigUpdate;
+use crate::sink::Sink;
+use crate::{
+    AbsorbPlacement, ActorMetrics, AuditEvent, DropReason, NotHandled, OverflowPolicy, Prioritized,
+    Priority, ReconfigureError, RuntimeConfig, TerminationReason,
+};
+use arc_swap::ArcSwap;
+use std::any::Any;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use tokio::sync::futures::Notified;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{oneshot, Notify};
+use tokio::time::Instant;
+
+/// A message on its way from a handle to the handler, together with the mailbox bookkeeping
+/// that travels with it.
+pub(crate) struct Envelope<M> {
+    pub(crate) msg: M,
+    pub(crate) permit: Option<MailboxPermit>,
+    pub(crate) origin: Origin,
+    // Released with the permit once the message leaves the queue, so the sender can tell
+    // whether it is still waiting.
+    pub(crate) ticket: Option<Arc<()>>,
+    // Past this, the message is dropped instead of handled.
+    pub(crate) deadline: Option<Instant>,
+    // Counts the message as pending for the actor it was sent to, until it is handled.
+    pub(crate) pending: Option<PendingGuard>,
+    // Queues the message at this priority instead of its own; never `Shutdown`.
+    pub(crate) priority: Option<Priority>,
+    // Follows the message into its handler, and on to the messages the handler sends.
+    pub(crate) correlation: Option<u64>,
+    // Set when the message leaves the queue: when it entered it.
+    pub(crate) enqueued_at: Option<Instant>,
+    // Tells a sender waiting with `send_confirmed` how the message fared.
+    pub(crate) confirm: Option<Confirm>,
+}
+
+impl<M: Prioritized> Envelope<M> {
+    /// An envelope without a permit, expiring after the message's own TTL, if any. Sent from
+    /// a handler, it carries on the correlation id of the message being handled.
+    pub(crate) fn new(msg: M, origin: Origin) -> Self {
+        let deadline = msg.ttl().and_then(|ttl| Instant::now().checked_add(ttl));
+        Envelope {
+            msg,
+            permit: None,
+            origin,
+            ticket: None,
+            deadline,
+            pending: None,
+            priority: None,
+            correlation: crate::correlation::current(),
+            enqueued_at: None,
+            confirm: None,
+        }
+    }
+
+    /// Queues the message at `priority` instead of its own, unless it is a `Shutdown`
+    /// message. An override of `Shutdown` is clamped to `High`, so only a real `Shutdown`
+    /// message jumps ahead of everything.
+    pub(crate) fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = Some(if priority == Priority::Shutdown {
+            Priority::High
+        } else {
+            priority
+        });
+        self
+    }
+
+    /// Whether the message has outlived its TTL. `Shutdown` messages never expire.
+    pub(crate) fn is_expired(&self, now: Instant) -> bool {
+        self.deadline.is_some_and(|deadline| now > deadline)
+            && self.msg.priority() != Priority::Shutdown
+    }
+}
+
+/// Where a message came from.
+///
+/// Used by [`ActorConfig::fairness`](crate::ActorConfig::fairness) to keep one source of
+/// messages from starving the others, and reported in [`AuditEvent`](crate::AuditEvent)s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Origin {
+    /// Sent through an [`ActorHandle`](crate::ActorHandle) by someone other than the actor.
+    External,
+    /// Sent by the actor to itself.
+    SelfSend,
+    /// Delivered by a schedule, such as [`send_interval`](crate::ScheduleExt::send_interval).
+    Scheduled,
+    /// Sent again after an earlier attempt failed.
+    Retry,
+}
+
+impl Origin {
+    pub(crate) const COUNT: usize = 4;
+    pub(crate) const ALL: [Origin; Origin::COUNT] = [
+        Origin::External,
+        Origin::SelfSend,
+        Origin::Scheduled,
+        Origin::Retry,
+    ];
+
+    pub(crate) fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl<M: Prioritized> Prioritized for Envelope<M> {
+    fn priority(&self) -> Priority {
+        match (self.msg.priority(), self.priority) {
+            (Priority::Shutdown, _) | (_, None) => self.msg.priority(),
+            (_, Some(priority)) => priority,
+        }
+    }
+}
+
+/// An actor's mailbox: senders queue messages straight into it and its receiver takes them
+/// out by priority.
+///
+/// The queue sits behind one lock, since the shed threshold, the queue limit, fairness and
+/// aging all look across priority levels. Messages none of that applies to skip it: senders
+/// stage them in [`Inlets`] locked per priority band, and whoever takes the lock next moves
+/// them into the queue first. Either lock is only held for constant-time work.
+pub(crate) struct Inbox<M> {
+    state: Mutex<State<M>>,
+    // `None` when every message has to go through the lock.
+    inlets: Option<Inlets<M>>,
+    // Cleared once the mailbox is retired or its receiver is gone; later sends fail. Only
+    // written under the lock.
+    open: AtomicBool,
+    // Wakes the receiver when a message arrives or the inbox closes.
+    arrived: Notify,
+    // Live `MailboxSender`s; the inbox closes once the last one is gone.
+    senders: AtomicUsize,
+    admission: Admission<M>,
+    // Closed with the inbox, so senders waiting for room give up.
+    permits: Option<Arc<PermitPool>>,
+}
+
+pub(crate) struct State<M> {
+    queue: MessageQueue<M>,
+    // Set once nothing more will be sent; the receiver stops once the queue is empty.
+    closed: bool,
+    retired: bool,
+    // Set by a graceful shutdown; the receiver stops once the queue is empty.
+    draining: bool,
+    // Set when a linked parent stops; the receiver stops without taking anything else.
+    halted: bool,
+    // Answered by the receiver the next time it looks for a message.
+    pings: Vec<oneshot::Sender<()>>,
+    // Run by the receiver against the actor's state, ahead of any message.
+    visits: Vec<Visit>,
+}
+
+/// A closure run against the actor's state by
+/// [`ActorHandle::with_state_mut`](crate::ActorHandle::with_state_mut).
+pub(crate) type Visit = Box<dyn FnOnce(&mut dyn Any) + Send>;
+
+impl<M> State<M> {
+    pub(crate) fn queue(&mut self) -> &mut MessageQueue<M> {
+        &mut self.queue
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    pub(crate) fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    /// Takes the visits queued since the last call.
+    pub(crate) fn take_visits(&mut self) -> Vec<Visit> {
+        std::mem::take(&mut self.visits)
+    }
+
+    /// Answers every ping sent since the last call.
+    pub(crate) fn answer_pings(&mut self) {
+        for ping in self.pings.drain(..) {
+            let _ = ping.send(());
+        }
+    }
+}
+
+/// Decides which messages get into the queue, and reports the ones that do not.
+pub(crate) struct Admission<M> {
+    pub(crate) runtime: Arc<ArcSwap<RuntimeConfig>>,
+    pub(crate) metrics: Arc<ActorMetrics>,
+    pub(crate) audit: Option<Sink<AuditEvent>>,
+    pub(crate) drops: Option<Arc<DropSampler<M>>>,
+    // Where coalesced messages go.
+    dead_letters: Option<Sink<M>>,
+    // Keeps concurrent reconfigurations from losing each other's changes.
+    reconfiguring: Mutex<()>,
+}
+
+impl<M: Prioritized> Admission<M> {
+    pub(crate) fn new(
+        runtime: Arc<ArcSwap<RuntimeConfig>>,
+        metrics: Arc<ActorMetrics>,
+        audit: Option<Sink<AuditEvent>>,
+        drops: Option<Arc<DropSampler<M>>>,
+    ) -> Self {
+        Admission {
+            runtime,
+            metrics,
+            audit,
+            drops,
+            dead_letters: None,
+            reconfiguring: Mutex::new(()),
+        }
+    }
+
+    /// Hands messages replaced by a newer one with the same coalescing key to `sink`.
+    pub(crate) fn with_dead_letters(mut self, sink: Option<Sink<M>>) -> Self {
+        self.dead_letters = sink;
+        self
+    }
+
+    /// Queues a message unless the shed threshold or the queue limit turns it away; returns
+    /// what was turned away or replaced, to be [`discard`](Self::discard)ed once the lock is
+    /// released. A message over its priority's quota under [`OverflowPolicy::Reject`] is
+    /// handed back instead, for the sender to deal with.
+    fn enqueue(
+        &self,
+        queue: &mut MessageQueue<M>,
+        msg: Envelope<M>,
+    ) -> Result<Option<(DropReason, Envelope<M>)>, Envelope<M>> {
+        // Taking a waiting message's place does not grow the queue, so no limit applies.
+        let msg = match queue.coalesce(msg) {
+            Ok(replaced) => {
+                self.metrics.message_enqueued();
+                return Ok(Some((DropReason::Coalesced, replaced)));
+            }
+            Err(msg) => msg,
+        };
+        let mut evicted = None;
+        if msg.priority() != Priority::Shutdown {
+            let runtime = self.runtime.load();
+            if runtime
+                .shed_threshold
+                .is_some_and(|limit| queue.len() >= limit)
+            {
+                return Ok(Some((DropReason::Shed, msg)));
+            }
+            let priority = msg.priority();
+            if runtime
+                .quota(priority)
+                .is_some_and(|quota| self.metrics.priority_depth(priority) >= quota)
+            {
+                return match runtime.overflow {
+                    OverflowPolicy::Reject => Err(msg),
+                    OverflowPolicy::DropLowest | OverflowPolicy::DropIncoming => {
+                        Ok(Some((DropReason::Overflow, msg)))
+                    }
+                };
+            }
+            if !queue.admit(priority) {
+                return Ok(Some((DropReason::Overflow, msg)));
+            }
+            if runtime.max_queued.is_some_and(|limit| queue.len() >= limit) {
+                evicted = match runtime.overflow {
+                    OverflowPolicy::DropLowest => queue.evict_below(msg.priority()),
+                    OverflowPolicy::Reject | OverflowPolicy::DropIncoming => None,
+                };
+                if evicted.is_none() {
+                    return Ok(Some((DropReason::Overflow, msg)));
+                }
+            }
+        }
+        queue.push(msg);
+        self.metrics.message_enqueued();
+        Ok(evicted.map(|evicted| (DropReason::Overflow, evicted)))
+    }
+
+    /// Accounts for a message the runtime drops without handling it.
+    pub(crate) fn discard(&self, reason: DropReason, mut envelope: Envelope<M>) {
+        if let Some(confirm) = envelope.confirm.take() {
+            confirm.send(Err(NotHandled::Dropped(reason)));
+        }
+        let (priority, origin) = (envelope.priority(), envelope.origin);
+        let metrics = &self.metrics;
+        let event = match reason {
+            DropReason::Shed => {
+                metrics.message_shed();
+                AuditEvent::Shed { priority, origin }
+            }
+            DropReason::Expired => {
+                metrics.message_expired();
+                AuditEvent::Expired { priority, origin }
+            }
+            DropReason::Overflow => {
+                metrics.message_overflowed();
+                AuditEvent::Overflowed { priority, origin }
+            }
+            DropReason::Coalesced => {
+                metrics.message_coalesced();
+                AuditEvent::Coalesced { priority, origin }
+            }
+        };
+        if let Some(audit) = &self.audit {
+            audit.deliver(event);
+        }
+        let variant = envelope.msg.variant_name();
+        let mut msg = Some(envelope.msg);
+        if reason == DropReason::Coalesced {
+            if let Some(dead_letters) = &self.dead_letters {
+                dead_letters.deliver(msg.take().expect("the message is still here"));
+            }
+        }
+        if let Some(drops) = &self.drops {
+            drops.record(reason, variant, priority, msg, origin);
+        }
+    }
+}
+
+impl<M: Prioritized> Inbox<M> {
+    pub(crate) fn new(
+        queue: MessageQueue<M>,
+        admission: Admission<M>,
+        permits: Option<Arc<PermitPool>>,
+    ) -> Self {
+        Inbox {
+            inlets: Inlets::feeding(&queue),
+            open: AtomicBool::new(true),
+            state: Mutex::new(State {
+                queue,
+                closed: false,
+                retired: false,
+                draining: false,
+                halted: false,
+                pings: Vec::new(),
+                visits: Vec::new(),
+            }),
+            arrived: Notify::new(),
+            senders: AtomicUsize::new(0),
+            admission,
+            permits,
+        }
+    }
+
+    /// Never held across an `.await`, nor while dropping a message: a message may own a
+    /// handle to this very actor. Staged messages are in the queue by the time this returns.
+    pub(crate) fn lock(&self) -> MutexGuard<'_, State<M>> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(inlets) = &self.inlets {
+            inlets.empty_into(&mut state.queue);
+        }
+        state
+    }
+
+    pub(crate) fn admission(&self) -> &Admission<M> {
+        &self.admission
+    }
+
+    /// Resolves after the next [`wake`](Self::wake), or right away if one happened since
+    /// the last call.
+    pub(crate) fn notified(&self) -> Notified<'_> {
+        self.arrived.notified()
+    }
+
+    fn wake(&self) {
+        // Leaves a permit behind if the receiver is not waiting yet, so it cannot miss this.
+        self.arrived.notify_one();
+    }
+
+    /// Queues a message sent from outside, handing it back once the mailbox is closed, or
+    /// while its priority's quota is full under [`OverflowPolicy::Reject`].
+    pub(crate) fn push(&self, envelope: Envelope<M>) -> Result<(), TrySendError<Envelope<M>>> {
+        if let Some(inlets) = &self.inlets {
+            if inlets.accepts(&envelope, &self.admission.runtime.load()) {
+                inlets
+                    .stage(envelope, &self.open)
+                    .map_err(TrySendError::Closed)?;
+                self.wake();
+                return Ok(());
+            }
+        }
+        let mut state = self.lock();
+        if !self.open.load(Ordering::Acquire) {
+            return Err(TrySendError::Closed(envelope));
+        }
+        let admitted = self.admission.enqueue(&mut state.queue, envelope);
+        drop(state);
+        let rejected = admitted.map_err(TrySendError::Full)?;
+        self.wake();
+        if let Some((reason, rejected)) = rejected {
+            self.admission.discard(reason, rejected);
+        }
+        Ok(())
+    }
+
+    /// Queues messages the actor sent itself while handling one; they get in even after
+    /// the mailbox has been retired.
+    pub(crate) fn push_looped(&self, envelopes: Vec<Envelope<M>>) {
+        let mut state = self.lock();
+        let rejected: Vec<_> = envelopes
+            .into_iter()
+            .filter_map(|envelope| {
+                // Nobody is waiting to take a message turned away back; it is dropped.
+                self.admission
+                    .enqueue(&mut state.queue, envelope)
+                    .unwrap_or_else(|rejected| Some((DropReason::Overflow, rejected)))
+            })
+            .collect();
+        drop(state);
+        for (reason, envelope) in rejected {
+            self.admission.discard(reason, envelope);
+        }
+    }
+
+    /// Queues messages taken from a retired actor; returns how many were queued, which is
+    /// none once this mailbox is closed.
+    pub(crate) fn adopt(&self, envelopes: Vec<Envelope<M>>, placement: AbsorbPlacement) -> usize {
+        let mut state = self.lock();
+        if !self.open.load(Ordering::Acquire) {
+            drop(state);
+            drop(envelopes);
+            return 0;
+        }
+        let moved = envelopes.len();
+        state.queue.adopt(envelopes, placement);
+        drop(state);
+        self.wake();
+        moved
+    }
+
+    /// Takes the queued messages `pick` picks, in the order they arrived. Drop them only
+    /// once this returns: a message may own a handle to this very actor.
+    pub(crate) fn extract(&self, pick: &mut dyn FnMut(&Envelope<M>) -> bool) -> Vec<Envelope<M>> {
+        self.lock().queue.extract(pick)
+    }
+
+    /// Runs `inspect` on the queued messages that have not expired, in priority order, with
+    /// the mailbox locked.
+    pub(crate) fn inspect<R>(&self, inspect: impl FnOnce(&[&M]) -> R) -> R {
+        let state = self.lock();
+        let waiting: Vec<_> = state
+            .queue
+            .waiting(Instant::now())
+            .into_iter()
+            .map(|envelope| &envelope.msg)
+            .collect();
+        inspect(&waiting)
+    }
+
+    /// Hands over every queued message in arrival order and closes the mailbox, so the
+    /// receiver stops once it finishes what it is doing.
+    pub(crate) fn retire(&self) -> Vec<Envelope<M>> {
+        let mut state = self.lock();
+        state.retired = true;
+        self.shut(state)
+    }
+
+    /// Closes the mailbox for good once the receiver is gone, handing back whatever was
+    /// left in it and why it was closed, if it was retired or halted.
+    pub(crate) fn stop(&self) -> (Option<TerminationReason>, Vec<Envelope<M>>) {
+        let state = self.lock();
+        let closed_by = if state.retired {
+            Some(TerminationReason::Absorbed)
+        } else if state.halted {
+            Some(TerminationReason::ParentStopped)
+        } else if state.draining {
+            Some(TerminationReason::Drained)
+        } else {
+            None
+        };
+        (closed_by, self.shut(state))
+    }
+
+    fn shut(&self, mut state: MutexGuard<'_, State<M>>) -> Vec<Envelope<M>> {
+        self.open.store(false, Ordering::SeqCst);
+        // Whatever was staged before senders saw the mailbox closed.
+        if let Some(inlets) = &self.inlets {
+            inlets.empty_into(&mut state.queue);
+        }
+        state.closed = true;
+        state.pings.clear();
+        let drained = state.queue.drain();
+        let visits = state.take_visits();
+        drop(state);
+        drop(visits);
+        // Wake senders waiting on a reservation; they find the mailbox closed.
+        if let Some(pool) = &self.permits {
+            pool.close();
+        }
+        self.wake();
+        drained
+    }
+
+    /// Closes the mailbox to senders and has the receiver stop once it has handled whatever
+    /// is queued. Does nothing once the mailbox is closed to senders.
+    pub(crate) fn drain(&self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if !self.open.load(Ordering::Acquire) {
+            return;
+        }
+        self.open.store(false, Ordering::SeqCst);
+        // Staged messages were sent before the drain, so they are handled too.
+        if let Some(inlets) = &self.inlets {
+            inlets.empty_into(&mut state.queue);
+        }
+        state.closed = true;
+        state.draining = true;
+        drop(state);
+        if let Some(pool) = &self.permits {
+            pool.close();
+        }
+        self.arrived.notify_one();
+    }
+
+    /// Swaps in an updated runtime configuration; it applies to every message queued after
+    /// this returns.
+    pub(crate) fn reconfigure(&self, update: ConfigUpdate) -> Result<(), ReconfigureError> {
+        let _serialized = self
+            .admission
+            .reconfiguring
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if !self.is_open() {
+            return Err(ReconfigureError::Closed);
+        }
+        let runtime = &self.admission.runtime;
+        let mut next = RuntimeConfig::clone(&runtime.load());
+        update(&mut next);
+        next.validate()?;
+        runtime.store(Arc::new(next));
+        Ok(())
+    }
+}
+
+impl<M> Inbox<M> {
+    pub(crate) fn is_open(&self) -> bool {
+        self.open.load(Ordering::Acquire)
+    }
+
+    /// Closes the mailbox and has the receiver stop once it finishes what it is doing,
+    /// leaving whatever is queued to be dropped when it stops. Does nothing once the mailbox
+    /// is closed to senders.
+    pub(crate) fn halt(&self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if !self.open.load(Ordering::Acquire) {
+            return;
+        }
+        self.open.store(false, Ordering::Release);
+        state.closed = true;
+        state.halted = true;
+        state.pings.clear();
+        let visits = state.take_visits();
+        drop(state);
+        drop(visits);
+        if let Some(pool) = &self.permits {
+            pool.close();
+        }
+        self.arrived.notify_one();
+    }
+
+    /// Asks the receiver to answer as soon as it is between messages, ahead of anything
+    /// queued. Returns `None` once the mailbox is closed to senders; the answer is dropped
+    /// if it closes before the receiver gets to it.
+    pub(crate) fn ping(&self) -> Option<oneshot::Receiver<()>> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if !self.open.load(Ordering::Acquire) {
+            return None;
+        }
+        let (tx, rx) = oneshot::channel();
+        state.pings.push(tx);
+        drop(state);
+        self.arrived.notify_one();
+        Some(rx)
+    }
+
+    /// Has the receiver run `visit` against the actor's state as soon as it is between
+    /// messages, ahead of anything queued. Hands it back once the mailbox is closed to
+    /// senders; it is dropped unrun if the mailbox closes before the receiver gets to it.
+    pub(crate) fn visit(&self, visit: Visit) -> Result<(), Visit> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if !self.open.load(Ordering::Acquire) {
+            return Err(visit);
+        }
+        state.visits.push(visit);
+        drop(state);
+        self.arrived.notify_one();
+        Ok(())
+    }
+
+    /// Called once the last sender is gone.
+    fn close(&self) {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .closed = true;
+        self.arrived.notify_one();
+    }
+}
+
+/// A sender into an inbox; the inbox closes once every sender is gone.
+pub(crate) struct MailboxSender<M> {
+    inbox: Arc<Inbox<M>>,
+}
+
+impl<M> MailboxSender<M> {
+    pub(crate) fn new(inbox: Arc<Inbox<M>>) -> Self {
+        inbox.senders.fetch_add(1, Ordering::Relaxed);
+        MailboxSender { inbox }
+    }
+
+    /// Number of live senders, this one included.
+    pub(crate) fn strong_count(&self) -> usize {
+        self.inbox.senders.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn downgrade(&self) -> WeakMailboxSender<M> {
+        WeakMailboxSender {
+            inbox: Arc::clone(&self.inbox),
+        }
+    }
+}
+
+impl<M> Clone for MailboxSender<M> {
+    fn clone(&self) -> Self {
+        MailboxSender::new(Arc::clone(&self.inbox))
+    }
+}
+
+impl<M> Drop for MailboxSender<M> {
+    fn drop(&mut self) {
+        if self.inbox.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.inbox.close();
+        }
+    }
+}
+
+impl<M> Deref for MailboxSender<M> {
+    type Target = Inbox<M>;
+
+    fn deref(&self) -> &Inbox<M> {
+        &self.inbox
+    }
+}
+
+/// A sender that does not keep the mailbox open.
+pub(crate) struct WeakMailboxSender<M> {
+    inbox: Arc<Inbox<M>>,
+}
+
+impl<M> Clone for WeakMailboxSender<M> {
+    fn clone(&self) -> Self {
+        WeakMailboxSender {
+            inbox: Arc::clone(&self.inbox),
+        }
+    }
+}
+
+impl<M> WeakMailboxSender<M> {
+    /// See [`Inbox::halt`].
+    pub(crate) fn halt(&self) {
+        self.inbox.halt()
+    }
+
+    pub(crate) fn is_open(&self) -> bool {
+        self.inbox.is_open()
+    }
+
+    /// Returns `None` once every strong sender is gone.
+    pub(crate) fn upgrade(&self) -> Option<MailboxSender<M>> {
+        let senders = &self.inbox.senders;
+        let mut count = senders.load(Ordering::Acquire);
+        loop {
+            // Once the count has dropped to zero the inbox is closed for good.
+            if count == 0 {
+                return None;
+            }
+            match senders.compare_exchange_weak(
+                count,
+                count + 1,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    return Some(MailboxSender {
+                        inbox: Arc::clone(&self.inbox),
+                    })
+                }
+                Err(actual) => count = actual,
+            }
+        }
+    }
+}
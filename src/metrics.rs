@@ -0,0 +1,349 @@
+use crate::{Origin, Priority};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use tokio::time::{Duration, Instant};
+
+/// Live counters describing an actor's mailbox.
+///
+/// Obtained through [`MetricsExt::metrics`]; every read is a relaxed atomic load, so values
+/// are a snapshot that may already be stale.
+#[derive(Debug)]
+pub struct ActorMetrics {
+    name: String,
+    permits_in_use: [AtomicUsize; Priority::LEVELS],
+    queue_depth: [AtomicUsize; Origin::COUNT],
+    priority_depth: [AtomicUsize; Priority::LEVELS],
+    messages_enqueued: AtomicU64,
+    messages_handled: AtomicU64,
+    messages_shed: AtomicU64,
+    messages_expired: AtomicU64,
+    messages_overflowed: AtomicU64,
+    messages_coalesced: AtomicU64,
+    messages_timed_out: AtomicU64,
+    scheduled_tasks: AtomicUsize,
+    // Nanoseconds from `created` to the end of the last handler, plus one; zero if none ran.
+    last_handled: AtomicU64,
+    created: Instant,
+    // Filled in only with `ActorConfig::track_queue_latency`.
+    queue_wait: [WaitStats; Priority::LEVELS],
+    #[cfg(feature = "metrics")]
+    handled_rate: crate::rate::RateCounter,
+    #[cfg(feature = "metrics")]
+    enqueued_rate: crate::rate::RateCounter,
+    // Filled in only with `ActorConfig::slow_handler_threshold`.
+    #[cfg(feature = "metrics")]
+    handler_times: std::sync::Mutex<std::collections::HashMap<&'static str, HandlerStats>>,
+}
+
+/// How long messages of one priority waited in the queue, from
+/// [`ActorMetrics::queue_latency`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueLatency {
+    /// Number of messages measured.
+    pub samples: u64,
+    /// The longest wait.
+    pub max: Duration,
+    /// The average wait; zero while no message has been measured.
+    pub mean: Duration,
+}
+
+/// How long the handlers for one message variant took, from
+/// [`ActorMetrics::handler_time`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HandlerTime {
+    /// Number of handlers measured.
+    pub samples: u64,
+    /// The longest handler.
+    pub max: Duration,
+    /// The average handler; zero while none has been measured.
+    pub mean: Duration,
+}
+
+#[cfg(feature = "metrics")]
+#[derive(Debug, Default)]
+struct HandlerStats {
+    samples: u64,
+    total_nanos: u128,
+    max: Duration,
+}
+
+#[derive(Debug, Default)]
+struct WaitStats {
+    samples: AtomicU64,
+    total_nanos: AtomicU64,
+    max_nanos: AtomicU64,
+}
+
+// Arrays this long have no `Default` impl of their own.
+impl Default for ActorMetrics {
+    fn default() -> Self {
+        ActorMetrics {
+            name: String::new(),
+            permits_in_use: std::array::from_fn(|_| AtomicUsize::new(0)),
+            queue_depth: Default::default(),
+            priority_depth: std::array::from_fn(|_| AtomicUsize::new(0)),
+            messages_enqueued: AtomicU64::new(0),
+            messages_handled: AtomicU64::new(0),
+            messages_shed: AtomicU64::new(0),
+            messages_expired: AtomicU64::new(0),
+            messages_overflowed: AtomicU64::new(0),
+            messages_coalesced: AtomicU64::new(0),
+            messages_timed_out: AtomicU64::new(0),
+            scheduled_tasks: AtomicUsize::new(0),
+            last_handled: AtomicU64::new(0),
+            created: Instant::now(),
+            queue_wait: std::array::from_fn(|_| WaitStats::default()),
+            #[cfg(feature = "metrics")]
+            handled_rate: Default::default(),
+            #[cfg(feature = "metrics")]
+            enqueued_rate: Default::default(),
+            #[cfg(feature = "metrics")]
+            handler_times: Default::default(),
+        }
+    }
+}
+
+impl ActorMetrics {
+    pub(crate) fn named(name: String) -> Self {
+        ActorMetrics {
+            name,
+            ..Default::default()
+        }
+    }
+
+    /// The actor's name, as in [`ActorHandle::name`](crate::ActorHandle::name), so that
+    /// exported metrics can be labelled with it.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Number of mailbox permits currently held by queued messages of `priority`.
+    ///
+    /// Always zero for an unbounded mailbox.
+    pub fn permits_in_use(&self, priority: Priority) -> usize {
+        self.permits_in_use[priority.index()].load(Ordering::Relaxed)
+    }
+
+    /// Number of messages from `origin` waiting in the queue.
+    ///
+    /// Counts messages the actor has received but not yet handled; messages still in transit
+    /// from a sender are not included.
+    pub fn queue_depth(&self, origin: Origin) -> usize {
+        self.queue_depth[origin.index()].load(Ordering::Relaxed)
+    }
+
+    /// Number of messages waiting in the queue, across all origins and priorities.
+    pub fn queue_len(&self) -> usize {
+        self.queue_depth
+            .iter()
+            .map(|depth| depth.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    /// Number of messages of `priority` waiting in the queue.
+    ///
+    /// Counts each message under its own priority, even while
+    /// [aging](crate::ActorConfig::aging) orders it higher.
+    pub fn priority_depth(&self, priority: Priority) -> usize {
+        self.priority_depth[priority.index()].load(Ordering::Relaxed)
+    }
+
+    /// Number of messages that entered the queue so far.
+    pub fn messages_enqueued(&self) -> u64 {
+        self.messages_enqueued.load(Ordering::Relaxed)
+    }
+
+    /// Number of messages handed to the actor so far.
+    pub fn messages_handled(&self) -> u64 {
+        self.messages_handled.load(Ordering::Relaxed)
+    }
+
+    /// Number of messages dropped unhandled so far, whether shed, expired, overflowed, or
+    /// coalesced.
+    pub fn messages_dropped(&self) -> u64 {
+        self.messages_shed()
+            + self.messages_expired()
+            + self.messages_overflowed()
+            + self.messages_coalesced()
+    }
+
+    /// When the actor last finished handling a message, or `None` if it has not yet.
+    pub fn last_handled(&self) -> Option<Instant> {
+        match self.last_handled.load(Ordering::Relaxed) {
+            0 => None,
+            nanos => Some(self.created + std::time::Duration::from_nanos(nanos - 1)),
+        }
+    }
+
+    /// Number of messages dropped because the queue had reached its shed threshold.
+    pub fn messages_shed(&self) -> u64 {
+        self.messages_shed.load(Ordering::Relaxed)
+    }
+
+    /// Number of messages dropped unhandled because they had outlived their TTL.
+    pub fn messages_expired(&self) -> u64 {
+        self.messages_expired.load(Ordering::Relaxed)
+    }
+
+    /// Number of messages dropped because the queue was at its limit.
+    pub fn messages_overflowed(&self) -> u64 {
+        self.messages_overflowed.load(Ordering::Relaxed)
+    }
+
+    /// Number of messages replaced in the queue by a newer one with the same
+    /// [coalescing key](crate::Prioritized::coalesce_key).
+    pub fn messages_coalesced(&self) -> u64 {
+        self.messages_coalesced.load(Ordering::Relaxed)
+    }
+
+    /// Number of handlers cancelled for running past their
+    /// [`handler_timeout`](crate::ActorConfig::handler_timeout).
+    pub fn messages_timed_out(&self) -> u64 {
+        self.messages_timed_out.load(Ordering::Relaxed)
+    }
+
+    /// Number of scheduler tasks currently delivering messages to the actor.
+    pub fn scheduled_tasks(&self) -> usize {
+        self.scheduled_tasks.load(Ordering::Relaxed)
+    }
+
+    /// How long messages of `priority` waited in the queue before their handler was
+    /// called, for actors spawned with
+    /// [`ActorConfig::track_queue_latency`](crate::ActorConfig::track_queue_latency).
+    ///
+    /// Counts each message under the priority it was queued at. Messages dropped unhandled
+    /// are not counted.
+    pub fn queue_latency(&self, priority: Priority) -> QueueLatency {
+        let stats = &self.queue_wait[priority.index()];
+        let samples = stats.samples.load(Ordering::Relaxed);
+        let total = stats.total_nanos.load(Ordering::Relaxed);
+        QueueLatency {
+            samples,
+            max: Duration::from_nanos(stats.max_nanos.load(Ordering::Relaxed)),
+            mean: Duration::from_nanos(total.checked_div(samples).unwrap_or(0)),
+        }
+    }
+
+    /// Recent handled and enqueued message rates.
+    #[cfg(feature = "metrics")]
+    pub fn rate(&self) -> crate::rate::MessageRates<'_> {
+        crate::rate::MessageRates {
+            handled: &self.handled_rate,
+            enqueued: &self.enqueued_rate,
+        }
+    }
+
+    /// How long handlers for messages of `variant`, as named by
+    /// [`Prioritized::variant_name`](crate::Prioritized::variant_name), took, for actors
+    /// spawned with
+    /// [`ActorConfig::slow_handler_threshold`](crate::ActorConfig::slow_handler_threshold).
+    ///
+    /// A batch counts once, under its first message. Handlers that timed out are counted
+    /// up to the timeout.
+    #[cfg(feature = "metrics")]
+    pub fn handler_time(&self, variant: &str) -> HandlerTime {
+        let times = self.handler_times.lock().unwrap();
+        let Some(stats) = times.get(variant) else {
+            return HandlerTime::default();
+        };
+        HandlerTime {
+            samples: stats.samples,
+            max: stats.max,
+            mean: Duration::from_nanos((stats.total_nanos / u128::from(stats.samples)) as u64),
+        }
+    }
+
+    pub(crate) fn handler_took(&self, variant: &'static str, elapsed: Duration) {
+        #[cfg(feature = "metrics")]
+        {
+            let mut times = self.handler_times.lock().unwrap();
+            let stats = times.entry(variant).or_default();
+            stats.samples += 1;
+            stats.total_nanos += elapsed.as_nanos();
+            stats.max = stats.max.max(elapsed);
+        }
+        #[cfg(not(feature = "metrics"))]
+        let _ = (variant, elapsed);
+    }
+
+    pub(crate) fn message_waited(&self, priority: Priority, wait: Duration) {
+        let stats = &self.queue_wait[priority.index()];
+        let nanos = u64::try_from(wait.as_nanos()).unwrap_or(u64::MAX);
+        stats.samples.fetch_add(1, Ordering::Relaxed);
+        stats.total_nanos.fetch_add(nanos, Ordering::Relaxed);
+        stats.max_nanos.fetch_max(nanos, Ordering::Relaxed);
+    }
+
+    pub(crate) fn permit_acquired(&self, priority: Priority) {
+        self.permits_in_use[priority.index()].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn permit_released(&self, priority: Priority) {
+        self.permits_in_use[priority.index()].fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn queued(&self, origin: Origin, priority: Priority) {
+        self.queue_depth[origin.index()].fetch_add(1, Ordering::Relaxed);
+        self.priority_depth[priority.index()].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn dequeued(&self, origin: Origin, priority: Priority) {
+        self.queue_depth[origin.index()].fetch_sub(1, Ordering::Relaxed);
+        self.priority_depth[priority.index()].fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn message_handled(&self) {
+        self.messages_handled.fetch_add(1, Ordering::Relaxed);
+        let nanos = u64::try_from(self.created.elapsed().as_nanos()).unwrap_or(u64::MAX - 1);
+        self.last_handled.store(nanos + 1, Ordering::Relaxed);
+        #[cfg(feature = "metrics")]
+        self.handled_rate.record();
+    }
+
+    pub(crate) fn message_enqueued(&self) {
+        self.messages_enqueued.fetch_add(1, Ordering::Relaxed);
+        #[cfg(feature = "metrics")]
+        self.enqueued_rate.record();
+    }
+
+    pub(crate) fn message_shed(&self) {
+        self.messages_shed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn message_expired(&self) {
+        self.messages_expired.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn message_overflowed(&self) {
+        self.messages_overflowed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn message_coalesced(&self) {
+        self.messages_coalesced.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn message_timed_out(&self) {
+        self.messages_timed_out.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn scheduler_started(&self) {
+        self.scheduled_tasks.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn scheduler_stopped(&self) {
+        self.scheduled_tasks.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Read access to an actor's [`ActorMetrics`].
+#[cfg(feature = "metrics")]
+pub trait MetricsExt: crate::sealed::Sealed {
+    /// Returns the actor's mailbox counters.
+    fn metrics(&self) -> std::sync::Arc<ActorMetrics>;
+}
+
+#[cfg(feature = "metrics")]
+impl<M> MetricsExt for crate::ActorHandle<M> {
+    fn metrics(&self) -> std::sync::Arc<ActorMetrics> {
+        std::sync::Arc::clone(&self.shared.metrics)
+    }
+}
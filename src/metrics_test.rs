@@ -0,0 +1,79 @@
+use crate::prelude::*;
+use crate::test_support::{stall, Gated, GatedMsg};
+use tokio::time::Duration;
+
+#[tokio::test(start_paused = true)]
+async fn test_depth_rises_while_stalled_and_drains_to_zero() {
+    let actor = spawn_actor_owned(Gated::new(), ActorConfig::new());
+    let metrics = actor.metrics();
+    assert_eq!(metrics.last_handled(), None);
+    let release = stall(&actor).await;
+
+    for _ in 0..3 {
+        actor.send(GatedMsg::Fill()).await.unwrap();
+    }
+    actor.send(GatedMsg::Urgent()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(1)).await;
+    assert_eq!(metrics.queue_len(), 4);
+    assert_eq!(metrics.priority_depth(Priority::Low), 3);
+    assert_eq!(metrics.priority_depth(Priority::High), 1);
+    assert_eq!(metrics.priority_depth(Priority::Medium), 0);
+    assert_eq!(metrics.messages_enqueued(), 5);
+    assert_eq!(metrics.messages_handled(), 0);
+
+    drop(release);
+    actor.join().await.unwrap();
+    assert_eq!(metrics.queue_len(), 0);
+    assert_eq!(metrics.priority_depth(Priority::Low), 0);
+    assert_eq!(metrics.priority_depth(Priority::High), 0);
+    assert_eq!(metrics.messages_handled(), 5);
+    assert_eq!(metrics.messages_dropped(), 0);
+    assert!(metrics.last_handled().is_some());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_queue_latency_reports_how_long_messages_waited() {
+    let waits = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let record = std::sync::Arc::clone(&waits);
+    let config = ActorConfig::new().on_dequeue(move |priority, wait| {
+        record.lock().unwrap().push((priority, wait));
+    });
+    let actor = spawn_actor_owned(Gated::new(), config);
+    let release = stall(&actor).await;
+
+    actor.send(GatedMsg::Fill()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    actor.send(GatedMsg::Urgent()).await.unwrap();
+    actor.send(GatedMsg::Fill()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    drop(release);
+    actor.await_idle().await;
+
+    let ms = Duration::from_millis;
+    assert_eq!(
+        *waits.lock().unwrap(),
+        [
+            (Priority::Medium, Duration::ZERO),
+            (Priority::High, ms(100)),
+            (Priority::Low, ms(200)),
+            (Priority::Low, ms(100)),
+        ]
+    );
+    let metrics = actor.metrics();
+    let low = metrics.queue_latency(Priority::Low);
+    assert_eq!((low.samples, low.max, low.mean), (2, ms(200), ms(150)));
+    let high = metrics.queue_latency(Priority::High);
+    assert_eq!((high.samples, high.max, high.mean), (1, ms(100), ms(100)));
+}
+
+#[tokio::test]
+async fn test_queue_latency_is_not_tracked_by_default() {
+    let actor = spawn_actor_owned(Gated::new(), ActorConfig::new());
+    actor.send(GatedMsg::Fill()).await.unwrap();
+    actor.await_idle().await;
+
+    assert_eq!(
+        actor.metrics().queue_latency(Priority::Low),
+        crate::QueueLatency::default()
+    );
+}
@@ -0,0 +1,98 @@
+use crate::Priority;
+use std::any::{type_name, Any};
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::time::Duration;
+
+/// What [`Middleware::before`] decides about a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterceptDecision {
+    /// Let the message through to the next middleware, and then to the handler.
+    Continue,
+    /// Drop the message without handling it. The actor carries on with its next message.
+    Drop,
+}
+
+/// Behavior wrapped around every message an actor handles, such as audit logging, feature
+/// flags or latency measurements, installed with
+/// [`ActorConfig::middleware`](crate::ActorConfig::middleware).
+///
+/// Middlewares run on the processor task, in the order they were installed, so they
+/// should not block.
+pub trait Middleware<M>: Send + 'static {
+    /// Called with each message before it is handled. By default every message goes on.
+    ///
+    /// A message dropped here never reaches later middlewares or the handler, and does not
+    /// count as handled; dropping a `Shutdown` message keeps the actor running.
+    fn before(&mut self, msg: &M) -> InterceptDecision {
+        let _ = msg;
+        InterceptDecision::Continue
+    }
+
+    /// Called once the handler for a message of `variant` at `priority` returned, after
+    /// `elapsed`. A batch reports its total time once for every message in it.
+    fn after(&mut self, variant: &'static str, priority: Priority, elapsed: Duration) {
+        let _ = (variant, priority, elapsed);
+    }
+}
+
+type Shared<M> = Arc<Mutex<dyn Middleware<M>>>;
+
+/// A middleware with its message type erased, since the config is not generic.
+#[derive(Clone)]
+pub(crate) struct ErasedMiddleware {
+    // A `Shared<M>`.
+    middleware: Arc<dyn Any + Send + Sync>,
+    msg_type: &'static str,
+}
+
+impl ErasedMiddleware {
+    pub(crate) fn new<M: 'static, W: Middleware<M>>(middleware: W) -> Self {
+        let shared: Shared<M> = Arc::new(Mutex::new(middleware));
+        ErasedMiddleware {
+            middleware: Arc::new(shared),
+            msg_type: type_name::<M>(),
+        }
+    }
+
+    fn downcast<M: 'static>(self) -> Shared<M> {
+        match self.middleware.downcast_ref::<Shared<M>>() {
+            Some(middleware) => Arc::clone(middleware),
+            None => panic!(
+                "middleware takes `{}`, not the actor's messages (`{}`)",
+                self.msg_type,
+                type_name::<M>()
+            ),
+        }
+    }
+}
+
+impl fmt::Debug for ErasedMiddleware {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Middleware<{}>", self.msg_type)
+    }
+}
+
+/// An actor's middlewares, in the order they were installed.
+pub(crate) struct Middlewares<M>(Vec<Shared<M>>);
+
+impl<M: 'static> Middlewares<M> {
+    pub(crate) fn new(erased: Vec<ErasedMiddleware>) -> Self {
+        Middlewares(erased.into_iter().map(ErasedMiddleware::downcast).collect())
+    }
+
+    /// Whether every middleware lets `msg` through.
+    pub(crate) fn admit(&self, msg: &M) -> bool {
+        self.0.iter().all(|middleware| {
+            let mut middleware = middleware.lock().unwrap_or_else(PoisonError::into_inner);
+            middleware.before(msg) == InterceptDecision::Continue
+        })
+    }
+
+    pub(crate) fn handled(&self, variant: &'static str, priority: Priority, elapsed: Duration) {
+        for middleware in &self.0 {
+            let mut middleware = middleware.lock().unwrap_or_else(PoisonError::into_inner);
+            middleware.after(variant, priority, elapsed);
+        }
+    }
+}
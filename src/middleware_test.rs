@@ -0,0 +1,143 @@
+use crate::prelude::*;
+use crate::test_support::{stall, Gated, GatedMsg};
+use crate::{spawn_actor_with, ActorConfig, InterceptDecision, Middleware};
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+use tokio::time::Duration;
+
+/// Drops every `Low` message.
+struct DropLow;
+
+impl<M: Prioritized> Middleware<M> for DropLow {
+    fn before(&mut self, msg: &M) -> InterceptDecision {
+        if msg.priority() == Priority::Low {
+            InterceptDecision::Drop
+        } else {
+            InterceptDecision::Continue
+        }
+    }
+}
+
+/// Records how long each handled message took.
+#[derive(Clone, Default)]
+struct Timings(Arc<Mutex<Vec<(&'static str, Duration)>>>);
+
+impl<M> Middleware<M> for Timings {
+    fn after(&mut self, variant: &'static str, _priority: Priority, elapsed: Duration) {
+        self.0.lock().unwrap().push((variant, elapsed));
+    }
+}
+
+/// Logs the messages it sees under its name.
+struct Named(&'static str, Arc<Mutex<Vec<String>>>);
+
+impl<M: Prioritized> Middleware<M> for Named {
+    fn before(&mut self, msg: &M) -> InterceptDecision {
+        let mut log = self.1.lock().unwrap();
+        log.push(format!("{} before {}", self.0, msg.variant_name()));
+        InterceptDecision::Continue
+    }
+
+    fn after(&mut self, variant: &'static str, _priority: Priority, _elapsed: Duration) {
+        let mut log = self.1.lock().unwrap();
+        log.push(format!("{} after {}", self.0, variant));
+    }
+}
+
+define_actor! {
+    Worker {}
+
+    impl WorkerMsg {
+        async fn Slow(&mut self) {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        fn Quick(&mut self) {}
+    }
+}
+
+async fn report(handle: &ActorHandle<GatedMsg>) -> Vec<Priority> {
+    let (tx, rx) = oneshot::channel();
+    handle.send(GatedMsg::Report(tx)).await.unwrap();
+    rx.await.unwrap()
+}
+
+#[tokio::test]
+async fn test_middleware_drops_low_messages() {
+    let handle = spawn_actor_with(
+        Gated::new(),
+        ActorConfig::new().middleware::<GatedMsg>(DropLow),
+    );
+    let release = stall(&handle).await;
+    handle.send(GatedMsg::Fill()).await.unwrap();
+    handle.send(GatedMsg::Routine()).await.unwrap();
+    handle.send(GatedMsg::Fill()).await.unwrap();
+    handle.send(GatedMsg::Urgent()).await.unwrap();
+    drop(release);
+    handle.await_idle().await;
+
+    assert_eq!(report(&handle).await, [Priority::High, Priority::Medium]);
+    assert!(
+        !handle.is_closed(),
+        "dropped messages must not stop the actor"
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_middleware_records_handling_durations() {
+    let timings = Timings::default();
+    let handle = spawn_actor_with(
+        Worker {},
+        ActorConfig::new().middleware::<WorkerMsg>(timings.clone()),
+    );
+    handle.send(WorkerMsg::Slow()).await.unwrap();
+    handle.send(WorkerMsg::Quick()).await.unwrap();
+    handle.await_idle().await;
+
+    assert_eq!(
+        *timings.0.lock().unwrap(),
+        [
+            ("Slow", Duration::from_millis(50)),
+            ("Quick", Duration::ZERO)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_middlewares_run_in_installation_order() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let config = ActorConfig::new()
+        .middleware::<GatedMsg>(Named("outer", Arc::clone(&log)))
+        .middleware::<GatedMsg>(DropLow)
+        .middleware::<GatedMsg>(Named("inner", Arc::clone(&log)));
+    let handle = spawn_actor_with(Gated::new(), config);
+    let release = stall(&handle).await;
+    handle.send(GatedMsg::Fill()).await.unwrap();
+    handle.send(GatedMsg::Urgent()).await.unwrap();
+    drop(release);
+    handle.await_idle().await;
+
+    assert_eq!(
+        *log.lock().unwrap(),
+        [
+            "outer before Hold",
+            "inner before Hold",
+            "outer after Hold",
+            "inner after Hold",
+            "outer before Urgent",
+            "inner before Urgent",
+            "outer after Urgent",
+            "inner after Urgent",
+            "outer before Fill",
+        ]
+    );
+}
+
+#[tokio::test]
+#[should_panic(expected = "middleware takes")]
+async fn test_middleware_for_another_message_type_panics() {
+    let _ = spawn_actor_with(
+        Gated::new(),
+        ActorConfig::new().middleware::<WorkerMsg>(DropLow),
+    );
+}
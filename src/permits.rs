@@ -0,0 +1,125 @@
+use crate::{ActorMetrics, Priority};
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, TryAcquireError};
+
+/// How much of a bounded mailbox a [reservation](crate::ActorConfig::reserve) holds back.
+///
+/// A plain `f64` converts into a [`Fraction`](Self::Fraction).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reserved {
+    /// A share of the capacity, within `0.0..=1.0`, rounded up to whole slots.
+    Fraction(f64),
+    /// A number of slots, all of the capacity at most.
+    Slots(usize),
+}
+
+impl From<f64> for Reserved {
+    fn from(fraction: f64) -> Self {
+        Reserved::Fraction(fraction)
+    }
+}
+
+impl Reserved {
+    fn slots(self, capacity: usize) -> usize {
+        match self {
+            Reserved::Fraction(fraction) => (capacity as f64 * fraction).ceil() as usize,
+            Reserved::Slots(slots) => slots,
+        }
+    }
+}
+
+/// Capacity accounting for a bounded mailbox.
+///
+/// Every queued message holds one permit of `total` until the processor pops it. A
+/// reservation for
+/// priority `P` adds a semaphore sized to the unreserved share, which every message below
+/// `P` must additionally acquire.
+pub(crate) struct PermitPool {
+    total: Arc<Semaphore>,
+    // Ordered from the highest reserved priority downwards so all senders acquire in the
+    // same order.
+    classes: Vec<(Priority, Arc<Semaphore>)>,
+    metrics: Arc<ActorMetrics>,
+}
+
+impl PermitPool {
+    pub(crate) fn new(
+        capacity: usize,
+        reservations: &[(Priority, Reserved)],
+        metrics: Arc<ActorMetrics>,
+    ) -> Self {
+        let mut classes: Vec<_> = reservations
+            .iter()
+            .map(|&(priority, reserved)| {
+                let shared = capacity.saturating_sub(reserved.slots(capacity));
+                (priority, Arc::new(Semaphore::new(shared)))
+            })
+            .collect();
+        classes.sort_by_key(|&(priority, _)| std::cmp::Reverse(priority));
+
+        PermitPool {
+            total: Arc::new(Semaphore::new(capacity)),
+            classes,
+            metrics,
+        }
+    }
+
+    /// Waits for room for a message of `priority`. Returns `None` once the pool is closed.
+    pub(crate) async fn acquire(&self, priority: Priority) -> Option<MailboxPermit> {
+        let mut permits = Vec::with_capacity(self.classes.len() + 1);
+        for (reserved_for, semaphore) in &self.classes {
+            if priority < *reserved_for {
+                permits.push(Arc::clone(semaphore).acquire_owned().await.ok()?);
+            }
+        }
+        permits.push(Arc::clone(&self.total).acquire_owned().await.ok()?);
+
+        self.metrics.permit_acquired(priority);
+        Some(MailboxPermit {
+            _permits: permits,
+            priority,
+            metrics: Arc::clone(&self.metrics),
+        })
+    }
+
+    /// Takes room for a message of `priority` only if it is available right now.
+    // Only the FFI layer sends without waiting so far.
+    #[cfg_attr(not(feature = "ffi"), allow(dead_code))]
+    pub(crate) fn try_acquire(&self, priority: Priority) -> Result<MailboxPermit, TryAcquireError> {
+        let mut permits = Vec::with_capacity(self.classes.len() + 1);
+        for (reserved_for, semaphore) in &self.classes {
+            if priority < *reserved_for {
+                permits.push(Arc::clone(semaphore).try_acquire_owned()?);
+            }
+        }
+        permits.push(Arc::clone(&self.total).try_acquire_owned()?);
+
+        self.metrics.permit_acquired(priority);
+        Ok(MailboxPermit {
+            _permits: permits,
+            priority,
+            metrics: Arc::clone(&self.metrics),
+        })
+    }
+
+    /// Wakes every blocked sender with an error; used once the actor has stopped.
+    pub(crate) fn close(&self) {
+        self.total.close();
+        for (_, semaphore) in &self.classes {
+            semaphore.close();
+        }
+    }
+}
+
+/// Permits held by one queued message, returned to the pool on drop.
+pub(crate) struct MailboxPermit {
+    _permits: Vec<OwnedSemaphorePermit>,
+    priority: Priority,
+    metrics: Arc<ActorMetrics>,
+}
+
+impl Drop for MailboxPermit {
+    fn drop(&mut self) {
+        self.metrics.permit_released(self.priority);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::test_support::{stall, Gated, GatedMsg};
+use crate::{spawn_actor_with, ActorConfig, MetricsExt, Priority, Reserved};
+use tokio::sync::oneshot;
+use tokio::time::{timeout, Duration};
+
+#[tokio::test]
+async fn test_low_sends_are_limited_to_their_share() {
+    let config = ActorConfig::new().reserve(Priority::High, 0.25);
+    let handle = spawn_actor_with(Gated::new(), config);
+    let metrics = handle.metrics();
+
+    // Stall the processor so nothing leaves the queue.
+    let release = stall(&handle).await;
+
+    // 25% of the 32-slot mailbox is reserved, leaving 24 slots for Low messages.
+    for _ in 0..24 {
+        handle.send(GatedMsg::Fill()).await.unwrap();
+    }
+    assert_eq!(metrics.permits_in_use(Priority::Low), 24);
+
+    let blocked = {
+        let handle = handle.clone();
+        tokio::spawn(async move { handle.send(GatedMsg::Fill()).await })
+    };
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!blocked.is_finished(), "Low send should wait for its share");
+
+    // A High send still finds room in the reserved share.
+    timeout(Duration::from_millis(100), handle.send(GatedMsg::Urgent()))
+        .await
+        .expect("High send should not wait for Low messages to drain")
+        .unwrap();
+    assert_eq!(metrics.permits_in_use(Priority::High), 1);
+
+    release.send(()).unwrap();
+    blocked.await.unwrap().unwrap();
+
+    // Report is High priority, so keep asking until the Low backlog has drained.
+    let handled = loop {
+        let (report_tx, report_rx) = oneshot::channel();
+        handle.send(GatedMsg::Report(report_tx)).await.unwrap();
+        let handled = report_rx.await.unwrap();
+        if handled.len() == 26 {
+            break handled;
+        }
+        tokio::task::yield_now().await;
+    };
+    assert_eq!(handled[0], Priority::High, "queued High message goes first");
+    assert_eq!(metrics.permits_in_use(Priority::Low), 0);
+}
+
+#[tokio::test]
+async fn test_reserved_slots_stay_free_under_low_load() {
+    let config = ActorConfig::new()
+        .capacity(8)
+        .reserve(Priority::High, Reserved::Slots(2));
+    let handle = spawn_actor_with(Gated::new(), config);
+    let release = stall(&handle).await;
+
+    for _ in 0..6 {
+        handle.send(GatedMsg::Fill()).await.unwrap();
+    }
+    assert!(handle.try_send(GatedMsg::Fill()).unwrap_err().is_full());
+    assert!(handle.try_send(GatedMsg::Routine()).unwrap_err().is_full());
+
+    // Both reserved slots take a High message without waiting for the backlog.
+    for _ in 0..2 {
+        timeout(Duration::from_millis(100), handle.send(GatedMsg::Urgent()))
+            .await
+            .expect("High send should not wait behind Low messages")
+            .unwrap();
+    }
+    assert!(handle.try_send(GatedMsg::Urgent()).unwrap_err().is_full());
+    assert_eq!(handle.metrics().permits_in_use(Priority::High), 2);
+
+    release.send(()).unwrap();
+    handle.await_idle().await;
+    let (report_tx, report_rx) = oneshot::channel();
+    handle.send(GatedMsg::Report(report_tx)).await.unwrap();
+    let handled = report_rx.await.unwrap();
+    assert_eq!(handled[..2], [Priority::High; 2]);
+    assert_eq!(handled[2..], [Priority::Low; 6]);
+}
+
+#[tokio::test]
+async fn test_send_fails_once_reserved_actor_stops() {
+    let config = ActorConfig::new().reserve(Priority::High, 1.0);
+    let handle = spawn_actor_with(Gated::new(), config);
+
+    // With everything reserved, a Low send waits until the actor stops and then errors.
+    let blocked = {
+        let handle = handle.clone();
+        tokio::spawn(async move { handle.send(GatedMsg::Fill()).await })
+    };
+    handle.send(GatedMsg::Shutdown).await.unwrap();
+
+    let result = timeout(Duration::from_secs(1), blocked)
+        .await
+        .expect("blocked sender should be woken when the actor stops")
+        .unwrap();
+    assert!(result.is_err());
+}
@@ -0,0 +1,123 @@
+//! Carrying an actor's pending messages across a restart.
+//!
+//! [`ActorHandle::drain_serialized`] takes the messages waiting in an actor's queue and
+//! encodes them as bytes; [`spawn_actor_with_backlog`] decodes such bytes and queues the
+//! messages in a fresh actor before it handles anything else. In between, the bytes can be
+//! written to disk or wherever else they should outlive the process.
+//!
+//! The backlog is the messages' `bincode` encoding, each with the priority it was queued
+//! at, in the order they arrived.
+
+use crate::mailbox::{Envelope, Origin};
+use crate::{
+    launch, task, AbsorbPlacement, Actor, ActorConfig, ActorHandle, NotHandled, Prioritized,
+    Priority,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+use tokio::runtime::Handle;
+
+/// A message that can be saved in a backlog.
+///
+/// Implemented for every message type that serde can encode and decode.
+pub trait PersistentMessage: Prioritized + Serialize + DeserializeOwned + Send + 'static {}
+
+impl<M: Prioritized + Serialize + DeserializeOwned + Send + 'static> PersistentMessage for M {}
+
+/// Why a backlog could not be saved or restored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BacklogError {
+    /// A pending message could not be encoded; the messages stay queued.
+    Encode(String),
+    /// The bytes are not a backlog of this message type; no actor was spawned.
+    Decode(String),
+}
+
+impl fmt::Display for BacklogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BacklogError::Encode(reason) => write!(f, "could not encode backlog: {reason}"),
+            BacklogError::Decode(reason) => write!(f, "could not decode backlog: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for BacklogError {}
+
+impl<M: PersistentMessage> ActorHandle<M> {
+    /// Takes every waiting message out of the queue and encodes them, for
+    /// [`spawn_actor_with_backlog`] to restore, e.g. in the next run of the process.
+    ///
+    /// Each message keeps the priority it was queued at, including one given with
+    /// [`send_with_priority`](Self::send_with_priority), and the messages keep their
+    /// order. The message being handled is not included, nor are `Shutdown` messages, which
+    /// stay queued. Nothing about the messages' delivery is saved: TTLs start over and
+    /// correlation ids are lost.
+    ///
+    /// The actor keeps running and messages sent from then on queue up as usual, outside
+    /// the backlog, so stop sending before draining. If a message fails to encode, every
+    /// message is put back in its place.
+    pub fn drain_serialized(&self) -> Result<Vec<u8>, BacklogError> {
+        let pending = self.tx.extract(&mut |_| true);
+        let backlog: Vec<(u8, &M)> = pending
+            .iter()
+            .map(|envelope| (level(envelope.priority()), &envelope.msg))
+            .collect();
+        match bincode::serialize(&backlog) {
+            Ok(bytes) => {
+                for confirm in pending.into_iter().filter_map(|envelope| envelope.confirm) {
+                    confirm.send(Err(NotHandled::Removed));
+                }
+                Ok(bytes)
+            }
+            Err(err) => {
+                self.tx.adopt(pending, AbsorbPlacement::Ahead);
+                Err(BacklogError::Encode(err.to_string()))
+            }
+        }
+    }
+}
+
+/// Spawns an actor like [`spawn_actor_with`](crate::spawn_actor_with), with the messages of
+/// `backlog` queued before it handles anything.
+///
+/// `backlog` comes from [`ActorHandle::drain_serialized`]. Its messages are handled by
+/// priority among anything sent afterwards, ahead of messages of equal priority, and keep
+/// their order among themselves. They are queued with [`Origin::External`] and do not
+/// count against the mailbox's capacity.
+pub fn spawn_actor_with_backlog<A>(
+    actor: A,
+    config: ActorConfig,
+    backlog: Vec<u8>,
+) -> Result<ActorHandle<A::Msg>, BacklogError>
+where
+    A: Actor + Send + 'static,
+    A::Msg: PersistentMessage,
+{
+    let backlog: Vec<(u8, A::Msg)> =
+        bincode::deserialize(&backlog).map_err(|err| BacklogError::Decode(err.to_string()))?;
+    let (handle, processor) = launch(actor, config, None);
+    let pending = backlog
+        .into_iter()
+        .map(|(level, msg)| {
+            let mut envelope = Envelope::new(msg, Origin::External);
+            if envelope.msg.priority() != Priority::Custom(level) {
+                envelope = envelope.with_priority(Priority::Custom(level));
+            }
+            envelope.pending = Some(handle.shared.pending.track());
+            envelope
+        })
+        .collect();
+    handle.tx.adopt(pending, AbsorbPlacement::Behind);
+    task::spawn_on(handle.name(), processor, &Handle::current());
+    Ok(handle)
+}
+
+// Queued messages other than `Shutdown` ones are never queued above the numeric levels.
+fn level(priority: Priority) -> u8 {
+    priority
+        .level()
+        .expect("`Shutdown` messages are not drained")
+}
@@ -0,0 +1,134 @@
+use crate::prelude::*;
+use crate::{spawn_actor_with_backlog, BacklogError};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+#[derive(Prioritized, Serialize, Deserialize)]
+enum Job {
+    #[priority(Low)]
+    Low(u32),
+    #[priority(Medium)]
+    Medium(u32),
+    #[priority(High)]
+    High(u32),
+    #[priority(Shutdown)]
+    Stop,
+}
+
+/// Logs the jobs it handles to a shared log. With a gate, the first job reports that it
+/// started and waits for the release to fire or drop.
+struct Worker {
+    log: Arc<Mutex<Vec<u32>>>,
+    gate: Option<(oneshot::Sender<()>, oneshot::Receiver<()>)>,
+}
+
+#[async_trait]
+impl Actor for Worker {
+    type Msg = Job;
+
+    async fn handle(&mut self, msg: Job, _ctx: &ActorContext<Job>) -> ControlFlow<ShutdownReason> {
+        if let Some((started, release)) = self.gate.take() {
+            let _ = started.send(());
+            let _ = release.await;
+        }
+        match msg {
+            Job::Low(id) | Job::Medium(id) | Job::High(id) => self.log.lock().unwrap().push(id),
+            Job::Stop => return ControlFlow::Break(ShutdownReason::Requested),
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// A worker held up by its first job until the returned sender fires or drops.
+async fn held_worker(log: &Arc<Mutex<Vec<u32>>>) -> (ActorHandle<Job>, oneshot::Sender<()>) {
+    let (started_tx, started) = oneshot::channel();
+    let (release, release_rx) = oneshot::channel();
+    let handle = spawn_actor(Worker {
+        log: Arc::clone(log),
+        gate: Some((started_tx, release_rx)),
+    });
+    handle.send(Job::Medium(0)).await.unwrap();
+    started.await.unwrap();
+    (handle, release)
+}
+
+/// Mixed priorities, one of them overridden on sending.
+async fn send_mixed(handle: &ActorHandle<Job>) {
+    for job in [
+        Job::Low(1),
+        Job::High(2),
+        Job::Medium(3),
+        Job::Low(4),
+        Job::High(5),
+    ] {
+        handle.send(job).await.unwrap();
+    }
+    handle
+        .send_with_priority(Job::Low(6), Priority::High)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_restored_backlog_is_handled_like_the_original_queue() {
+    let direct = Arc::new(Mutex::new(Vec::new()));
+    let (handle, release) = held_worker(&direct).await;
+    send_mixed(&handle).await;
+    drop(release);
+    handle.await_idle().await;
+
+    let drained = Arc::new(Mutex::new(Vec::new()));
+    let (handle, release) = held_worker(&drained).await;
+    send_mixed(&handle).await;
+    let backlog = handle.drain_serialized().unwrap();
+    drop(release);
+    handle.await_idle().await;
+    assert_eq!(*drained.lock().unwrap(), [0]);
+
+    let restored = Arc::new(Mutex::new(vec![0]));
+    let worker = Worker {
+        log: Arc::clone(&restored),
+        gate: None,
+    };
+    let handle = spawn_actor_with_backlog(worker, ActorConfig::new(), backlog).unwrap();
+    handle.await_idle().await;
+
+    assert_eq!(*direct.lock().unwrap(), [0, 2, 5, 6, 3, 1, 4]);
+    assert_eq!(*restored.lock().unwrap(), *direct.lock().unwrap());
+}
+
+#[tokio::test]
+async fn test_restored_backlog_goes_ahead_of_later_sends() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (handle, release) = held_worker(&log).await;
+    handle.send(Job::Low(1)).await.unwrap();
+    handle.send(Job::Low(2)).await.unwrap();
+    let backlog = handle.drain_serialized().unwrap();
+    drop(release);
+    drop(handle);
+
+    let restored = Arc::new(Mutex::new(Vec::new()));
+    let worker = Worker {
+        log: Arc::clone(&restored),
+        gate: None,
+    };
+    // A capacity below the backlog's length: restored jobs take no mailbox slots.
+    let config = ActorConfig::new().capacity(1);
+    let handle = spawn_actor_with_backlog(worker, config, backlog).unwrap();
+    handle.send(Job::Low(3)).await.unwrap();
+    handle.await_idle().await;
+
+    assert_eq!(*restored.lock().unwrap(), [1, 2, 3]);
+}
+
+#[tokio::test]
+async fn test_garbage_backlog_spawns_nothing() {
+    let worker = Worker {
+        log: Arc::default(),
+        gate: None,
+    };
+    let restored = spawn_actor_with_backlog(worker, ActorConfig::new(), vec![0xff; 3]);
+    assert!(matches!(restored, Err(BacklogError::Decode(_))));
+}
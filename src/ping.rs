@@ -0,0 +1,36 @@
+use crate::{ActorHandle, Error};
+use tokio::sync::oneshot;
+use tokio::time::{Duration, Instant};
+
+impl<M> ActorHandle<M> {
+    /// Checks that the actor is alive and making progress, returning how long it took to
+    /// answer.
+    ///
+    /// The actor answers without handling anything, as soon as it is between messages and
+    /// ahead of whatever is queued, so a ping needs no domain message and takes no mailbox
+    /// slot. A handler that is still running holds the answer up; past the
+    /// [ping timeout](crate::ActorConfig::ping_timeout) this reports
+    /// [`Error::Timeout`]. Use [`is_closed`](Self::is_closed) to check only whether
+    /// the actor still accepts messages.
+    pub async fn ping(&self) -> Result<Duration, Error> {
+        let started = Instant::now();
+        let answer = self
+            .pinged()
+            .ok_or_else(|| Error::closed(self.name(), None))?;
+        match tokio::time::timeout(self.shared.ping_timeout, answer).await {
+            Ok(Ok(())) => Ok(started.elapsed()),
+            Ok(Err(_)) => Err(Error::closed(self.name(), None)),
+            Err(_elapsed) => Err(Error::Timeout {
+                actor: self.name().to_owned(),
+            }),
+        }
+    }
+
+    /// Pings this actor, or whichever absorbed it.
+    fn pinged(&self) -> Option<oneshot::Receiver<()>> {
+        self.tx.ping().or_else(|| {
+            let absorber = self.shared.redirect.load_full()?;
+            absorber.pinged()
+        })
+    }
+}
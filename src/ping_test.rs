@@ -0,0 +1,36 @@
+use crate::prelude::*;
+use crate::test_support::{stall, Gated, GatedMsg};
+use crate::{spawn_actor_with, ActorConfig};
+use tokio::time::Duration;
+
+#[tokio::test]
+async fn test_healthy_actor_answers_a_ping() {
+    let handle = spawn_actor(Gated::new());
+    handle.send(GatedMsg::Fill()).await.unwrap();
+
+    assert!(handle.ping().await.is_ok());
+    assert!(!handle.is_closed());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_ping_times_out_while_a_handler_is_stuck() {
+    let handle = spawn_actor_with(
+        Gated::new(),
+        ActorConfig::new().ping_timeout(Duration::from_millis(200)),
+    );
+    let release = stall(&handle).await;
+
+    assert!(matches!(handle.ping().await, Err(Error::Timeout { .. })));
+    drop(release);
+    assert!(handle.ping().await.is_ok());
+}
+
+#[tokio::test]
+async fn test_ping_after_shutdown_reports_closed() {
+    let handle = spawn_actor(Gated::new());
+    handle.send(GatedMsg::Shutdown).await.unwrap();
+    handle.clone().join().await;
+
+    assert!(handle.is_closed());
+    assert!(handle.ping().await.unwrap_err().is_closed());
+}
@@ -0,0 +1,137 @@
+//! Piping `futures` streams into actors.
+
+use crate::mailbox::{Envelope, Origin};
+use crate::{ActorHandle, Error, Prioritized};
+use futures_core::Stream;
+use futures_sink::Sink;
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::sync::{Mutex, PoisonError};
+use std::task::{Context, Poll};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::task::JoinHandle;
+
+type SendFuture<M> = Pin<Box<dyn Future<Output = Result<(), Error<M>>> + Send>>;
+
+/// A send that found the mailbox full and is waiting for room.
+// Only ever touched through `&mut`, so the mutex is never locked; it just keeps the handle
+// `Sync` around a future that need not be.
+pub(crate) struct InFlight<M>(Mutex<Option<SendFuture<M>>>);
+
+impl<M> Default for InFlight<M> {
+    fn default() -> Self {
+        InFlight(Mutex::new(None))
+    }
+}
+
+impl<M> InFlight<M> {
+    fn slot(&mut self) -> &mut Option<SendFuture<M>> {
+        self.0.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Sends each item with [`Origin::External`], so a stream can be piped straight into an
+/// actor with `stream.map(Ok).forward(handle)`.
+///
+/// A message that finds the mailbox full is held by the handle until there is room, and
+/// the sink is not ready for the next one until then; messages therefore arrive in the
+/// order the stream produced them and are then handled by priority like any others.
+/// Fails with the unsent message once the actor has stopped, while it is not
+/// [ready](crate::ActorConfig::readiness) for it, or while its priority's
+/// [quota](crate::RuntimeConfig::quotas) turns it away.
+///
+/// Closing the sink only waits for the held message to go out. The handle keeps the actor
+/// alive until it is dropped, which `forward` does once the stream ends.
+impl<M: Prioritized + Send + 'static> Sink<M> for ActorHandle<M> {
+    type Error = Error<M>;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_flush(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, msg: M) -> Result<(), Self::Error> {
+        let this = self.get_mut();
+        let envelope = this.check_ready(Envelope::new(msg, Origin::External))?;
+        match this.try_send_envelope(envelope) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Closed(envelope)) => {
+                Err(Error::closed(this.name(), Some(envelope.msg)))
+            }
+            Err(TrySendError::Full(envelope)) => {
+                let handle = this.clone();
+                *this.in_flight.slot() =
+                    Some(Box::pin(async move { handle.deliver(envelope).await }));
+                Ok(())
+            }
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        let slot = this.in_flight.slot();
+        let Some(send) = slot else {
+            return Poll::Ready(Ok(()));
+        };
+        let result = std::task::ready!(send.as_mut().poll(cx));
+        *slot = None;
+        Poll::Ready(result)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_flush(cx)
+    }
+}
+
+impl<M: Prioritized + Send + 'static> ActorHandle<M> {
+    /// Spawns a task that sends every item of `stream` to this actor.
+    ///
+    /// Same as [`spawn_stream_forwarder`] with the items sent as they are.
+    pub fn attach_stream<S>(&self, stream: S) -> JoinHandle<usize>
+    where
+        S: Stream<Item = M> + Send + 'static,
+    {
+        spawn_stream_forwarder(self.clone(), stream, |msg| msg)
+    }
+}
+
+/// Spawns a task that turns every item of `stream` into a message with `map` and sends it to
+/// the actor behind `handle`.
+///
+/// Each send waits for room in the mailbox before the next item is pulled, so a fast stream
+/// is slowed to the actor's pace rather than buffered. The task ends once the stream does or
+/// the actor stops, whichever comes first, and resolves to the number of messages it sent;
+/// messages their priority's [quota](crate::RuntimeConfig::quotas) turns away are skipped.
+/// It holds `handle` until then, keeping the actor alive while the stream runs.
+pub fn spawn_stream_forwarder<M, S, F>(
+    handle: ActorHandle<M>,
+    stream: S,
+    mut map: F,
+) -> JoinHandle<usize>
+where
+    M: Prioritized + Send + 'static,
+    S: Stream + Send + 'static,
+    F: FnMut(S::Item) -> M + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut status = handle.shared.status.clone();
+        let mut stream = std::pin::pin!(stream);
+        let mut forwarded = 0;
+        loop {
+            let msg = tokio::select! {
+                item = poll_fn(|cx| stream.as_mut().poll_next(cx)) => match item {
+                    Some(item) => map(item),
+                    None => break,
+                },
+                // Stop waiting on an idle stream once nobody is listening.
+                _ = status.wait_for(Option::is_some) => break,
+            };
+            match handle.send(msg).await {
+                Ok(()) => forwarded += 1,
+                // Turned away by its priority's quota; the next item may still get in.
+                Err(err) if err.is_full() => {}
+                Err(_) => break,
+            }
+        }
+        forwarded
+    })
+}
@@ -0,0 +1,105 @@
+use crate::prelude::*;
+use crate::spawn_stream_forwarder;
+use crate::test_support::{gated_init, stall, Gated, GatedMsg};
+use crate::Readiness;
+use futures::{stream, SinkExt, StreamExt};
+use tokio::time::Duration;
+
+fn mixed(n: usize) -> impl futures::Stream<Item = Result<GatedMsg, Error<GatedMsg>>> {
+    stream::iter(0..n).map(|i| {
+        Ok(if i % 2 == 0 {
+            GatedMsg::Fill()
+        } else {
+            GatedMsg::Urgent()
+        })
+    })
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_forwarded_batch_is_handled_in_priority_order() {
+    let actor = spawn_actor_owned(Gated::new(), ActorConfig::new().capacity(1000));
+    let release = stall(&actor).await;
+    mixed(1000).forward(actor.handle().clone()).await.unwrap();
+    // Let the whole batch reach the queue before the actor drains it.
+    tokio::time::sleep(Duration::from_millis(1)).await;
+
+    drop(release);
+    let handled = actor.join().await.unwrap().handled.clone();
+    let mut expected = vec![Priority::High; 500];
+    expected.extend([Priority::Low; 500]);
+    assert_eq!(handled, expected);
+}
+
+#[tokio::test]
+async fn test_forward_waits_for_room_in_a_full_mailbox() {
+    let actor = spawn_actor_owned(Gated::new(), ActorConfig::new().capacity(4));
+    let release = stall(&actor).await;
+    let forward = tokio::spawn(mixed(1000).forward(actor.handle().clone()));
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(!forward.is_finished());
+
+    drop(release);
+    forward.await.unwrap().unwrap();
+    let handled = actor.join().await.unwrap().handled.clone();
+    assert_eq!(handled.len(), 1000);
+}
+
+/// Yields `()` every `period`, forever.
+fn ticks(period: Duration) -> impl futures::Stream<Item = ()> {
+    stream::unfold(tokio::time::interval(period), |mut interval| async move {
+        interval.tick().await;
+        Some(((), interval))
+    })
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_forwarder_sends_every_item_until_the_stream_ends() {
+    let actor = spawn_actor_owned(Gated::new(), ActorConfig::new());
+    let forwarder = spawn_stream_forwarder(
+        actor.handle().clone(),
+        ticks(Duration::from_millis(10)).take(5),
+        |()| GatedMsg::Fill(),
+    );
+
+    assert_eq!(forwarder.await.unwrap(), 5);
+    let handled = actor.join().await.unwrap().handled.clone();
+    assert_eq!(handled, [Priority::Low; 5]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_attached_stream_stops_when_the_actor_does() {
+    let handle = spawn_actor(Gated::new());
+    let forwarder =
+        handle.attach_stream(ticks(Duration::from_millis(10)).map(|()| GatedMsg::Urgent()));
+
+    tokio::time::sleep(Duration::from_millis(35)).await;
+    handle.send(GatedMsg::Shutdown).await.unwrap();
+    assert_eq!(handle.join().await, TerminationReason::ShutdownMessage);
+    assert_eq!(forwarder.await.unwrap(), 4);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_sink_and_forwarder_respect_readiness() {
+    let (release, gate) = tokio::sync::oneshot::channel();
+    let handle = gated_init(ActorConfig::new().readiness(Readiness::Reject), gate);
+
+    let mut sink = handle.clone();
+    let err = SinkExt::send(&mut sink, GatedMsg::Urgent())
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::NotReady {
+            returned: GatedMsg::Urgent(),
+            ..
+        }
+    ));
+    let forwarder = handle.attach_stream(stream::iter([GatedMsg::Fill(), GatedMsg::Fill()]));
+    assert_eq!(forwarder.await.unwrap(), 0);
+
+    release.send(()).unwrap();
+    handle.ready().await.unwrap();
+    SinkExt::send(&mut sink, GatedMsg::Urgent()).await.unwrap();
+    let forwarder = handle.attach_stream(stream::iter([GatedMsg::Fill(), GatedMsg::Fill()]));
+    assert_eq!(forwarder.await.unwrap(), 2);
+}
@@ -0,0 +1,24 @@
+//! The types a typical actor needs, importable in one line.
+//!
+//! ```
+//! use priact::prelude::*;
+//! ```
+//!
+//! `define_actor!` expands to unqualified `Actor`, `Prioritized`, and `Priority` paths, so
+//! glob-importing the prelude is enough to use the macro.
+
+pub use crate::{
+    define_actor, spawn_actor, spawn_actor_owned, spawn_actor_supervised, spawn_actor_with, Actor,
+    ActorConfig, ActorContext, ActorHandle, Error, EventEmitter, Prioritized, Priority,
+    RestartStrategy, ShutdownReason, SpawnedActor, TerminationReason, TryActor,
+};
+pub use std::ops::ControlFlow;
+
+#[cfg(feature = "ask")]
+pub use crate::AskExt;
+#[cfg(feature = "metrics")]
+pub use crate::MetricsExt;
+#[cfg(feature = "schedule")]
+pub use crate::ScheduleExt;
+#[cfg(feature = "tap")]
+pub use crate::TapExt;
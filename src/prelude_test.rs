@@ -0,0 +1,45 @@
+// Everything below must compile with nothing but the prelude in scope.
+use crate::prelude::*;
+use tokio::sync::oneshot;
+
+define_actor! {
+    Tally {
+        total: u64,
+    }
+
+    impl TallyMsg {
+        @priority(High)
+        fn Add(&mut self, amount: u64) {
+            self.total += amount;
+        }
+
+        @priority(Low)
+        fn Total(&mut self, tx: oneshot::Sender<u64>) {
+            let _ = tx.send(self.total);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_prelude_suffices_for_typical_actor() {
+    let handle: ActorHandle<TallyMsg> =
+        spawn_actor_with(Tally { total: 0 }, ActorConfig::new().capacity(8));
+    handle.send(TallyMsg::Add(5)).await.unwrap();
+    let _ = handle.metrics().permits_in_use(Priority::Low);
+
+    let (tx, rx) = oneshot::channel();
+    handle.send(TallyMsg::Total(tx)).await.unwrap();
+    assert_eq!(rx.await.unwrap(), 5);
+
+    let _other = spawn_actor(Tally { total: 0 });
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_old_wrapper_path_still_resolves() {
+    use crate::PrioritizedWrapper;
+
+    let low: PrioritizedWrapper<Priority> = crate::internal::PrioritizedWrapper(Priority::Low, 0);
+    let high: PrioritizedWrapper<Priority> = crate::internal::PrioritizedWrapper(Priority::High, 1);
+    assert!(high > low);
+}
@@ -0,0 +1,43 @@
+use tokio::sync::watch;
+
+/// How full an actor's queue is, relative to the watermarks set with
+/// [`ActorConfig::watermarks`](crate::ActorConfig::watermarks).
+///
+/// Observed through [`ActorHandle::pressure`](crate::ActorHandle::pressure), e.g. to stop
+/// reading from a socket before sends start to wait.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MailboxPressure {
+    /// The queue has not reached the high watermark since it last fell to the low one.
+    Normal,
+    /// The queue reached the high watermark and has not fallen to the low one since.
+    High,
+}
+
+/// Flips an actor's [`MailboxPressure`] as its queue grows and shrinks.
+pub(crate) struct Watermarks {
+    high: usize,
+    low: usize,
+    pressure: watch::Sender<MailboxPressure>,
+}
+
+impl Watermarks {
+    pub(crate) fn new(high: usize, low: usize, pressure: watch::Sender<MailboxPressure>) -> Self {
+        Watermarks {
+            high,
+            low,
+            pressure,
+        }
+    }
+
+    /// Called with the queue's length whenever it changes; observers only hear about flips.
+    pub(crate) fn update(&self, len: usize) {
+        self.pressure.send_if_modified(|pressure| {
+            let next = match *pressure {
+                MailboxPressure::Normal if len >= self.high => MailboxPressure::High,
+                MailboxPressure::High if len <= self.low => MailboxPressure::Normal,
+                unchanged => unchanged,
+            };
+            std::mem::replace(pressure, next) != next
+        });
+    }
+}
@@ -0,0 +1,73 @@
+use crate::test_support::{stall, Gated, GatedMsg};
+use crate::{spawn_actor, spawn_actor_with, ActorConfig, MailboxPressure};
+
+#[tokio::test]
+async fn test_pressure_flips_at_the_watermarks() {
+    let handle = spawn_actor_with(
+        Gated::new(),
+        ActorConfig::new().unbounded().watermarks(4, 1),
+    );
+    let pressure = handle.pressure();
+    let first = stall(&handle).await;
+
+    // A second stall waits in the queue, to hold the queue partly drained later on.
+    let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+    let (second, release_rx) = tokio::sync::oneshot::channel::<()>();
+    handle
+        .send(GatedMsg::Hold(started_tx, release_rx))
+        .await
+        .unwrap();
+    for _ in 0..2 {
+        handle.send(GatedMsg::Fill()).await.unwrap();
+    }
+    assert_eq!(*pressure.borrow(), MailboxPressure::Normal);
+    handle.send(GatedMsg::Fill()).await.unwrap();
+    assert_eq!(*pressure.borrow(), MailboxPressure::High);
+
+    // Three still queued is above the low watermark, so the pressure holds.
+    drop(first);
+    started_rx.await.unwrap();
+    assert_eq!(*pressure.borrow(), MailboxPressure::High);
+
+    drop(second);
+    handle.await_idle().await;
+    assert_eq!(*pressure.borrow(), MailboxPressure::Normal);
+}
+
+#[tokio::test]
+async fn test_observers_hear_only_about_flips() {
+    let handle = spawn_actor_with(
+        Gated::new(),
+        ActorConfig::new().unbounded().watermarks(2, 0),
+    );
+    let mut pressure = handle.pressure();
+    let release = stall(&handle).await;
+    for _ in 0..5 {
+        handle.send(GatedMsg::Fill()).await.unwrap();
+    }
+    pressure.changed().await.unwrap();
+    assert_eq!(*pressure.borrow_and_update(), MailboxPressure::High);
+    assert!(!pressure.has_changed().unwrap());
+
+    drop(release);
+    pressure.changed().await.unwrap();
+    assert_eq!(*pressure.borrow_and_update(), MailboxPressure::Normal);
+}
+
+#[tokio::test]
+async fn test_pressure_stays_normal_without_watermarks() {
+    let handle = spawn_actor(Gated::new());
+    let mut pressure = handle.pressure();
+    let _release = stall(&handle).await;
+    for _ in 0..10 {
+        handle.send(GatedMsg::Fill()).await.unwrap();
+    }
+    assert_eq!(*pressure.borrow(), MailboxPressure::Normal);
+    assert!(pressure.changed().await.is_err());
+}
+
+#[test]
+#[should_panic(expected = "must be below the high watermark")]
+fn test_low_watermark_must_be_below_the_high_one() {
+    let _ = ActorConfig::new().watermarks(3, 3);
+}
@@ -0,0 +1,767 @@
+use crate::internal::PrioritizedWrapper;
+use crate::mailbox::{Envelope, Origin};
+use crate::pressure::Watermarks;
+use crate::throttle::Throttle;
+use crate::RuntimeConfig;
+use crate::{AbsorbPlacement, ActorMetrics, Prioritized, Priority};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use tokio::time::{Duration, Instant};
+
+/// The messages in an actor's mailbox, waiting to be handled.
+///
+/// Messages are kept in one [`Lane`] per [`Origin`]. Without a fairness limit the queue
+/// always hands out the highest-priority, earliest message across all lanes; with one, an
+/// origin that has supplied `limit` messages in a row yields to the best message from any
+/// other origin, if there is one. `Shutdown` messages are never held back.
+///
+/// With aging, a message is ordered as if its priority were one named level higher for every
+/// `step` it has waited, up to `High`. Priorities are brought up to date whenever the next
+/// message is picked and some message is due for a raise.
+///
+/// With [`SchedulingPolicy::WeightedFair`], the highest-priority message no longer always
+/// goes first: each pop takes the best message of the band whose turn it is, moving round
+/// the bands from `High` down with a deficit counter per band, topped up by the band's
+/// weight on every turn and spent one message at a time.
+///
+/// With rate limits, a level whose delaying limit has run out of tokens is passed over as
+/// if it had nothing waiting, until a token comes in.
+pub(crate) struct MessageQueue<M> {
+    lanes: [Lane<M>; Origin::COUNT],
+    // Shared with the queue's `Inlets`, so messages staged there keep their place in line.
+    next_seq: Arc<AtomicU64>,
+    // Handed out downwards to messages adopted ahead of everything queued.
+    first_seq: u64,
+    fairness: Option<usize>,
+    aging: Option<Duration>,
+    // When the next queued message is due for a raise.
+    next_raise: Option<Instant>,
+    // The origin of the last pops and how many came from it in a row.
+    streak: Option<(Origin, usize)>,
+    // `None` under strict priority.
+    round: Option<Round>,
+    throttle: Option<Throttle>,
+    metrics: Arc<ActorMetrics>,
+    watermarks: Option<Watermarks>,
+}
+
+impl<M: Prioritized> MessageQueue<M> {
+    pub(crate) fn new(
+        fairness: Option<usize>,
+        aging: Option<Duration>,
+        metrics: Arc<ActorMetrics>,
+    ) -> Self {
+        MessageQueue {
+            lanes: std::array::from_fn(|_| Lane::default()),
+            next_seq: Arc::new(AtomicU64::new(FIRST_SEQ)),
+            first_seq: FIRST_SEQ,
+            fairness,
+            aging,
+            next_raise: None,
+            streak: None,
+            round: None,
+            throttle: None,
+            metrics,
+            watermarks: None,
+        }
+    }
+
+    /// Reports the queue's pressure to `watermarks` as it grows and shrinks.
+    pub(crate) fn with_watermarks(mut self, watermarks: Option<Watermarks>) -> Self {
+        self.watermarks = watermarks;
+        self
+    }
+
+    /// Picks between priority bands by `policy`.
+    pub(crate) fn with_scheduling(mut self, policy: SchedulingPolicy) -> Self {
+        self.round = match policy {
+            SchedulingPolicy::Strict => None,
+            SchedulingPolicy::WeightedFair { weights } => Some(Round::new(weights)),
+        };
+        self
+    }
+
+    /// Holds messages back by the rate limits of their priorities.
+    pub(crate) fn with_throttle(mut self, throttle: Option<Throttle>) -> Self {
+        self.throttle = throttle;
+        self
+    }
+
+    /// Spends a token on a message of `priority` arriving; `false` if a rejecting rate
+    /// limit turns it away.
+    pub(crate) fn admit(&mut self, priority: Priority) -> bool {
+        self.throttle
+            .as_mut()
+            .is_none_or(|throttle| throttle.admit(priority))
+    }
+
+    /// When a message held back by a rate limit can next be handled, if every message
+    /// waiting is held back.
+    pub(crate) fn held_back_until(&self) -> Option<Instant> {
+        let throttle = self.throttle.as_ref()?;
+        throttle.released_at(self.lanes.iter().flat_map(Lane::levels_waiting))
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.lanes.iter().map(|lane| lane.len).sum()
+    }
+
+    /// Queues a message behind everything of equal priority that arrived before it.
+    pub(crate) fn push(&mut self, envelope: Envelope<M>) {
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        self.insert(envelope, seq);
+    }
+
+    fn insert(&mut self, envelope: Envelope<M>, seq: u64) {
+        let queued = Queued::new(envelope);
+        self.metrics.queued(queued.envelope.origin, queued.base);
+        self.place(PrioritizedWrapper(queued, seq));
+    }
+
+    /// Puts a message, already counted as queued, in its lane.
+    fn place(&mut self, wrapper: PrioritizedWrapper<Queued<M>>) {
+        if let Some(step) = self.aging {
+            if let Some(at) = wrapper.0.next_raise(step) {
+                self.next_raise = Some(self.next_raise.map_or(at, |next| next.min(at)));
+            }
+        }
+        self.lanes[wrapper.0.envelope.origin.index()].insert(wrapper);
+        self.track_pressure();
+    }
+
+    fn track_pressure(&self) {
+        if let Some(watermarks) = &self.watermarks {
+            watermarks.update(self.len());
+        }
+    }
+
+    /// Puts `envelope` in the place of the queued message with the same
+    /// [coalescing key](Prioritized::coalesce_key), returning the message it replaced, or
+    /// hands `envelope` back if there is none.
+    ///
+    /// The newcomer keeps the replaced message's place in line, and how long it has waited
+    /// as far as aging goes.
+    pub(crate) fn coalesce(&mut self, envelope: Envelope<M>) -> Result<Envelope<M>, Envelope<M>> {
+        let Some(key) = coalesce_key(&envelope.msg) else {
+            return Err(envelope);
+        };
+        let Some((origin, replaced)) = Origin::ALL.into_iter().find_map(|origin| {
+            let replaced = self.lanes[origin.index()].remove(|queued| queued.key == Some(key))?;
+            Some((origin, replaced))
+        }) else {
+            return Err(envelope);
+        };
+        self.metrics.dequeued(origin, replaced.0.base);
+        let PrioritizedWrapper(replaced, seq) = replaced;
+        let mut queued = Queued::new(envelope);
+        queued.enqueued_at = replaced.enqueued_at;
+        if let Some(step) = self.aging {
+            queued.raise(step, Instant::now());
+        }
+        self.metrics.queued(queued.envelope.origin, queued.base);
+        self.place(PrioritizedWrapper(queued, seq));
+        Ok(replaced.envelope)
+    }
+
+    /// Removes every queued message, in the order they arrived.
+    pub(crate) fn drain(&mut self) -> Vec<Envelope<M>> {
+        let mut drained = Vec::with_capacity(self.len());
+        for origin in Origin::ALL {
+            for wrapper in self.lanes[origin.index()].take() {
+                self.metrics.dequeued(origin, wrapper.0.base);
+                drained.push(wrapper);
+            }
+        }
+        self.streak = None;
+        self.track_pressure();
+        drained.sort_unstable_by_key(|wrapper| wrapper.1);
+        drained
+            .into_iter()
+            .map(|wrapper| wrapper.0.envelope)
+            .collect()
+    }
+
+    /// Queues `envelopes`, keeping their order among themselves, either behind or ahead of
+    /// the queued messages of equal priority.
+    pub(crate) fn adopt(&mut self, envelopes: Vec<Envelope<M>>, placement: AbsorbPlacement) {
+        if placement == AbsorbPlacement::Ahead {
+            // Number them below every message queued so far.
+            self.first_seq -= envelopes.len() as u64;
+            for (seq, envelope) in (self.first_seq..).zip(envelopes) {
+                self.insert(envelope, seq);
+            }
+        } else {
+            envelopes
+                .into_iter()
+                .for_each(|envelope| self.push(envelope));
+        }
+    }
+
+    /// Removes every message `pick` picks, apart from `Shutdown` messages, leaving the rest
+    /// in their order. Returns them in the order they arrived.
+    pub(crate) fn extract(
+        &mut self,
+        pick: &mut dyn FnMut(&Envelope<M>) -> bool,
+    ) -> Vec<Envelope<M>> {
+        let mut extracted = Vec::new();
+        for origin in Origin::ALL {
+            for wrapper in self.lanes[origin.index()].extract(|queued| {
+                queued.envelope.priority() != Priority::Shutdown && pick(&queued.envelope)
+            }) {
+                self.metrics.dequeued(origin, wrapper.0.base);
+                extracted.push(wrapper);
+            }
+        }
+        self.track_pressure();
+        extracted.sort_unstable_by_key(|wrapper| wrapper.1);
+        extracted
+            .into_iter()
+            .map(|wrapper| wrapper.0.envelope)
+            .collect()
+    }
+
+    /// The queued messages that have not expired by `now`, highest priority first and in
+    /// the order they arrived within a priority.
+    pub(crate) fn waiting(&self, now: Instant) -> Vec<&Envelope<M>> {
+        let mut waiting: Vec<_> = self
+            .lanes
+            .iter()
+            .flat_map(Lane::iter)
+            .filter(|wrapper| !wrapper.0.envelope.is_expired(now))
+            .collect();
+        waiting.sort_unstable_by(|a, b| b.cmp(a));
+        waiting
+            .into_iter()
+            .map(|wrapper| &wrapper.0.envelope)
+            .collect()
+    }
+
+    /// Removes the message that would be handled last, if its priority is below `priority`.
+    pub(crate) fn evict_below(&mut self, priority: Priority) -> Option<Envelope<M>> {
+        let origin = Origin::ALL
+            .into_iter()
+            .filter_map(|origin| Some((origin, self.lanes[origin.index()].back()?)))
+            .min_by(|(_, a), (_, b)| a.cmp(b))
+            .filter(|(_, last)| last.0.priority() < priority)
+            .map(|(origin, _)| origin)?;
+
+        let evicted = self.lanes[origin.index()]
+            .pop_back()
+            .expect("the message to evict is queued");
+        self.metrics.dequeued(origin, evicted.0.base);
+        self.track_pressure();
+        Some(evicted.0.envelope)
+    }
+
+    /// The message [`pop`](Self::pop) would return.
+    pub(crate) fn peek(&mut self) -> Option<&Envelope<M>> {
+        self.raise_waiting();
+        self.refill();
+        let (origin, round) = self.next()?;
+        let eligible = eligible(self.throttle.as_ref(), round.map(|round| round.band));
+        self.lanes[origin.index()]
+            .front(eligible)
+            .map(|top| &top.0.envelope)
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<Envelope<M>> {
+        self.raise_waiting();
+        self.refill();
+        let (origin, round) = self.next()?;
+        let eligible = eligible(self.throttle.as_ref(), round.map(|round| round.band));
+        let mut queued = self.lanes[origin.index()].pop_front(eligible)?.0;
+        if round.is_some() {
+            self.round = round;
+        }
+        if let Some(throttle) = &mut self.throttle {
+            throttle.dispatched(queued.priority);
+        }
+        self.streak = match self.streak {
+            Some((last, run)) if last == origin => Some((origin, run + 1)),
+            _ => Some((origin, 1)),
+        };
+        self.metrics.dequeued(origin, queued.base);
+        self.track_pressure();
+        queued.envelope.enqueued_at = Some(queued.enqueued_at);
+        Some(queued.envelope)
+    }
+
+    /// Brings aged priorities up to date, if any message is due for a raise.
+    fn raise_waiting(&mut self) {
+        let Some(step) = self.aging else {
+            return;
+        };
+        let now = Instant::now();
+        if self.next_raise.is_none_or(|at| now < at) {
+            return;
+        }
+        let mut next_raise: Option<Instant> = None;
+        for lane in &mut self.lanes {
+            // A raised message moves to a higher level's FIFO, so rebuild the lane.
+            let mut queued = lane.take();
+            queued.sort_unstable_by_key(|wrapper| wrapper.1);
+            for mut wrapper in queued {
+                wrapper.0.raise(step, now);
+                if let Some(at) = wrapper.0.next_raise(step) {
+                    next_raise = Some(next_raise.map_or(at, |next| next.min(at)));
+                }
+                lane.insert(wrapper);
+            }
+        }
+        self.next_raise = next_raise;
+    }
+
+    /// Hands out the tokens rate limits have gained since they were last spent.
+    fn refill(&mut self) {
+        if let Some(throttle) = &mut self.throttle {
+            throttle.refill(Instant::now());
+        }
+    }
+
+    /// The origin of the next message and, under weighted fair scheduling, the round as it
+    /// stands once that message is taken. Picking changes nothing, so `peek` and `pop`
+    /// agree.
+    fn next(&self) -> Option<(Origin, Option<Round>)> {
+        let best = self.best_origin(None, None)?;
+        let round = match self.round {
+            Some(round) if !self.is_shutdown(best) => round,
+            _ => return Some((self.next_origin(best, None), None)),
+        };
+        let mut waiting = [false; BANDS];
+        let eligible = eligible(self.throttle.as_ref(), None);
+        for lane in &self.lanes {
+            lane.levels_waiting()
+                .filter(|level| eligible(*level))
+                .for_each(|level| waiting[band_of(level)] = true);
+        }
+        let round = round.next(waiting);
+        let best = self
+            .best_origin(None, Some(round.band))
+            .expect("the band whose turn it is has messages waiting");
+        Some((self.next_origin(best, Some(round.band)), Some(round)))
+    }
+
+    /// `best` unless its origin has used up its fairness limit and another origin has a
+    /// message waiting in `band`.
+    fn next_origin(&self, best: Origin, band: Option<usize>) -> Origin {
+        let Some(limit) = self.fairness else {
+            return best;
+        };
+        match self.streak {
+            Some((last, run)) if last == best && run >= limit && !self.is_shutdown(best) => {
+                self.best_origin(Some(best), band).unwrap_or(best)
+            }
+            _ => best,
+        }
+    }
+
+    /// The origin whose head message in `band`, or overall, comes first, ignoring `except`.
+    fn best_origin(&self, except: Option<Origin>, band: Option<usize>) -> Option<Origin> {
+        Origin::ALL
+            .into_iter()
+            .filter(|origin| Some(*origin) != except)
+            .filter_map(|origin| {
+                let eligible = eligible(self.throttle.as_ref(), band);
+                Some((origin, self.lanes[origin.index()].front(eligible)?))
+            })
+            .max_by(|(_, a), (_, b)| a.cmp(b))
+            .map(|(origin, _)| origin)
+    }
+
+    fn is_shutdown(&self, origin: Origin) -> bool {
+        self.lanes[origin.index()]
+            .front(|_| true)
+            .is_some_and(|top| top.0.priority() == Priority::Shutdown)
+    }
+}
+
+/// Messages sent without taking the mailbox lock, kept in one inlet per priority band and
+/// one for `Shutdown` until the queue next takes them in.
+///
+/// Only messages that nothing looking across the whole queue has a say over are staged: none
+/// with a coalescing key, and none while a shed threshold, a queue limit or a quota for their
+/// level is set. A queue with rate limits or watermarks takes no staged messages at all. A
+/// staged message is numbered in the queue's arrival order while its inlet is locked, so it
+/// keeps its place in line however late the queue takes it in.
+pub(crate) struct Inlets<M> {
+    inlets: [Inlet<M>; BANDS + 1],
+    next_seq: Arc<AtomicU64>,
+    metrics: Arc<ActorMetrics>,
+}
+
+impl<M: Prioritized> Inlets<M> {
+    /// Inlets feeding `queue`, or `None` if it has to see every message as it arrives.
+    pub(crate) fn feeding(queue: &MessageQueue<M>) -> Option<Self> {
+        if queue.throttle.is_some() || queue.watermarks.is_some() {
+            return None;
+        }
+        Some(Inlets {
+            inlets: std::array::from_fn(|_| Inlet::default()),
+            next_seq: Arc::clone(&queue.next_seq),
+            metrics: Arc::clone(&queue.metrics),
+        })
+    }
+
+    /// Whether `envelope` may be staged under `runtime` instead of queued under the mailbox
+    /// lock.
+    pub(crate) fn accepts(&self, envelope: &Envelope<M>, runtime: &RuntimeConfig) -> bool {
+        let priority = envelope.priority();
+        priority == Priority::Shutdown
+            || (coalesce_key(&envelope.msg).is_none()
+                && runtime.shed_threshold.is_none()
+                && runtime.max_queued.is_none()
+                && runtime.quota(priority).is_none())
+    }
+
+    /// Stages a message, handing it back once `open` is cleared.
+    pub(crate) fn stage(
+        &self,
+        envelope: Envelope<M>,
+        open: &AtomicBool,
+    ) -> Result<(), Envelope<M>> {
+        let priority = envelope.priority();
+        let band = if priority == Priority::Shutdown {
+            BANDS
+        } else {
+            band_of(priority)
+        };
+        let inlet = &self.inlets[band];
+        let mut staged = inlet.lock();
+        inlet.len.fetch_add(1, Ordering::SeqCst);
+        if !open.load(Ordering::SeqCst) {
+            inlet.len.fetch_sub(1, Ordering::SeqCst);
+            return Err(envelope);
+        }
+        let queued = Queued::new(envelope);
+        // Counted before the receiver can see it, so it is never counted out first.
+        self.metrics.queued(queued.envelope.origin, queued.base);
+        self.metrics.message_enqueued();
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        staged.push(PrioritizedWrapper(queued, seq));
+        Ok(())
+    }
+
+    /// Moves every staged message into `queue`. Once `open` is cleared, the last call
+    /// takes in every message that will ever be staged.
+    pub(crate) fn empty_into(&self, queue: &mut MessageQueue<M>) {
+        for inlet in &self.inlets {
+            if inlet.len.load(Ordering::SeqCst) == 0 {
+                continue;
+            }
+            let mut staged = inlet.lock();
+            inlet.len.fetch_sub(staged.len(), Ordering::SeqCst);
+            for wrapper in staged.drain(..) {
+                queue.place(wrapper);
+            }
+        }
+    }
+}
+
+/// The messages staged for one band.
+struct Inlet<M> {
+    staged: Mutex<Vec<PrioritizedWrapper<Queued<M>>>>,
+    // Counted up before a sender checks whether the mailbox is open, so whoever closes it
+    // cannot miss a message about to be staged, and counted down as they are taken in.
+    len: AtomicUsize,
+}
+
+impl<M> Default for Inlet<M> {
+    fn default() -> Self {
+        Inlet {
+            staged: Mutex::default(),
+            len: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl<M> Inlet<M> {
+    fn lock(&self) -> MutexGuard<'_, Vec<PrioritizedWrapper<Queued<M>>>> {
+        self.staged.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// One origin's messages, in a FIFO per priority level, each ordered by arrival.
+///
+/// Pushing and popping cost the same however many messages wait; only the handful of levels
+/// in use are scanned, from the highest down.
+struct Lane<M> {
+    // Ordered from the highest priority down. Emptied levels are kept, along with their
+    // allocation, since they tend to fill up again.
+    levels: Vec<(Priority, VecDeque<PrioritizedWrapper<Queued<M>>>)>,
+    len: usize,
+}
+
+impl<M> Default for Lane<M> {
+    fn default() -> Self {
+        Lane {
+            levels: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<M> Lane<M> {
+    /// Queues a message at its current priority, behind every message of that priority
+    /// that arrived before it.
+    fn insert(&mut self, wrapper: PrioritizedWrapper<Queued<M>>) {
+        let priority = wrapper.0.priority;
+        let at = match self
+            .levels
+            .binary_search_by(|(level, _)| priority.cmp(level))
+        {
+            Ok(at) => at,
+            Err(at) => {
+                self.levels.insert(at, (priority, VecDeque::new()));
+                at
+            }
+        };
+        let fifo = &mut self.levels[at].1;
+        // Nearly always the back: only adopted, staged and raised messages can be older.
+        let slot = fifo.partition_point(|queued| queued.1 < wrapper.1);
+        fifo.insert(slot, wrapper);
+        self.len += 1;
+    }
+
+    /// The message of this lane's `eligible` levels that would be handled first.
+    fn front(&self, eligible: impl Fn(Priority) -> bool) -> Option<&PrioritizedWrapper<Queued<M>>> {
+        self.levels
+            .iter()
+            .filter(|(level, _)| eligible(*level))
+            .find_map(|(_, fifo)| fifo.front())
+    }
+
+    fn pop_front(
+        &mut self,
+        eligible: impl Fn(Priority) -> bool,
+    ) -> Option<PrioritizedWrapper<Queued<M>>> {
+        let popped = self
+            .levels
+            .iter_mut()
+            .filter(|(level, _)| eligible(*level))
+            .find_map(|(_, fifo)| fifo.pop_front())?;
+        self.len -= 1;
+        Some(popped)
+    }
+
+    /// The levels that have messages waiting.
+    fn levels_waiting(&self) -> impl Iterator<Item = Priority> + '_ {
+        self.levels
+            .iter()
+            .filter(|(_, fifo)| !fifo.is_empty())
+            .map(|(level, _)| *level)
+    }
+
+    /// The message of this lane that would be handled last.
+    fn back(&self) -> Option<&PrioritizedWrapper<Queued<M>>> {
+        self.levels.iter().rev().find_map(|(_, fifo)| fifo.back())
+    }
+
+    fn pop_back(&mut self) -> Option<PrioritizedWrapper<Queued<M>>> {
+        let popped = self
+            .levels
+            .iter_mut()
+            .rev()
+            .find_map(|(_, fifo)| fifo.pop_back())?;
+        self.len -= 1;
+        Some(popped)
+    }
+
+    /// Removes the first message `matches` picks, level by level.
+    fn remove(
+        &mut self,
+        mut matches: impl FnMut(&Queued<M>) -> bool,
+    ) -> Option<PrioritizedWrapper<Queued<M>>> {
+        let removed = self.levels.iter_mut().find_map(|(_, fifo)| {
+            let at = fifo.iter().position(|wrapper| matches(&wrapper.0))?;
+            fifo.remove(at)
+        })?;
+        self.len -= 1;
+        Some(removed)
+    }
+
+    /// Removes the messages `extract` picks, keeping the rest in order.
+    fn extract(
+        &mut self,
+        mut extract: impl FnMut(&Queued<M>) -> bool,
+    ) -> Vec<PrioritizedWrapper<Queued<M>>> {
+        let mut extracted = Vec::new();
+        for (_, fifo) in &mut self.levels {
+            let queued: Vec<_> = fifo.drain(..).collect();
+            for wrapper in queued {
+                if extract(&wrapper.0) {
+                    extracted.push(wrapper);
+                } else {
+                    fifo.push_back(wrapper);
+                }
+            }
+        }
+        self.len -= extracted.len();
+        extracted
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &PrioritizedWrapper<Queued<M>>> {
+        self.levels.iter().flat_map(|(_, fifo)| fifo)
+    }
+
+    /// Removes every message, level by level.
+    fn take(&mut self) -> Vec<PrioritizedWrapper<Queued<M>>> {
+        self.len = 0;
+        self.levels
+            .iter_mut()
+            .flat_map(|(_, fifo)| fifo.drain(..))
+            .collect()
+    }
+}
+
+/// How an actor picks the next message among those waiting at different priorities.
+///
+/// Set with [`ActorConfig::scheduling`](crate::ActorConfig::scheduling). `Shutdown`
+/// messages always go first under either policy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SchedulingPolicy {
+    /// The highest-priority message goes first, however long lower ones have waited.
+    #[default]
+    Strict,
+    /// Handling is shared between the `Low`, `Medium` and `High` bands in proportion to
+    /// `weights`, given in that order, whenever more than one band has messages waiting.
+    ///
+    /// Each band must weigh at least one. With `[1, 1, 9]`, a steady flow of `High`
+    /// messages leaves every tenth slot to `Low` ones while no `Medium` message waits, and
+    /// a band with nothing waiting gives up its turn. A custom level belongs to the band of
+    /// the named level at or below it; within a band, messages are handled by priority and
+    /// arrival as usual.
+    WeightedFair { weights: [u32; BANDS] },
+}
+
+/// The priority bands weighted fair scheduling shares slots between: `Low`, `Medium` and
+/// `High`, with `Shutdown` counted as `High`.
+const BANDS: usize = 3;
+
+/// Where arrival numbers start, leaving room below for messages adopted ahead of the rest.
+const FIRST_SEQ: u64 = 1 << 62;
+
+fn band_of(priority: Priority) -> usize {
+    if priority >= Priority::High {
+        2
+    } else if priority >= Priority::Medium {
+        1
+    } else {
+        0
+    }
+}
+
+/// Whether messages of a level may be picked: it is in `band`, if one is given, and not
+/// held back by a rate limit.
+fn eligible(throttle: Option<&Throttle>, band: Option<usize>) -> impl Fn(Priority) -> bool + '_ {
+    move |level| {
+        band.is_none_or(|band| band_of(level) == band)
+            && !throttle.is_some_and(|throttle| throttle.holds_back(level))
+    }
+}
+
+/// The key `msg` coalesces by; `Shutdown` messages never coalesce.
+fn coalesce_key<M: Prioritized>(msg: &M) -> Option<u64> {
+    if msg.priority() == Priority::Shutdown {
+        return None;
+    }
+    msg.coalesce_key()
+}
+
+/// Where weighted fair scheduling stands: the band whose turn it is and what each band
+/// has left to spend.
+#[derive(Clone, Copy)]
+struct Round {
+    weights: [u32; BANDS],
+    band: usize,
+    deficits: [u32; BANDS],
+}
+
+impl Round {
+    fn new(weights: [u32; BANDS]) -> Self {
+        // Starts at the bottom, so the first turn goes to `High`.
+        Round {
+            weights,
+            band: 0,
+            deficits: [0; BANDS],
+        }
+    }
+
+    /// The round once one message is taken, from the band whose turn it is by then.
+    ///
+    /// A band keeps its turn while it has messages waiting and deficit left; an empty band
+    /// forfeits what it had left. Some band must have messages waiting.
+    fn next(mut self, waiting: [bool; BANDS]) -> Self {
+        loop {
+            if waiting[self.band] && self.deficits[self.band] > 0 {
+                self.deficits[self.band] -= 1;
+                return self;
+            }
+            if !waiting[self.band] {
+                self.deficits[self.band] = 0;
+            }
+            // The next band down, or back to `High`.
+            self.band = (self.band + BANDS - 1) % BANDS;
+            self.deficits[self.band] =
+                self.deficits[self.band].saturating_add(self.weights[self.band]);
+        }
+    }
+}
+
+/// A queued message and the priority it is ordered by.
+struct Queued<M> {
+    envelope: Envelope<M>,
+    enqueued_at: Instant,
+    // The message's own priority.
+    base: Priority,
+    // `base` plus whatever it has gained by aging.
+    priority: Priority,
+    raises: u32,
+    key: Option<u64>,
+}
+
+impl<M: Prioritized> Queued<M> {
+    fn new(envelope: Envelope<M>) -> Self {
+        Queued {
+            key: coalesce_key(&envelope.msg),
+            base: envelope.priority(),
+            priority: envelope.priority(),
+            envelope,
+            enqueued_at: Instant::now(),
+            raises: 0,
+        }
+    }
+
+    /// Raises the priority by a level for every `step` waited so far.
+    fn raise(&mut self, step: Duration, now: Instant) {
+        let waited = now.saturating_duration_since(self.enqueued_at);
+        self.raises = u32::try_from(waited.as_nanos() / step.as_nanos().max(1)).unwrap_or(u32::MAX);
+        self.priority = self.base.aged(self.raises);
+    }
+
+    /// When the priority goes up next, or `None` if it cannot go any higher.
+    fn next_raise(&self, step: Duration) -> Option<Instant> {
+        if self.priority.aged(1) == self.priority {
+            return None;
+        }
+        self.enqueued_at
+            .checked_add(step.checked_mul(self.raises.checked_add(1)?)?)
+    }
+}
+
+impl<M> Prioritized for Queued<M> {
+    fn priority(&self) -> Priority {
+        self.priority
+    }
+}
+
+impl<M> Drop for MessageQueue<M> {
+    // Messages still queued when the actor stops are dropped with it.
+    fn drop(&mut self) {
+        for origin in Origin::ALL {
+            for wrapper in self.lanes[origin.index()].iter() {
+                self.metrics.dequeued(origin, wrapper.0.base);
+            }
+        }
+    }
+}
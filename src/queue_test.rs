@@ -0,0 +1,525 @@
+use crate::mailbox::Envelope;
+use crate::prelude::*;
+use crate::queue::{Inlets, MessageQueue};
+use crate::test_support::{stall, Gated, GatedMsg};
+use crate::{AbsorbPlacement, ActorMetrics, DropReason, Origin, OverflowPolicy, SchedulingPolicy};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::oneshot;
+use tokio::time::Duration;
+
+/// Answers every external message with two self-sent ticks, and every tick with another
+/// one until its budget runs out: a feedback loop that keeps the queue full of its own work.
+struct Feedback {
+    me: Option<ActorHandle<FeedbackMsg>>,
+    ticks_left: usize,
+    pops: usize,
+    external_at: Vec<usize>,
+}
+
+enum FeedbackMsg {
+    Start(ActorHandle<FeedbackMsg>),
+    External,
+    Tick,
+    Report(oneshot::Sender<Vec<usize>>),
+}
+
+impl Prioritized for FeedbackMsg {
+    fn priority(&self) -> Priority {
+        match self {
+            FeedbackMsg::Start(_) => Priority::High,
+            FeedbackMsg::Tick => Priority::Medium,
+            FeedbackMsg::External | FeedbackMsg::Report(_) => Priority::Low,
+        }
+    }
+}
+
+impl Feedback {
+    async fn tick(&mut self) {
+        let me = self.me.as_ref().expect("started");
+        me.send_from(Origin::SelfSend, FeedbackMsg::Tick)
+            .await
+            .unwrap();
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for Feedback {
+    type Msg = FeedbackMsg;
+
+    async fn handle(
+        &mut self,
+        msg: FeedbackMsg,
+        _ctx: &ActorContext<FeedbackMsg>,
+    ) -> ControlFlow<ShutdownReason> {
+        match msg {
+            FeedbackMsg::Start(me) => self.me = Some(me),
+            FeedbackMsg::External => {
+                self.pops += 1;
+                self.external_at.push(self.pops);
+                self.tick().await;
+                self.tick().await;
+            }
+            FeedbackMsg::Tick => {
+                self.pops += 1;
+                if self.ticks_left > 0 {
+                    self.ticks_left -= 1;
+                    self.tick().await;
+                }
+            }
+            FeedbackMsg::Report(tx) => {
+                let _ = tx.send(std::mem::take(&mut self.external_at));
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// Runs ten external messages through the feedback loop and returns the pop count at which
+/// each of them was handled.
+async fn external_pops(config: ActorConfig) -> Vec<usize> {
+    let actor = Feedback {
+        me: None,
+        ticks_left: 200,
+        pops: 0,
+        external_at: Vec::new(),
+    };
+    let handle = spawn_actor_with(actor, config.unbounded());
+    handle
+        .send(FeedbackMsg::Start(handle.clone()))
+        .await
+        .unwrap();
+    for _ in 0..10 {
+        handle.send(FeedbackMsg::External).await.unwrap();
+    }
+    let (tx, rx) = oneshot::channel();
+    handle.send(FeedbackMsg::Report(tx)).await.unwrap();
+    rx.await.unwrap()
+}
+
+#[tokio::test]
+async fn test_self_sends_starve_external_traffic_without_fairness() {
+    let pops = external_pops(ActorConfig::new()).await;
+
+    assert_eq!(pops.len(), 10);
+    assert_eq!(pops[0], 1);
+    // Everything after the first external message waits for the whole feedback loop.
+    assert!(
+        pops[1] > 200,
+        "second external message handled at pop {}",
+        pops[1]
+    );
+}
+
+#[tokio::test]
+async fn test_fairness_bounds_external_lag() {
+    let pops = external_pops(ActorConfig::new().fairness(4)).await;
+
+    assert_eq!(pops.len(), 10);
+    for pair in pops.windows(2) {
+        assert_eq!(pair[1] - pair[0], 5, "external pops: {:?}", pops);
+    }
+}
+
+#[tokio::test]
+async fn test_queue_depth_is_tracked_per_origin() {
+    let handle = spawn_actor(Gated::new());
+    let metrics = handle.metrics();
+    let release = stall(&handle).await;
+
+    for _ in 0..3 {
+        handle.send(GatedMsg::Fill()).await.unwrap();
+    }
+    for _ in 0..2 {
+        handle
+            .send_from(Origin::Retry, GatedMsg::Fill())
+            .await
+            .unwrap();
+    }
+    let (tx, rx) = oneshot::channel();
+    handle.send(GatedMsg::Report(tx)).await.unwrap();
+    tokio::task::yield_now().await;
+
+    // The Report is still in flight or queued; the five Fills are queued for sure.
+    let queued = |origin| metrics.queue_depth(origin);
+    assert!(queued(Origin::External) >= 3);
+    assert_eq!(queued(Origin::Retry), 2);
+    assert_eq!(queued(Origin::SelfSend), 0);
+
+    release.send(()).unwrap();
+    rx.await.unwrap();
+    // Hold, Report, and the five Fills.
+    tokio::time::timeout(tokio::time::Duration::from_secs(1), async {
+        while metrics.messages_handled() < 7 {
+            tokio::task::yield_now().await;
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(queued(Origin::External), 0);
+    assert_eq!(queued(Origin::Retry), 0);
+}
+
+/// Queues a `Low` message, lets it wait for `waited`, then queues three fresh `High` ones
+/// and returns the order they were all handled in.
+async fn after_waiting(config: ActorConfig, waited: Duration) -> Vec<Priority> {
+    let spawned = spawn_actor_owned(Gated::new(), config);
+    let release = stall(&spawned).await;
+    spawned.send(GatedMsg::Fill()).await.unwrap();
+    tokio::time::sleep(waited).await;
+    for _ in 0..3 {
+        spawned.send(GatedMsg::Urgent()).await.unwrap();
+    }
+    drop(release);
+    spawned.join().await.unwrap().handled.clone()
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_aging_lets_an_old_low_message_beat_fresh_high_ones() {
+    const HIGH: Priority = Priority::High;
+    const LOW: Priority = Priority::Low;
+    let aging = || ActorConfig::new().aging(Duration::from_secs(1));
+
+    let strict = after_waiting(ActorConfig::new(), Duration::from_secs(60)).await;
+    assert_eq!(strict, [HIGH, HIGH, HIGH, LOW]);
+    // One step in, the message is ordered like a `Medium` one.
+    let medium = after_waiting(aging(), Duration::from_millis(1500)).await;
+    assert_eq!(medium, [HIGH, HIGH, HIGH, LOW]);
+    // Two steps in, it ties with `High` and arrived first.
+    let high = after_waiting(aging(), Duration::from_millis(2500)).await;
+    assert_eq!(high, [LOW, HIGH, HIGH, HIGH]);
+}
+
+const WEIGHTED: SchedulingPolicy = SchedulingPolicy::WeightedFair { weights: [1, 1, 9] };
+
+#[tokio::test]
+async fn test_weighted_fair_scheduling_keeps_low_messages_moving_under_high_load() {
+    let envelope = |priority| Envelope::new(priority, Origin::External);
+    let mut queue =
+        MessageQueue::new(None, None, Arc::new(ActorMetrics::default())).with_scheduling(WEIGHTED);
+    for _ in 0..20 {
+        queue.push(envelope(Priority::Low));
+        queue.push(envelope(Priority::High));
+    }
+
+    // Every `High` message handled is replaced by a fresh one.
+    let mut popped = Vec::new();
+    for _ in 0..100 {
+        let peeked = queue.peek().unwrap().msg;
+        let priority = queue.pop().unwrap().msg;
+        assert_eq!(priority, peeked);
+        if priority == Priority::High {
+            queue.push(envelope(Priority::High));
+        }
+        popped.push(priority);
+    }
+    for slots in popped.chunks(10) {
+        assert_eq!(slots[..9], [Priority::High; 9]);
+        assert_eq!(slots[9], Priority::Low);
+    }
+
+    queue.push(envelope(Priority::Shutdown));
+    assert_eq!(queue.pop().unwrap().msg, Priority::Shutdown);
+}
+
+#[tokio::test]
+async fn test_weighted_fair_scheduling_takes_turns_between_bands() {
+    const HIGH: Priority = Priority::High;
+    const MEDIUM: Priority = Priority::Medium;
+    const LOW: Priority = Priority::Low;
+    let spawned = spawn_actor_owned(
+        Gated::new(),
+        ActorConfig::new().unbounded().scheduling(WEIGHTED),
+    );
+    let release = stall(&spawned).await;
+    for _ in 0..3 {
+        spawned.send(GatedMsg::Fill()).await.unwrap();
+        spawned.send(GatedMsg::Routine()).await.unwrap();
+    }
+    for _ in 0..18 {
+        spawned.send(GatedMsg::Urgent()).await.unwrap();
+    }
+    drop(release);
+
+    let mut expected = Vec::new();
+    for _ in 0..2 {
+        expected.push(LOW);
+        expected.extend([HIGH; 9]);
+        expected.push(MEDIUM);
+    }
+    expected.extend([LOW, MEDIUM]);
+    assert_eq!(spawned.join().await.unwrap().handled, expected);
+}
+
+/// Parks a Gated actor whose queue holds two messages under `overflow`, and returns it
+/// with the release for its handler.
+async fn limited(overflow: OverflowPolicy) -> (SpawnedActor<Gated>, oneshot::Sender<()>) {
+    let config = ActorConfig::new()
+        .unbounded()
+        .max_queued(2, overflow)
+        .drop_exemplars(0);
+    let spawned = spawn_actor_owned(Gated::new(), config);
+    let release = stall(&spawned).await;
+    (spawned, release)
+}
+
+/// Lets the actor pick up the next message.
+async fn settle() {
+    tokio::time::sleep(Duration::from_millis(1)).await;
+}
+
+/// Overflow drops per variant, in variant name order.
+fn overflowed(handle: &ActorHandle<GatedMsg>) -> Vec<(&'static str, u64)> {
+    handle
+        .take_drop_exemplars()
+        .into_iter()
+        .filter(|entry| entry.reason == DropReason::Overflow)
+        .map(|entry| (entry.variant, entry.dropped))
+        .collect()
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_reject_turns_offers_away_while_the_queue_is_full() {
+    let (spawned, release) = limited(OverflowPolicy::Reject).await;
+    spawned.offer(GatedMsg::Fill()).await.unwrap();
+    spawned.offer(GatedMsg::Fill()).await.unwrap();
+    settle().await;
+
+    assert!(matches!(
+        spawned.offer(GatedMsg::Urgent()).await,
+        Err(Error::MailboxFull {
+            returned: GatedMsg::Urgent(),
+            ..
+        })
+    ));
+    // Plain sends are not turned away, but dropped on arrival.
+    spawned.send(GatedMsg::Urgent()).await.unwrap();
+    settle().await;
+    assert_eq!(overflowed(&spawned), [("Urgent", 1)]);
+    assert_eq!(spawned.metrics().messages_overflowed(), 1);
+
+    drop(release);
+    let metrics = spawned.metrics();
+    while metrics.queue_depth(Origin::External) > 0 {
+        settle().await;
+    }
+    spawned.offer(GatedMsg::Urgent()).await.unwrap();
+    let actor = spawned.join().await.unwrap();
+    assert_eq!(
+        actor.handled,
+        [Priority::Low, Priority::Low, Priority::High]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_drop_incoming_keeps_what_is_queued() {
+    let (spawned, release) = limited(OverflowPolicy::DropIncoming).await;
+    spawned.send(GatedMsg::Fill()).await.unwrap();
+    spawned.send(GatedMsg::Fill()).await.unwrap();
+    spawned.send(GatedMsg::Urgent()).await.unwrap();
+    // `Shutdown` always gets in.
+    spawned.send(GatedMsg::Shutdown).await.unwrap();
+    settle().await;
+
+    assert_eq!(overflowed(&spawned), [("Urgent", 1)]);
+    drop(release);
+    let metrics = spawned.metrics();
+    assert!(spawned.join().await.is_some());
+    assert_eq!(metrics.messages_overflowed(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_drop_lowest_evicts_for_more_urgent_messages() {
+    let (spawned, release) = limited(OverflowPolicy::DropLowest).await;
+    spawned.send(GatedMsg::Fill()).await.unwrap();
+    spawned.send(GatedMsg::Fill()).await.unwrap();
+    // Evicts the second Fill.
+    spawned.send(GatedMsg::Urgent()).await.unwrap();
+    // Nothing queued ranks below it, so it is dropped itself.
+    spawned.send(GatedMsg::Fill()).await.unwrap();
+    settle().await;
+
+    assert_eq!(overflowed(&spawned), [("Fill", 2)]);
+    drop(release);
+    let actor = spawned.join().await.unwrap();
+    assert_eq!(actor.handled, [Priority::High, Priority::Low]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_a_full_quota_turns_away_only_its_priority() {
+    let config = ActorConfig::new()
+        .unbounded()
+        .quota(Priority::Low, 2)
+        .drop_exemplars(0);
+    let spawned = spawn_actor_owned(Gated::new(), config);
+    let release = stall(&spawned).await;
+    spawned.offer(GatedMsg::Fill()).await.unwrap();
+    spawned.offer(GatedMsg::Fill()).await.unwrap();
+    settle().await;
+
+    assert!(matches!(
+        spawned.offer(GatedMsg::Fill()).await,
+        Err(Error::MailboxFull {
+            returned: GatedMsg::Fill(),
+            ..
+        })
+    ));
+    // Plain sends are turned away too, handing the message back.
+    assert!(matches!(
+        spawned.send(GatedMsg::Fill()).await,
+        Err(Error::MailboxFull {
+            returned: GatedMsg::Fill(),
+            ..
+        })
+    ));
+    assert!(spawned
+        .send_with_priority(GatedMsg::Routine(), Priority::Low)
+        .await
+        .unwrap_err()
+        .is_full());
+    for _ in 0..3 {
+        spawned.send(GatedMsg::Urgent()).await.unwrap();
+    }
+    spawned.send(GatedMsg::Routine()).await.unwrap();
+    settle().await;
+    assert!(overflowed(&spawned).is_empty());
+    assert_eq!(spawned.metrics().messages_overflowed(), 0);
+
+    drop(release);
+    let actor = spawned.join().await.unwrap();
+    assert_eq!(
+        actor.handled,
+        [
+            Priority::High,
+            Priority::High,
+            Priority::High,
+            Priority::Medium,
+            Priority::Low,
+            Priority::Low
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_a_full_quota_drops_sends_unless_rejecting() {
+    let config = ActorConfig::new()
+        .unbounded()
+        .quota(Priority::Low, 1)
+        .max_queued(100, OverflowPolicy::DropIncoming)
+        .drop_exemplars(0);
+    let spawned = spawn_actor_owned(Gated::new(), config);
+    let release = stall(&spawned).await;
+    spawned.send(GatedMsg::Fill()).await.unwrap();
+    spawned.send(GatedMsg::Fill()).await.unwrap();
+    settle().await;
+    assert_eq!(overflowed(&spawned), [("Fill", 1)]);
+
+    drop(release);
+    let actor = spawned.join().await.unwrap();
+    assert_eq!(actor.handled, [Priority::Low]);
+}
+
+#[tokio::test]
+async fn test_quotas_can_be_reconfigured() {
+    let handle = spawn_actor_with(Gated::new(), ActorConfig::new().quota(Priority::Low, 1));
+    let release = stall(&handle).await;
+    handle.offer(GatedMsg::Fill()).await.unwrap();
+    assert!(handle.offer(GatedMsg::Fill()).await.is_err());
+
+    handle.reconfigure(|cfg| cfg.quotas.clear()).await.unwrap();
+    handle.offer(GatedMsg::Fill()).await.unwrap();
+    assert!(handle
+        .reconfigure(|cfg| cfg.quotas.push((Priority::High, 0)))
+        .await
+        .is_err());
+    drop(release);
+}
+
+/// A message at a numeric level, told apart from others at that level by `id`.
+struct Job {
+    level: u8,
+    id: u32,
+}
+
+impl Prioritized for Job {
+    fn priority(&self) -> Priority {
+        Priority::Custom(self.level)
+    }
+}
+
+#[tokio::test]
+async fn test_each_level_stays_first_in_first_out() {
+    let envelope = |level, id| Envelope::new(Job { level, id }, Origin::External);
+    let mut queue = MessageQueue::new(None, None, Arc::new(ActorMetrics::default()));
+    for (id, level) in (0..).zip([7, 200, 7, 42, 200, 0]) {
+        queue.push(envelope(level, id));
+    }
+    queue.adopt(
+        vec![envelope(42, 10), envelope(9, 11)],
+        AbsorbPlacement::Ahead,
+    );
+    // Only the `Custom(0)` message ranks below `Custom(1)`.
+    assert_eq!(queue.evict_below(Priority::Custom(1)).unwrap().msg.id, 5);
+    assert!(queue.evict_below(Priority::Custom(1)).is_none());
+
+    let mut popped = Vec::new();
+    while let Some(envelope) = queue.pop() {
+        popped.push(envelope.msg.id);
+    }
+    assert_eq!(popped, [1, 4, 10, 3, 11, 0, 2]);
+}
+
+#[tokio::test]
+async fn test_staged_messages_keep_their_place_in_line() {
+    let envelope = |level, id| Envelope::new(Job { level, id }, Origin::External);
+    let mut queue = MessageQueue::new(None, None, Arc::new(ActorMetrics::default()));
+    let inlets = Inlets::feeding(&queue).unwrap();
+    let open = AtomicBool::new(true);
+    assert!(inlets.stage(envelope(7, 0), &open).is_ok());
+    queue.push(envelope(7, 1));
+    assert!(inlets.stage(envelope(7, 2), &open).is_ok());
+    assert!(inlets.stage(envelope(200, 3), &open).is_ok());
+    inlets.empty_into(&mut queue);
+    queue.push(envelope(7, 4));
+    open.store(false, Ordering::Release);
+    assert!(inlets.stage(envelope(200, 5), &open).is_err());
+    inlets.empty_into(&mut queue);
+
+    let mut popped = Vec::new();
+    while let Some(envelope) = queue.pop() {
+        popped.push(envelope.msg.id);
+    }
+    assert_eq!(popped, [3, 0, 1, 2, 4]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_senders_on_every_band_race_without_losing_order() {
+    let spawned = spawn_actor_owned(Gated::new(), ActorConfig::new().unbounded());
+    let release = stall(&spawned).await;
+    let senders: Vec<_> = (0..12)
+        .map(|i| {
+            let handle = spawned.clone();
+            tokio::spawn(async move {
+                for _ in 0..100 {
+                    let msg = match i % 3 {
+                        0 => GatedMsg::Fill(),
+                        1 => GatedMsg::Routine(),
+                        _ => GatedMsg::Urgent(),
+                    };
+                    handle.send(msg).await.unwrap();
+                }
+            })
+        })
+        .collect();
+    for sender in senders {
+        sender.await.unwrap();
+    }
+    assert_eq!(spawned.metrics().queue_len(), 1200);
+    drop(release);
+
+    let handled = spawned.join().await.unwrap().handled.clone();
+    let mut expected = vec![Priority::High; 400];
+    expected.extend([Priority::Medium; 400]);
+    expected.extend([Priority::Low; 400]);
+    assert_eq!(handled, expected);
+}
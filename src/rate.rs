@@ -0,0 +1,101 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::time::{Duration, Instant};
+
+/// Longest window a rate can be read over.
+pub const MAX_RATE_WINDOW: Duration = Duration::from_secs(60);
+
+// One more bucket than the longest window, so the bucket being filled never overlaps one
+// that is still being read.
+const BUCKETS: usize = MAX_RATE_WINDOW.as_secs() as usize + 1;
+
+/// Events per second over the last minute, kept as a ring of per-second buckets.
+///
+/// Each bucket packs the second it belongs to (high 32 bits) with its count (low 32 bits).
+/// Recording is a single `fetch_add` unless the bucket still holds an older second, in which
+/// case it is reset first; readers skip buckets whose second has fallen out of the window, so
+/// nothing needs to roll them in the background. Time comes from `tokio::time`, so paused
+/// test clocks apply.
+#[derive(Debug)]
+pub(crate) struct RateCounter {
+    start: Instant,
+    buckets: [AtomicU64; BUCKETS],
+}
+
+impl Default for RateCounter {
+    fn default() -> Self {
+        RateCounter {
+            start: Instant::now(),
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+        }
+    }
+}
+
+impl RateCounter {
+    fn now_sec(&self) -> u64 {
+        self.start.elapsed().as_secs()
+    }
+
+    pub(crate) fn record(&self) {
+        let sec = self.now_sec();
+        let bucket = &self.buckets[sec as usize % BUCKETS];
+        let mut current = bucket.load(Ordering::Relaxed);
+        loop {
+            if current >> 32 == sec {
+                bucket.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+            match bucket.compare_exchange_weak(
+                current,
+                (sec << 32) | 1,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    /// Average events per second over the current second and the `window - 1` before it.
+    pub(crate) fn per_second(&self, window: Duration) -> f64 {
+        assert!(
+            window >= Duration::from_secs(1) && window <= MAX_RATE_WINDOW,
+            "rate window must be between 1s and {:?}, got {:?}",
+            MAX_RATE_WINDOW,
+            window
+        );
+        let secs = window.as_secs();
+        let now = self.now_sec();
+        let oldest = now.saturating_sub(secs - 1);
+        let total: u64 = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.load(Ordering::Relaxed))
+            .filter(|packed| (oldest..=now).contains(&(packed >> 32)))
+            .map(|packed| packed & u64::from(u32::MAX))
+            .sum();
+        total as f64 / secs as f64
+    }
+}
+
+/// Recent message rates of an actor, from [`ActorMetrics::rate`](crate::ActorMetrics::rate).
+///
+/// Windows are whole seconds between one second and [`MAX_RATE_WINDOW`]; a window of `n`
+/// seconds covers the current, partly elapsed second and the `n - 1` before it.
+#[derive(Debug, Clone, Copy)]
+pub struct MessageRates<'a> {
+    pub(crate) handled: &'a RateCounter,
+    pub(crate) enqueued: &'a RateCounter,
+}
+
+impl MessageRates<'_> {
+    /// Messages handled per second over `window`.
+    pub fn handled_per_sec(&self, window: Duration) -> f64 {
+        self.handled.per_second(window)
+    }
+
+    /// Messages queued per second over `window`, not counting shed ones.
+    pub fn enqueued_per_sec(&self, window: Duration) -> f64 {
+        self.enqueued.per_second(window)
+    }
+}
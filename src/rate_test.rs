@@ -0,0 +1,65 @@
+use crate::prelude::*;
+use crate::test_support::{Gated, GatedMsg};
+use tokio::sync::oneshot;
+use tokio::time::{advance, Duration};
+
+const SECOND: Duration = Duration::from_secs(1);
+const TEN_SECONDS: Duration = Duration::from_secs(10);
+
+/// Sends `n` messages and waits until the actor has handled all of them.
+async fn burst(handle: &ActorHandle<GatedMsg>, n: usize) {
+    for _ in 0..n {
+        handle.send(GatedMsg::Urgent()).await.unwrap();
+    }
+    let (tx, rx) = oneshot::channel();
+    handle.send(GatedMsg::Report(tx)).await.unwrap();
+    rx.await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_rates_follow_a_scripted_workload() {
+    let handle = spawn_actor(Gated::new());
+    let metrics = handle.metrics();
+
+    // Second 0: 20 messages, plus the Report that waits for them.
+    burst(&handle, 20).await;
+    let rate = metrics.rate();
+    assert_eq!(rate.handled_per_sec(SECOND), 21.0);
+    assert_eq!(rate.enqueued_per_sec(SECOND), 21.0);
+
+    // Second 3: 8 more, plus their Report.
+    advance(Duration::from_secs(3)).await;
+    burst(&handle, 8).await;
+    let rate = metrics.rate();
+    assert_eq!(rate.handled_per_sec(SECOND), 9.0);
+    assert_eq!(rate.handled_per_sec(TEN_SECONDS), 3.0);
+
+    // Second 11: the first burst has left the ten-second window.
+    advance(Duration::from_secs(8)).await;
+    let rate = metrics.rate();
+    assert_eq!(rate.handled_per_sec(SECOND), 0.0);
+    assert_eq!(rate.handled_per_sec(TEN_SECONDS), 0.9);
+    assert_eq!(rate.handled_per_sec(Duration::from_secs(60)), 0.5);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_buckets_are_reused_after_a_minute() {
+    let handle = spawn_actor(Gated::new());
+    let metrics = handle.metrics();
+
+    burst(&handle, 4).await;
+    // Lands in the same ring slot as second 0.
+    advance(Duration::from_secs(61)).await;
+    burst(&handle, 1).await;
+
+    let rate = metrics.rate();
+    assert_eq!(rate.handled_per_sec(SECOND), 2.0);
+    assert_eq!(rate.handled_per_sec(Duration::from_secs(60)), 2.0 / 60.0);
+}
+
+#[test]
+#[should_panic(expected = "rate window")]
+fn test_window_longer_than_a_minute_panics() {
+    let metrics = crate::ActorMetrics::default();
+    metrics.rate().handled_per_sec(Duration::from_secs(61));
+}
@@ -0,0 +1,43 @@
+use crate::Priority;
+use tokio::sync::watch;
+
+/// What happens to messages sent before an actor is ready, i.e. before the future given to
+/// [`spawn_actor_init`](crate::spawn_actor_init), if any, and its `on_start` hook have
+/// completed. Set with [`ActorConfig::readiness`](crate::ActorConfig::readiness).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Readiness {
+    /// Messages wait in the queue and are handled once the actor is ready.
+    #[default]
+    Queue,
+    /// Sends fail with [`Error::NotReady`](crate::Error::NotReady), handing the message
+    /// back. `Shutdown` messages are queued all the same.
+    Reject,
+}
+
+/// Whether an actor is ready yet, as its handles see it; the processor flips it once,
+/// before it handles its first message.
+pub(crate) struct ReadyGate {
+    readiness: Readiness,
+    ready: watch::Receiver<bool>,
+}
+
+impl ReadyGate {
+    pub(crate) fn new(readiness: Readiness) -> (Self, watch::Sender<bool>) {
+        let (tx, ready) = watch::channel(false);
+        (ReadyGate { readiness, ready }, tx)
+    }
+
+    /// Whether a message of `priority` sent now is turned away.
+    pub(crate) fn rejects(&self, priority: Priority) -> bool {
+        self.readiness == Readiness::Reject
+            && priority != Priority::Shutdown
+            && !*self.ready.borrow()
+    }
+
+    /// Resolves with `true` once the actor is ready, or `false` if it stops first.
+    pub(crate) async fn wait(&self) -> bool {
+        let mut ready = self.ready.clone();
+        let ready = ready.wait_for(|ready| *ready).await.is_ok();
+        ready
+    }
+}
@@ -0,0 +1,110 @@
+use crate::prelude::*;
+use crate::test_support::{gated_init, Gated, GatedMsg};
+use crate::{spawn_actor_init_with, Readiness};
+use tokio::sync::oneshot;
+use tokio::time::Duration;
+
+async fn handled(handle: &ActorHandle<GatedMsg>) -> Vec<Priority> {
+    let (tx, rx) = oneshot::channel();
+    handle.send(GatedMsg::Report(tx)).await.unwrap();
+    rx.await.unwrap()
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_ready_resolves_once_init_completes() {
+    let handle = spawn_actor_init_with(
+        || async {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            Ok::<_, ()>(Gated::new())
+        },
+        ActorConfig::new(),
+    );
+    handle.send(GatedMsg::Routine()).await.unwrap();
+    let started = tokio::time::Instant::now();
+    handle.ready().await.unwrap();
+    assert_eq!(started.elapsed(), Duration::from_secs(1));
+
+    // Queued messages are handled once the actor is ready, and `ready` keeps resolving.
+    assert_eq!(handled(&handle).await, [Priority::Medium]);
+    handle.ready().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_rejecting_actor_hands_early_messages_back() {
+    let (release, gate) = oneshot::channel();
+    let config = ActorConfig::new().readiness(Readiness::Reject);
+    let handle = gated_init(config, gate);
+
+    let err = handle.send(GatedMsg::Urgent()).await.unwrap_err();
+    assert!(err.is_not_ready());
+    assert_eq!(err.actor(), handle.name());
+    assert!(matches!(err.into_returned(), Some(GatedMsg::Urgent())));
+    let err = handle.try_send(GatedMsg::Fill()).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::NotReady {
+            returned: GatedMsg::Fill(),
+            ..
+        }
+    ));
+    assert!(futures::poll!(std::pin::pin!(handle.ready())).is_pending());
+
+    release.send(()).unwrap();
+    handle.ready().await.unwrap();
+    handle.send(GatedMsg::Urgent()).await.unwrap();
+    assert_eq!(handled(&handle).await, [Priority::High]);
+}
+
+#[tokio::test]
+async fn test_rejecting_actor_still_takes_shutdown() {
+    let (release, gate) = oneshot::channel();
+    let config = ActorConfig::new().readiness(Readiness::Reject);
+    let handle = gated_init(config, gate);
+
+    handle.send(GatedMsg::Shutdown).await.unwrap();
+    release.send(()).unwrap();
+    assert_eq!(handle.join().await, TerminationReason::ShutdownMessage);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_sends_racing_init_are_either_rejected_or_handled() {
+    let (release, gate) = oneshot::channel();
+    let config = ActorConfig::new().readiness(Readiness::Reject).unbounded();
+    let handle = gated_init(config, gate);
+
+    let sender = handle.clone();
+    let racer = tokio::spawn(async move {
+        let (mut accepted, mut rejected) = (0, 0);
+        while accepted < 100 {
+            match sender.send(GatedMsg::Routine()).await {
+                Ok(()) => accepted += 1,
+                Err(err) => {
+                    assert!(err.is_not_ready());
+                    // The actor never goes back to rejecting once it is ready.
+                    assert_eq!(accepted, 0);
+                    rejected += 1;
+                }
+            }
+            tokio::task::yield_now().await;
+        }
+        (accepted, rejected)
+    });
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    release.send(()).unwrap();
+    handle.ready().await.unwrap();
+
+    let (accepted, rejected) = racer.await.unwrap();
+    assert!(rejected > 0);
+    assert_eq!(handled(&handle).await.len(), accepted);
+}
+
+#[tokio::test]
+async fn test_ready_fails_if_init_fails() {
+    let handle = spawn_actor_init_with(
+        || async { Err::<Gated, _>("unreachable database") },
+        ActorConfig::new(),
+    );
+    let err = handle.ready().await.unwrap_err();
+    assert!(err.is_closed());
+    assert_eq!(handle.join().await, TerminationReason::InitFailed);
+}
@@ -0,0 +1,231 @@
+use crate::mailbox::{Admission, Envelope, Inbox, MailboxSender, Origin, Visit};
+use crate::permits::PermitPool;
+use crate::queue::MessageQueue;
+use crate::{ActorMetrics, Prioritized, Priority, TerminationReason};
+use arc_swap::ArcSwap;
+use std::sync::Arc;
+use tokio::sync::mpsc::error::{SendError, TryRecvError, TrySendError};
+use tokio::sync::TryAcquireError;
+use tokio::time::Instant;
+
+/// What an actor's receiver hands out next.
+pub(crate) enum Received<T> {
+    Message(Envelope<T>),
+    Visits(Vec<Visit>),
+}
+
+/// The receiving end of a priority channel: hands out the highest-priority message first,
+/// and messages of equal priority in the order they were sent.
+///
+/// Created by [`priority_channel`] for custom dispatch loops. Actors receive through one
+/// as well, so it orders messages exactly as an actor would.
+pub struct PriorityReceiver<T: Prioritized> {
+    inbox: Arc<Inbox<T>>,
+}
+
+impl<T: Prioritized> PriorityReceiver<T> {
+    pub(crate) fn new(inbox: Arc<Inbox<T>>) -> Self {
+        PriorityReceiver { inbox }
+    }
+
+    pub(crate) fn inbox(&self) -> &Inbox<T> {
+        &self.inbox
+    }
+
+    /// Closes the channel, handing back whatever was still queued and why it was closed, if
+    /// it was retired or halted.
+    pub(crate) fn stop(&self) -> (Option<TerminationReason>, Vec<Envelope<T>>) {
+        self.inbox.stop()
+    }
+
+    /// Waits for the next message, highest priority first.
+    ///
+    /// Returns `None` once every sender is gone and every message has been received.
+    pub async fn recv(&mut self) -> Option<T> {
+        self.recv_envelope(None).await.map(|envelope| envelope.msg)
+    }
+
+    /// Takes the next message if one has arrived, without waiting.
+    ///
+    /// Fails with [`TryRecvError::Empty`] while senders remain, and with
+    /// [`TryRecvError::Disconnected`] once every sender is gone and every message has been
+    /// received.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let mut state = self.inbox.lock();
+        match state.queue().pop() {
+            Some(envelope) => Ok(envelope.msg),
+            None if state.is_closed() => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// The number of messages ready to be received.
+    pub fn len(&self) -> usize {
+        self.inbox.lock().queue().len()
+    }
+
+    /// Returns `true` if no message is ready to be received.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Takes up to `max` more messages that are waiting right now, stopping short of a
+    /// `Shutdown` message so that it is handled on its own.
+    pub(crate) fn recv_ready(&mut self, max: usize) -> Vec<Envelope<T>> {
+        let mut state = self.inbox.lock();
+        let queue = state.queue();
+        let mut ready = Vec::new();
+        while ready.len() < max
+            && queue
+                .peek()
+                .is_some_and(|next| next.priority() != Priority::Shutdown)
+        {
+            ready.extend(queue.pop());
+        }
+        ready
+    }
+
+    /// Waits for the next message, holding back all but `Shutdown` messages until
+    /// `not_before`.
+    pub(crate) async fn recv_envelope(
+        &mut self,
+        not_before: Option<Instant>,
+    ) -> Option<Envelope<T>> {
+        loop {
+            match self.recv_next(not_before).await? {
+                Received::Message(envelope) => return Some(envelope),
+                // Only actor handles queue visits, and an actor receives through `recv_next`.
+                Received::Visits(visits) => drop(visits),
+            }
+        }
+    }
+
+    /// Like [`recv_envelope`](Self::recv_envelope), also handing out the visits queued
+    /// for the actor's state, ahead of any message.
+    pub(crate) async fn recv_next(&mut self, not_before: Option<Instant>) -> Option<Received<T>> {
+        loop {
+            let wait_until = {
+                let mut state = self.inbox.lock();
+                state.answer_pings();
+                let visits = state.take_visits();
+                if !visits.is_empty() {
+                    return Some(Received::Visits(visits));
+                }
+                if state.is_halted() {
+                    return None;
+                }
+                let closed = state.is_closed();
+                let queue = state.queue();
+                match (queue.peek().map(|top| top.priority()), not_before) {
+                    (Some(top), Some(at)) if top != Priority::Shutdown && Instant::now() < at => {
+                        Some(at)
+                    }
+                    (Some(_), _) => return queue.pop().map(Received::Message),
+                    // Whatever is left waits for its rate limit.
+                    (None, _) if queue.len() > 0 => queue.held_back_until(),
+                    // Checked under the lock, after the last message was queued.
+                    (None, _) if closed => return None,
+                    (None, _) => None,
+                }
+            };
+            match wait_until {
+                // Wait out the limit, but wake early for anything more urgent.
+                Some(at) => tokio::select! {
+                    _ = tokio::time::sleep_until(at) => {}
+                    _ = self.inbox.notified() => {}
+                },
+                None => self.inbox.notified().await,
+            }
+        }
+    }
+}
+
+impl<T: Prioritized> Drop for PriorityReceiver<T> {
+    fn drop(&mut self) {
+        // Fails later sends and drops what is left, outside the inbox lock.
+        drop(self.inbox.stop());
+    }
+}
+
+/// The sending end of a [`priority_channel`].
+///
+/// Cloning a sender is cheap; the channel closes once every clone is gone.
+pub struct PrioritySender<T> {
+    tx: MailboxSender<T>,
+    permits: Arc<PermitPool>,
+}
+
+impl<T> Clone for PrioritySender<T> {
+    fn clone(&self) -> Self {
+        PrioritySender {
+            tx: self.tx.clone(),
+            permits: Arc::clone(&self.permits),
+        }
+    }
+}
+
+impl<T: Prioritized + Send + 'static> PrioritySender<T> {
+    /// Sends a message, waiting while the channel is at capacity.
+    ///
+    /// Fails with the message once the receiver is gone.
+    pub async fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        let mut envelope = Envelope::new(msg, Origin::External);
+        match self.permits.acquire(envelope.priority()).await {
+            Some(permit) => envelope.permit = Some(permit),
+            None => return Err(SendError(envelope.msg)),
+        }
+        self.tx.push(envelope).map_err(|err| {
+            let (TrySendError::Full(envelope) | TrySendError::Closed(envelope)) = err;
+            SendError(envelope.msg)
+        })
+    }
+
+    /// Sends a message only if the channel has room right now.
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        let mut envelope = Envelope::new(msg, Origin::External);
+        match self.permits.try_acquire(envelope.priority()) {
+            Ok(permit) => envelope.permit = Some(permit),
+            Err(TryAcquireError::NoPermits) => return Err(TrySendError::Full(envelope.msg)),
+            Err(TryAcquireError::Closed) => return Err(TrySendError::Closed(envelope.msg)),
+        }
+        self.tx.push(envelope).map_err(|err| match err {
+            TrySendError::Full(envelope) => TrySendError::Full(envelope.msg),
+            TrySendError::Closed(envelope) => TrySendError::Closed(envelope.msg),
+        })
+    }
+
+    /// Returns `true` once the receiver is gone.
+    pub fn is_closed(&self) -> bool {
+        !self.tx.is_open()
+    }
+}
+
+/// Creates a channel whose receiver hands out messages by priority, for dispatch loops the
+/// actors in this crate do not cover, such as interleaving two mailboxes.
+///
+/// At most `capacity` messages are sent but not yet received; further sends wait.
+pub fn priority_channel<T: Prioritized + Send + 'static>(
+    capacity: usize,
+) -> (PrioritySender<T>, PriorityReceiver<T>) {
+    assert!(capacity > 0, "capacity must be greater than zero");
+    let metrics = Arc::new(ActorMetrics::default());
+    let permits = Arc::new(PermitPool::new(capacity, &[], Arc::clone(&metrics)));
+    let admission = Admission::new(
+        Arc::new(ArcSwap::from_pointee(Default::default())),
+        Arc::clone(&metrics),
+        None,
+        None,
+    );
+    let inbox = Arc::new(Inbox::new(
+        MessageQueue::new(None, None, metrics),
+        admission,
+        Some(Arc::clone(&permits)),
+    ));
+    (
+        PrioritySender {
+            tx: MailboxSender::new(Arc::clone(&inbox)),
+            permits,
+        },
+        PriorityReceiver::new(inbox),
+    )
+}
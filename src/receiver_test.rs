@@ -0,0 +1,86 @@
+use crate::{priority_channel, Prioritized, TryRecvError};
+
+#[derive(Debug, PartialEq, Prioritized)]
+enum Job {
+    #[priority(Low)]
+    Sweep(u32),
+    #[priority(Medium)]
+    Build(u32),
+    #[priority(High)]
+    Page,
+}
+
+#[tokio::test]
+async fn test_recv_hands_out_messages_by_priority_then_in_order() {
+    let (tx, mut rx) = priority_channel(16);
+    for job in [
+        Job::Sweep(1),
+        Job::Build(1),
+        Job::Sweep(2),
+        Job::Page,
+        Job::Build(2),
+    ] {
+        tx.send(job).await.unwrap();
+    }
+    assert_eq!(rx.len(), 5);
+
+    let mut received = Vec::new();
+    while let Ok(job) = rx.try_recv() {
+        received.push(job);
+    }
+    assert_eq!(
+        received,
+        [
+            Job::Page,
+            Job::Build(1),
+            Job::Build(2),
+            Job::Sweep(1),
+            Job::Sweep(2)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_try_recv_on_an_empty_channel() {
+    let (tx, mut rx) = priority_channel::<Job>(4);
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    assert!(rx.is_empty());
+
+    drop(tx);
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+}
+
+#[tokio::test]
+async fn test_recv_returns_none_once_senders_are_gone_and_drained() {
+    let (tx, mut rx) = priority_channel(4);
+    tx.send(Job::Build(1)).await.unwrap();
+    tx.send(Job::Build(2)).await.unwrap();
+    drop(tx);
+
+    assert_eq!(rx.recv().await, Some(Job::Build(1)));
+    assert_eq!(rx.recv().await, Some(Job::Build(2)));
+    assert_eq!(rx.recv().await, None);
+    assert_eq!(rx.recv().await, None);
+}
+
+#[tokio::test]
+async fn test_capacity_counts_messages_not_yet_received() {
+    let (tx, mut rx) = priority_channel(2);
+    tx.try_send(Job::Sweep(1)).unwrap();
+    tx.try_send(Job::Sweep(2)).unwrap();
+    assert!(matches!(
+        tx.try_send(Job::Page),
+        Err(crate::TrySendError::Full(Job::Page))
+    ));
+
+    assert_eq!(rx.recv().await, Some(Job::Sweep(1)));
+    tx.try_send(Job::Page).unwrap();
+}
+
+#[tokio::test]
+async fn test_sends_fail_once_the_receiver_is_gone() {
+    let (tx, rx) = priority_channel(2);
+    drop(rx);
+    assert!(tx.is_closed());
+    assert!(tx.send(Job::Page).await.is_err());
+}
@@ -0,0 +1,136 @@
+use crate::handle::WeakHandle;
+use crate::{Actor, ActorHandle, TerminationReason};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::watch;
+
+/// Why [`Registry::register`] did not register a handle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RegistryError {
+    /// A running actor is already registered under this name.
+    NameTaken(String),
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::NameTaken(name) => {
+                write!(f, "an actor named `{}` is already registered", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+/// Actors addressable by name, so handles need not be threaded through every constructor.
+///
+/// Entries are typed by actor: looking a name up as a different actor type finds nothing.
+/// The registry does not keep actors alive, and an actor drops out of it as soon as it stops.
+/// Cloning a registry is cheap and every clone shares the same entries; [`Registry::global`]
+/// is one shared by the whole process.
+#[derive(Clone, Default)]
+pub struct Registry {
+    entries: Arc<Mutex<HashMap<String, Entry>>>,
+}
+
+struct Entry {
+    actor: TypeId,
+    // A `WeakHandle` of the actor's message type.
+    handle: Box<dyn Any + Send + Sync>,
+    status: watch::Receiver<Option<TerminationReason>>,
+}
+
+impl Entry {
+    fn is_stopped(&self) -> bool {
+        self.status.borrow().is_some()
+    }
+}
+
+impl Registry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The registry shared by the whole process.
+    pub fn global() -> &'static Registry {
+        static GLOBAL: OnceLock<Registry> = OnceLock::new();
+        GLOBAL.get_or_init(Registry::new)
+    }
+
+    /// Registers `handle` to actor `A` under `name`.
+    ///
+    /// Fails with [`RegistryError::NameTaken`] while another running actor holds the name;
+    /// names of stopped actors are free again.
+    pub fn register<A: Actor>(
+        &self,
+        name: impl Into<String>,
+        handle: &ActorHandle<A::Msg>,
+    ) -> Result<(), RegistryError> {
+        let name = name.into();
+        let mut entries = self.entries.lock().unwrap();
+        if entries.get(&name).is_some_and(|entry| !entry.is_stopped()) {
+            return Err(RegistryError::NameTaken(name));
+        }
+        entries.insert(
+            name,
+            Entry {
+                actor: TypeId::of::<A>(),
+                handle: Box::new(handle.downgrade()),
+                status: handle.shared.status.clone(),
+            },
+        );
+        Ok(())
+    }
+
+    /// The handle registered under `name`, or `None` if there is none, it belongs to an
+    /// actor other than `A`, or that actor has stopped.
+    pub fn lookup<A: Actor>(&self, name: &str) -> Option<ActorHandle<A::Msg>> {
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.get(name)?;
+        if entry.actor != TypeId::of::<A>() {
+            return None;
+        }
+        let handle = if entry.is_stopped() {
+            None
+        } else {
+            entry
+                .handle
+                .downcast_ref::<WeakHandle<A::Msg>>()
+                .and_then(WeakHandle::upgrade)
+        };
+        if handle.is_none() {
+            entries.remove(name);
+        }
+        handle
+    }
+
+    /// Removes the entry under `name`, returning `true` if a running actor was registered
+    /// there.
+    pub fn unregister(&self, name: &str) -> bool {
+        self.entries
+            .lock()
+            .unwrap()
+            .remove(name)
+            .is_some_and(|entry| !entry.is_stopped())
+    }
+
+    /// The names of the running actors, in no particular order.
+    pub fn names(&self) -> Vec<String> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, entry| !entry.is_stopped());
+        entries.keys().cloned().collect()
+    }
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Registry")
+            .field("names", &self.names())
+            .finish()
+    }
+}
@@ -0,0 +1,68 @@
+use crate::prelude::*;
+use crate::test_support::{Gated, GatedMsg};
+use crate::{Registry, RegistryError};
+
+define_actor! {
+    Other {}
+
+    impl OtherMsg {
+        @priority(Medium)
+        fn Ping(&mut self) {}
+    }
+}
+
+#[tokio::test]
+async fn test_lookup_finds_registered_actor() {
+    let registry = Registry::new();
+    let handle = spawn_actor(Gated::new());
+    registry.register::<Gated>("gate", &handle).unwrap();
+
+    let found = registry.lookup::<Gated>("gate").expect("registered");
+    found.send(GatedMsg::Fill()).await.unwrap();
+    assert!(registry.lookup::<Gated>("missing").is_none());
+    assert_eq!(registry.names(), ["gate"]);
+}
+
+#[tokio::test]
+async fn test_duplicate_names_are_rejected() {
+    let registry = Registry::new();
+    let first = spawn_actor(Gated::new());
+    let second = spawn_actor(Other {});
+    registry.register::<Gated>("svc", &first).unwrap();
+    assert_eq!(
+        registry.register::<Other>("svc", &second),
+        Err(RegistryError::NameTaken("svc".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn test_lookup_as_another_actor_type_finds_nothing() {
+    let registry = Registry::new();
+    let handle = spawn_actor(Gated::new());
+    registry.register::<Gated>("gate", &handle).unwrap();
+    assert!(registry.lookup::<Other>("gate").is_none());
+    assert!(registry.lookup::<Gated>("gate").is_some());
+}
+
+#[tokio::test]
+async fn test_stopped_actors_are_deregistered() {
+    let registry = Registry::new();
+    let handle = spawn_actor(Gated::new());
+    registry.register::<Gated>("gate", &handle).unwrap();
+    handle.send(GatedMsg::Shutdown).await.unwrap();
+    assert_eq!(handle.join().await, TerminationReason::ShutdownMessage);
+
+    assert!(registry.lookup::<Gated>("gate").is_none());
+    assert!(registry.names().is_empty());
+    let next = spawn_actor(Gated::new());
+    registry.register::<Gated>("gate", &next).unwrap();
+}
+
+#[tokio::test]
+async fn test_registry_does_not_keep_actors_alive() {
+    let registry = Registry::new();
+    let actor = spawn_actor_owned(Gated::new(), ActorConfig::new());
+    registry.register::<Gated>("gate", actor.handle()).unwrap();
+    assert!(actor.join().await.is_some());
+    assert!(!registry.unregister("gate"));
+}
@@ -0,0 +1,219 @@
+//! Actors served over TCP, for messages sent from another process.
+//!
+//! [`serve_actor`] accepts connections on a [`TcpListener`] and forwards every message it
+//! reads to a local actor, which handles them by priority like any other. On the other
+//! side, [`RemoteHandle::connect`] opens a connection and [`RemoteHandle::send`] ships
+//! messages over it.
+//!
+//! On the wire each message is a frame: its length as a big-endian `u32`, then the message
+//! encoded with `bincode`. Connections only carry messages towards the actor; nothing is
+//! sent back, so there are no replies. A message type holding a reply channel such as a
+//! `oneshot::Sender` does not implement `Serialize`, and an actor receiving one cannot be
+//! served: ask-style messages are rejected at compile time by the [`RemoteMessage`] bound.
+
+use crate::log::diag;
+use crate::{ActorHandle, Prioritized};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::{watch, Mutex};
+use tokio::task::JoinSet;
+
+/// The largest frame accepted from a connection, in bytes; longer frames end it.
+pub const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// A message that can be sent to an actor in another process.
+///
+/// Implemented for every message type that serde can encode and decode.
+pub trait RemoteMessage: Prioritized + Serialize + DeserializeOwned + Send + 'static {}
+
+impl<M: Prioritized + Serialize + DeserializeOwned + Send + 'static> RemoteMessage for M {}
+
+/// Accepts connections on `listener` and forwards the messages sent over them to the actor
+/// behind `handle`.
+///
+/// Each connection is read by its own task, and its messages are sent in the order they
+/// arrive, so a slow actor holds back its remote senders just as it would local ones. A
+/// connection that closes, or sends a frame that fails to decode, is dropped without
+/// affecting the others.
+///
+/// Serving keeps the actor alive. Returns once the actor has stopped, for instance on a
+/// `Shutdown` message from a remote sender, closing every connection; fails if accepting a
+/// connection fails.
+pub async fn serve_actor<M: RemoteMessage>(
+    handle: ActorHandle<M>,
+    listener: TcpListener,
+) -> io::Result<()> {
+    let mut status = handle.shared.status.clone();
+    let mut connections = JoinSet::new();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, _) = accepted?;
+                connections.spawn(serve_connection(handle.clone(), stream));
+            }
+            _ = status.wait_for(Option::is_some) => break,
+        }
+        while connections.try_join_next().is_some() {}
+    }
+    // Dropping the tasks closes their connections.
+    connections.shutdown().await;
+    Ok(())
+}
+
+/// Forwards the messages read from one connection until it closes or the actor stops.
+async fn serve_connection<M: RemoteMessage>(handle: ActorHandle<M>, stream: TcpStream) {
+    let mut reader = BufReader::new(stream);
+    loop {
+        let msg = match read_frame::<M, _>(&mut reader).await {
+            Ok(Some(msg)) => msg,
+            Ok(None) => return,
+            Err(err) => {
+                diag!(warn, handle.name(), "dropping remote connection: {}", err);
+                return;
+            }
+        };
+        if handle.send(msg).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Reads one frame, or `None` if the connection closed cleanly between frames.
+async fn read_frame<M, R>(reader: &mut R) -> io::Result<Option<M>>
+where
+    M: DeserializeOwned,
+    R: AsyncRead + Unpin,
+{
+    let len = match reader.read_u32().await {
+        Ok(len) => len as usize,
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    if len > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame of {len} bytes exceeds MAX_FRAME_LEN"),
+        ));
+    }
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body).await?;
+    bincode::deserialize(&body)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Encodes `msg` as a length-prefixed frame.
+fn encode_frame<M: Serialize>(msg: &M) -> Result<Vec<u8>, RemoteError> {
+    let body = bincode::serialize(msg).map_err(|err| RemoteError::Encode(err.to_string()))?;
+    if body.len() > MAX_FRAME_LEN {
+        return Err(RemoteError::Encode(format!(
+            "message of {} bytes exceeds MAX_FRAME_LEN",
+            body.len()
+        )));
+    }
+    let mut frame = Vec::with_capacity(4 + body.len());
+    frame.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    frame.extend_from_slice(&body);
+    Ok(frame)
+}
+
+/// Why a [`RemoteHandle`] could not send a message.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RemoteError {
+    /// The connection is closed: the actor stopped, or the server went away.
+    Closed,
+    /// The message could not be encoded, or is larger than [`MAX_FRAME_LEN`].
+    Encode(String),
+    /// Writing to the connection failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemoteError::Closed => f.write_str("remote actor connection is closed"),
+            RemoteError::Encode(reason) => write!(f, "could not encode message: {reason}"),
+            RemoteError::Io(err) => write!(f, "could not send message: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for RemoteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RemoteError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Sends messages to an actor served by [`serve_actor`], possibly in another process.
+///
+/// Clones share one connection, and messages sent through it arrive in the order they
+/// were sent. The connection closes once every clone is gone.
+pub struct RemoteHandle<M> {
+    writer: Arc<Mutex<OwnedWriteHalf>>,
+    closed: watch::Receiver<bool>,
+    _msg: PhantomData<fn(M)>,
+}
+
+impl<M> Clone for RemoteHandle<M> {
+    fn clone(&self) -> Self {
+        RemoteHandle {
+            writer: Arc::clone(&self.writer),
+            closed: self.closed.clone(),
+            _msg: PhantomData,
+        }
+    }
+}
+
+impl<M: RemoteMessage> RemoteHandle<M> {
+    /// Connects to an actor served at `addr`.
+    pub async fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr).await?;
+        stream.set_nodelay(true)?;
+        let (mut reader, writer) = stream.into_split();
+        let (closed_tx, closed) = watch::channel(false);
+        // The server never writes, so the read only returns once the connection is gone.
+        tokio::spawn(async move {
+            let _ = reader.read(&mut [0; 1]).await;
+            let _ = closed_tx.send(true);
+        });
+        Ok(RemoteHandle {
+            writer: Arc::new(Mutex::new(writer)),
+            closed,
+            _msg: PhantomData,
+        })
+    }
+
+    /// Sends a message to the remote actor.
+    ///
+    /// Returns once the message is written to the connection, not once the actor has it: a
+    /// message sent just as the connection drops may be lost without an error.
+    pub async fn send(&self, msg: M) -> Result<(), RemoteError> {
+        if self.is_closed() {
+            return Err(RemoteError::Closed);
+        }
+        let frame = encode_frame(&msg)?;
+        let mut writer = self.writer.lock().await;
+        writer.write_all(&frame).await.map_err(RemoteError::Io)
+    }
+
+    /// Returns `true` once the connection has closed.
+    pub fn is_closed(&self) -> bool {
+        *self.closed.borrow()
+    }
+
+    /// Waits until the connection has closed.
+    pub async fn closed(&self) {
+        let _ = self.closed.clone().wait_for(|closed| *closed).await;
+    }
+}
@@ -0,0 +1,158 @@
+use crate::prelude::*;
+use crate::remote::{serve_actor, RemoteError, RemoteHandle};
+use crate::SpawnedActor;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+
+#[derive(Prioritized, Serialize, Deserialize)]
+enum Note {
+    #[priority(Low)]
+    Low(u32),
+    #[priority(High)]
+    High(u32),
+    #[priority(Shutdown)]
+    Stop,
+}
+
+/// Records the notes it handles. With a gate, the first one reports that it started and
+/// waits for the release to fire or drop.
+struct Notes {
+    log: Vec<u32>,
+    gate: Option<Gate>,
+}
+
+type Gate = (oneshot::Sender<()>, oneshot::Receiver<()>);
+
+#[async_trait]
+impl Actor for Notes {
+    type Msg = Note;
+
+    async fn handle(
+        &mut self,
+        msg: Note,
+        _ctx: &ActorContext<Note>,
+    ) -> ControlFlow<ShutdownReason> {
+        if let Some((started, release)) = self.gate.take() {
+            let _ = started.send(());
+            let _ = release.await;
+        }
+        match msg {
+            Note::Low(n) | Note::High(n) => self.log.push(n),
+            Note::Stop => return ControlFlow::Break(ShutdownReason::Requested),
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+type Server = JoinHandle<std::io::Result<()>>;
+
+/// Serves a fresh `Notes` actor on a local port, returning the port's address.
+async fn serve(gate: Option<Gate>) -> (SpawnedActor<Notes>, Server, String) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let actor = spawn_actor_owned(
+        Notes {
+            log: Vec::new(),
+            gate,
+        },
+        ActorConfig::new(),
+    );
+    let server = tokio::spawn(serve_actor(actor.handle().clone(), listener));
+    (actor, server, addr)
+}
+
+/// Polls until the actor has seen `n` messages, queued or handled.
+async fn until_seen(actor: &SpawnedActor<Notes>, n: u64) {
+    while actor.handle().metrics().messages_enqueued() < n {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+}
+
+#[tokio::test]
+async fn test_remote_messages_are_handled_in_priority_order() {
+    let (started_tx, started) = oneshot::channel();
+    let (release, release_rx) = oneshot::channel();
+    let (actor, server, addr) = serve(Some((started_tx, release_rx))).await;
+    let remote = RemoteHandle::connect(&addr).await.unwrap();
+    remote.send(Note::Low(0)).await.unwrap();
+    started.await.unwrap();
+    for note in [Note::Low(1), Note::Low(2), Note::High(3), Note::High(4)] {
+        remote.send(note).await.unwrap();
+    }
+    until_seen(&actor, 5).await;
+
+    drop(release);
+    actor.handle().await_idle().await;
+    remote.send(Note::Stop).await.unwrap();
+    server.await.unwrap().unwrap();
+    assert_eq!(actor.join().await.unwrap().log, [0, 3, 4, 1, 2]);
+}
+
+#[tokio::test]
+async fn test_stopping_the_actor_closes_remote_connections() {
+    let (actor, server, addr) = serve(None).await;
+    let remote = RemoteHandle::connect(&addr).await.unwrap();
+    let other = RemoteHandle::<Note>::connect(&addr).await.unwrap();
+
+    remote.send(Note::Stop).await.unwrap();
+    server.await.unwrap().unwrap();
+    other.closed().await;
+    assert!(other.is_closed());
+    assert!(matches!(
+        other.send(Note::Low(1)).await,
+        Err(RemoteError::Closed)
+    ));
+    actor.join().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_server_going_away_closes_the_remote_handle() {
+    let (actor, server, addr) = serve(None).await;
+    let remote = RemoteHandle::<Note>::connect(&addr).await.unwrap();
+    remote.send(Note::Low(1)).await.unwrap();
+    until_seen(&actor, 1).await;
+
+    server.abort();
+    remote.closed().await;
+    assert!(matches!(
+        remote.send(Note::Low(2)).await,
+        Err(RemoteError::Closed)
+    ));
+}
+
+#[tokio::test]
+async fn test_dropped_connection_leaves_the_actor_serving_others() {
+    let (actor, server, addr) = serve(None).await;
+    let first = RemoteHandle::connect(&addr).await.unwrap();
+    first.send(Note::Low(1)).await.unwrap();
+    drop(first);
+    until_seen(&actor, 1).await;
+
+    let second = RemoteHandle::connect(&addr).await.unwrap();
+    second.send(Note::High(2)).await.unwrap();
+    until_seen(&actor, 2).await;
+    actor.handle().await_idle().await;
+    assert!(!actor.handle().is_closed());
+
+    second.send(Note::Stop).await.unwrap();
+    server.await.unwrap().unwrap();
+    assert_eq!(actor.join().await.unwrap().log, [1, 2]);
+}
+
+#[tokio::test]
+async fn test_oversized_frame_drops_the_connection() {
+    let (actor, server, addr) = serve(None).await;
+    let mut stream = TcpStream::connect(&addr).await.unwrap();
+    stream.write_u32(u32::MAX).await.unwrap();
+    assert_eq!(stream.read(&mut [0; 1]).await.unwrap(), 0);
+
+    let remote = RemoteHandle::connect(&addr).await.unwrap();
+    remote.send(Note::Stop).await.unwrap();
+    server.await.unwrap().unwrap();
+    assert_eq!(actor.join().await.unwrap().log, Vec::<u32>::new());
+}
@@ -0,0 +1,119 @@
+use crate::Priority;
+use std::fmt;
+
+/// Spawn options that may be changed while the actor runs.
+///
+/// Set initially through [`ActorConfig`](crate::ActorConfig) and replaced with
+/// [`ActorHandle::reconfigure`](crate::ActorHandle::reconfigure). A change never reaches back
+/// to work already decided: options applied when a message is enqueued only affect messages
+/// that reach the queue afterwards, and options applied when a message is popped only affect
+/// the next dispatch.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct RuntimeConfig {
+    /// Most messages handled per second, or `None` for no limit.
+    ///
+    /// Applied when a message is popped: the processor waits between dispatches, while
+    /// `Shutdown` messages are dispatched without waiting.
+    pub rate_limit: Option<u32>,
+    /// Queue length at which incoming messages are dropped instead of queued, or `None` to
+    /// never shed.
+    ///
+    /// Applied when a message is enqueued. `Shutdown` messages are never shed; the number of
+    /// shed messages is reported by [`ActorMetrics::messages_shed`](crate::ActorMetrics::messages_shed).
+    pub shed_threshold: Option<usize>,
+    /// Most messages the queue holds, or `None` for no limit; what happens to a message
+    /// beyond it is up to [`overflow`](Self::overflow).
+    ///
+    /// Applied when a message is enqueued. `Shutdown` messages always get in, even past the
+    /// limit. Dropped messages are reported by
+    /// [`ActorMetrics::messages_overflowed`](crate::ActorMetrics::messages_overflowed).
+    pub max_queued: Option<usize>,
+    /// What to do with a message that arrives while the queue holds `max_queued` messages.
+    pub overflow: OverflowPolicy,
+    /// Most messages of a single priority the queue holds, independently of every other
+    /// priority; priorities not listed have no quota of their own.
+    ///
+    /// Applied when a message is enqueued, at the priority it is queued at. Under
+    /// [`OverflowPolicy::Reject`], a send whose priority is at its quota fails with
+    /// `MailboxFull`, handing the message back; under the other policies the message is
+    /// dropped on arrival and reported by
+    /// [`ActorMetrics::messages_overflowed`](crate::ActorMetrics::messages_overflowed).
+    /// Messages the actor sends itself are always dropped. `Shutdown` messages always get
+    /// in.
+    pub quotas: Vec<(Priority, usize)>,
+}
+
+/// What happens to a message that arrives while the queue is at
+/// [`RuntimeConfig::max_queued`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OverflowPolicy {
+    /// Turn senders away: [`ActorHandle::offer`](crate::ActorHandle::offer) fails with
+    /// `Full` instead of sending. Messages sent any other way are dropped on arrival, as
+    /// with [`DropIncoming`](Self::DropIncoming), unless it is their priority's
+    /// [quota](RuntimeConfig::quotas) that is full: then the send fails with `Full` too.
+    #[default]
+    Reject,
+    /// Drop the queued message that would be handled last to make room, if it has a lower
+    /// priority than the new one; otherwise drop the new one.
+    DropLowest,
+    /// Drop the new message.
+    DropIncoming,
+}
+
+impl RuntimeConfig {
+    pub(crate) fn validate(&self) -> Result<(), ReconfigureError> {
+        if self.rate_limit == Some(0) {
+            return Err(ReconfigureError::Invalid(
+                "rate_limit must be greater than zero".to_string(),
+            ));
+        }
+        if self.max_queued == Some(0) {
+            return Err(ReconfigureError::Invalid(
+                "max_queued must be greater than zero".to_string(),
+            ));
+        }
+        if self.quotas.iter().any(|&(_, quota)| quota == 0) {
+            return Err(ReconfigureError::Invalid(
+                "quotas must be greater than zero".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The quota for messages of exactly `priority`, if it has one.
+    pub(crate) fn quota(&self, priority: Priority) -> Option<usize> {
+        if priority == Priority::Shutdown {
+            return None;
+        }
+        self.quotas
+            .iter()
+            .find(|(level, _)| *level == priority)
+            .map(|&(_, quota)| quota)
+    }
+}
+
+/// Why [`ActorHandle::reconfigure`](crate::ActorHandle::reconfigure) left the configuration
+/// unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ReconfigureError {
+    /// The updated configuration was rejected.
+    Invalid(String),
+    /// The actor has stopped.
+    Closed,
+}
+
+impl fmt::Display for ReconfigureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReconfigureError::Invalid(reason) => write!(f, "invalid configuration: {}", reason),
+            ReconfigureError::Closed => write!(f, "actor has stopped"),
+        }
+    }
+}
+
+impl std::error::Error for ReconfigureError {}
+
+pub(crate) type ConfigUpdate = Box<dyn FnOnce(&mut RuntimeConfig) + Send>;
@@ -0,0 +1,141 @@
+use crate::prelude::*;
+use crate::{ReconfigureError, RuntimeConfig};
+use tokio::sync::oneshot;
+use tokio::time::{Duration, Instant};
+
+define_actor! {
+    Paced {
+        handled: Vec<(usize, Instant)>,
+    }
+
+    impl PacedMsg {
+        @priority(Medium)
+        async fn Hold(&mut self, started: oneshot::Sender<()>, release: oneshot::Receiver<()>) {
+            let _ = started.send(());
+            let _ = release.await;
+        }
+
+        @priority(Low)
+        fn Work(&mut self, id: usize) {
+            self.handled.push((id, Instant::now()));
+        }
+
+        @priority(Low)
+        fn Handled(&mut self, tx: oneshot::Sender<Vec<(usize, Instant)>>) {
+            let _ = tx.send(std::mem::take(&mut self.handled));
+        }
+    }
+}
+
+fn paced() -> Paced {
+    Paced {
+        handled: Vec::new(),
+    }
+}
+
+async fn handled(handle: &ActorHandle<PacedMsg>) -> Vec<(usize, Instant)> {
+    let (tx, rx) = oneshot::channel();
+    handle.send(PacedMsg::Handled(tx)).await.unwrap();
+    rx.await.unwrap()
+}
+
+fn gaps(handled: &[(usize, Instant)]) -> Vec<Duration> {
+    handled.windows(2).map(|w| w[1].1 - w[0].1).collect()
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_tightened_rate_limit_slows_later_messages() {
+    let handle = spawn_actor_with(paced(), ActorConfig::new().rate_limit(10));
+
+    for id in 0..5 {
+        handle.send(PacedMsg::Work(id)).await.unwrap();
+    }
+    let fast = handled(&handle).await;
+    assert_eq!(fast.len(), 5);
+    for gap in gaps(&fast) {
+        assert!(gap >= Duration::from_millis(100) && gap < Duration::from_millis(200));
+    }
+
+    handle
+        .reconfigure(|cfg| cfg.rate_limit = Some(2))
+        .await
+        .unwrap();
+    for id in 5..10 {
+        handle.send(PacedMsg::Work(id)).await.unwrap();
+    }
+    let slow = handled(&handle).await;
+    assert_eq!(slow.len(), 5);
+    for gap in gaps(&slow) {
+        assert!(gap >= Duration::from_millis(500) && gap < Duration::from_millis(600));
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_loosened_shed_threshold_stops_shedding() {
+    let handle = spawn_actor_with(paced(), ActorConfig::new().shed_threshold(3));
+    let (started_tx, started_rx) = oneshot::channel();
+    let (release_tx, release_rx) = oneshot::channel();
+    handle
+        .send(PacedMsg::Hold(started_tx, release_rx))
+        .await
+        .unwrap();
+    started_rx.await.unwrap();
+
+    for id in 0..10 {
+        handle.send(PacedMsg::Work(id)).await.unwrap();
+    }
+    handle
+        .reconfigure(|cfg| cfg.shed_threshold = None)
+        .await
+        .unwrap();
+    assert_eq!(handle.metrics().messages_shed(), 7);
+
+    for id in 10..15 {
+        handle.send(PacedMsg::Work(id)).await.unwrap();
+    }
+    release_tx.send(()).unwrap();
+
+    let ids: Vec<usize> = handled(&handle)
+        .await
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+    assert_eq!(ids, [0, 1, 2, 10, 11, 12, 13, 14]);
+    assert_eq!(handle.metrics().messages_shed(), 7);
+}
+
+#[tokio::test]
+async fn test_invalid_reconfigure_is_rejected() {
+    let handle = spawn_actor(paced());
+    let err = handle
+        .reconfigure(|cfg| cfg.rate_limit = Some(0))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ReconfigureError::Invalid(_)));
+
+    // The rejected change left the actor unthrottled.
+    handle
+        .reconfigure(|cfg| assert_eq!(*cfg, RuntimeConfig::default()))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_reconfigure_after_stop_reports_closed() {
+    let handle = spawn_actor(paced());
+    let observer = handle.clone();
+    handle.send(PacedMsg::Shutdown).await.unwrap();
+    handle.join().await;
+
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while !observer.is_closed() {
+            tokio::task::yield_now().await;
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(
+        observer.reconfigure(|_| {}).await,
+        Err(ReconfigureError::Closed)
+    );
+}
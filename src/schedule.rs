@@ -0,0 +1,279 @@
+use crate::handle::WeakHandle;
+use crate::ActorMetrics;
+use std::sync::{Arc, Mutex};
+use tokio::task::{AbortHandle, JoinHandle};
+
+/// The scheduler tasks feeding one actor.
+///
+/// Tasks are tracked here so the processor can cancel them, and wait for them to be gone,
+/// before it reports that the actor stopped.
+pub(crate) struct Schedules {
+    // `None` once the actor has stopped; later schedules are not started.
+    tasks: Mutex<Option<Vec<JoinHandle<()>>>>,
+    keep_alive: bool,
+    metrics: Arc<ActorMetrics>,
+}
+
+// Only `ScheduleExt` starts tasks.
+#[cfg_attr(not(feature = "schedule"), allow(dead_code))]
+impl Schedules {
+    pub(crate) fn new(keep_alive: bool, metrics: Arc<ActorMetrics>) -> Self {
+        Schedules {
+            tasks: Mutex::new(Some(Vec::new())),
+            keep_alive,
+            metrics,
+        }
+    }
+
+    /// Whether scheduler tasks hold strong handles and so keep the actor alive.
+    pub(crate) fn keep_alive(&self) -> bool {
+        self.keep_alive
+    }
+
+    /// Starts a scheduler task, unless the actor has already stopped.
+    pub(crate) fn spawn<F>(&self, task: F) -> Option<AbortHandle>
+    where
+        F: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let mut tasks = self.tasks.lock().unwrap();
+        let tasks = tasks.as_mut()?;
+        tasks.retain(|task| !task.is_finished());
+
+        let live = LiveTask::new(Arc::clone(&self.metrics));
+        let task = tokio::spawn(async move {
+            let _live = live;
+            task.await;
+        });
+        let abort = task.abort_handle();
+        tasks.push(task);
+        Some(abort)
+    }
+
+    /// Cancels every scheduler task and waits until they are gone.
+    pub(crate) async fn shutdown(&self) {
+        let tasks = self.tasks.lock().unwrap().take().unwrap_or_default();
+        for task in &tasks {
+            task.abort();
+        }
+        for task in tasks {
+            let _ = task.await;
+        }
+    }
+}
+
+/// Counts a scheduler task as live for as long as it exists, including when it is aborted
+/// before its first poll.
+struct LiveTask(Arc<ActorMetrics>);
+
+impl LiveTask {
+    fn new(metrics: Arc<ActorMetrics>) -> Self {
+        metrics.scheduler_started();
+        LiveTask(metrics)
+    }
+}
+
+impl Drop for LiveTask {
+    fn drop(&mut self) {
+        self.0.scheduler_stopped();
+    }
+}
+
+/// Where a scheduler task delivers its messages.
+#[cfg_attr(not(feature = "schedule"), allow(dead_code))]
+pub(crate) enum Target<M> {
+    /// Keeps the actor alive for as long as the task runs.
+    Strong(crate::ActorHandle<M>),
+    /// Lets the actor shut down implicitly; the task ends once the upgrade fails.
+    Weak(WeakHandle<M>),
+}
+
+#[cfg_attr(not(feature = "schedule"), allow(dead_code))]
+impl<M> Target<M> {
+    /// A weak or strong target for `handle`, as its actor's configuration asks.
+    pub(crate) fn of(handle: &crate::ActorHandle<M>) -> Self {
+        if handle.shared.schedules.keep_alive() {
+            Target::Strong(handle.clone())
+        } else {
+            Target::Weak(handle.downgrade())
+        }
+    }
+
+    pub(crate) fn get(&self) -> Option<crate::ActorHandle<M>> {
+        match self {
+            Target::Strong(handle) => Some(handle.clone()),
+            Target::Weak(weak) => weak.upgrade(),
+        }
+    }
+}
+
+/// Delivering messages to an actor on a timer.
+///
+/// Scheduler tasks hold weak handles by default, so an actor whose only remaining senders
+/// are its schedules still shuts down implicitly, and the schedules end with it. Spawn with
+/// [`ActorConfig::keep_alive_while_scheduled`](crate::ActorConfig::keep_alive_while_scheduled)
+/// for actors that are driven purely by timers. Either way, every schedule is cancelled
+/// before [`join`](crate::ActorHandle::join) resolves.
+#[cfg(feature = "schedule")]
+pub trait ScheduleExt: crate::sealed::Sealed {
+    type Msg;
+
+    /// Sends a message built by `make` every `period`, starting one period from now, until
+    /// the returned guard is dropped or the actor stops.
+    ///
+    /// Ticks that come before an actor spawned with
+    /// [`Readiness::Reject`](crate::Readiness::Reject) is ready are skipped.
+    ///
+    /// Same as [`send_interval_with`](Self::send_interval_with) with
+    /// [`MissedTicks::Queue`].
+    fn send_interval<F>(&self, period: tokio::time::Duration, make: F) -> ScheduleGuard
+    where
+        F: Fn() -> Self::Msg + Send + 'static;
+
+    /// Like [`send_interval`](Self::send_interval), with `missed` deciding what happens to a
+    /// tick while the previous one is still waiting to be handled.
+    fn send_interval_with<F>(
+        &self,
+        period: tokio::time::Duration,
+        missed: MissedTicks,
+        make: F,
+    ) -> ScheduleGuard
+    where
+        F: Fn() -> Self::Msg + Send + 'static;
+
+    /// Sends `msg` once `delay` has passed, unless the returned guard is dropped or
+    /// [aborted](ScheduleGuard::abort) first.
+    ///
+    /// The message is sent like any other when the timer fires, so it queues behind what was
+    /// sent before then and its priority counts from that point. If the actor has stopped by
+    /// then, the message is dropped.
+    fn send_after(&self, msg: Self::Msg, delay: tokio::time::Duration) -> ScheduleGuard;
+
+    /// Number of scheduler tasks currently running for this actor.
+    fn scheduled_count(&self) -> usize;
+}
+
+/// What a repeating schedule does when it is due to send again while its previous message
+/// has not been handled yet, e.g. because the actor is busy.
+#[cfg(feature = "schedule")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MissedTicks {
+    /// Send anyway, so every tick is handled eventually. A slow actor lets ticks pile up.
+    #[default]
+    Queue,
+    /// Skip the tick, leaving at most one message from the schedule waiting.
+    Skip,
+}
+
+/// Cancels a scheduled delivery when dropped, returned by [`ScheduleExt::send_after`] and
+/// [`ScheduleExt::send_interval`].
+#[cfg(feature = "schedule")]
+#[must_use = "dropping the guard cancels the delivery"]
+pub struct ScheduleGuard(Option<AbortHandle>);
+
+#[cfg(feature = "schedule")]
+impl ScheduleGuard {
+    /// Cancels the delivery, if it has not happened yet.
+    pub fn abort(self) {
+        // Dropping the guard aborts the task.
+    }
+
+    /// Lets the delivery go ahead without holding on to the guard.
+    ///
+    /// The schedule still ends when the actor stops.
+    pub fn detach(mut self) {
+        self.0 = None;
+    }
+
+    /// Returns `true` once the delivery has happened or been cancelled, or for a repeating
+    /// schedule, once it has stopped.
+    pub fn is_finished(&self) -> bool {
+        self.0.as_ref().is_none_or(AbortHandle::is_finished)
+    }
+}
+
+#[cfg(feature = "schedule")]
+impl Drop for ScheduleGuard {
+    fn drop(&mut self) {
+        if let Some(task) = &self.0 {
+            task.abort();
+        }
+    }
+}
+
+#[cfg(feature = "schedule")]
+impl std::fmt::Debug for ScheduleGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScheduleGuard")
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+
+#[cfg(feature = "schedule")]
+impl<M: crate::Prioritized + Send + 'static> ScheduleExt for crate::ActorHandle<M> {
+    type Msg = M;
+
+    fn send_interval<F>(&self, period: tokio::time::Duration, make: F) -> ScheduleGuard
+    where
+        F: Fn() -> M + Send + 'static,
+    {
+        self.send_interval_with(period, MissedTicks::Queue, make)
+    }
+
+    fn send_interval_with<F>(
+        &self,
+        period: tokio::time::Duration,
+        missed: MissedTicks,
+        make: F,
+    ) -> ScheduleGuard
+    where
+        F: Fn() -> M + Send + 'static,
+    {
+        let target = Target::of(self);
+        let task = self.shared.schedules.spawn(async move {
+            let start = tokio::time::Instant::now() + period;
+            let mut ticks = tokio::time::interval_at(start, period);
+            // Held by the last message sent until it leaves the queue.
+            let ticket = Arc::new(());
+            if missed == MissedTicks::Skip {
+                ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            }
+            loop {
+                ticks.tick().await;
+                let Some(handle) = target.get() else {
+                    break;
+                };
+                if missed == MissedTicks::Skip && Arc::strong_count(&ticket) > 1 {
+                    continue;
+                }
+                let mut envelope = crate::mailbox::Envelope::new(make(), crate::Origin::Scheduled);
+                envelope.ticket = Some(Arc::clone(&ticket));
+                match handle.deliver(envelope).await {
+                    Ok(()) => {}
+                    // A tick the actor is not ready for yet, or that its quota turns away,
+                    // is skipped.
+                    Err(err) if err.is_not_ready() || err.is_full() => {}
+                    // The mailbox closed: the actor has stopped and so does the schedule.
+                    Err(_) => break,
+                }
+            }
+        });
+        ScheduleGuard(task)
+    }
+
+    fn send_after(&self, msg: M, delay: tokio::time::Duration) -> ScheduleGuard {
+        let target = Target::of(self);
+        let task = self.shared.schedules.spawn(async move {
+            tokio::time::sleep(delay).await;
+            if let Some(handle) = target.get() {
+                let _ = handle.send_from(crate::Origin::Scheduled, msg).await;
+            }
+        });
+        ScheduleGuard(task)
+    }
+
+    fn scheduled_count(&self) -> usize {
+        self.shared.metrics.scheduled_tasks()
+    }
+}
@@ -0,0 +1,235 @@
+use crate::prelude::*;
+use crate::test_support::{gated_init, stall, Gated, GatedMsg};
+use crate::{MissedTicks, Readiness};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::oneshot;
+use tokio::time::{sleep, Duration};
+
+/// Counts ticks and stops itself after `stop_after` of them.
+struct Ticker {
+    ticks: Arc<AtomicUsize>,
+    stop_after: usize,
+}
+
+enum TickerMsg {
+    Tick,
+    Stop,
+}
+
+impl Prioritized for TickerMsg {
+    fn priority(&self) -> Priority {
+        match self {
+            TickerMsg::Tick => Priority::Medium,
+            TickerMsg::Stop => Priority::Shutdown,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for Ticker {
+    type Msg = TickerMsg;
+
+    async fn handle(
+        &mut self,
+        msg: TickerMsg,
+        _ctx: &ActorContext<TickerMsg>,
+    ) -> ControlFlow<ShutdownReason> {
+        let done = match msg {
+            TickerMsg::Tick => self.ticks.fetch_add(1, Ordering::SeqCst) + 1 >= self.stop_after,
+            TickerMsg::Stop => true,
+        };
+        if done {
+            ControlFlow::Break(ShutdownReason::Requested)
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+fn ticker(stop_after: usize) -> (Ticker, Arc<AtomicUsize>) {
+    let ticks = Arc::new(AtomicUsize::new(0));
+    let actor = Ticker {
+        ticks: Arc::clone(&ticks),
+        stop_after,
+    };
+    (actor, ticks)
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_schedules_do_not_keep_actor_alive_by_default() {
+    let (actor, ticks) = ticker(usize::MAX);
+    let handle = spawn_actor(actor);
+    let _ticks = handle.send_interval(Duration::from_millis(100), || TickerMsg::Tick);
+    assert_eq!(handle.scheduled_count(), 1);
+
+    sleep(Duration::from_millis(350)).await;
+    assert_eq!(ticks.load(Ordering::SeqCst), 3);
+
+    // Only the schedule is left, so the actor winds down and takes the schedule with it.
+    let metrics = handle.metrics();
+    assert_eq!(handle.join().await, TerminationReason::SendersDropped);
+    assert_eq!(metrics.scheduled_tasks(), 0);
+
+    sleep(Duration::from_secs(1)).await;
+    assert_eq!(ticks.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_keep_alive_while_scheduled_runs_timer_driven_actor() {
+    let (actor, ticks) = ticker(10);
+    let handle = spawn_actor_with(actor, ActorConfig::new().keep_alive_while_scheduled(true));
+    handle
+        .send_interval(Duration::from_millis(100), || TickerMsg::Tick)
+        .detach();
+    let metrics = handle.metrics();
+    drop(handle);
+
+    // No handle remains, yet the schedule keeps delivering.
+    sleep(Duration::from_millis(550)).await;
+    assert_eq!(ticks.load(Ordering::SeqCst), 5);
+    assert_eq!(metrics.scheduled_tasks(), 1);
+
+    // The tenth tick stops the actor, which cancels the schedule.
+    sleep(Duration::from_secs(1)).await;
+    assert_eq!(ticks.load(Ordering::SeqCst), 10);
+    assert_eq!(metrics.scheduled_tasks(), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_explicit_shutdown_cancels_schedules_before_join() {
+    let (actor, ticks) = ticker(usize::MAX);
+    let handle = spawn_actor_with(actor, ActorConfig::new().keep_alive_while_scheduled(true));
+    let _fast = handle.send_interval(Duration::from_millis(100), || TickerMsg::Tick);
+    let _slow = handle.send_interval(Duration::from_millis(250), || TickerMsg::Tick);
+    assert_eq!(handle.scheduled_count(), 2);
+
+    sleep(Duration::from_millis(320)).await;
+    assert_eq!(ticks.load(Ordering::SeqCst), 4);
+    let observer = handle.clone();
+    handle.send(TickerMsg::Stop).await.unwrap();
+    assert_eq!(handle.join().await, TerminationReason::ShutdownMessage);
+    assert_eq!(observer.scheduled_count(), 0);
+
+    // A stopped actor starts no new schedules.
+    let late = observer.send_interval(Duration::from_millis(100), || TickerMsg::Tick);
+    assert!(late.is_finished());
+    assert_eq!(observer.scheduled_count(), 0);
+    sleep(Duration::from_secs(1)).await;
+    assert_eq!(ticks.load(Ordering::SeqCst), 4);
+}
+
+async fn report(handle: &ActorHandle<GatedMsg>) -> Vec<Priority> {
+    let (tx, rx) = oneshot::channel();
+    handle.send(GatedMsg::Report(tx)).await.unwrap();
+    rx.await.unwrap()
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_send_after_delivers_behind_earlier_sends() {
+    let handle = spawn_actor(Gated::new());
+    let _guard = handle.send_after(GatedMsg::Urgent(), Duration::from_millis(100));
+    handle.send(GatedMsg::Fill()).await.unwrap();
+
+    sleep(Duration::from_millis(50)).await;
+    assert_eq!(report(&handle).await, [Priority::Low]);
+    sleep(Duration::from_millis(100)).await;
+    assert_eq!(report(&handle).await, [Priority::Low, Priority::High]);
+    assert_eq!(handle.scheduled_count(), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_send_after_priority_counts_once_delivered() {
+    let handle = spawn_actor(Gated::new());
+    let release = stall(&handle).await;
+    handle.send(GatedMsg::Fill()).await.unwrap();
+    handle.send(GatedMsg::Fill()).await.unwrap();
+    handle
+        .send_after(GatedMsg::Urgent(), Duration::from_millis(10))
+        .detach();
+
+    sleep(Duration::from_millis(20)).await;
+    drop(release);
+    handle.await_idle().await;
+    assert_eq!(
+        report(&handle).await,
+        [Priority::High, Priority::Low, Priority::Low]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_send_after_is_cancelled_by_its_guard() {
+    let handle = spawn_actor(Gated::new());
+    let dropped = handle.send_after(GatedMsg::Fill(), Duration::from_millis(100));
+    let aborted = handle.send_after(GatedMsg::Urgent(), Duration::from_millis(100));
+    assert_eq!(handle.scheduled_count(), 2);
+    drop(dropped);
+    aborted.abort();
+
+    sleep(Duration::from_millis(200)).await;
+    assert_eq!(report(&handle).await, []);
+    assert_eq!(handle.scheduled_count(), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_send_after_to_a_stopped_actor_is_dropped() {
+    let handle = spawn_actor(Gated::new());
+    let observer = handle.clone();
+    let pending = handle.send_after(GatedMsg::Fill(), Duration::from_millis(100));
+    handle.send(GatedMsg::Shutdown).await.unwrap();
+    assert_eq!(handle.join().await, TerminationReason::ShutdownMessage);
+    assert!(pending.is_finished());
+
+    // Nothing is scheduled for an actor that has already stopped.
+    let late = observer.send_after(GatedMsg::Fill(), Duration::from_millis(100));
+    assert!(late.is_finished());
+    assert_eq!(observer.scheduled_count(), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_dropping_the_interval_guard_stops_ticks() {
+    let (actor, ticks) = ticker(usize::MAX);
+    let handle = spawn_actor(actor);
+    let guard = handle.send_interval(Duration::from_millis(100), || TickerMsg::Tick);
+
+    sleep(Duration::from_millis(250)).await;
+    drop(guard);
+    sleep(Duration::from_secs(1)).await;
+    assert_eq!(ticks.load(Ordering::SeqCst), 2);
+    assert_eq!(handle.scheduled_count(), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_missed_ticks_queue_or_skip_while_the_actor_is_busy() {
+    for (missed, expected) in [(MissedTicks::Queue, 10), (MissedTicks::Skip, 1)] {
+        let handle = spawn_actor(Gated::new());
+        let release = stall(&handle).await;
+        let _ticks = handle.send_interval_with(Duration::from_millis(10), missed, GatedMsg::Fill);
+
+        sleep(Duration::from_millis(105)).await;
+        drop(release);
+        handle.await_idle().await;
+        assert_eq!(report(&handle).await.len(), expected, "{:?}", missed);
+
+        // Once the actor catches up, every tick is delivered again.
+        sleep(Duration::from_millis(20)).await;
+        assert_eq!(report(&handle).await.len(), expected + 2, "{:?}", missed);
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_interval_skips_ticks_until_the_actor_is_ready() {
+    let (release, gate) = oneshot::channel();
+    let handle = gated_init(ActorConfig::new().readiness(Readiness::Reject), gate);
+    let _guard = handle.send_interval(Duration::from_millis(300), GatedMsg::Routine);
+
+    // Ticks at 300, 600 and 900 ms find the actor still starting.
+    sleep(Duration::from_millis(1000)).await;
+    release.send(()).unwrap();
+    handle.ready().await.unwrap();
+    sleep(Duration::from_millis(850)).await;
+
+    let (tx, rx) = oneshot::channel();
+    handle.send(GatedMsg::Report(tx)).await.unwrap();
+    assert_eq!(rx.await.unwrap(), [Priority::Medium; 3]);
+}
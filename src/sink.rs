@@ -0,0 +1,255 @@
+//! Observer endpoints the runtime reports to without ever waiting on them.
+//!
+//! Dead-letter, audit, and other observer hooks all deliver through a [`Sink`]. A sink whose
+//! channel is full or closed drops the item and counts it; a callback that panics is caught,
+//! logged at most once per second, and switched off after a few panics. Either way the actor
+//! keeps running at full speed.
+
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+/// Panics tolerated from a callback before it is disabled.
+pub const DEFAULT_MAX_PANICS: u32 = 3;
+
+const LOG_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A non-blocking destination for items the runtime reports, such as dead letters.
+pub struct Sink<T> {
+    inner: Arc<SinkInner<T>>,
+}
+
+struct SinkInner<T> {
+    target: Target<T>,
+    stats: SinkStats,
+    guard: PanicGuard,
+}
+
+enum Target<T> {
+    Channel(mpsc::Sender<T>),
+    Unbounded(mpsc::UnboundedSender<T>),
+    Callback(Mutex<Box<dyn FnMut(T) + Send>>),
+}
+
+impl<T> Clone for Sink<T> {
+    fn clone(&self) -> Self {
+        Sink {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sink<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sink")
+            .field("stats", &self.inner.stats)
+            .finish()
+    }
+}
+
+impl<T: Send + 'static> Sink<T> {
+    /// Delivers into a bounded channel with `try_send`; items that don't fit are dropped.
+    pub fn channel(tx: mpsc::Sender<T>) -> Self {
+        Self::from_target(Target::Channel(tx))
+    }
+
+    /// Delivers into an unbounded channel.
+    pub fn unbounded(tx: mpsc::UnboundedSender<T>) -> Self {
+        Self::from_target(Target::Unbounded(tx))
+    }
+
+    /// Calls `f` for every item, on whichever actor task produced it.
+    ///
+    /// `f` should return quickly. A panic inside `f` is caught; after
+    /// [`DEFAULT_MAX_PANICS`] panics the callback is disabled (see
+    /// [`max_panics`](Self::max_panics)).
+    pub fn callback(f: impl FnMut(T) + Send + 'static) -> Self {
+        Self::from_target(Target::Callback(Mutex::new(Box::new(f))))
+    }
+
+    /// Sets how many panics a callback sink tolerates before it is disabled.
+    pub fn max_panics(self, max_panics: u32) -> Self {
+        self.inner
+            .guard
+            .max_panics
+            .store(max_panics, Ordering::Relaxed);
+        self
+    }
+
+    fn from_target(target: Target<T>) -> Self {
+        Sink {
+            inner: Arc::new(SinkInner {
+                target,
+                stats: SinkStats::default(),
+                guard: PanicGuard::new(DEFAULT_MAX_PANICS),
+            }),
+        }
+    }
+}
+
+impl<T> Sink<T> {
+    /// Hands `item` to the sink without waiting. Returns `true` if it was delivered.
+    pub fn deliver(&self, item: T) -> bool {
+        let inner = &*self.inner;
+        let stats = &inner.stats;
+        let delivered = match &inner.target {
+            Target::Channel(tx) => match tx.try_send(item) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    stats.overflowed.fetch_add(1, Ordering::Relaxed);
+                    false
+                }
+                Err(TrySendError::Closed(_)) => {
+                    stats.closed.fetch_add(1, Ordering::Relaxed);
+                    false
+                }
+            },
+            Target::Unbounded(tx) => {
+                let sent = tx.send(item).is_ok();
+                if !sent {
+                    stats.closed.fetch_add(1, Ordering::Relaxed);
+                }
+                sent
+            }
+            Target::Callback(f) => {
+                if inner.guard.is_tripped() {
+                    stats.skipped.fetch_add(1, Ordering::Relaxed);
+                    return false;
+                }
+                // A panic is caught before the lock guard unwinds, so the mutex never poisons.
+                let mut f = match f.lock() {
+                    Ok(f) => f,
+                    Err(poisoned) => poisoned.into_inner(),
+                };
+                let called = inner.guard.call("sink callback", || f(item)).is_some();
+                if !called {
+                    stats.panicked.fetch_add(1, Ordering::Relaxed);
+                }
+                called
+            }
+        };
+        if delivered {
+            stats.delivered.fetch_add(1, Ordering::Relaxed);
+        }
+        delivered
+    }
+
+    /// Counters describing how well the sink has kept up.
+    pub fn stats(&self) -> &SinkStats {
+        &self.inner.stats
+    }
+
+    /// Returns `true` once a callback sink has been disabled after repeated panics.
+    pub fn is_disabled(&self) -> bool {
+        self.inner.guard.is_tripped()
+    }
+}
+
+/// Delivery counters for a [`Sink`].
+#[derive(Debug, Default)]
+pub struct SinkStats {
+    delivered: AtomicU64,
+    overflowed: AtomicU64,
+    closed: AtomicU64,
+    panicked: AtomicU64,
+    skipped: AtomicU64,
+}
+
+impl SinkStats {
+    /// Items the sink accepted.
+    pub fn delivered(&self) -> u64 {
+        self.delivered.load(Ordering::Relaxed)
+    }
+
+    /// Items dropped because the sink's channel was full.
+    pub fn overflowed(&self) -> u64 {
+        self.overflowed.load(Ordering::Relaxed)
+    }
+
+    /// Items dropped because the sink's receiver was gone.
+    pub fn closed(&self) -> u64 {
+        self.closed.load(Ordering::Relaxed)
+    }
+
+    /// Callback invocations that panicked.
+    pub fn panicked(&self) -> u64 {
+        self.panicked.load(Ordering::Relaxed)
+    }
+
+    /// Items dropped because the callback had been disabled.
+    pub fn skipped(&self) -> u64 {
+        self.skipped.load(Ordering::Relaxed)
+    }
+
+    /// Every item that did not reach the sink, for whatever reason.
+    pub fn dropped(&self) -> u64 {
+        self.overflowed() + self.closed() + self.panicked() + self.skipped()
+    }
+}
+
+/// Runs user callbacks behind `catch_unwind`, with a rate-limited error log and a circuit
+/// that trips after `max_panics` panics.
+pub(crate) struct PanicGuard {
+    panics: AtomicU32,
+    max_panics: AtomicU32,
+    tripped: AtomicBool,
+    last_log: Mutex<Option<Instant>>,
+}
+
+impl PanicGuard {
+    pub(crate) fn new(max_panics: u32) -> Self {
+        PanicGuard {
+            panics: AtomicU32::new(0),
+            max_panics: AtomicU32::new(max_panics),
+            tripped: AtomicBool::new(false),
+            last_log: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn is_tripped(&self) -> bool {
+        self.tripped.load(Ordering::Relaxed)
+    }
+
+    /// Calls `f`, returning `None` if the circuit is open or `f` panicked.
+    pub(crate) fn call<R>(&self, what: &str, f: impl FnOnce() -> R) -> Option<R> {
+        if self.is_tripped() {
+            return None;
+        }
+        match catch_unwind(AssertUnwindSafe(f)) {
+            Ok(result) => Some(result),
+            Err(_) => {
+                let panics = self.panics.fetch_add(1, Ordering::Relaxed) + 1;
+                let tripped = panics >= self.max_panics.load(Ordering::Relaxed);
+                if tripped {
+                    self.tripped.store(true, Ordering::Relaxed);
+                }
+                self.log(what, panics, tripped);
+                None
+            }
+        }
+    }
+
+    fn log(&self, what: &str, panics: u32, tripped: bool) {
+        let mut last_log = match self.last_log.lock() {
+            Ok(last_log) => last_log,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let due = last_log.is_none_or(|at| at.elapsed() >= LOG_INTERVAL);
+        if due || tripped {
+            *last_log = Some(Instant::now());
+            let disabling = if tripped { "; disabling it" } else { "" };
+            #[cfg(feature = "tracing")]
+            tracing::warn!(panics, "{} panicked{}", what, disabling);
+            #[cfg(all(not(feature = "tracing"), feature = "log-stdout"))]
+            println!(
+                "[priact] {} panicked ({} so far){}",
+                what, panics, disabling
+            );
+            #[cfg(not(any(feature = "tracing", feature = "log-stdout")))]
+            let _ = (what, panics, disabling);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+// Chaos tests: each sink is broken on purpose while an actor reports into it from its
+// handlers, and the actor must keep handling every message.
+use crate::prelude::*;
+use crate::sink::Sink;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{timeout, Duration};
+
+const WORKLOAD: u64 = 1_000;
+
+define_actor! {
+    Reporter {
+        handled: u64,
+        sink: Sink<u64>,
+    }
+
+    impl ReporterMsg {
+        @priority(Low)
+        fn Work(&mut self, n: u64) {
+            self.handled += 1;
+            self.sink.deliver(n);
+        }
+
+        @priority(Low)
+        fn Handled(&mut self, tx: oneshot::Sender<u64>) {
+            let _ = tx.send(self.handled);
+        }
+    }
+}
+
+/// Runs the workload against `sink` and returns once the actor has handled all of it.
+async fn run_workload(sink: Sink<u64>) {
+    let handle = spawn_actor(Reporter { handled: 0, sink });
+    timeout(Duration::from_secs(5), async {
+        for n in 0..WORKLOAD {
+            handle.send(ReporterMsg::Work(n)).await.unwrap();
+        }
+        loop {
+            let (tx, rx) = oneshot::channel();
+            handle.send(ReporterMsg::Handled(tx)).await.unwrap();
+            if rx.await.unwrap() == WORKLOAD {
+                break;
+            }
+        }
+    })
+    .await
+    .expect("a broken sink must not slow the actor down");
+}
+
+#[tokio::test]
+async fn test_full_channel_sink_overflows_without_blocking() {
+    let (tx, _never_polled) = mpsc::channel(1);
+    let sink = Sink::channel(tx);
+    run_workload(sink.clone()).await;
+
+    assert_eq!(sink.stats().delivered(), 1);
+    assert_eq!(sink.stats().overflowed(), WORKLOAD - 1);
+}
+
+#[tokio::test]
+async fn test_dropped_receiver_sink_counts_closed() {
+    let (tx, rx) = mpsc::channel(16);
+    drop(rx);
+    let sink = Sink::channel(tx);
+    run_workload(sink.clone()).await;
+
+    assert_eq!(sink.stats().delivered(), 0);
+    assert_eq!(sink.stats().closed(), WORKLOAD);
+
+    let (tx, rx) = mpsc::unbounded_channel();
+    drop(rx);
+    let sink = Sink::unbounded(tx);
+    run_workload(sink.clone()).await;
+    assert_eq!(sink.stats().closed(), WORKLOAD);
+}
+
+#[tokio::test]
+async fn test_panicking_callback_is_disabled() {
+    let sink = Sink::callback(|_: u64| panic!("deliberately broken sink")).max_panics(5);
+    run_workload(sink.clone()).await;
+
+    let stats = sink.stats();
+    assert!(sink.is_disabled());
+    assert_eq!(stats.panicked(), 5);
+    assert_eq!(stats.delivered(), 0);
+    assert_eq!(stats.skipped(), WORKLOAD - 5);
+    assert_eq!(stats.dropped(), WORKLOAD);
+}
+
+#[tokio::test]
+async fn test_healthy_callback_sees_every_item() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let sink = Sink::callback(move |n: u64| {
+        let _ = tx.send(n);
+    });
+    run_workload(sink.clone()).await;
+
+    assert_eq!(sink.stats().delivered(), WORKLOAD);
+    assert_eq!(sink.stats().dropped(), 0);
+    let mut seen = 0;
+    while rx.try_recv().is_ok() {
+        seen += 1;
+    }
+    assert_eq!(seen, WORKLOAD);
+}
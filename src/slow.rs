@@ -0,0 +1,58 @@
+use crate::log::diag;
+use crate::sink::Sink;
+use crate::{ActorMetrics, Priority};
+use tokio::time::Duration;
+
+/// A handler that ran past [`ActorConfig::slow_handler_threshold`](crate::ActorConfig::slow_handler_threshold),
+/// as passed to [`ActorConfig::on_slow_handler`](crate::ActorConfig::on_slow_handler).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SlowHandler {
+    /// The actor's [name](crate::ActorHandle::name).
+    pub actor: String,
+    /// The message's [variant name](crate::Prioritized::variant_name); a batch goes by its
+    /// first message.
+    pub variant: &'static str,
+    /// The priority the message was queued at.
+    pub priority: Priority,
+    /// How long the handler took.
+    pub elapsed: Duration,
+}
+
+/// Where an actor with a slow-handler threshold times its handlers.
+pub(crate) struct SlowHandlers {
+    pub(crate) threshold: Duration,
+    pub(crate) on_slow: Option<Sink<SlowHandler>>,
+}
+
+impl SlowHandlers {
+    pub(crate) fn record(
+        &self,
+        metrics: &ActorMetrics,
+        actor: &str,
+        variant: &'static str,
+        priority: Priority,
+        elapsed: Duration,
+    ) {
+        metrics.handler_took(variant, elapsed);
+        if elapsed <= self.threshold {
+            return;
+        }
+        diag!(
+            warn,
+            actor,
+            "handler for a {:?} {} message took {:?}",
+            priority,
+            variant,
+            elapsed
+        );
+        if let Some(on_slow) = &self.on_slow {
+            on_slow.deliver(SlowHandler {
+                actor: actor.to_owned(),
+                variant,
+                priority,
+                elapsed,
+            });
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use crate::prelude::*;
+use crate::SlowHandler;
+use std::sync::{Arc, Mutex};
+use tokio::time::Duration;
+
+define_actor! {
+    /// Sleeps for as many milliseconds as each message says.
+    Napper {}
+
+    impl NapperMsg {
+        @priority(Medium)
+        async fn Nap(&mut self, millis: u64) {
+            tokio::time::sleep(Duration::from_millis(millis)).await;
+        }
+
+        @priority(High)
+        async fn Doze(&mut self, millis: u64) {
+            tokio::time::sleep(Duration::from_millis(millis)).await;
+        }
+    }
+}
+
+/// A napper reporting handlers over 100ms to the returned log.
+fn watched_napper() -> (ActorHandle<NapperMsg>, Arc<Mutex<Vec<SlowHandler>>>) {
+    let slow = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&slow);
+    let config = ActorConfig::new()
+        .name("napper")
+        .slow_handler_threshold(Duration::from_millis(100))
+        .on_slow_handler(move |handler| log.lock().unwrap().push(handler));
+    (spawn_actor_with(Napper {}, config), slow)
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_handlers_over_the_threshold_are_reported() {
+    let (handle, slow) = watched_napper();
+    handle.send(NapperMsg::Nap(50)).await.unwrap();
+    handle.send(NapperMsg::Doze(150)).await.unwrap();
+    handle.send(NapperMsg::Nap(100)).await.unwrap();
+    handle.await_idle().await;
+
+    let slow = slow.lock().unwrap();
+    assert_eq!(slow.len(), 1);
+    assert_eq!(slow[0].actor, "napper");
+    assert_eq!(slow[0].variant, "Doze");
+    assert_eq!(slow[0].priority, Priority::High);
+    assert_eq!(slow[0].elapsed, Duration::from_millis(150));
+}
+
+#[cfg(feature = "metrics")]
+#[tokio::test(start_paused = true)]
+async fn test_handler_times_are_kept_per_variant() {
+    use crate::{HandlerTime, MetricsExt};
+
+    let (handle, _slow) = watched_napper();
+    for millis in [50, 250, 30] {
+        handle.send(NapperMsg::Nap(millis)).await.unwrap();
+    }
+    handle.await_idle().await;
+
+    let metrics = handle.metrics();
+    assert_eq!(
+        metrics.handler_time("Nap"),
+        HandlerTime {
+            samples: 3,
+            max: Duration::from_millis(250),
+            mean: Duration::from_millis(110),
+        }
+    );
+    assert_eq!(metrics.handler_time("Doze"), HandlerTime::default());
+}
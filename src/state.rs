@@ -0,0 +1,62 @@
+use crate::mailbox::Visit;
+use crate::{ActorHandle, Error};
+use std::any::{type_name, Any};
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use tokio::sync::oneshot;
+
+// What a visit reports back: `None` if the actor is not an `A`, or else what the closure
+// returned or the panic it raised.
+type Visited<R> = Option<std::thread::Result<R>>;
+
+impl<M> ActorHandle<M> {
+    /// Runs `f` against the actor's state and returns what it returns, e.g. to read a field
+    /// while debugging without adding a message for it.
+    ///
+    /// This bypasses the actor's messages altogether: `f` sees every field, whatever the
+    /// actor's own handlers make of them. The actor runs `f` between messages, ahead of
+    /// anything queued; a handler that is still running holds it up. `A` must be the type of
+    /// the actor behind this handle, or this panics. A panic in `f` is raised again here and
+    /// leaves the actor running.
+    ///
+    /// A handle to an absorbed actor reports [`Error::MailboxClosed`]; its state is gone.
+    pub async fn with_state<A, R, F>(&self, f: F) -> Result<R, Error>
+    where
+        A: 'static,
+        R: Send + 'static,
+        F: FnOnce(&A) -> R + Send + 'static,
+    {
+        self.with_state_mut(move |state: &mut A| f(state)).await
+    }
+
+    /// Like [`with_state`](Self::with_state), letting `f` change the state as well.
+    ///
+    /// The actor's handlers get no say in the change, so it can break whatever they rely
+    /// on; if `f` panics halfway, the state stays as `f` left it.
+    pub async fn with_state_mut<A, R, F>(&self, f: F) -> Result<R, Error>
+    where
+        A: 'static,
+        R: Send + 'static,
+        F: FnOnce(&mut A) -> R + Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel::<Visited<R>>();
+        let visit: Visit = Box::new(move |state: &mut dyn Any| {
+            let visited = state
+                .downcast_mut::<A>()
+                .map(|state| catch_unwind(AssertUnwindSafe(|| f(state))));
+            let _ = tx.send(visited);
+        });
+        if let Err(visit) = self.tx.visit(visit) {
+            drop(visit);
+            return Err(Error::closed(self.name(), None));
+        }
+        match rx.await {
+            Ok(Some(Ok(result))) => Ok(result),
+            Ok(Some(Err(panic))) => resume_unwind(panic),
+            Ok(None) => panic!(
+                "with_state takes `{}`, which is not the type of this actor",
+                type_name::<A>()
+            ),
+            Err(_) => Err(Error::closed(self.name(), None)),
+        }
+    }
+}
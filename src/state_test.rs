@@ -0,0 +1,92 @@
+use crate::prelude::*;
+use crate::test_support::{Gated, GatedMsg};
+use tokio::sync::oneshot;
+
+define_actor! {
+    Tally {
+        total: u64,
+        history: Vec<u64>,
+    }
+
+    impl TallyMsg {
+        fn Add(&mut self, amount: u64) {
+            self.total += amount;
+            self.history.push(amount);
+        }
+
+        fn Total(&mut self, tx: oneshot::Sender<u64>) {
+            let _ = tx.send(self.total);
+        }
+    }
+}
+
+fn tally() -> Tally {
+    Tally {
+        total: 0,
+        history: Vec::new(),
+    }
+}
+
+#[tokio::test]
+async fn test_with_state_reads_fields_without_a_message() {
+    let handle = spawn_actor(tally());
+    for amount in [3, 4, 5] {
+        handle.send(TallyMsg::Add(amount)).await.unwrap();
+    }
+    handle.await_idle().await;
+
+    let history = handle
+        .with_state(|tally: &Tally| tally.history.clone())
+        .await;
+    assert_eq!(history, Ok(vec![3, 4, 5]));
+}
+
+#[tokio::test]
+async fn test_with_state_mut_changes_what_handlers_see() {
+    let handle = spawn_actor(tally());
+    handle.send(TallyMsg::Add(2)).await.unwrap();
+    handle.await_idle().await;
+
+    let before = handle
+        .with_state_mut(|tally: &mut Tally| std::mem::replace(&mut tally.total, 40))
+        .await;
+    handle.send(TallyMsg::Add(2)).await.unwrap();
+    let (tx, rx) = oneshot::channel();
+    handle.send(TallyMsg::Total(tx)).await.unwrap();
+
+    assert_eq!(before, Ok(2));
+    assert_eq!(rx.await, Ok(42));
+}
+
+#[tokio::test]
+async fn test_with_state_on_a_stopped_actor_reports_closed() {
+    let handle = spawn_actor(tally());
+    handle.send(TallyMsg::Shutdown).await.unwrap();
+    handle.clone().join().await;
+
+    let total = handle.with_state(|tally: &Tally| tally.total).await;
+    assert!(total.unwrap_err().is_closed());
+}
+
+#[tokio::test]
+async fn test_panicking_closure_panics_the_caller_not_the_actor() {
+    let handle = spawn_actor(tally());
+    let visit = tokio::spawn({
+        let handle = handle.clone();
+        async move {
+            handle
+                .with_state(|_: &Tally| -> u64 { panic!("inspection failed") })
+                .await
+        }
+    });
+
+    assert!(visit.await.unwrap_err().is_panic());
+    assert_eq!(handle.with_state(|tally: &Tally| tally.total).await, Ok(0));
+}
+
+#[tokio::test]
+#[should_panic(expected = "not the type of this actor")]
+async fn test_with_state_for_another_actor_type_panics() {
+    let handle: ActorHandle<GatedMsg> = spawn_actor(Gated::new());
+    let _ = handle.with_state(|tally: &Tally| tally.total).await;
+}
@@ -0,0 +1,110 @@
+use crate::log::diag;
+use crate::TerminationReason;
+use std::future::Future;
+use std::ops::ControlFlow;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::time::Duration;
+
+/// What a supervised actor does when one of its handlers panics; see
+/// [`spawn_actor_supervised`](crate::spawn_actor_supervised).
+///
+/// The message that caused the panic is lost either way. Messages queued behind it stay in
+/// the mailbox, and handles keep working across restarts.
+#[non_exhaustive]
+pub enum RestartStrategy<A> {
+    /// Stop the actor, reporting [`TerminationReason::Panicked`].
+    Stop,
+    /// Replace the actor with a fresh one from `factory` and carry on with the next message.
+    ///
+    /// The `n`th restart waits `backoff * 2^(n - 1)` first. The actor stops with
+    /// [`TerminationReason::Panicked`] on the first panic after `max_restarts` restarts.
+    RestartWithFactory {
+        factory: Box<dyn FnMut() -> A + Send>,
+        max_restarts: u32,
+        backoff: Duration,
+    },
+}
+
+impl<A> RestartStrategy<A> {
+    /// [`RestartWithFactory`](Self::RestartWithFactory) with the given factory and limits.
+    pub fn restart_with<F>(factory: F, max_restarts: u32, backoff: Duration) -> Self
+    where
+        F: FnMut() -> A + Send + 'static,
+    {
+        RestartStrategy::RestartWithFactory {
+            factory: Box::new(factory),
+            max_restarts,
+            backoff,
+        }
+    }
+}
+
+/// Applies a [`RestartStrategy`] inside the processor task.
+pub(crate) struct Supervisor<A> {
+    strategy: RestartStrategy<A>,
+    restarts: u32,
+}
+
+impl<A> Supervisor<A> {
+    pub(crate) fn new(strategy: RestartStrategy<A>) -> Self {
+        Supervisor {
+            strategy,
+            restarts: 0,
+        }
+    }
+
+    /// Called after a handler of `actor` panicked. Either swaps in a fresh actor, or says why
+    /// the actor stops.
+    pub(crate) async fn recover(
+        &mut self,
+        actor: &mut A,
+        actor_name: &str,
+    ) -> ControlFlow<TerminationReason> {
+        match &mut self.strategy {
+            RestartStrategy::RestartWithFactory {
+                factory,
+                max_restarts,
+                backoff,
+            } if self.restarts < *max_restarts => {
+                let delay = backoff.saturating_mul(1 << self.restarts.min(31));
+                self.restarts += 1;
+                diag!(
+                    warn,
+                    actor_name,
+                    "handler panicked; restarting (restart {}) in {:?}",
+                    self.restarts,
+                    delay
+                );
+                tokio::time::sleep(delay).await;
+                *actor = factory();
+                ControlFlow::Continue(())
+            }
+            _ => {
+                diag!(
+                    error,
+                    actor_name,
+                    "handler panicked; processor task terminating"
+                );
+                ControlFlow::Break(TerminationReason::Panicked)
+            }
+        }
+    }
+}
+
+/// Resolves to `Err` with the panic payload if polling the inner future panics.
+pub(crate) struct CatchUnwind<F>(pub(crate) F);
+
+impl<F: Future + Unpin> Future for CatchUnwind<F> {
+    type Output = std::thread::Result<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let inner = &mut self.0;
+        match catch_unwind(AssertUnwindSafe(|| Pin::new(&mut *inner).poll(cx))) {
+            Ok(Poll::Pending) => Poll::Pending,
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Err(panic) => Poll::Ready(Err(panic)),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use crate::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::oneshot;
+use tokio::time::{Duration, Instant};
+
+define_actor! {
+    Fragile {
+        generation: usize,
+        seen: Vec<u32>,
+    }
+
+    impl FragileMsg {
+        @priority(Medium)
+        fn Poison(&mut self) {
+            panic!("poisoned");
+        }
+
+        @priority(Medium)
+        fn Record(&mut self, value: u32) {
+            self.seen.push(value);
+        }
+
+        @priority(Medium)
+        fn Report(&mut self, tx: oneshot::Sender<(usize, Vec<u32>)>) {
+            let _ = tx.send((self.generation, self.seen.clone()));
+        }
+    }
+}
+
+/// A factory numbering the actors it builds, and how many it has built.
+fn factory() -> (impl FnMut() -> Fragile + Send + 'static, Arc<AtomicUsize>) {
+    let built = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&built);
+    let factory = move || Fragile {
+        generation: counter.fetch_add(1, Ordering::SeqCst) + 1,
+        seen: Vec::new(),
+    };
+    (factory, built)
+}
+
+async fn report(handle: &ActorHandle<FragileMsg>) -> (usize, Vec<u32>) {
+    let (tx, rx) = oneshot::channel();
+    handle.send(FragileMsg::Report(tx)).await.unwrap();
+    rx.await.unwrap()
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_restart_keeps_the_mailbox_and_later_messages() {
+    let (factory, built) = factory();
+    let handle = spawn_actor_supervised(
+        Fragile {
+            generation: 0,
+            seen: Vec::new(),
+        },
+        ActorConfig::new(),
+        RestartStrategy::restart_with(factory, 3, Duration::from_millis(100)),
+    );
+
+    handle.send(FragileMsg::Record(1)).await.unwrap();
+    handle.send(FragileMsg::Poison()).await.unwrap();
+    handle.send(FragileMsg::Record(2)).await.unwrap();
+
+    // The replacement starts from scratch and picks up where the old actor left off.
+    assert_eq!(report(&handle).await, (1, vec![2]));
+    assert_eq!(built.load(Ordering::SeqCst), 1);
+    assert!(!handle.is_closed());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_backoff_doubles_per_restart() {
+    let (factory, _) = factory();
+    let handle = spawn_actor_supervised(
+        Fragile {
+            generation: 0,
+            seen: Vec::new(),
+        },
+        ActorConfig::new(),
+        RestartStrategy::restart_with(factory, 3, Duration::from_millis(100)),
+    );
+
+    let start = Instant::now();
+    for _ in 0..3 {
+        handle.send(FragileMsg::Poison()).await.unwrap();
+    }
+    assert_eq!(report(&handle).await.0, 3);
+    assert_eq!(start.elapsed(), Duration::from_millis(100 + 200 + 400));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_gives_up_after_max_restarts() {
+    let (factory, built) = factory();
+    let handle = spawn_actor_supervised(
+        Fragile {
+            generation: 0,
+            seen: Vec::new(),
+        },
+        ActorConfig::new(),
+        RestartStrategy::restart_with(factory, 2, Duration::ZERO),
+    );
+
+    for _ in 0..3 {
+        handle.send(FragileMsg::Poison()).await.unwrap();
+    }
+    assert_eq!(handle.join().await, TerminationReason::Panicked);
+    assert_eq!(built.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_stop_strategy_reports_panicked() {
+    let handle = spawn_actor_supervised(
+        Fragile {
+            generation: 0,
+            seen: Vec::new(),
+        },
+        ActorConfig::new(),
+        RestartStrategy::Stop,
+    );
+
+    let terminated = handle.terminated();
+    handle.send(FragileMsg::Poison()).await.unwrap();
+    assert_eq!(terminated.await, TerminationReason::Panicked);
+    assert_eq!(handle.join().await, TerminationReason::Panicked);
+}
@@ -0,0 +1,150 @@
+use crate::Origin;
+#[cfg(feature = "tap")]
+use crate::{Prioritized, Priority};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+#[cfg(feature = "tap")]
+use std::sync::Arc;
+use std::sync::Mutex;
+#[cfg(feature = "tap")]
+use tokio::sync::mpsc;
+
+type Mirror<M> = Box<dyn Fn(&M, Origin) + Send + Sync>;
+
+/// Observers that see matching messages right before the actor handles them.
+pub(crate) struct Taps<M> {
+    taps: Mutex<Vec<(u64, Mirror<M>)>>,
+    // Lets the processor skip the lock while nobody is tapping.
+    active: AtomicBool,
+    #[cfg_attr(not(feature = "tap"), allow(dead_code))]
+    next_id: AtomicU64,
+}
+
+impl<M> Taps<M> {
+    pub(crate) fn new() -> Self {
+        Taps {
+            taps: Mutex::new(Vec::new()),
+            active: AtomicBool::new(false),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Shows `msg` to every tap. Taps never wait: an observer that is full or gone misses it.
+    pub(crate) fn mirror(&self, msg: &M, origin: Origin) {
+        if !self.active.load(Ordering::Acquire) {
+            return;
+        }
+        for (_, mirror) in self.taps.lock().unwrap().iter() {
+            mirror(msg, origin);
+        }
+    }
+
+    // Only `TapExt` installs taps.
+    #[cfg(feature = "tap")]
+    fn install(self: &Arc<Self>, mirror: Mirror<M>) -> TapGuard
+    where
+        M: 'static,
+    {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut taps = self.taps.lock().unwrap();
+        taps.push((id, mirror));
+        self.active.store(true, Ordering::Release);
+
+        let weak = Arc::downgrade(self);
+        TapGuard {
+            remove: Some(Box::new(move || {
+                if let Some(taps) = weak.upgrade() {
+                    let mut list = taps.taps.lock().unwrap();
+                    list.retain(|(tap, _)| *tap != id);
+                    taps.active.store(!list.is_empty(), Ordering::Release);
+                }
+            })),
+        }
+    }
+}
+
+/// What a summary tap reports about each matching message.
+#[cfg(feature = "tap")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MsgSummary {
+    /// The message's variant, from [`Prioritized::variant_name`].
+    pub variant: &'static str,
+    pub priority: Priority,
+    pub origin: Origin,
+}
+
+/// Keeps a tap installed; dropping it removes the tap.
+#[cfg(feature = "tap")]
+#[must_use = "the tap is removed as soon as its guard is dropped"]
+pub struct TapGuard {
+    remove: Option<Box<dyn FnOnce() + Send + Sync>>,
+}
+
+#[cfg(feature = "tap")]
+impl Drop for TapGuard {
+    fn drop(&mut self) {
+        if let Some(remove) = self.remove.take() {
+            remove();
+        }
+    }
+}
+
+/// Mirroring the messages an actor handles to observers, without changing the actor.
+///
+/// Each tap has a filter and a channel. Matching messages are offered to the channel with
+/// `try_send` right before the actor handles them, so a slow or full observer only misses
+/// messages and never holds the actor up. Any number of taps may be installed at once.
+#[cfg(feature = "tap")]
+pub trait TapExt: crate::sealed::Sealed {
+    type Msg;
+
+    /// Reports a [`MsgSummary`] for every handled message that `filter` accepts.
+    fn tap<F>(&self, filter: F, tx: mpsc::Sender<MsgSummary>) -> TapGuard
+    where
+        F: Fn(&Self::Msg) -> bool + Send + Sync + 'static;
+
+    /// Reports a [`MsgSummary`] for every handled message of the named variant.
+    fn tap_variant(&self, variant: &'static str, tx: mpsc::Sender<MsgSummary>) -> TapGuard;
+
+    /// Sends a copy of every handled message that `filter` accepts.
+    fn tap_messages<F>(&self, filter: F, tx: mpsc::Sender<Self::Msg>) -> TapGuard
+    where
+        Self::Msg: Clone,
+        F: Fn(&Self::Msg) -> bool + Send + Sync + 'static;
+}
+
+#[cfg(feature = "tap")]
+impl<M: Prioritized + Send + 'static> TapExt for crate::ActorHandle<M> {
+    type Msg = M;
+
+    fn tap<F>(&self, filter: F, tx: mpsc::Sender<MsgSummary>) -> TapGuard
+    where
+        F: Fn(&M) -> bool + Send + Sync + 'static,
+    {
+        self.shared.taps.install(Box::new(move |msg, origin| {
+            if filter(msg) {
+                let _ = tx.try_send(MsgSummary {
+                    variant: msg.variant_name(),
+                    priority: msg.priority(),
+                    origin,
+                });
+            }
+        }))
+    }
+
+    fn tap_variant(&self, variant: &'static str, tx: mpsc::Sender<MsgSummary>) -> TapGuard {
+        self.tap(move |msg| msg.variant_name() == variant, tx)
+    }
+
+    fn tap_messages<F>(&self, filter: F, tx: mpsc::Sender<M>) -> TapGuard
+    where
+        M: Clone,
+        F: Fn(&M) -> bool + Send + Sync + 'static,
+    {
+        self.shared.taps.install(Box::new(move |msg, _| {
+            if filter(msg) {
+                let _ = tx.try_send(msg.clone());
+            }
+        }))
+    }
+}
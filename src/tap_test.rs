@@ -0,0 +1,128 @@
+use crate::prelude::*;
+use crate::test_support::GatedMsg;
+use crate::{MsgSummary, Origin, TapExt};
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+
+#[derive(Prioritized, Clone, Debug, PartialEq)]
+enum TallyMsg {
+    #[priority(Low)]
+    Add(u32),
+    #[priority(High)]
+    Reset,
+    Ping,
+    #[priority(Low)]
+    Stop,
+}
+
+struct Tally {
+    total: u32,
+}
+
+#[async_trait]
+impl Actor for Tally {
+    type Msg = TallyMsg;
+
+    async fn handle(
+        &mut self,
+        msg: TallyMsg,
+        _ctx: &ActorContext<TallyMsg>,
+    ) -> ControlFlow<ShutdownReason> {
+        match msg {
+            TallyMsg::Add(n) => self.total += n,
+            TallyMsg::Reset => self.total = 0,
+            TallyMsg::Ping => {}
+            TallyMsg::Stop => return ControlFlow::Break(ShutdownReason::Requested),
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+fn drain<T>(rx: &mut mpsc::Receiver<T>) -> Vec<T> {
+    std::iter::from_fn(|| rx.try_recv().ok()).collect()
+}
+
+#[test]
+fn test_variant_names() {
+    assert_eq!(GatedMsg::Fill().variant_name(), "Fill");
+    assert_eq!(GatedMsg::Shutdown.variant_name(), "Shutdown");
+    assert_eq!(TallyMsg::Add(1).variant_name(), "Add");
+    assert_eq!(TallyMsg::Reset.variant_name(), "Reset");
+}
+
+#[tokio::test]
+async fn test_taps_see_their_subset_in_handling_order() {
+    let handle = spawn_actor(Tally { total: 0 });
+    let (changes_tx, mut changes_rx) = mpsc::channel(16);
+    let (pings_tx, mut pings_rx) = mpsc::channel(16);
+    let _changes = handle.tap_messages(
+        |msg| matches!(msg, TallyMsg::Add(_) | TallyMsg::Reset),
+        changes_tx,
+    );
+    let _pings = handle.tap_variant("Ping", pings_tx);
+
+    // Everything is queued before the actor handles any of it.
+    for msg in [
+        TallyMsg::Add(1),
+        TallyMsg::Ping,
+        TallyMsg::Reset,
+        TallyMsg::Add(2),
+        TallyMsg::Ping,
+        TallyMsg::Reset,
+        TallyMsg::Stop,
+    ] {
+        handle.send(msg).await.unwrap();
+    }
+    handle.join().await;
+
+    assert_eq!(
+        drain(&mut changes_rx),
+        [
+            TallyMsg::Reset,
+            TallyMsg::Reset,
+            TallyMsg::Add(1),
+            TallyMsg::Add(2),
+        ]
+    );
+    let ping = MsgSummary {
+        variant: "Ping",
+        priority: Priority::Medium,
+        origin: Origin::External,
+    };
+    assert_eq!(drain(&mut pings_rx), [ping, ping]);
+}
+
+#[tokio::test]
+async fn test_full_tap_never_delays_the_actor() {
+    let handle = spawn_actor_with(Tally { total: 0 }, ActorConfig::new().unbounded());
+    let (tx, mut rx) = mpsc::channel(1);
+    let _guard = handle.tap(|_| true, tx);
+
+    for n in 0..1000 {
+        handle.send(TallyMsg::Add(n)).await.unwrap();
+    }
+    handle.send(TallyMsg::Stop).await.unwrap();
+    timeout(Duration::from_secs(1), handle.join())
+        .await
+        .unwrap();
+
+    // Only the first message fit; the rest were dropped rather than waited for.
+    assert_eq!(drain(&mut rx).len(), 1);
+}
+
+#[tokio::test]
+async fn test_dropping_the_guard_removes_the_tap() {
+    let handle = spawn_actor(Tally { total: 0 });
+    let (tx, mut rx) = mpsc::channel(16);
+    let guard = handle.tap_variant("Add", tx);
+
+    handle.send(TallyMsg::Add(1)).await.unwrap();
+    assert_eq!(rx.recv().await.unwrap().variant, "Add");
+    drop(guard);
+    handle.send(TallyMsg::Add(2)).await.unwrap();
+    handle.send(TallyMsg::Stop).await.unwrap();
+    handle.join().await;
+
+    assert!(drain(&mut rx).is_empty());
+}
@@ -0,0 +1,69 @@
+//! Spawning processor tasks, named after their actor where tokio can name tasks.
+//!
+//! Tokio only names tasks when built with `--cfg tokio_unstable`; with the `tracing` feature
+//! on as well, tools such as `tokio-console` then list each processor as `<actor>/proc`.
+//! There is no separate receiving task: senders queue straight into the processor's inbox.
+
+use std::future::Future;
+use tokio::runtime::Handle;
+use tokio::task::{JoinHandle, JoinSet};
+
+#[cfg(all(tokio_unstable, feature = "tracing"))]
+const SPAWN_FAILED: &str = "failed to spawn the actor's processor task";
+
+/// The task name for an actor's processor.
+#[cfg(all(tokio_unstable, feature = "tracing"))]
+fn task_name(actor: &str) -> String {
+    format!("{}/proc", actor)
+}
+
+pub(crate) fn spawn_on<F>(name: &str, processor: F, runtime: &Handle) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    #[cfg(all(tokio_unstable, feature = "tracing"))]
+    return tokio::task::Builder::new()
+        .name(&task_name(name))
+        .spawn_on(processor, runtime)
+        .expect(SPAWN_FAILED);
+    #[cfg(not(all(tokio_unstable, feature = "tracing")))]
+    {
+        let _ = name;
+        runtime.spawn(processor)
+    }
+}
+
+pub(crate) fn spawn_in<F>(name: &str, processor: F, tasks: &mut JoinSet<()>)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    #[cfg(all(tokio_unstable, feature = "tracing"))]
+    tasks
+        .build_task()
+        .name(&task_name(name))
+        .spawn(processor)
+        .expect(SPAWN_FAILED);
+    #[cfg(not(all(tokio_unstable, feature = "tracing")))]
+    {
+        let _ = name;
+        tasks.spawn(processor);
+    }
+}
+
+pub(crate) fn spawn_local<F>(name: &str, processor: F)
+where
+    F: Future + 'static,
+    F::Output: 'static,
+{
+    #[cfg(all(tokio_unstable, feature = "tracing"))]
+    tokio::task::Builder::new()
+        .name(&task_name(name))
+        .spawn_local(processor)
+        .expect(SPAWN_FAILED);
+    #[cfg(not(all(tokio_unstable, feature = "tracing")))]
+    {
+        let _ = name;
+        tokio::task::spawn_local(processor);
+    }
+}
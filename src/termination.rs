@@ -0,0 +1,97 @@
+use crate::Priority;
+use std::collections::BTreeMap;
+
+/// Why an actor's processor stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TerminationReason {
+    /// A handler returned `ControlFlow::Break(ShutdownReason::Requested)`, as the generated
+    /// `Shutdown` message does.
+    ShutdownMessage,
+    /// A handler failed in a way the actor could not recover from, returning
+    /// `ControlFlow::Break(ShutdownReason::Failed)`.
+    HandlerFailed,
+    /// A handler panicked and the actor's
+    /// [`RestartStrategy`](crate::RestartStrategy) gave up on it.
+    Panicked,
+    /// Every handle was dropped and the queue had drained.
+    SendersDropped,
+    /// Another actor took over the pending messages with
+    /// [`ActorHandle::absorb`](crate::ActorHandle::absorb).
+    Absorbed,
+    /// The actor was linked to a parent with
+    /// [`ActorHandle::link`](crate::ActorHandle::link), and the parent stopped.
+    ParentStopped,
+    /// [`ActorHandle::shutdown_with_timeout`](crate::ActorHandle::shutdown_with_timeout)
+    /// closed the mailbox and the actor handled everything left in it.
+    Drained,
+    /// [`ActorHandle::shutdown_with_timeout`](crate::ActorHandle::shutdown_with_timeout)
+    /// gave up on draining the mailbox, cancelling the running handler if `mid_handler`.
+    DeadlineExceeded {
+        /// Whether a handler was running, and was cancelled, when the deadline passed.
+        mid_handler: bool,
+    },
+    /// The token given to [`ActorConfig::cancel_on`](crate::ActorConfig::cancel_on) was
+    /// cancelled.
+    Cancelled,
+    /// The future building the actor, passed to
+    /// [`spawn_actor_init`](crate::spawn_actor_init), failed.
+    InitFailed,
+    /// The processor task ended without reporting a reason, e.g. because a handler of an
+    /// unsupervised actor panicked or the runtime shut down.
+    Aborted,
+}
+
+/// Why a handler asked its actor to stop, carried by `ControlFlow::Break`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ShutdownReason {
+    /// The actor is done, e.g. it received the generated `Shutdown` message.
+    Requested,
+    /// The actor hit an error it could not recover from.
+    Failed,
+}
+
+/// What an actor left behind when it stopped, from
+/// [`ActorHandle::join_with_report`](crate::ActorHandle::join_with_report).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ShutdownReport {
+    /// Messages still queued when the actor stopped, which will never be handled, by the
+    /// priority they were queued at.
+    pub pending: BTreeMap<Priority, usize>,
+    /// Messages handed to the actor over its life, the one that stopped it included.
+    pub handled: u64,
+}
+
+impl ShutdownReport {
+    /// Messages still queued when the actor stopped, at every priority.
+    pub fn total_pending(&self) -> usize {
+        self.pending.values().sum()
+    }
+}
+
+/// How [`ActorHandle::shutdown_with_timeout`](crate::ActorHandle::shutdown_with_timeout)
+/// went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ShutdownOutcome {
+    /// The actor stopped before the deadline, normally once its mailbox had drained.
+    /// [`ActorHandle::join`](crate::ActorHandle::join) tells why it stopped.
+    Completed,
+    /// The deadline passed while the actor was between messages, e.g. held back by its rate
+    /// limit; it stopped without handling the rest.
+    TimedOutInQueue,
+    /// The deadline passed while a handler was running; the handler was cancelled and the
+    /// actor stopped without handling the rest.
+    TimedOutInHandler,
+}
+
+impl From<ShutdownReason> for TerminationReason {
+    fn from(reason: ShutdownReason) -> Self {
+        match reason {
+            ShutdownReason::Requested => TerminationReason::ShutdownMessage,
+            ShutdownReason::Failed => TerminationReason::HandlerFailed,
+        }
+    }
+}
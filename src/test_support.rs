@@ -0,0 +1,73 @@
+//! Actors shared by the unit tests.
+
+use crate::{
+    define_actor, spawn_actor_init_with, Actor, ActorConfig, ActorHandle, Prioritized, Priority,
+};
+use tokio::sync::oneshot;
+
+define_actor! {
+    Gated {
+        handled: Vec<Priority>,
+    }
+
+    impl GatedMsg {
+        @debug_drop
+
+        @priority(Medium)
+        async fn Hold(&mut self, started: oneshot::Sender<()>, release: oneshot::Receiver<()>) {
+            let _ = started.send(());
+            let _ = release.await;
+        }
+
+        @priority(Medium)
+        fn Routine(&mut self) {
+            self.handled.push(Priority::Medium);
+        }
+
+        @priority(Low)
+        fn Fill(&mut self) {
+            self.handled.push(Priority::Low);
+        }
+
+        @priority(High)
+        fn Urgent(&mut self) {
+            self.handled.push(Priority::High);
+        }
+
+        @priority(High)
+        fn Report(&mut self, tx: oneshot::Sender<Vec<Priority>>) {
+            let _ = tx.send(self.handled.clone());
+        }
+    }
+}
+
+impl Gated {
+    pub fn new() -> Self {
+        Gated {
+            handled: Vec::new(),
+        }
+    }
+}
+
+/// Parks the actor's processor inside a handler until the returned sender fires or drops.
+pub async fn stall(handle: &ActorHandle<GatedMsg>) -> oneshot::Sender<()> {
+    let (started_tx, started_rx) = oneshot::channel();
+    let (release_tx, release_rx) = oneshot::channel();
+    handle
+        .send(GatedMsg::Hold(started_tx, release_rx))
+        .await
+        .unwrap();
+    started_rx.await.unwrap();
+    release_tx
+}
+
+/// A `Gated` actor whose init waits until `release` fires or drops.
+pub fn gated_init(config: ActorConfig, release: oneshot::Receiver<()>) -> ActorHandle<GatedMsg> {
+    spawn_actor_init_with(
+        || async move {
+            let _ = release.await;
+            Ok::<_, ()>(Gated::new())
+        },
+        config,
+    )
+}
@@ -0,0 +1,200 @@
+//! Drive actors directly from tests and fuzzers, without spawning any tasks.
+
+use crate::confirm::Confirm;
+use crate::mailbox::{Envelope, Origin};
+use crate::queue::MessageQueue;
+use crate::{Actor, ActorContext, ActorMetrics, Confirmation, Prioritized, ShutdownReason};
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+/// State checks a fuzzer or test runs after every handled message.
+pub trait Invariants {
+    /// Returns a description of the first broken invariant, if any.
+    fn check_invariants(&self) -> Result<(), String>;
+}
+
+/// What [`ActorHarness::step`] did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepResult {
+    /// A message was handled and the actor keeps running.
+    Handled,
+    /// A message was handled and its handler asked the actor to stop.
+    Stopped(ShutdownReason),
+    /// Nothing was handled: no message is queued, or the actor has already stopped.
+    Idle,
+}
+
+/// Runs an actor's handlers one message at a time without spawning it, so a test decides
+/// exactly when each message is handled and can inspect the actor in between.
+///
+/// Messages wait in the same queue a spawned actor's mailbox uses, so they are handled in
+/// exactly the order the runtime would handle them. Messages a handler sends itself through
+/// its [`ActorContext`] are queued, as [`Origin::SelfSend`](crate::Origin::SelfSend), once
+/// the handler returns.
+pub struct ActorHarness<A: Actor> {
+    actor: A,
+    context: ActorContext<A::Msg>,
+    queue: MessageQueue<A::Msg>,
+    stopped: bool,
+}
+
+/// Former name of [`ActorHarness`].
+#[deprecated(note = "renamed to `ActorHarness`")]
+pub type TestDriver<A> = ActorHarness<A>;
+
+impl<A: Actor> ActorHarness<A> {
+    pub fn new(actor: A) -> Self {
+        ActorHarness {
+            actor,
+            context: ActorContext::detached(std::any::type_name::<A>()),
+            queue: MessageQueue::new(None, None, Arc::new(ActorMetrics::default())),
+            stopped: false,
+        }
+    }
+
+    /// Queues a message without handling it.
+    pub fn send(&mut self, msg: A::Msg) {
+        self.queue.push(Envelope::new(msg, Origin::External));
+    }
+
+    /// Queues a message without handling it, returning a [`Confirmation`] that resolves
+    /// once a [`step`](Self::step) has handled it, as
+    /// [`ActorHandle::send_confirmed`](crate::ActorHandle::send_confirmed) does for a
+    /// spawned actor.
+    pub fn send_confirmed(&mut self, msg: A::Msg) -> Confirmation {
+        let (confirm, confirmation) = Confirm::new();
+        let mut envelope = Envelope::new(msg, Origin::External);
+        envelope.confirm = Some(confirm);
+        self.queue.push(envelope);
+        confirmation
+    }
+
+    /// Number of queued, unhandled messages.
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns `true` once a handler has asked the actor to stop.
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Handles the highest-priority queued message, if any.
+    pub async fn step(&mut self) -> StepResult {
+        if self.stopped {
+            return StepResult::Idle;
+        }
+        let Some(envelope) = self.queue.pop() else {
+            return StepResult::Idle;
+        };
+        self.context.set_priority(Some(envelope.priority()));
+        let Envelope { msg, confirm, .. } = envelope;
+        let flow = self.actor.handle(msg, &self.context).await;
+        self.context.set_priority(None);
+        if let Some(confirm) = confirm {
+            confirm.send(Ok(()));
+        }
+        let looped = self.context.take_loopback();
+        match flow {
+            ControlFlow::Continue(()) => {
+                for (msg, correlation) in looped {
+                    let mut envelope = Envelope::new(msg, Origin::SelfSend);
+                    envelope.correlation = correlation;
+                    self.queue.push(envelope);
+                }
+                StepResult::Handled
+            }
+            ControlFlow::Break(reason) => {
+                self.stopped = true;
+                StepResult::Stopped(reason)
+            }
+        }
+    }
+
+    /// Handles queued messages, including the ones handlers send themselves, until none is
+    /// left or the actor stops.
+    ///
+    /// Returns [`StepResult::Stopped`] if a handler stopped the actor, and
+    /// [`StepResult::Idle`] otherwise.
+    pub async fn run_until_idle(&mut self) -> StepResult {
+        loop {
+            match self.step().await {
+                StepResult::Handled => {}
+                done => return done,
+            }
+        }
+    }
+
+    pub fn actor(&self) -> &A {
+        &self.actor
+    }
+
+    pub fn actor_mut(&mut self) -> &mut A {
+        &mut self.actor
+    }
+
+    pub fn into_inner(self) -> A {
+        self.actor
+    }
+}
+
+/// Feeds a message sequence decoded from fuzzer bytes through a fresh actor.
+///
+/// Each decoded operation either queues an arbitrary message or handles the
+/// highest-priority pending one, so the fuzzer also controls how much work piles up between
+/// steps; whatever is still queued at the end is drained. Invariants are checked after every
+/// handled message and a violation panics, which the fuzzer reports as a crash. Runs on a
+/// paused-clock runtime, so handlers that sleep cost no wall time.
+///
+/// Message enums get the required `Arbitrary` impl from `@arbitrary` in `define_actor!`.
+#[cfg(any(test, feature = "fuzzing"))]
+pub fn fuzz_actor<A, F>(state_factory: F, data: &[u8])
+where
+    A: Actor + Invariants,
+    A::Msg: for<'a> arbitrary::Arbitrary<'a>,
+    F: FnOnce() -> A,
+{
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .start_paused(true)
+        .build()
+        .expect("failed to build the fuzzing runtime");
+
+    runtime.block_on(async {
+        let mut u = arbitrary::Unstructured::new(data);
+        let mut harness = ActorHarness::new(state_factory());
+        assert_invariants(harness.actor());
+
+        while !u.is_empty() {
+            let Ok(queue) = u.arbitrary::<bool>() else {
+                break;
+            };
+            if queue {
+                let Ok(msg) = u.arbitrary() else {
+                    break;
+                };
+                harness.send(msg);
+            } else if !step_checked(&mut harness).await {
+                return;
+            }
+        }
+        while harness.pending() > 0 && step_checked(&mut harness).await {}
+    });
+}
+
+// Handles one message, if any, and checks invariants; `false` once the actor has stopped.
+#[cfg(any(test, feature = "fuzzing"))]
+async fn step_checked<A: Actor + Invariants>(harness: &mut ActorHarness<A>) -> bool {
+    let step = harness.step().await;
+    if step != StepResult::Idle {
+        assert_invariants(harness.actor());
+    }
+    !harness.is_stopped()
+}
+
+#[cfg(any(test, feature = "fuzzing"))]
+fn assert_invariants<A: Invariants>(actor: &A) {
+    if let Err(violation) = actor.check_invariants() {
+        panic!("invariant violated: {}", violation);
+    }
+}
@@ -0,0 +1,141 @@
+use crate::Priority;
+use tokio::time::{Duration, Instant};
+
+/// What happens to a message whose priority has used up its
+/// [rate limit](crate::ActorConfig::priority_rate_limit).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RateLimitPolicy {
+    /// The message waits in the queue until its priority has a token again; messages of
+    /// other priorities are handled meanwhile, lower ones included.
+    #[default]
+    Delay,
+    /// The message is dropped on arrival, counted in
+    /// [`ActorMetrics::messages_overflowed`](crate::ActorMetrics::messages_overflowed).
+    Reject,
+}
+
+/// A token bucket: holds up to `burst` tokens, starts full and gains `per_second` tokens
+/// a second.
+///
+/// Refills in whole tokens from the last refill, carrying the remainder over, so that it
+/// only depends on [`Instant::now`] and stays exact under paused time.
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    burst: u32,
+    // The time it takes to gain one token.
+    interval: Duration,
+    tokens: u32,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(burst: u32, per_second: u32) -> Self {
+        TokenBucket {
+            burst,
+            interval: Duration::from_secs(1) / per_second,
+            tokens: burst,
+            refilled_at: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        if self.tokens == self.burst {
+            self.refilled_at = now;
+            return;
+        }
+        let gained =
+            now.saturating_duration_since(self.refilled_at).as_nanos() / self.interval.as_nanos();
+        let gained = u32::try_from(gained).unwrap_or(u32::MAX);
+        if gained == 0 {
+            return;
+        }
+        self.tokens = self.tokens.saturating_add(gained).min(self.burst);
+        self.refilled_at = if self.tokens == self.burst {
+            now
+        } else {
+            self.refilled_at + self.interval * gained
+        };
+    }
+
+    /// When the next token comes in.
+    fn next_token_at(&self) -> Instant {
+        self.refilled_at + self.interval
+    }
+}
+
+/// The rate limits of an actor's priorities, kept by its queue.
+#[derive(Debug, Clone)]
+pub(crate) struct Throttle {
+    buckets: Vec<(Priority, TokenBucket, RateLimitPolicy)>,
+}
+
+impl Throttle {
+    /// `None` without any limit.
+    pub(crate) fn new(limits: &[(Priority, u32, u32, RateLimitPolicy)]) -> Option<Self> {
+        if limits.is_empty() {
+            return None;
+        }
+        let buckets = limits
+            .iter()
+            .map(|&(priority, burst, per_second, policy)| {
+                (priority, TokenBucket::new(burst, per_second), policy)
+            })
+            .collect();
+        Some(Throttle { buckets })
+    }
+
+    /// Brings delaying buckets up to date.
+    pub(crate) fn refill(&mut self, now: Instant) {
+        for (_, bucket, policy) in &mut self.buckets {
+            if *policy == RateLimitPolicy::Delay {
+                bucket.refill(now);
+            }
+        }
+    }
+
+    /// Whether messages of `level` have to wait for a token before they are handled.
+    pub(crate) fn holds_back(&self, level: Priority) -> bool {
+        self.buckets.iter().any(|(priority, bucket, policy)| {
+            *priority == level && *policy == RateLimitPolicy::Delay && bucket.tokens == 0
+        })
+    }
+
+    /// Spends a token on a message of `level` leaving the queue, if it has a delaying
+    /// limit.
+    pub(crate) fn dispatched(&mut self, level: Priority) {
+        for (priority, bucket, policy) in &mut self.buckets {
+            if *priority == level && *policy == RateLimitPolicy::Delay {
+                bucket.tokens = bucket.tokens.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Spends a token on a message of `level` arriving, returning `false` if it has a
+    /// rejecting limit and no token left.
+    pub(crate) fn admit(&mut self, level: Priority) -> bool {
+        let now = Instant::now();
+        for (priority, bucket, policy) in &mut self.buckets {
+            if *priority == level && *policy == RateLimitPolicy::Reject {
+                bucket.refill(now);
+                if bucket.tokens == 0 {
+                    return false;
+                }
+                bucket.tokens -= 1;
+            }
+        }
+        true
+    }
+
+    /// When the first of `levels` that is held back gets a token again.
+    pub(crate) fn released_at(&self, levels: impl Iterator<Item = Priority>) -> Option<Instant> {
+        levels
+            .filter(|level| self.holds_back(*level))
+            .filter_map(|level| {
+                self.buckets
+                    .iter()
+                    .find(|(priority, _, _)| *priority == level)
+                    .map(|(_, bucket, _)| bucket.next_token_at())
+            })
+            .min()
+    }
+}
@@ -0,0 +1,117 @@
+use crate::prelude::*;
+use crate::{channel, run_actor, RateLimitPolicy};
+use std::sync::{Arc, Mutex};
+use tokio::time::{Duration, Instant};
+
+define_actor! {
+    /// Logs the priority of every message it handles.
+    Recorder {
+        log: Arc<Mutex<Vec<Priority>>>,
+    }
+
+    impl RecorderMsg {
+        @priority(Low)
+        fn Chore(&mut self, ctx: &ActorContext<RecorderMsg>) {
+            self.log.lock().unwrap().push(ctx.priority().unwrap());
+        }
+
+        @priority(Medium)
+        fn Task(&mut self, ctx: &ActorContext<RecorderMsg>) {
+            self.log.lock().unwrap().push(ctx.priority().unwrap());
+        }
+
+        @priority(High)
+        fn Alert(&mut self, ctx: &ActorContext<RecorderMsg>) {
+            self.log.lock().unwrap().push(ctx.priority().unwrap());
+        }
+    }
+}
+
+/// A recorder with `msgs` already queued when it starts, and its log.
+async fn recorder_with(
+    config: ActorConfig,
+    msgs: impl IntoIterator<Item = RecorderMsg>,
+) -> (ActorHandle<RecorderMsg>, Arc<Mutex<Vec<Priority>>>) {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (handle, mailbox) = channel(config.unbounded());
+    for msg in msgs {
+        handle.send(msg).await.unwrap();
+    }
+    let recorder = Recorder {
+        log: Arc::clone(&log),
+    };
+    tokio::spawn(run_actor(recorder, mailbox));
+    (handle, log)
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_flood_of_limited_messages_lets_lower_priorities_through() {
+    let config =
+        ActorConfig::new().priority_rate_limit(Priority::High, 1, 10, RateLimitPolicy::Delay);
+    let flood = (0..100).map(|_| RecorderMsg::Alert());
+    let others = (0..5).flat_map(|_| [RecorderMsg::Task(), RecorderMsg::Chore()]);
+    let (handle, log) = recorder_with(config, flood.chain(others)).await;
+    let started = Instant::now();
+    handle.await_idle().await;
+
+    let log = log.lock().unwrap();
+    assert_eq!(log.len(), 110);
+    // One `High` message spends the only token, then the rest wait their turn.
+    let expected: Vec<_> = std::iter::once(Priority::High)
+        .chain([Priority::Medium; 5])
+        .chain([Priority::Low; 5])
+        .chain([Priority::High; 99])
+        .collect();
+    assert_eq!(*log, expected);
+    assert_eq!(started.elapsed(), Duration::from_millis(9900));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_limited_messages_interleave_with_a_steady_flow() {
+    let config =
+        ActorConfig::new().priority_rate_limit(Priority::High, 2, 10, RateLimitPolicy::Delay);
+    let (handle, log) = recorder_with(config, (0..10).map(|_| RecorderMsg::Alert())).await;
+    for _ in 0..3 {
+        tokio::time::sleep(Duration::from_millis(130)).await;
+        handle.send(RecorderMsg::Task()).await.unwrap();
+    }
+    handle.await_idle().await;
+
+    let log = log.lock().unwrap();
+    let tasks: Vec<_> = log
+        .iter()
+        .enumerate()
+        .filter(|(_, priority)| **priority == Priority::Medium)
+        .map(|(at, _)| at)
+        .collect();
+    assert_eq!(tasks, [3, 5, 7]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_rejecting_limit_drops_messages_on_arrival() {
+    let config =
+        ActorConfig::new().priority_rate_limit(Priority::High, 3, 1, RateLimitPolicy::Reject);
+    let msgs = (0..5).flat_map(|_| [RecorderMsg::Alert(), RecorderMsg::Task()]);
+    let (handle, log) = recorder_with(config, msgs).await;
+    handle.await_idle().await;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    handle.send(RecorderMsg::Alert()).await.unwrap();
+    handle.send(RecorderMsg::Alert()).await.unwrap();
+    handle.await_idle().await;
+
+    let log = log.lock().unwrap();
+    let alerts = log.iter().filter(|p| **p == Priority::High).count();
+    assert_eq!(alerts, 4);
+    assert_eq!(log.len(), 9);
+    #[cfg(feature = "metrics")]
+    {
+        use crate::MetricsExt;
+        assert_eq!(handle.metrics().messages_overflowed(), 3);
+    }
+}
+
+#[test]
+#[should_panic(expected = "`Shutdown` messages cannot be rate limited")]
+fn test_shutdown_messages_cannot_be_rate_limited() {
+    ActorConfig::new().priority_rate_limit(Priority::Shutdown, 1, 1, RateLimitPolicy::Delay);
+}
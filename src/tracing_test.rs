@@ -0,0 +1,107 @@
+use crate::prelude::*;
+use crate::test_support::{Gated, GatedMsg};
+use crate::{spawn_actor_with, ActorConfig};
+use std::io;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Collects everything a `fmt` subscriber writes.
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl Captured {
+    fn text(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl io::Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for Captured {
+    type Writer = Captured;
+
+    fn make_writer(&'a self) -> Captured {
+        self.clone()
+    }
+}
+
+/// Sends everything traced on this thread to the returned buffer until the guard drops.
+fn capture() -> (Captured, tracing::subscriber::DefaultGuard) {
+    let captured = Captured::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::TRACE)
+        .with_ansi(false)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(captured.clone())
+        .finish();
+    // The test runtime is single-threaded, so the actor's tasks see this default too.
+    (captured, tracing::subscriber::set_default(subscriber))
+}
+
+#[tokio::test]
+async fn test_lifecycle_events_and_handle_spans_are_traced() {
+    let (captured, _guard) = capture();
+
+    let handle = spawn_actor(Gated::new());
+    handle.send_traced(GatedMsg::Urgent(), 42).await.unwrap();
+    let (tx, rx) = oneshot::channel();
+    handle.send(GatedMsg::Report(tx)).await.unwrap();
+    rx.await.unwrap();
+    handle.send(GatedMsg::Shutdown).await.unwrap();
+    handle.join().await;
+
+    let text = captured.text();
+    let actor = std::any::type_name::<Gated>();
+    assert!(text.contains("message processor task started"), "{}", text);
+    assert!(text.contains("actor received shutdown signal"), "{}", text);
+    assert!(text.contains(&format!("actor={}", actor)), "{}", text);
+    assert!(text.contains("handle{"), "{}", text);
+    assert!(text.contains("variant=\"Urgent\""), "{}", text);
+    assert!(text.contains("priority=High"), "{}", text);
+    assert!(text.contains("queue_depth=1"), "{}", text);
+    assert!(text.contains("correlation_id=42"), "{}", text);
+    assert!(text.contains("actor instance being dropped"), "{}", text);
+}
+
+#[tokio::test]
+async fn test_events_carry_each_actors_own_name() {
+    let (captured, _guard) = capture();
+
+    for name in ["ingest-east", "ingest-west"] {
+        let handle = spawn_actor_with(Gated::new(), ActorConfig::new().name(name));
+        handle.send(GatedMsg::Urgent()).await.unwrap();
+        handle.send(GatedMsg::Shutdown).await.unwrap();
+        handle.join().await;
+    }
+
+    let text = captured.text();
+    for name in ["ingest-east", "ingest-west"] {
+        let events: Vec<_> = text
+            .lines()
+            .filter(|line| line.contains(&format!("actor={}", name)))
+            .collect();
+        assert!(
+            events
+                .iter()
+                .any(|line| line.contains("processor task started")),
+            "{}",
+            text
+        );
+        assert!(
+            events.iter().any(|line| line.contains("handle{")),
+            "{}",
+            text
+        );
+    }
+}
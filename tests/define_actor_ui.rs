@@ -0,0 +1,13 @@
+//! Compile checks for `define_actor!`.
+
+#[test]
+fn define_actor_accepts_user_code() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/pass/*.rs");
+}
+
+#[test]
+fn define_actor_errors() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/define_actor/*.rs");
+}
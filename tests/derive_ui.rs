@@ -0,0 +1,7 @@
+//! Compile-fail checks for `#[derive(Prioritized)]`.
+
+#[test]
+fn derive_prioritized_errors() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
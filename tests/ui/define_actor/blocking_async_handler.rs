@@ -0,0 +1,17 @@
+use priact::prelude::*;
+
+define_actor! {
+    Encoder {
+        encoded: u32,
+    }
+
+    impl EncoderMsg {
+        @blocking
+        async fn Encode(&mut self, frame: Vec<u8>) {
+            let _ = frame;
+            self.encoded += 1;
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use priact::prelude::*;
+
+define_actor! {
+    Canvas {
+        frame: u64,
+    }
+
+    impl CanvasMsg {
+        @priority(Low) @coalesce
+        fn Redraw(&mut self, frame: u64) -> u64 {
+            self.frame = frame;
+            frame
+        }
+    }
+}
+
+fn main() {}
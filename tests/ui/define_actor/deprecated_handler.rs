@@ -0,0 +1,26 @@
+#![deny(deprecated)]
+
+use priact::prelude::*;
+
+define_actor! {
+    Counter {
+        count: i32,
+    }
+
+    impl CounterMsg {
+        fn Increment(&mut self) {
+            self.count += 1;
+        }
+
+        #[deprecated(note = "send `Increment` to a fresh actor instead")]
+        fn Reset(&mut self) {
+            self.count = 0;
+        }
+    }
+}
+
+fn main() {
+    let _ = CounterMsg::Increment();
+    let _ = CounterMsg::Reset();
+    Counter { count: 1 }.Reset();
+}
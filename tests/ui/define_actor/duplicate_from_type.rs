@@ -0,0 +1,21 @@
+use priact::prelude::*;
+
+define_actor! {
+    Mirror {
+        lines: Vec<String>,
+    }
+
+    impl MirrorMsg {
+        @from
+        fn Stdout(&mut self, line: String) {
+            self.lines.push(line);
+        }
+
+        @from
+        fn Stderr(&mut self, line: String) {
+            self.lines.push(line);
+        }
+    }
+}
+
+fn main() {}
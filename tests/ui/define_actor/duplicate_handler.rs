@@ -0,0 +1,19 @@
+use priact::prelude::*;
+
+define_actor! {
+    Counter {
+        count: i32,
+    }
+
+    impl CounterMsg {
+        fn Increment(&mut self) {
+            self.count += 1;
+        }
+
+        fn Increment(&mut self, by: i32) {
+            self.count += by;
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use priact::prelude::*;
+
+define_actor! {
+    Counter {
+        count: i32,
+    }
+
+    impl CounterMsg {
+        fn Reset(&mut self) {
+            self.count = 0;
+        }
+
+        extern Reset(i32) => |self, count| self.count = count,
+    }
+}
+
+fn main() {}
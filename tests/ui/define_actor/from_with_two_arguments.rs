@@ -0,0 +1,17 @@
+use priact::prelude::*;
+
+define_actor! {
+    Mirror {
+        lines: Vec<String>,
+    }
+
+    impl MirrorMsg {
+        @from
+        fn Line(&mut self, stream: u8, line: String) {
+            let _ = stream;
+            self.lines.push(line);
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use priact::prelude::*;
+
+define_actor! {
+    Fetcher {
+        fetched: u32,
+    }
+
+    impl FetcherMsg {
+        fn FetchURL(&mut self, url: String) {
+            self.fetched += 1;
+        }
+
+        fn FetchUrl(&mut self, url: String) {
+            self.fetched += 1;
+        }
+    }
+}
+
+fn main() {}
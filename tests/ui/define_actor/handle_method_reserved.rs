@@ -0,0 +1,15 @@
+use priact::prelude::*;
+
+define_actor! {
+    Wrapper {
+        wrapped: u32,
+    }
+
+    impl WrapperMsg {
+        fn IntoInner(&mut self) {
+            self.wrapped = 0;
+        }
+    }
+}
+
+fn main() {}
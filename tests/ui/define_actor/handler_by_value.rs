@@ -0,0 +1,15 @@
+use priact::prelude::*;
+
+define_actor! {
+    Counter {
+        count: i32,
+    }
+
+    impl CounterMsg {
+        fn Consume(self) {
+            let _ = self.count;
+        }
+    }
+}
+
+fn main() {}
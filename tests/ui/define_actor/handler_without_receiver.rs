@@ -0,0 +1,15 @@
+use priact::prelude::*;
+
+define_actor! {
+    Counter {
+        count: i32,
+    }
+
+    impl CounterMsg {
+        fn Orphan(value: i32) {
+            let _ = value;
+        }
+    }
+}
+
+fn main() {}
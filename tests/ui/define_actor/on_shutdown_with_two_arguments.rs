@@ -0,0 +1,19 @@
+use priact::prelude::*;
+
+define_actor! {
+    Session {
+        open: bool,
+    }
+
+    impl SessionMsg {
+        @on_shutdown
+        fn close(&mut self, code: u16, reason: String) {
+            let _ = (code, reason);
+            self.open = false;
+        }
+
+        fn Ping(&mut self) {}
+    }
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use priact::prelude::*;
+
+define_actor! {
+    Counter {
+        count: i32,
+    }
+
+    impl CounterMsg {
+        fn Pick(&mut self, (Some(value) | None): Option<i32>) {
+            self.count = 1;
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+mod shard {
+    use priact::prelude::*;
+
+    define_actor! {
+        pub(self) Counter {
+            count: i32,
+        }
+
+        impl CounterMsg {
+            fn Increment(&mut self) {
+                self.count += 1;
+            }
+        }
+    }
+
+    pub fn start() {
+        let _ = Counter { count: 0 };
+        let _ = CounterMsg::Increment();
+    }
+}
+
+fn main() {
+    shard::start();
+    let _ = shard::CounterMsg::Shutdown;
+}
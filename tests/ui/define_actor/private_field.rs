@@ -0,0 +1,27 @@
+mod shard {
+    use priact::prelude::*;
+
+    define_actor! {
+        Counter {
+            count: i32,
+            pub(self) secret: u64,
+        }
+
+        impl CounterMsg {
+            fn Increment(&mut self) {
+                self.count += 1;
+                self.secret += 1;
+            }
+        }
+    }
+
+    pub fn counter() -> Counter {
+        Counter { count: 0, secret: 42 }
+    }
+}
+
+fn main() {
+    let counter = shard::counter();
+    let _ = counter.count;
+    let _ = counter.secret;
+}
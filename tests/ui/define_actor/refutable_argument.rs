@@ -0,0 +1,15 @@
+use priact::prelude::*;
+
+define_actor! {
+    Counter {
+        count: i32,
+    }
+
+    impl CounterMsg {
+        fn Set(&mut self, 0..=9: u8) {
+            self.count = 0;
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,27 @@
+pub mod outer {
+    pub mod inner {
+        use priact::prelude::*;
+
+        define_actor! {
+            pub(super) Counter {
+                count: i32,
+            }
+
+            impl CounterMsg {
+                fn Increment(&mut self) {
+                    self.count += 1;
+                }
+            }
+        }
+    }
+
+    pub fn start() {
+        let mut counter = inner::Counter { count: 0 };
+        counter.Increment();
+    }
+}
+
+fn main() {
+    outer::start();
+    let _ = outer::inner::Counter { count: 0 };
+}
@@ -0,0 +1,15 @@
+use priact::prelude::*;
+
+define_actor! {
+    Counter {
+        count: i32,
+    }
+
+    impl CounterMsg {
+        fn Shutdown(&mut self) {
+            self.count = 0;
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use priact::prelude::*;
+
+define_actor! {
+    Counter {
+        count: i32,
+    }
+
+    impl CounterMsg {
+        @priority(Hgh)
+        fn Increment(&mut self) {
+            self.count += 1;
+        }
+    }
+}
+
+fn main() {}
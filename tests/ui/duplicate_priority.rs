@@ -0,0 +1,10 @@
+use priact::Prioritized;
+
+#[derive(Prioritized)]
+enum Msg {
+    #[priority(Low)]
+    #[priority(High)]
+    Ping,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use priact::Prioritized;
+
+#[derive(Prioritized)]
+struct Msg {
+    id: u32,
+}
+
+fn main() {}
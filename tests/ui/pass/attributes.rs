@@ -0,0 +1,50 @@
+use priact::prelude::*;
+use tokio::sync::oneshot;
+
+define_actor! {
+    #[derive(Debug, Default)]
+    Counter {
+        /// Number of increments so far.
+        count: i32,
+        #[allow(dead_code)]
+        label: String,
+    }
+
+    #[derive(Debug)]
+    impl CounterMsg {
+        @priority(Low)
+        fn Increment(&mut self, by: i32) {
+            self.count += by;
+        }
+
+        @priority(High)
+        fn Get(&mut self, tx: oneshot::Sender<i32>) {
+            let _ = tx.send(self.count);
+        }
+    }
+}
+
+define_actor! {
+    Plain {}
+
+    #[derive(Debug, Clone, PartialEq)]
+    impl PlainMsg {
+        @priority(Medium)
+        fn Echo(&mut self, text: String) {
+            let _ = text;
+        }
+    }
+}
+
+fn main() {
+    let counter = Counter::default();
+    assert_eq!(format!("{:?}", counter), r#"Counter { count: 0, label: "" }"#);
+
+    let (tx, _rx) = oneshot::channel();
+    assert!(format!("{:?}", CounterMsg::Get(tx)).starts_with("Get("));
+    assert_eq!(format!("{:?}", CounterMsg::Increment(2)), "Increment(2)");
+
+    let msg = PlainMsg::Echo("hi".to_string());
+    assert_eq!(msg.clone(), msg);
+    assert_eq!(PlainMsg::Shutdown, PlainMsg::Shutdown);
+}
@@ -0,0 +1,38 @@
+use priact::prelude::*;
+
+define_actor! {
+    Mixed {}
+
+    impl MixedMsg {
+        @priority(High)
+        fn Annotated(&mut self) {}
+
+        fn Unannotated(&mut self) {}
+
+        async fn UnannotatedAsync(&mut self) {}
+    }
+}
+
+define_actor! {
+    Background {}
+
+    impl BackgroundMsg {
+        @default_priority(Low)
+
+        fn Sweep(&mut self) {}
+
+        @priority(Custom(150))
+        fn Flush(&mut self) {}
+    }
+}
+
+fn main() {
+    assert_eq!(MixedMsg::Annotated().priority(), Priority::High);
+    assert_eq!(MixedMsg::Unannotated().priority(), Priority::Medium);
+    assert_eq!(MixedMsg::UnannotatedAsync().priority(), Priority::Medium);
+    assert_eq!(MixedMsg::Shutdown.priority(), Priority::Shutdown);
+
+    assert_eq!(BackgroundMsg::Sweep().priority(), Priority::Low);
+    assert_eq!(BackgroundMsg::Flush().priority(), Priority::Custom(150));
+    assert_eq!(BackgroundMsg::Shutdown.priority(), Priority::Shutdown);
+}
@@ -0,0 +1,46 @@
+//! Every generated public item can carry documentation.
+#![deny(missing_docs)]
+
+use priact::prelude::*;
+use tokio::sync::oneshot;
+
+define_actor! {
+    /// Counts things.
+    Counter {
+        /// Number of increments so far.
+        count: i32,
+    }
+
+    /// Messages understood by [`Counter`].
+    impl CounterMsg {
+        /// Adds one to the count.
+        #[allow(unused_mut)]
+        @priority(Low)
+        fn Increment(&mut self) {
+            self.count += 1;
+        }
+
+        @priority(High)
+        /// Reports the current count.
+        fn Get(&mut self, tx: oneshot::Sender<i32>) {
+            let _ = tx.send(self.count);
+        }
+
+        /// Clears the count.
+        #[deprecated(note = "send `Increment` to a fresh actor instead")]
+        fn Reset(&mut self) {
+            self.count = 0;
+        }
+
+        /// Opens resources before the first message.
+        @on_start
+        fn open(&mut self) {}
+    }
+}
+
+fn main() {
+    let mut counter = Counter { count: 0 };
+    counter.Increment();
+    assert_eq!(counter.count, 1);
+    let _ = CounterMsg::Increment();
+}
@@ -0,0 +1,69 @@
+use priact::prelude::*;
+use tokio::sync::oneshot;
+
+#[derive(Debug)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Debug)]
+pub struct Meters(pub f32);
+
+define_actor! {
+    Plotter {
+        log: Vec<String>,
+    }
+
+    impl PlotterMsg {
+        fn Load(&mut self, mut buf: Vec<u8>) {
+            buf.push(b'!');
+            self.log.push(String::from_utf8(buf).unwrap());
+        }
+
+        fn Move(&mut self, (x, y): (f32, f32)) {
+            self.log.push(format!("move {} {}", x, y));
+        }
+
+        fn Jump(&mut self, Point { x, y: height }: Point) {
+            self.log.push(format!("jump {} {}", x, height));
+        }
+
+        fn Walk(&mut self, Meters(distance): Meters, _: u8) {
+            self.log.push(format!("walk {}", distance));
+        }
+
+        fn Pair(&mut self, [first, second]: [u8; 2]) {
+            self.log.push(format!("pair {} {}", first, second));
+        }
+
+        fn Distance(&self, (x, _): (f32, f32)) -> f32 {
+            x.abs()
+        }
+
+        fn Log(&mut self, tx: oneshot::Sender<Vec<String>>) {
+            let _ = tx.send(std::mem::take(&mut self.log));
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let handle = spawn_actor(Plotter { log: Vec::new() });
+    handle.send(PlotterMsg::Load(b"hi".to_vec())).await.unwrap();
+    handle.send(PlotterMsg::Move((1.0, 2.0))).await.unwrap();
+    handle.send(PlotterMsg::Jump(Point { x: 3.0, y: 4.0 })).await.unwrap();
+    handle.send(PlotterMsg::Walk(Meters(5.0), 0)).await.unwrap();
+    handle.send(PlotterMsg::Pair([6, 7])).await.unwrap();
+
+    let (msg, reply) = PlotterMsg::distance((-8.0, 0.0));
+    handle.send(msg).await.unwrap();
+    assert_eq!(reply.await.unwrap(), 8.0);
+
+    let (tx, rx) = oneshot::channel();
+    handle.send(PlotterMsg::Log(tx)).await.unwrap();
+    assert_eq!(
+        rx.await.unwrap(),
+        ["hi!", "move 1 2", "jump 3 4", "walk 5", "pair 6 7"]
+    );
+}
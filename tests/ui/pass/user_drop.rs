@@ -0,0 +1,26 @@
+use priact::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+define_actor! {
+    Connection {
+        id: u32,
+    }
+
+    impl ConnectionMsg {
+        @priority(Medium)
+        fn Ping(&mut self) {}
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(self.id as usize, Ordering::SeqCst);
+    }
+}
+
+fn main() {
+    drop(Connection { id: 7 });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 7);
+}
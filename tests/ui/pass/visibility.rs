@@ -0,0 +1,35 @@
+#![deny(unreachable_pub)]
+
+mod shard {
+    use priact::prelude::*;
+
+    define_actor! {
+        pub(crate) Counter {
+            count: i32,
+            pub(self) cache: Vec<i32>,
+        }
+
+        impl CounterMsg {
+            fn Increment(&mut self) {
+                self.count += 1;
+                self.cache.push(self.count);
+            }
+        }
+    }
+
+    impl Counter {
+        pub(crate) fn new() -> Self {
+            Counter {
+                count: 0,
+                cache: Vec::new(),
+            }
+        }
+    }
+}
+
+fn main() {
+    let mut counter = shard::Counter::new();
+    counter.Increment();
+    assert_eq!(counter.count, 1);
+    let _ = shard::CounterMsg::Increment();
+}
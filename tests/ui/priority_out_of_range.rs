@@ -0,0 +1,9 @@
+use priact::Prioritized;
+
+#[derive(Prioritized)]
+enum Msg {
+    #[priority(Custom(300))]
+    Ping,
+}
+
+fn main() {}
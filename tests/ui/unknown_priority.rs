@@ -0,0 +1,9 @@
+use priact::Prioritized;
+
+#[derive(Prioritized)]
+enum Msg {
+    #[priority(Urgent)]
+    Ping,
+}
+
+fn main() {}